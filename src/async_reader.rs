@@ -1,12 +1,37 @@
-use super::endian::{BigEndian, Endian, LittleEndian};
+use super::endian::{BigEndian, Endian, Endianness, LittleEndian, NativeEndian, Primitive};
+use crate::checksum::{ChecksumKind, ChecksumWriter};
+use crate::fixed_size::FixedSize;
+use crate::reader::{
+    ArrayBuilder, DEFAULT_MAX_FRAME_SIZE, DEFAULT_SMALL_BUF_SIZE, EndianMarker, IntWidth, LenPolicy, PatternScan, Pending, ScanStep, Staged, byte_run, mismatch_in_element, parse_or_poison,
+    peek_error, peek_window, rebase_limit, reserved_violations, widen,
+};
 use crate::result::{ReadError, ReadResult};
-use std::{fmt::Debug, marker::PhantomData};
-use tokio::io::AsyncReadExt;
+use crate::retry;
+use crate::version::{Version, VersionRange};
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt};
+use tokio::time::Instant;
 
 pub struct AsyncBinaryReader<E, R> {
     reader: R,
-    total_bytes_read: usize,
-    limit_bytes: Option<usize>,
+    total_bytes_read: u64,
+    limit_bytes: Option<u64>,
+    poisoned_at: Option<u64>,
+    max_depth: Option<u32>,
+    depth: u32,
+    max_alloc: Option<usize>,
+    strict_strings: bool,
+    /// Lookahead a `peek` staged past the end of the source's buffer, see `BinaryReader::peek`.
+    pending: Pending,
+    #[cfg(feature = "stats")]
+    pub(crate) stats: Option<std::sync::Arc<crate::stats::Stats>>,
     _endian: PhantomData<fn() -> E>,
 }
 
@@ -23,128 +48,532 @@ pub trait AsyncReadWith<A>: Sized {
     fn read_with<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>, arg: A) -> impl Future<Output = Result<Self>> + Send;
 }
 
-impl<'a> AsyncBinaryReader<BigEndian, tokio::io::BufReader<&'a [u8]>> {
-    pub fn from_be_bytes(buf: &'a [u8]) -> Self {
-        let reader = tokio::io::BufReader::new(buf);
-        AsyncBinaryReader {
-            reader,
-            total_bytes_read: 0,
-            limit_bytes: Some(buf.len()),
-            _endian: PhantomData::<fn() -> BigEndian>,
+pub trait AsyncReadInto {
+    fn read_into<E: Endian, R: ReaderBase>(&mut self, reader: &mut AsyncBinaryReader<E, R>) -> impl Future<Output = Result<()>> + Send;
+}
+
+pub trait AsyncReadIntoWith<A> {
+    fn read_into_with<E: Endian, R: ReaderBase>(&mut self, reader: &mut AsyncBinaryReader<E, R>, arg: A) -> impl Future<Output = Result<()>> + Send;
+}
+
+impl<T: AsyncRead + Send> AsyncReadInto for T {
+    async fn read_into<E: Endian, R: ReaderBase>(&mut self, reader: &mut AsyncBinaryReader<E, R>) -> Result<()> {
+        *self = T::read(reader).await?;
+        Ok(())
+    }
+}
+
+impl<T: AsyncRead + Send> AsyncReadIntoWith<usize> for Vec<T> {
+    async fn read_into_with<E: Endian, R: ReaderBase>(&mut self, reader: &mut AsyncBinaryReader<E, R>, len: usize) -> Result<()> {
+        self.clear();
+        self.reserve(len);
+        for _ in 0..len {
+            self.push(T::read(reader).await?);
         }
+        Ok(())
     }
 }
 
-impl<R: ReaderBase> AsyncBinaryReader<BigEndian, R> {
-    pub fn new_be(reader: R) -> Self {
+impl<E: Endian, R: ReaderBase> AsyncBinaryReader<E, R> {
+    pub fn new(reader: R) -> Self {
         AsyncBinaryReader {
             reader,
             total_bytes_read: 0,
             limit_bytes: None,
-            _endian: PhantomData::<fn() -> BigEndian>,
+            poisoned_at: None,
+            max_depth: None,
+            depth: 0,
+            max_alloc: None,
+            strict_strings: false,
+            pending: Pending::EMPTY,
+            #[cfg(feature = "stats")]
+            stats: None,
+            _endian: PhantomData,
         }
     }
+}
+
+impl<'a, E: Endian> AsyncBinaryReader<E, &'a [u8]> {
+    pub fn from_bytes(buf: &'a [u8]) -> Self {
+        let mut reader = Self::new(buf);
+        reader.limit_bytes = Some(buf.len() as u64);
+        reader
+    }
+}
+
+impl<R: tokio::io::AsyncRead + Unpin + Send> AsyncBinaryReader<BigEndian, tokio::io::BufReader<R>> {
+    pub fn new_be_with_capacity(reader: R, capacity: usize) -> Self {
+        Self::new_be(tokio::io::BufReader::with_capacity(capacity, reader))
+    }
+}
+
+impl<R: tokio::io::AsyncRead + Unpin + Send> AsyncBinaryReader<LittleEndian, tokio::io::BufReader<R>> {
+    pub fn new_le_with_capacity(reader: R, capacity: usize) -> Self {
+        Self::new_le(tokio::io::BufReader::with_capacity(capacity, reader))
+    }
+}
+
+impl<'a> AsyncBinaryReader<BigEndian, &'a [u8]> {
+    pub fn from_be_bytes(buf: &'a [u8]) -> Self {
+        Self::from_bytes(buf)
+    }
+}
+
+impl<R: ReaderBase> AsyncBinaryReader<BigEndian, R> {
+    pub fn new_be(reader: R) -> Self {
+        Self::new(reader)
+    }
     pub async fn read_as_le<T: AsyncRead>(&mut self) -> Result<T> {
-        let mut rdr = AsyncBinaryReader {
-            reader: &mut self.reader,
-            total_bytes_read: self.total_bytes_read,
-            limit_bytes: self.limit_bytes,
-            _endian: PhantomData::<fn() -> LittleEndian>,
-        };
-        let res = T::read(&mut rdr).await?;
-        self.total_bytes_read = rdr.total_bytes_read; // take over the total_bytes_read
-        Ok(res)
+        with_endian!(self, AsyncBinaryReader { pending }, LittleEndian, |view| T::read(&mut view).await)
     }
-    pub async fn read_as_le_with<A, T: AsyncReadWith<A>>(&mut self, arg: A) -> Result<T> {
-        let mut rdr = AsyncBinaryReader {
-            reader: &mut self.reader,
-            total_bytes_read: self.total_bytes_read,
-            limit_bytes: self.limit_bytes,
-            _endian: PhantomData::<fn() -> LittleEndian>,
-        };
-        let res = T::read_with(&mut rdr, arg).await?;
-        self.total_bytes_read = rdr.total_bytes_read; // take over the total_bytes_read
-        Ok(res)
+    pub async fn read_as_le_with<T: AsyncReadWith<U>, U>(&mut self, arg: U) -> Result<T> {
+        with_endian!(self, AsyncBinaryReader { pending }, LittleEndian, |view| T::read_with(&mut view, arg).await)
     }
 }
 
-impl<'a> AsyncBinaryReader<LittleEndian, tokio::io::BufReader<&'a [u8]>> {
+impl<'a> AsyncBinaryReader<LittleEndian, &'a [u8]> {
     pub fn from_le_bytes(buf: &'a [u8]) -> Self {
-        let reader = tokio::io::BufReader::new(buf);
-        AsyncBinaryReader {
-            reader,
-            total_bytes_read: 0,
-            limit_bytes: Some(buf.len()),
-            _endian: PhantomData,
-        }
+        Self::from_bytes(buf)
     }
 }
 
 impl<R: ReaderBase> AsyncBinaryReader<LittleEndian, R> {
     pub fn new_le(reader: R) -> Self {
-        AsyncBinaryReader {
-            reader,
-            total_bytes_read: 0,
-            limit_bytes: None,
-            _endian: PhantomData,
-        }
+        Self::new(reader)
     }
     pub async fn read_as_be<T: AsyncRead>(&mut self) -> Result<T> {
-        let mut rdr = AsyncBinaryReader {
-            reader: &mut self.reader,
-            total_bytes_read: self.total_bytes_read,
-            limit_bytes: self.limit_bytes,
-            _endian: PhantomData::<fn() -> BigEndian>,
-        };
-        let res = T::read(&mut rdr).await?;
-        self.total_bytes_read = rdr.total_bytes_read; // take over the total_bytes_read
-        Ok(res)
+        with_endian!(self, AsyncBinaryReader { pending }, BigEndian, |view| T::read(&mut view).await)
     }
-    pub async fn read_as_be_with<A, T: AsyncReadWith<A>>(&mut self, arg: A) -> Result<T> {
-        let mut rdr = AsyncBinaryReader {
-            reader: &mut self.reader,
-            total_bytes_read: self.total_bytes_read,
-            limit_bytes: self.limit_bytes,
-            _endian: PhantomData::<fn() -> BigEndian>,
-        };
-        let res = T::read_with(&mut rdr, arg).await?;
-        self.total_bytes_read = rdr.total_bytes_read; // take over the total_bytes_read
-        Ok(res)
+    pub async fn read_as_be_with<T: AsyncReadWith<U>, U>(&mut self, arg: U) -> Result<T> {
+        with_endian!(self, AsyncBinaryReader { pending }, BigEndian, |view| T::read_with(&mut view, arg).await)
+    }
+}
+
+impl<R: ReaderBase> AsyncBinaryReader<NativeEndian, R> {
+    pub fn new_ne(reader: R) -> Self {
+        Self::new(reader)
+    }
+}
+
+impl<'a> AsyncBinaryReader<NativeEndian, &'a [u8]> {
+    pub fn from_ne_bytes(buf: &'a [u8]) -> Self {
+        Self::from_bytes(buf)
+    }
+}
+
+impl<R: ReaderBase> AsyncBinaryReader<LittleEndian, R> {
+    /// Creates a reader whose byte order `endianness` is chosen at runtime, see `AsyncEitherEndianReader`.
+    pub fn new_dyn(reader: R, endianness: Endianness) -> AsyncEitherEndianReader<R> {
+        AsyncEitherEndianReader::new(reader, endianness)
+    }
+}
+
+/// The async counterpart of `EitherEndianReader`: a reader whose byte order is decided at runtime,
+/// by `AsyncEitherEndianReader::detect_endian` or `AsyncBinaryReader::new_dyn`.
+pub enum AsyncEitherEndianReader<R> {
+    Little(AsyncBinaryReader<LittleEndian, R>),
+    Big(AsyncBinaryReader<BigEndian, R>),
+}
+
+impl<R: ReaderBase> AsyncEitherEndianReader<R> {
+    pub fn new(reader: R, endianness: Endianness) -> Self {
+        match endianness {
+            Endianness::Little => AsyncEitherEndianReader::Little(AsyncBinaryReader::new_le(reader)),
+            Endianness::Big => AsyncEitherEndianReader::Big(AsyncBinaryReader::new_be(reader)),
+        }
+    }
+
+    /// Reads a byte-order marker from the start of `reader` and returns a reader using that order,
+    /// see `EitherEndianReader::detect_endian`.
+    pub async fn detect_endian(reader: R, marker: EndianMarker<'_>) -> Result<Self> {
+        let (le, be) = marker.patterns();
+        if le.len() != be.len() {
+            return Err(ReadError::InvalidArgument);
+        }
+        Self::detect_endian_with(reader, le.len(), |data| match data {
+            _ if data == le => Some(Endianness::Little),
+            _ if data == be => Some(Endianness::Big),
+            _ => None,
+        })
+        .await
+    }
+
+    /// Reads the first `len` bytes of `reader` and returns a reader using the byte order `decide`
+    /// picks for them, see `EitherEndianReader::detect_endian_with`.
+    pub async fn detect_endian_with(reader: R, len: usize, decide: impl FnOnce(&[u8]) -> Option<Endianness>) -> Result<Self> {
+        let mut reader = AsyncBinaryReader::new_le(reader);
+        let endianness = reader
+            .read_from_slice(len, |data| {
+                decide(data).ok_or_else(|| ReadError::invalid_data_format(format!("unrecognized byte-order marker {:02x?}", data)))
+            })
+            .await?;
+        Ok(if endianness == Endianness::Little {
+            AsyncEitherEndianReader::Little(reader)
+        } else {
+            AsyncEitherEndianReader::Big(AsyncBinaryReader {
+                reader: reader.reader,
+                total_bytes_read: reader.total_bytes_read,
+                limit_bytes: reader.limit_bytes,
+                poisoned_at: reader.poisoned_at,
+                max_depth: reader.max_depth,
+                depth: reader.depth,
+                max_alloc: reader.max_alloc,
+                strict_strings: reader.strict_strings,
+                pending: reader.pending,
+                #[cfg(feature = "stats")]
+                stats: reader.stats,
+                _endian: PhantomData,
+            })
+        })
+    }
+}
+
+/// Defines `AsyncEitherEndianReader` methods that await the `AsyncBinaryReader` method of the same
+/// name on either variant, with the generics syntax of `forward_either_reader!`.
+macro_rules! forward_async_either_reader {
+    ( $( fn $name:ident $([$($generics:tt)*] $(::<$($turbofish:ty),*>)?)? (&mut $self:ident $(, $arg:ident: $ty:ty)*) $(-> $ret:ty)?; )* ) => {
+        $(
+            #[inline]
+            pub async fn $name $(<$($generics)*>)? (&mut $self $(, $arg: $ty)*) $(-> $ret)? {
+                match $self {
+                    AsyncEitherEndianReader::Little(reader) => reader.$name $($(::<$($turbofish),*>)?)? ($($arg),*).await,
+                    AsyncEitherEndianReader::Big(reader) => reader.$name $($(::<$($turbofish),*>)?)? ($($arg),*).await,
+                }
+            }
+        )*
+    };
+}
+
+impl<R: ReaderBase> AsyncEitherEndianReader<R> {
+    pub fn endianness(&self) -> Endianness {
+        match self {
+            AsyncEitherEndianReader::Little(_) => Endianness::Little,
+            AsyncEitherEndianReader::Big(_) => Endianness::Big,
+        }
+    }
+
+    pub fn is_little_endian(&self) -> bool {
+        matches!(self, AsyncEitherEndianReader::Little(_))
+    }
+
+    pub fn position(&self) -> u64 {
+        match self {
+            AsyncEitherEndianReader::Little(reader) => reader.position(),
+            AsyncEitherEndianReader::Big(reader) => reader.position(),
+        }
+    }
+
+    pub fn remaining(&self) -> Option<u64> {
+        match self {
+            AsyncEitherEndianReader::Little(reader) => reader.remaining(),
+            AsyncEitherEndianReader::Big(reader) => reader.remaining(),
+        }
+    }
+
+    pub fn is_poisoned(&self) -> bool {
+        match self {
+            AsyncEitherEndianReader::Little(reader) => reader.is_poisoned(),
+            AsyncEitherEndianReader::Big(reader) => reader.is_poisoned(),
+        }
+    }
+
+    pub fn ensure(&self, len: usize) -> Result<()> {
+        match self {
+            AsyncEitherEndianReader::Little(reader) => reader.ensure(len),
+            AsyncEitherEndianReader::Big(reader) => reader.ensure(len),
+        }
+    }
+
+    pub fn clear_poison(&mut self) {
+        match self {
+            AsyncEitherEndianReader::Little(reader) => reader.clear_poison(),
+            AsyncEitherEndianReader::Big(reader) => reader.clear_poison(),
+        }
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: u32) {
+        match self {
+            AsyncEitherEndianReader::Little(reader) => reader.set_max_depth(max_depth),
+            AsyncEitherEndianReader::Big(reader) => reader.set_max_depth(max_depth),
+        }
+    }
+
+    forward_async_either_reader! {
+        fn read_from_slice[T](&mut self, len: usize, parse: impl FnOnce(&[u8]) -> Result<T>) -> Result<T>;
+        fn read_from_array[T, const N: usize](&mut self, parse: impl FnOnce(&[u8; N]) -> T) -> Result<T>;
+        fn read_while[T, const N: usize](&mut self, try_parse: impl Fn(&[u8; N]) -> Option<T>) -> Result<Vec<T>>;
+        fn read_until[T: AsyncRead + PartialEq + Send + Sync](&mut self, terminator: &T) -> Result<Vec<T>>;
+        fn read_if_remaining[T: AsyncRead](&mut self) -> Result<Option<T>>;
+        fn read_remaining_or_default[T: AsyncRead + Default](&mut self) -> Result<T>;
+        fn read_all[T: AsyncRead](&mut self) -> Result<Vec<T>>;
+        fn peek[T: AsyncRead](&mut self) -> Result<T>;
+        fn peek_u8(&mut self) -> Result<u8>;
+        fn peek_u16(&mut self) -> Result<u16>;
+        fn peek_u32(&mut self) -> Result<u32>;
+        fn u8(&mut self) -> Result<u8>;
+        fn i8(&mut self) -> Result<i8>;
+        fn u16(&mut self) -> Result<u16>;
+        fn i16(&mut self) -> Result<i16>;
+        fn u32(&mut self) -> Result<u32>;
+        fn i32(&mut self) -> Result<i32>;
+        fn f32(&mut self) -> Result<f32>;
+        fn u64(&mut self) -> Result<u64>;
+        fn i64(&mut self) -> Result<i64>;
+        fn f64(&mut self) -> Result<f64>;
+        fn u128(&mut self) -> Result<u128>;
+        fn i128(&mut self) -> Result<i128>;
+        fn usize_from_u16(&mut self) -> Result<usize>;
+        fn usize_from_u32(&mut self) -> Result<usize>;
+        fn usize_from_u64(&mut self) -> Result<usize>;
+        fn read_uint(&mut self, width: IntWidth) -> Result<u64>;
+        fn read_num[T: Primitive + Send](&mut self) -> Result<T>;
+        fn read_num_as[B: Endian, T: Primitive + Send]::<B, T>(&mut self) -> Result<T>;
+        fn read[T: AsyncRead](&mut self) -> Result<T>;
+        fn read_with[A, T: AsyncReadWith<A>](&mut self, arg: A) -> Result<T>;
+        fn read_into[T: AsyncReadInto](&mut self, target: &mut T) -> Result<()>;
+        fn read_into_with[A, T: AsyncReadIntoWith<A>](&mut self, target: &mut T, arg: A) -> Result<()>;
+        fn value[T: AsyncRead + PartialEq + Debug](&mut self, expected: &T) -> Result<()>;
+        fn values[T: AsyncRead + PartialEq + Debug + 'static](&mut self, expected: &[T]) -> Result<()>;
+        fn reserved[const N: usize]::<N>(&mut self, expected_value: u8) -> Result<()>;
+        fn reserved_report[const N: usize]::<N>(&mut self, expected_value: u8) -> Result<Vec<(usize, u8)>>;
+        fn reserved_report_len(&mut self, len: usize, expected_value: u8) -> Result<Vec<(usize, u8)>>;
+        fn reserved_for[T: FixedSize]::<T>(&mut self, expected_value: u8) -> Result<()>;
+        fn magic(&mut self, expected: impl AsRef<[u8]>) -> Result<()>;
+        fn version(&mut self, supported: impl Into<VersionRange>) -> Result<Version>;
+        fn verify_trailing_checksum(&mut self, kind: ChecksumKind) -> Result<()>;
+        fn read_partial[T: AsyncRead](&mut self, len: usize) -> Result<T>;
+        fn read_partial_with[A, T: AsyncReadWith<A>](&mut self, len: usize, arg: A) -> Result<T>;
+        fn read_timeout[T: AsyncRead](&mut self, timeout: Duration) -> Result<T>;
+        fn read_frame_timeout[T: AsyncRead](&mut self, timeout: Duration) -> Result<T>;
+        fn read_frame[T: AsyncRead](&mut self) -> Result<T>;
+        fn read_frame_limited[T: AsyncRead](&mut self, max_size: u32) -> Result<T>;
+        fn read_len_prefixed[L: AsyncRead + FixedSize + Into<u64>, T: AsyncRead]::<L, T>(&mut self, policy: LenPolicy) -> Result<T>;
+        fn skip(&mut self, len: usize) -> Result<()>;
+        fn copy_to(&mut self, dst: &mut (impl tokio::io::AsyncWrite + Unpin + Send), len: u64) -> Result<()>;
+        fn skip_to(&mut self, pos: u64) -> Result<()>;
+        fn skip_records[T: FixedSize]::<T>(&mut self, n: usize) -> Result<()>;
+        fn skip_aligned(&mut self, align: usize) -> Result<()>;
+        fn read_array[T: AsyncRead + Send, const N: usize](&mut self) -> Result<[T; N]>;
+        fn read_vec_validated[T: AsyncRead + Send](&mut self, len: usize, validate: impl Fn(usize, &T) -> Result<()>) -> Result<Vec<T>>;
+        fn read_vec_prefixed_chunked[P: AsyncRead + TryInto<usize> + Send, T: AsyncRead + Send]::<P, T>(&mut self, continuation_flag: u8) -> Result<Vec<T>>;
+        fn read_records[T: AsyncRead + FixedSize + Send](&mut self, n: usize) -> Result<Vec<T>>;
+        fn utf8_str(&mut self, mode: crate::string::StringMode) -> Result<String>;
+        fn utf16_str(&mut self, mode: crate::string::StringMode) -> Result<String>;
+        fn utf8_str_list(&mut self, max_bytes: usize) -> Result<Vec<String>>;
+        fn read_os_string(&mut self, encoding: crate::string::OsStrEncoding) -> Result<std::ffi::OsString>;
+        fn read_path_buf(&mut self, encoding: crate::string::OsStrEncoding) -> Result<std::path::PathBuf>;
+        fn varint_u64(&mut self) -> Result<u64>;
+        fn varint_i64(&mut self) -> Result<i64>;
+        fn vlq_u32(&mut self) -> Result<u32>;
+    }
+}
+
+impl<R: ReaderBase + tokio::io::AsyncSeek> AsyncEitherEndianReader<R> {
+    forward_async_either_reader! {
+        fn seek_record[T: FixedSize]::<T>(&mut self, base: u64, index: u64) -> Result<()>;
+        fn seek_to(&mut self, target: u64) -> Result<()>;
+        fn seek_relative(&mut self, delta: i64) -> Result<()>;
+        fn scan_for(&mut self, pattern: &[u8], max_scan: Option<usize>) -> Result<u64>;
+    }
+}
+
+impl<E, R> AsyncBinaryReader<E, R> {
+    /// Starts counting calls and bytes into `stats`, replacing any collector attached before.
+    #[cfg(feature = "stats")]
+    pub fn attach_stats(&mut self, stats: &std::sync::Arc<crate::stats::Stats>) {
+        self.stats = Some(stats.clone());
+    }
+
+    /// Rebases the reader so that its current position is reported as `offset`.
+    ///
+    /// `offset` may be below the current position. An existing limit is shifted by the same
+    /// amount, so it still covers the same bytes, saturating at `u64::MAX` and `0`.
+    pub fn with_base_offset(mut self, offset: u64) -> Self {
+        self.limit_bytes = self.limit_bytes.map(|limit| rebase_limit(limit, self.total_bytes_read, offset));
+        self.total_bytes_read = offset;
+        self
+    }
+
+    /// Applies the options collected in `builder`.
+    pub(crate) fn with_options(mut self, builder: &crate::builder::BinaryReaderBuilder<E>) -> Self {
+        self.limit_bytes = builder.limit_for(self.limit_bytes);
+        self.max_alloc = builder.max_alloc;
+        self.max_depth = builder.max_depth;
+        self.strict_strings = builder.strict_strings;
+        #[cfg(feature = "stats")]
+        if let Some(stats) = &builder.stats {
+            self.attach_stats(stats);
+        }
+        self.with_base_offset(builder.base_offset)
+    }
+
+    /// Returns the number of bytes consumed so far.
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.total_bytes_read
+    }
+
+    /// Returns the number of bytes left before the active limit, or `None` if unlimited.
+    #[inline]
+    pub fn remaining(&self) -> Option<u64> {
+        self.limit_bytes.map(|limit| limit.saturating_sub(self.total_bytes_read))
+    }
+
+    /// Returns `true` if an earlier I/O failure left the stream out of step with the position.
+    ///
+    /// While poisoned, every read fails with `ReadError::Desynchronized`.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned_at.is_some()
+    }
+
+    /// Lets reads proceed again after a failure, e.g. once a recovery parser has resynced on a magic.
+    ///
+    /// The position is left as it was before the failed read, which may lag behind the stream.
+    pub fn clear_poison(&mut self) {
+        self.poisoned_at = None;
+    }
+
+    /// Limits how deeply `read`, `read_with` and `read_partial` calls may nest, see
+    /// `BinaryReader::set_max_depth`.
+    pub fn set_max_depth(&mut self, max_depth: u32) {
+        self.max_depth = Some(max_depth);
+    }
+
+    /// Returns `true` if the string readers reject invalid sequences, see
+    /// `BinaryReaderBuilder::strict_strings`.
+    pub(crate) fn strict_strings(&self) -> bool {
+        self.strict_strings
+    }
+
+    /// Fails if allocating `bytes` up front would exceed the `max_alloc` the reader was built with.
+    #[inline]
+    pub(crate) fn check_alloc(&self, bytes: usize) -> Result<()> {
+        match self.max_alloc {
+            Some(max_alloc) if bytes > max_alloc => Err(ReadError::AllocationLimitExceeded {
+                requested: bytes,
+                max_alloc,
+                offset: self.total_bytes_read,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    #[inline]
+    fn enter_level(&mut self) -> Result<()> {
+        if let Some(max_depth) = self.max_depth {
+            if self.depth >= max_depth {
+                return Err(ReadError::DepthLimitExceeded {
+                    max_depth,
+                    offset: self.total_bytes_read,
+                });
+            }
+            self.depth += 1;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn exit_level(&mut self) {
+        if self.max_depth.is_some() {
+            self.depth = self.depth.saturating_sub(1);
+        }
     }
 }
 
+macro_rules! read_deinterleaved_fns {
+    ( $( fn $name:ident($($col:ident: $C:ident),+); )* ) => {
+        $(
+            pub async fn $name<$($C: AsyncRead),+>(&mut self, rows: usize, $($col: &mut Vec<$C>),+) -> Result<()> {
+                $($col.reserve(rows);)+
+                for index in 0..rows {
+                    let offset = self.total_bytes_read;
+                    let row: Result<()> = async {
+                        $($col.push(self.read().await?);)+
+                        Ok(())
+                    }
+                    .await;
+                    row.map_err(|e| ReadError::InElement { index, offset, source: Box::new(e) })?;
+                }
+                Ok(())
+            }
+        )*
+    };
+}
+
 impl<E: Endian, R: ReaderBase> AsyncBinaryReader<E, R> {
+    fn poison(&mut self, error: std::io::Error) -> ReadError {
+        // `read_exact` may have consumed part of the value before failing
+        self.poisoned_at = Some(self.total_bytes_read);
+        ReadError::io_error(error)
+    }
+    /// Returns the stream as reads see it, starting with any lookahead a `peek` staged.
+    fn source(&mut self) -> Staged<'_, R> {
+        Staged {
+            pending: &mut self.pending,
+            reader: &mut self.reader,
+        }
+    }
+    /// Fails, without reading anything, unless `len` more bytes may be read before the limit,
+    /// see `BinaryReader::ensure`.
+    #[inline]
+    pub fn ensure(&self, len: usize) -> Result<()> {
+        self.check_size(len)
+    }
+
     #[inline]
     fn check_size(&self, len: usize) -> Result<()> {
+        self.check_len(len as u64)
+    }
+    #[inline]
+    fn check_len(&self, len: u64) -> Result<()> {
+        if let Some(at_offset) = self.poisoned_at {
+            return Err(ReadError::Desynchronized { at_offset });
+        }
         if let Some(limit) = self.limit_bytes {
-            if self.total_bytes_read + len > limit {
-                return Err(ReadError::not_enough_bytes(len, limit - self.total_bytes_read));
+            if self.total_bytes_read > limit {
+                // A nested `read_partial` declared more bytes than the enclosing limit allowed.
+                return Err(ReadError::PositionBeyondLimit {
+                    position: self.total_bytes_read,
+                    limit,
+                });
+            }
+            if self.total_bytes_read.saturating_add(len) > limit {
+                return Err(ReadError::not_enough_bytes(usize::try_from(len).unwrap_or(usize::MAX), limit, self.total_bytes_read));
             }
         }
         Ok(())
     }
+    fn copy_error(&mut self, error: std::io::Error, consumed: bool) -> ReadError {
+        if consumed { self.poison(error) } else { ReadError::io_error(error) }
+    }
+    /// Reads `len` bytes, applies `parse`, and returns the parsed value. A panic in `parse`
+    /// poisons the reader at the start of the slice, as for `BinaryReader::read_from_slice`.
     #[inline]
-    pub async fn read_from_slice<T>(&mut self, len: usize, parse: impl Fn(&[u8]) -> Result<T>) -> Result<T> {
+    pub async fn read_from_slice<T>(&mut self, len: usize, parse: impl FnOnce(&[u8]) -> Result<T>) -> Result<T> {
         self.check_size(len)?;
-        const POPULAR_BUF_SIZE: usize = 512;
-        if len <= POPULAR_BUF_SIZE {
-            let mut buf = [0u8; POPULAR_BUF_SIZE];
-            self.reader.read_exact(&mut buf[..len]).await.map_err(ReadError::io_error)?;
-            self.total_bytes_read += len;
-            parse(&buf[..len])
+        let start = self.total_bytes_read;
+        if len <= DEFAULT_SMALL_BUF_SIZE {
+            let mut buf = [0u8; DEFAULT_SMALL_BUF_SIZE];
+            retry::read_exact(&mut self.source(), &mut buf[..len]).await.map_err(|e| self.poison(e))?;
+            self.total_bytes_read += len as u64;
+            record_stats!(self, Bulk, len);
+            parse_or_poison(&mut self.poisoned_at, start, &buf[..len], parse)
         } else {
+            self.check_alloc(len)?;
             let mut buf = vec![0u8; len];
-            self.reader.read_exact(&mut buf).await.map_err(ReadError::io_error)?;
-            self.total_bytes_read += len;
-            parse(&buf)
+            retry::read_exact(&mut self.source(), &mut buf).await.map_err(|e| self.poison(e))?;
+            self.total_bytes_read += len as u64;
+            record_stats!(self, Bulk, len);
+            parse_or_poison(&mut self.poisoned_at, start, &buf, parse)
         }
     }
 
-    pub async fn read_from_array<T, const N: usize>(&mut self, parse: impl Fn(&[u8; N]) -> T) -> Result<T> {
+    pub async fn read_from_array<T, const N: usize>(&mut self, parse: impl FnOnce(&[u8; N]) -> T) -> Result<T> {
         self.check_size(N)?;
         let mut buf = [0u8; N];
-        self.reader.read_exact(&mut buf).await.map_err(ReadError::io_error)?;
-        self.total_bytes_read += N;
+        retry::read_exact(&mut self.source(), &mut buf).await.map_err(|e| self.poison(e))?;
+        self.total_bytes_read += N as u64;
+        record_stats!(self, Primitive, N);
         Ok(parse(&buf))
     }
 
@@ -154,15 +583,129 @@ impl<E: Endian, R: ReaderBase> AsyncBinaryReader<E, R> {
         let mut buf = [0u8; N];
         loop {
             self.check_size(N)?;
-            self.reader.read_exact(&mut buf).await.map_err(ReadError::io_error)?;
-            self.total_bytes_read += N;
+            retry::read_exact(&mut self.source(), &mut buf).await.map_err(|e| self.poison(e))?;
+            self.total_bytes_read += N as u64;
             match try_parse(&buf) {
+                Some(_) if N == 0 => return Err(ReadError::no_progress()),
                 Some(value) => values.push(value),
                 None => break,
             }
         }
+        record_stats!(self, Bulk, (values.len() + 1) * N);
+        Ok(values)
+    }
+    pub async fn read_until<T: AsyncRead + PartialEq + Send + Sync>(&mut self, terminator: &T) -> Result<Vec<T>> {
+        let mut values = Vec::new();
+        loop {
+            let start = self.total_bytes_read;
+            let value = T::read(self).await?;
+            if value == *terminator {
+                return Ok(values);
+            }
+            if self.total_bytes_read == start {
+                return Err(ReadError::no_progress());
+            }
+            values.push(value);
+        }
+    }
+    async fn at_end(&mut self) -> Result<bool> {
+        if self.remaining() == Some(0) {
+            return Ok(true);
+        }
+        retry::fill_buf_len(&mut self.source()).await.map(|available| available == 0).map_err(|e| self.poison(e))
+    }
+    pub async fn read_if_remaining<T: AsyncRead>(&mut self) -> Result<Option<T>> {
+        self.check_size(0)?;
+        if self.at_end().await? {
+            return Ok(None);
+        }
+        self.read().await.map(Some)
+    }
+    pub async fn read_remaining_or_default<T: AsyncRead + Default>(&mut self) -> Result<T> {
+        self.read_if_remaining().await.map(Option::unwrap_or_default)
+    }
+    /// Reads a `T` from the buffered data without consuming it, like `BinaryReader::peek`.
+    ///
+    /// Every source is an `AsyncBufRead`, so a `T` of up to 16 bytes is staged across a refill
+    /// like there. Larger values have to fit what the source holds: wrap a bare socket or file in
+    /// a `tokio::io::BufReader` at least as large as the largest peek, or a `T` that runs past the
+    /// buffer fails with `ReadError::PeekBeyondBuffer`.
+    pub async fn peek<T: AsyncRead>(&mut self) -> Result<T> {
+        self.check_size(0)?;
+        let offset = self.total_bytes_read;
+        let remaining = self.remaining();
+        let settings = (self.max_depth, self.depth, self.max_alloc, self.strict_strings);
+        retry::fill_buf_len(&mut self.source()).await.map_err(|e| self.poison(e))?;
+        let buf = match self.source().into_async_buf().await {
+            Ok(buf) => buf,
+            Err(e) => return Err(self.poison(e)),
+        };
+        let (window, at_limit) = peek_window(buf, remaining);
+        match Self::peek_in(window, offset, settings).await.map_err(|e| peek_error(e, offset, window.len(), at_limit)) {
+            Err(ReadError::PeekBeyondBuffer { buffered, .. }) if buffered < Pending::CAPACITY => {}
+            result => return result,
+        }
+        // The value straddles the buffer: stage it and read on into the next refill.
+        let max = remaining.map_or(usize::MAX, |remaining| usize::try_from(remaining).unwrap_or(usize::MAX));
+        let mut ended = false;
+        while !ended && !self.pending.spare(max).is_empty() {
+            match retry::read(&mut self.reader, self.pending.spare(max)).await {
+                Ok(0) => ended = true,
+                Ok(n) => self.pending.grow(n),
+                Err(e) => return Err(self.poison(e)),
+            }
+        }
+        let (window, at_limit) = peek_window(self.pending.lookahead(), remaining);
+        Self::peek_in(window, offset, settings).await.map_err(|e| match e {
+            ReadError::NotEnoughBytes { limit, .. } if ended && limit == offset + window.len() as u64 => ReadError::io_error(std::io::ErrorKind::UnexpectedEof.into()),
+            e => peek_error(e, offset, window.len(), at_limit),
+        })
+    }
+    /// Parses a `T` from `window`, the bytes at `offset`, under the nesting and allocation
+    /// `settings` of the peeking reader.
+    async fn peek_in<T: AsyncRead>(window: &[u8], offset: u64, settings: (Option<u32>, u32, Option<usize>, bool)) -> Result<T> {
+        let mut peeker = AsyncBinaryReader::<E, &[u8]>::from_bytes(window);
+        peeker.total_bytes_read = offset;
+        peeker.limit_bytes = Some(offset + window.len() as u64);
+        (peeker.max_depth, peeker.depth, peeker.max_alloc, peeker.strict_strings) = settings;
+        T::read(&mut peeker).await
+    }
+    pub async fn peek_u8(&mut self) -> Result<u8> {
+        self.peek().await
+    }
+    pub async fn peek_u16(&mut self) -> Result<u16> {
+        self.peek().await
+    }
+    pub async fn peek_u32(&mut self) -> Result<u32> {
+        self.peek().await
+    }
+    pub async fn read_all<T: AsyncRead>(&mut self) -> Result<Vec<T>> {
+        let mut values = Vec::new();
+        while let Some((_, value)) = self.read_next_indexed().await? {
+            values.push(value);
+        }
         Ok(values)
     }
+    /// Returns a cursor over the values of `T` up to the limit or the end of the stream, each with
+    /// the position it started at; the async counterpart of `BinaryReader::read_indexed_iter`.
+    pub fn read_indexed_iter<T: AsyncRead>(&mut self) -> IndexedRecords<'_, E, R, T> {
+        IndexedRecords {
+            reader: self,
+            done: false,
+            _record: PhantomData,
+        }
+    }
+    async fn read_next_indexed<T: AsyncRead>(&mut self) -> Result<Option<(u64, T)>> {
+        if self.at_end().await? {
+            return Ok(None);
+        }
+        let start = self.total_bytes_read;
+        let value = T::read(self).await?;
+        if self.total_bytes_read == start {
+            return Err(ReadError::no_progress());
+        }
+        Ok(Some((start, value)))
+    }
     #[inline]
     pub async fn u8(&mut self) -> Result<u8> {
         self.read_from_array(|b: &[u8; 1]| b[0]).await
@@ -203,30 +746,100 @@ impl<E: Endian, R: ReaderBase> AsyncBinaryReader<E, R> {
     pub async fn f64(&mut self) -> Result<f64> {
         self.read_from_array(E::f64_from_bytes).await
     }
+    #[inline]
+    pub async fn u128(&mut self) -> Result<u128> {
+        self.read_from_array(E::u128_from_bytes).await
+    }
+    #[inline]
+    pub async fn i128(&mut self) -> Result<i128> {
+        self.read_from_array(E::i128_from_bytes).await
+    }
+    #[inline]
+    pub async fn usize_from_u16(&mut self) -> Result<usize> {
+        self.u16().await.map(usize::from)
+    }
+    #[inline]
+    pub async fn usize_from_u32(&mut self) -> Result<usize> {
+        let offset = self.total_bytes_read;
+        widen(self.u32().await?, offset)
+    }
+    #[inline]
+    pub async fn usize_from_u64(&mut self) -> Result<usize> {
+        let offset = self.total_bytes_read;
+        widen(self.u64().await?, offset)
+    }
+
+    #[inline]
+    pub async fn read_uint(&mut self, width: IntWidth) -> Result<u64> {
+        match width {
+            IntWidth::U8 => self.u8().await.map(u64::from),
+            IntWidth::U16 => self.u16().await.map(u64::from),
+            IntWidth::U32 => self.u32().await.map(u64::from),
+            IntWidth::U64 => self.u64().await,
+        }
+    }
+
+    #[inline]
+    pub async fn read_num<T: Primitive + Send>(&mut self) -> Result<T> {
+        self.read_num_as::<E, T>().await
+    }
 
+    #[inline]
+    pub async fn read_num_as<B: Endian, T: Primitive + Send>(&mut self) -> Result<T> {
+        let mut bytes = T::Bytes::default();
+        let buf = bytes.as_mut();
+        self.check_size(buf.len())?;
+        retry::read_exact(&mut self.source(), buf).await.map_err(|e| self.poison(e))?;
+        self.total_bytes_read += buf.len() as u64;
+        record_stats!(self, Primitive, buf.len());
+        Ok(T::from_bytes::<B>(bytes))
+    }
+    read_deinterleaved_fns! {
+        fn read_deinterleaved2(a: A, b: B);
+        fn read_deinterleaved3(a: A, b: B, c: C);
+        fn read_deinterleaved4(a: A, b: B, c: C, d: D);
+    }
     #[inline]
     pub async fn read<T: AsyncRead>(&mut self) -> Result<T> {
-        T::read(self).await
+        self.enter_level()?;
+        let result = T::read(self).await;
+        self.exit_level();
+        result
     }
     #[inline]
     pub async fn read_with<A, T: AsyncReadWith<A>>(&mut self, arg: A) -> Result<T> {
-        T::read_with(self, arg).await
+        self.enter_level()?;
+        let result = T::read_with(self, arg).await;
+        self.exit_level();
+        result
+    }
+    #[inline]
+    pub async fn read_into<T: AsyncReadInto>(&mut self, target: &mut T) -> Result<()> {
+        target.read_into(self).await
+    }
+    #[inline]
+    pub async fn read_into_with<A, T: AsyncReadIntoWith<A>>(&mut self, target: &mut T, arg: A) -> Result<()> {
+        target.read_into_with(self, arg).await
     }
 
     #[inline]
     pub async fn value<T: AsyncRead + PartialEq + Debug>(&mut self, expected: &T) -> Result<()> {
+        let offset = self.total_bytes_read;
         let actual = self.read::<T>().await?;
         if actual == *expected {
             Ok(())
         } else {
-            Err(ReadError::invalid_data_format(format!("Expected {:?}, got {:?}", expected, actual)))
+            Err(ReadError::unexpected_value(expected, &actual, offset))
         }
     }
 
-    #[inline]
-    pub async fn values<T: AsyncRead + PartialEq + Debug>(&mut self, expected: &[T]) -> Result<()> {
-        for e in expected {
-            self.value(e).await?;
+    pub async fn values<T: AsyncRead + PartialEq + Debug + 'static>(&mut self, expected: &[T]) -> Result<()> {
+        if let Some(bytes) = byte_run(expected) {
+            return self.magic(bytes).await;
+        }
+        for (index, e) in expected.iter().enumerate() {
+            let offset = self.total_bytes_read;
+            self.value(e).await.map_err(|e| mismatch_in_element(e, index, offset))?;
         }
         Ok(())
     }
@@ -234,10 +847,8 @@ impl<E: Endian, R: ReaderBase> AsyncBinaryReader<E, R> {
     #[inline]
     pub async fn reserved<const N: usize>(&mut self, expected_value: u8) -> Result<()> {
         self.read_from_array(|buf: &[u8; N]| {
-            for &byte in buf {
-                if byte != expected_value {
-                    return Err(ReadError::invalid_data_format(format!("Expected {:?}, got {:?}", expected_value, byte)));
-                }
+            if buf.iter().any(|&byte| byte != expected_value) {
+                return Err(ReadError::expected_mismatch(&[expected_value; N], buf));
             }
             Ok(())
         })
@@ -245,47 +856,492 @@ impl<E: Endian, R: ReaderBase> AsyncBinaryReader<E, R> {
     }
 
     #[inline]
-    pub async fn skip(&mut self, len: usize) -> Result<()> {
-        self.read_from_slice(len, |_| Ok(())).await
+    pub async fn reserved_report<const N: usize>(&mut self, expected_value: u8) -> Result<Vec<(usize, u8)>> {
+        self.reserved_report_len(N, expected_value).await
     }
 
-    #[inline]
-    pub async fn skip_aligned(&mut self, align: usize) -> Result<()> {
-        let remainder = self.total_bytes_read % align;
-        if remainder != 0 {
-            self.skip(align - remainder).await?;
-        }
-        Ok(())
+    pub async fn reserved_report_len(&mut self, len: usize, expected_value: u8) -> Result<Vec<(usize, u8)>> {
+        self.read_from_slice(len, |data| Ok(reserved_violations(data, expected_value))).await
     }
 
     #[inline]
-    pub async fn read_partial<T: AsyncRead>(&mut self, len: usize) -> Result<T> {
-        let original_limit = self.limit_bytes; // Save the original limit
-        self.limit_bytes = Some(len + self.total_bytes_read);
-        let result = T::read(self).await?;
-        self.limit_bytes = original_limit; // Restore the original limit
-        Ok(result)
+    pub async fn reserved_for<T: FixedSize>(&mut self, expected_value: u8) -> Result<()> {
+        self.read_from_slice(T::SIZE, |data| {
+            if data.iter().any(|&byte| byte != expected_value) {
+                return Err(ReadError::expected_mismatch(&vec![expected_value; data.len()], data));
+            }
+            Ok(())
+        })
+        .await
     }
 
     #[inline]
-    pub async fn read_partial_with<A, T: AsyncReadWith<A>>(&mut self, len: usize, arg: A) -> Result<T> {
-        let original_limit = self.limit_bytes; // Save the original limit
-        self.limit_bytes = Some(len + self.total_bytes_read);
-        let result = T::read_with(self, arg).await?;
-        self.limit_bytes = original_limit; // Restore the original limit
-        Ok(result)
+    pub async fn magic(&mut self, expected: impl AsRef<[u8]>) -> Result<()> {
+        let expected = expected.as_ref();
+        self.read_from_slice(expected.len(), |data| {
+            if data != expected {
+                return Err(ReadError::expected_mismatch(expected, data));
+            }
+            Ok(())
+        })
+        .await
     }
-}
 
-macro_rules! impl_readable_for_numeric_primitives {
-    ( $( $t:ty: $func:ident ),* $(,)? ) => {
-        $(
-            impl AsyncRead for $t {
-                #[inline]
-                async fn read<E: Endian, R: ReaderBase>(
-                    reader: &mut AsyncBinaryReader<E, R>
-                ) -> Result<Self> {
-                    reader.$func().await
+    pub async fn version(&mut self, supported: impl Into<VersionRange>) -> Result<Version> {
+        let (supported, offset) = (supported.into(), self.total_bytes_read);
+        let found = self.read::<Version>().await?;
+        if !supported.contains(found) {
+            return Err(ReadError::UnsupportedVersion { found, supported, offset });
+        }
+        Ok(found)
+    }
+
+    pub async fn verify_trailing_checksum(&mut self, kind: ChecksumKind) -> Result<()> {
+        let remaining = self.remaining().ok_or(ReadError::InvalidArgument)?;
+        let mut hasher = ChecksumWriter::new(tokio::io::sink(), kind);
+        self.copy_to(&mut hasher, remaining.saturating_sub(kind.digest_len() as u64)).await?;
+        let offset = self.total_bytes_read;
+        let (stored, computed) = (self.u32().await?, hasher.checksum().value());
+        if stored != computed {
+            return Err(ReadError::ChecksumMismatch { kind, stored, computed, offset });
+        }
+        Ok(())
+    }
+
+    pub async fn skip(&mut self, len: usize) -> Result<()> {
+        count_stats!(self, Skip, async {
+            self.check_size(len)?;
+            let mut left = len;
+            while left > 0 {
+                // consume straight out of the buffer instead of allocating `len` bytes
+                let available = retry::fill_buf_len(&mut self.source()).await.map_err(|e| self.poison(e))?;
+                if available == 0 {
+                    return Err(self.poison(std::io::ErrorKind::UnexpectedEof.into()));
+                }
+                let chunk = available.min(left);
+                self.source().consume(chunk);
+                left -= chunk;
+            }
+            self.total_bytes_read += len as u64;
+            Ok(())
+        })
+    }
+
+    pub async fn copy_to(&mut self, dst: &mut (impl tokio::io::AsyncWrite + Unpin + Send), len: u64) -> Result<()> {
+        count_stats!(self, Bulk, async {
+            self.check_len(len)?;
+            let mut left = len;
+            while left > 0 {
+                let consumed = left < len;
+                let available = retry::fill_buf_len(&mut self.source()).await.map_err(|e| self.copy_error(e, consumed))?;
+                if available == 0 {
+                    return Err(self.copy_error(std::io::ErrorKind::UnexpectedEof.into(), consumed));
+                }
+                let chunk = (available as u64).min(left) as usize;
+                let buf = match self.source().into_async_buf().await {
+                    Ok(buf) => &buf[..chunk],
+                    Err(e) => return Err(self.copy_error(e, consumed)),
+                };
+                if let Err(e) = retry::write_all(dst, buf).await {
+                    return Err(self.copy_error(e, consumed));
+                }
+                self.source().consume(chunk);
+                self.total_bytes_read += chunk as u64;
+                left -= chunk as u64;
+            }
+            Ok(())
+        })
+    }
+
+    pub async fn skip_to(&mut self, pos: u64) -> Result<()> {
+        if pos < self.total_bytes_read {
+            return Err(ReadError::InvalidArgument);
+        }
+        count_stats!(self, Skip, async {
+            let mut left = pos - self.total_bytes_read;
+            while left > 0 {
+                let chunk = left.min(usize::MAX as u64) as usize;
+                self.skip(chunk).await?;
+                left -= chunk as u64;
+            }
+            Ok(())
+        })
+    }
+
+    pub async fn skip_records<T: FixedSize>(&mut self, n: usize) -> Result<()> {
+        let len = (n as u64).checked_mul(T::SIZE as u64).ok_or(ReadError::InvalidArgument)?;
+        self.skip_to(self.total_bytes_read.checked_add(len).ok_or(ReadError::InvalidArgument)?).await
+    }
+
+    pub async fn read_array<T: AsyncRead + Send, const N: usize>(&mut self) -> Result<[T; N]> {
+        let mut array = ArrayBuilder::new();
+        for _ in 0..N {
+            array.push(T::read(self).await?);
+        }
+        Ok(array.finish())
+    }
+
+    pub async fn read_vec_validated<T: AsyncRead + Send>(&mut self, len: usize, validate: impl Fn(usize, &T) -> Result<()>) -> Result<Vec<T>> {
+        self.check_alloc(len.saturating_mul(size_of::<T>()))?;
+        let mut values = Vec::with_capacity(len);
+        for index in 0..len {
+            let offset = self.total_bytes_read;
+            let value = T::read(self)
+                .await
+                .and_then(|value| validate(index, &value).map(|()| value))
+                .map_err(|e| ReadError::InElement { index, offset, source: Box::new(e) })?;
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    pub async fn read_vec_prefixed_chunked<P, T>(&mut self, continuation_flag: u8) -> Result<Vec<T>>
+    where
+        P: AsyncRead + TryInto<usize> + Send,
+        T: AsyncRead + Send,
+    {
+        let mut values = Vec::new();
+        loop {
+            let count = self.read::<P>().await?.try_into().map_err(|_| ReadError::invalid_data_format("chunk count does not fit in usize"))?;
+            for _ in 0..count {
+                values.push(T::read(self).await?);
+            }
+            match self.u8().await? {
+                0 => return Ok(values),
+                flag if flag == continuation_flag => continue,
+                flag => return Err(ReadError::invalid_data_format(format!("unexpected chunk flag {flag:#04x}"))),
+            }
+        }
+    }
+
+    pub async fn read_records<T: AsyncRead + FixedSize + Send>(&mut self, n: usize) -> Result<Vec<T>> {
+        let len = n.checked_mul(T::SIZE).ok_or(ReadError::InvalidArgument)?;
+        self.check_size(len)?;
+        count_stats!(self, Bulk, async { self.read_with(n).await })
+    }
+
+    #[inline]
+    pub async fn skip_aligned(&mut self, align: usize) -> Result<()> {
+        if align == 0 {
+            return Err(ReadError::InvalidArgument);
+        }
+        let remainder = (self.total_bytes_read % align as u64) as usize;
+        if remainder != 0 {
+            self.skip(align - remainder).await?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub async fn read_partial<T: AsyncRead>(&mut self, len: usize) -> Result<T> {
+        self.enter_level()?;
+        let original_limit = self.limit_bytes; // Save the original limit
+        self.limit_bytes = Some(len as u64 + self.total_bytes_read);
+        let result = T::read(self).await;
+        self.limit_bytes = original_limit; // Restore the original limit, also on error
+        self.exit_level();
+        result
+    }
+
+    #[inline]
+    pub async fn read_partial_with<A, T: AsyncReadWith<A>>(&mut self, len: usize, arg: A) -> Result<T> {
+        self.enter_level()?;
+        let original_limit = self.limit_bytes; // Save the original limit
+        self.limit_bytes = Some(len as u64 + self.total_bytes_read);
+        let result = T::read_with(self, arg).await;
+        self.limit_bytes = original_limit; // Restore the original limit, also on error
+        self.exit_level();
+        result
+    }
+
+    /// Returns a sub-reader that can read at most `len` more bytes, enforced by `tokio::io::Take`
+    /// underneath as well as by the logical limit. This reader's position catches up when it is dropped.
+    pub fn by_ref_take(&mut self, len: u64) -> AsyncTakeReader<'_, E, R> {
+        let end = self.total_bytes_read.saturating_add(len);
+        AsyncTakeReader {
+            inner: AsyncBinaryReader {
+                // staged lookahead is served first and counts toward `len`
+                reader: (&mut self.reader).take(len.saturating_sub(self.pending.lookahead().len() as u64)),
+                total_bytes_read: self.total_bytes_read,
+                limit_bytes: Some(self.limit_bytes.map_or(end, |limit| limit.min(end))),
+                poisoned_at: self.poisoned_at,
+                max_depth: self.max_depth,
+                depth: self.depth,
+                max_alloc: self.max_alloc,
+                strict_strings: self.strict_strings,
+                pending: self.pending,
+                #[cfg(feature = "stats")]
+                stats: self.stats.clone(),
+                _endian: PhantomData,
+            },
+            parent_position: &mut self.total_bytes_read,
+            parent_poisoned_at: &mut self.poisoned_at,
+            parent_pending: &mut self.pending,
+        }
+    }
+
+    #[inline]
+    /// Reads a `T`, failing with `ReadError::TimedOut` if it has not completed within `timeout`.
+    ///
+    /// A timeout before any byte arrived leaves the reader untouched. Once the read has started,
+    /// part of the value may already be consumed, so the reader is poisoned at the current position.
+    pub async fn read_timeout<T: AsyncRead>(&mut self, timeout: Duration) -> Result<T> {
+        let deadline = Instant::now() + timeout;
+        self.check_size(0)?;
+        // `fill_buf` consumes nothing, so giving up here keeps the stream in step
+        if tokio::time::timeout_at(deadline, self.source().fill_buf()).await.is_err() {
+            return Err(ReadError::TimedOut);
+        }
+        let (limit_bytes, depth) = (self.limit_bytes, self.depth);
+        #[cfg(feature = "stats")]
+        let stats = self.stats.clone();
+        match tokio::time::timeout_at(deadline, T::read(self)).await {
+            Ok(result) => result,
+            Err(_) => {
+                // the abandoned read may have been inside `read_partial`, a nested `read` or `count_stats!`
+                (self.limit_bytes, self.depth) = (limit_bytes, depth);
+                #[cfg(feature = "stats")]
+                {
+                    self.stats = stats;
+                }
+                self.poisoned_at = Some(self.total_bytes_read);
+                Err(ReadError::TimedOut)
+            }
+        }
+    }
+    /// Reads a `u32` length-prefixed frame like `read_frame`, with a deadline over the whole frame.
+    pub async fn read_frame_timeout<T: AsyncRead>(&mut self, timeout: Duration) -> Result<T> {
+        self.read_timeout::<Frame<T>>(timeout).await.map(|frame| frame.0)
+    }
+    pub async fn read_frame<T: AsyncRead>(&mut self) -> Result<T> {
+        self.read_frame_limited(DEFAULT_MAX_FRAME_SIZE).await
+    }
+
+    pub async fn read_frame_limited<T: AsyncRead>(&mut self, max_size: u32) -> Result<T> {
+        let len = self.u32().await?;
+        if len > max_size {
+            return Err(ReadError::invalid_data_format(format!("frame of {len} bytes exceeds the maximum of {max_size}")));
+        }
+        self.check_size(len as usize)?;
+        let end = self.total_bytes_read + len as u64;
+        let result = self.read_partial(len as usize).await?;
+        if self.total_bytes_read != end {
+            return Err(ReadError::invalid_data_format(format!("frame of {len} bytes left {} bytes unread", end - self.total_bytes_read)));
+        }
+        Ok(result)
+    }
+    pub async fn read_len_prefixed<L: AsyncRead + FixedSize + Into<u64>, T: AsyncRead>(&mut self, policy: LenPolicy) -> Result<T> {
+        let len = policy.payload_len(self.read::<L>().await?.into(), L::SIZE)?;
+        self.check_size(len)?;
+        let end = self.total_bytes_read + len as u64;
+        let result = self.read_partial(len).await?;
+        if self.total_bytes_read != end {
+            return Err(ReadError::invalid_data_format(format!("payload of {len} bytes left {} bytes unread", end - self.total_bytes_read)));
+        }
+        Ok(result)
+    }
+}
+
+impl<E: Endian, R: ReaderBase + tokio::io::AsyncSeek> AsyncBinaryReader<E, R> {
+    pub async fn seek_record<T: FixedSize>(&mut self, base: u64, index: u64) -> Result<()> {
+        let target = index.checked_mul(T::SIZE as u64).and_then(|offset| offset.checked_add(base)).ok_or(ReadError::InvalidArgument)?;
+        self.seek_to(target).await
+    }
+
+    pub async fn seek_to(&mut self, target: u64) -> Result<()> {
+        if let Some(limit) = self.limit_bytes
+            && target > limit
+        {
+            return Err(ReadError::PositionBeyondLimit { position: target, limit });
+        }
+        // the stream is ahead of the position by whatever a peek staged
+        let stream_pos = i128::from(self.total_bytes_read) + self.pending.lookahead().len() as i128;
+        let delta = i64::try_from(i128::from(target) - stream_pos).map_err(|_| ReadError::InvalidArgument)?;
+        self.reader.seek(std::io::SeekFrom::Current(delta)).await.map_err(|e| self.poison(e))?;
+        self.total_bytes_read = target;
+        self.poisoned_at = None;
+        self.pending = Pending::EMPTY;
+        Ok(())
+    }
+
+    pub async fn seek_relative(&mut self, delta: i64) -> Result<()> {
+        let target = self.total_bytes_read.checked_add_signed(delta).ok_or(ReadError::InvalidArgument)?;
+        self.seek_to(target).await
+    }
+
+    /// Skips forward to the next occurrence of `pattern` and returns the number of bytes skipped,
+    /// leaving the reader at the first byte of the match, like `BinaryReader::scan_for`.
+    pub async fn scan_for(&mut self, pattern: &[u8], max_scan: Option<usize>) -> Result<u64> {
+        if pattern.is_empty() {
+            return Err(ReadError::InvalidArgument);
+        }
+        self.check_size(0)?;
+        let start = self.total_bytes_read;
+        let mut end = max_scan.map_or(u64::MAX, |max| start.saturating_add(max as u64).saturating_add(pattern.len() as u64));
+        if let Some(limit) = self.limit_bytes {
+            end = end.min(limit);
+        }
+        count_stats!(self, Skip, async {
+            let mut scan = PatternScan::new(pattern);
+            loop {
+                let available = retry::fill_buf_len(&mut self.source()).await.map_err(|e| self.poison(e))?;
+                let window = (end - self.total_bytes_read).min(available as u64) as usize;
+                if window == 0 {
+                    return Err(ReadError::PatternNotFound {
+                        offset: start,
+                        scanned: self.total_bytes_read - start,
+                    });
+                }
+                let step = match self.source().into_async_buf().await {
+                    Ok(buf) => scan.step(&buf[..window]),
+                    Err(e) => return Err(self.poison(e)),
+                };
+                match step {
+                    ScanStep::At(offset) => {
+                        self.source().consume(offset);
+                        self.total_bytes_read += offset as u64;
+                        return Ok(self.total_bytes_read - start);
+                    }
+                    ScanStep::Straddling(back) => {
+                        self.reader.seek(std::io::SeekFrom::Current(-(back as i64))).await.map_err(|e| self.poison(e))?;
+                        self.total_bytes_read -= back as u64;
+                        return Ok(self.total_bytes_read - start);
+                    }
+                    ScanStep::Continue => {
+                        self.source().consume(window);
+                        self.total_bytes_read += window as u64;
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Parses a `T` from `bytes` with its `AsyncRead` impl, without an async runtime.
+///
+/// The future is polled on the current thread with a no-op waker, which only works because an
+/// in-memory slice never returns `Pending`. Panics if the impl awaits anything else that does,
+/// such as a timer or a channel.
+pub fn read_blocking<E: Endian, T: AsyncRead>(bytes: &[u8]) -> Result<T> {
+    let mut reader = AsyncBinaryReader::<E, _>::from_bytes(bytes);
+    let mut future = std::pin::pin!(T::read(&mut reader));
+    let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+    match future.as_mut().poll(&mut cx) {
+        std::task::Poll::Ready(result) => result,
+        std::task::Poll::Pending => panic!("read_blocking: reading {} awaited something other than the slice", std::any::type_name::<T>()),
+    }
+}
+
+/// Reads a `T` as a `read_frame` payload, so frames can go through `read_timeout`.
+struct Frame<T>(T);
+
+impl<T: AsyncRead> AsyncRead for Frame<T> {
+    async fn read<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>) -> Result<Self> {
+        reader.read_frame().await.map(Frame)
+    }
+}
+
+/// Records with their starting positions, returned by `AsyncBinaryReader::read_indexed_iter`.
+pub struct IndexedRecords<'r, E, R, T> {
+    reader: &'r mut AsyncBinaryReader<E, R>,
+    done: bool,
+    _record: PhantomData<fn() -> T>,
+}
+
+impl<E: Endian, R: ReaderBase, T: AsyncRead> IndexedRecords<'_, E, R, T> {
+    /// Reads the next record, or returns `None` once the end is reached or an error was returned.
+    pub async fn next_record(&mut self) -> Option<Result<(u64, T)>> {
+        if self.done {
+            return None;
+        }
+        let next = self.reader.read_next_indexed().await.transpose();
+        self.done = !matches!(next, Some(Ok(_)));
+        next
+    }
+}
+
+/// A sub-reader returned by `AsyncBinaryReader::by_ref_take`.
+impl<'a, R: tokio::io::AsyncBufRead + Unpin + ?Sized> Staged<'a, R> {
+    /// Like `fill_buf`, keeping the buffer borrowed for as long as the stream.
+    async fn into_async_buf(self) -> std::io::Result<&'a [u8]> {
+        let pending: &'a Pending = self.pending;
+        if pending.lookahead().is_empty() {
+            return self.reader.fill_buf().await;
+        }
+        Ok(pending.lookahead())
+    }
+}
+
+impl<R: tokio::io::AsyncBufRead + Unpin + ?Sized> tokio::io::AsyncRead for Staged<'_, R> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut tokio::io::ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        match this.pending.take_into(buf.initialize_unfilled()) {
+            0 => Pin::new(&mut *this.reader).poll_read(cx, buf),
+            n => {
+                buf.advance(n);
+                Poll::Ready(Ok(()))
+            }
+        }
+    }
+}
+
+impl<R: tokio::io::AsyncBufRead + Unpin + ?Sized> tokio::io::AsyncBufRead for Staged<'_, R> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<&[u8]>> {
+        let this = self.get_mut();
+        if this.pending.lookahead().is_empty() {
+            return Pin::new(&mut *this.reader).poll_fill_buf(cx);
+        }
+        Poll::Ready(Ok(this.pending.lookahead()))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.get_mut();
+        if this.pending.lookahead().is_empty() {
+            Pin::new(&mut *this.reader).consume(amt);
+        } else {
+            this.pending.consume(amt);
+        }
+    }
+}
+
+pub struct AsyncTakeReader<'p, E, R> {
+    inner: AsyncBinaryReader<E, tokio::io::Take<&'p mut R>>,
+    parent_position: &'p mut u64,
+    parent_poisoned_at: &'p mut Option<u64>,
+    parent_pending: &'p mut Pending,
+}
+
+impl<'p, E, R> std::ops::Deref for AsyncTakeReader<'p, E, R> {
+    type Target = AsyncBinaryReader<E, tokio::io::Take<&'p mut R>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<E, R> std::ops::DerefMut for AsyncTakeReader<'_, E, R> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl<E, R> Drop for AsyncTakeReader<'_, E, R> {
+    fn drop(&mut self) {
+        *self.parent_position = self.inner.total_bytes_read;
+        *self.parent_poisoned_at = self.inner.poisoned_at;
+        *self.parent_pending = self.inner.pending;
+    }
+}
+
+macro_rules! impl_readable_for_numeric_primitives {
+    ( $( $t:ty: $func:ident ),* $(,)? ) => {
+        $(
+            impl AsyncRead for $t {
+                #[inline]
+                async fn read<E: Endian, R: ReaderBase>(
+                    reader: &mut AsyncBinaryReader<E, R>
+                ) -> Result<Self> {
+                    reader.$func().await
                 }
             }
         )*
@@ -303,6 +1359,8 @@ impl_readable_for_numeric_primitives! {
     u64: u64,
     i64: i64,
     f64: f64,
+    u128: u128,
+    i128: i128,
 }
 
 impl AsyncRead for bool {
@@ -354,20 +1412,24 @@ impl_readable_for_tuples! {
     (T1, T2, T3, T4, T5, T6),
 }
 
-impl<T: AsyncRead + Send + Default, const N: usize> AsyncRead for [T; N] {
+impl<T: AsyncRead + Send, const N: usize> AsyncRead for [T; N] {
     #[inline]
     async fn read<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>) -> Result<Self> {
-        let mut arr = std::array::from_fn(|_| T::default());
-        for elem in arr.iter_mut() {
-            *elem = T::read(reader).await?;
-        }
-        Ok(arr)
+        reader.read_array().await
+    }
+}
+
+impl AsyncReadWith<IntWidth> for u64 {
+    #[inline]
+    async fn read_with<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>, width: IntWidth) -> Result<Self> {
+        reader.read_uint(width).await
     }
 }
 
 impl<T: AsyncRead + Send> AsyncReadWith<usize> for Vec<T> {
     #[inline]
     async fn read_with<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>, len: usize) -> Result<Self> {
+        reader.check_alloc(len.saturating_mul(size_of::<T>()))?;
         let mut vec = Vec::with_capacity(len);
         for _ in 0..len {
             vec.push(T::read(reader).await?);
@@ -376,20 +1438,858 @@ impl<T: AsyncRead + Send> AsyncReadWith<usize> for Vec<T> {
     }
 }
 
-impl<A: Send + Sync, T: for<'a> AsyncReadWith<&'a A> + Send> AsyncReadWith<(usize, &A)> for Vec<T> {
+impl<A: Clone + Send, T: AsyncReadWith<A> + Send> AsyncReadWith<(usize, A)> for Vec<T> {
     #[inline]
-    async fn read_with<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>, (len, arg): (usize, &A)) -> Result<Self> {
+    async fn read_with<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>, (len, arg): (usize, A)) -> Result<Self> {
+        reader.check_alloc(len.saturating_mul(size_of::<T>()))?;
         let mut vec = Vec::with_capacity(len);
         for _ in 0..len {
-            vec.push(T::read_with(reader, arg).await?);
+            vec.push(T::read_with(reader, arg.clone()).await?);
         }
         Ok(vec)
     }
 }
 
+macro_rules! impl_async_readable_for_slice_containers {
+    ( $( $container:ident ),* ) => {
+        $(
+            impl<T: AsyncRead + Send> AsyncReadWith<usize> for $container<[T]> {
+                #[inline]
+                async fn read_with<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>, len: usize) -> Result<Self> {
+                    Vec::<T>::read_with(reader, len).await.map(Into::into)
+                }
+            }
+
+            impl<A: Clone + Send, T: AsyncReadWith<A> + Send> AsyncReadWith<(usize, A)> for $container<[T]> {
+                #[inline]
+                async fn read_with<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>, arg: (usize, A)) -> Result<Self> {
+                    Vec::<T>::read_with(reader, arg).await.map(Into::into)
+                }
+            }
+        )*
+    };
+}
+
+impl_async_readable_for_slice_containers!(Box, Arc);
+
 #[tokio::test]
 async fn test_reserverd() {
     let buf: [u8; 4] = [0, 0, 0, 0];
     let mut reader = AsyncBinaryReader::from_be_bytes(&buf);
     reader.reserved::<4>(0x00).await.unwrap();
 }
+
+#[tokio::test]
+async fn test_skip_aligned_zero() {
+    let buf: [u8; 4] = [0, 2, 3, 4];
+    let mut reader = AsyncBinaryReader::from_le_bytes(&buf);
+    let align = reader.u8().await.unwrap() as usize;
+    assert!(matches!(reader.skip_aligned(align).await, Err(ReadError::InvalidArgument)));
+    assert_eq!(reader.u8().await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn test_partial_overshoot() {
+    struct Inner;
+    impl AsyncRead for Inner {
+        async fn read<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>) -> Result<Self> {
+            reader.read::<(u32, u32)>().await?;
+            Ok(Inner)
+        }
+    }
+    struct Middle;
+    impl AsyncRead for Middle {
+        async fn read<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>) -> Result<Self> {
+            reader.read_partial::<Inner>(8).await?;
+            reader.u8().await?;
+            Ok(Middle)
+        }
+    }
+    let buf = [0u8; 16];
+    let mut reader = AsyncBinaryReader::new_le(&buf[..]);
+    let result = reader.read_partial::<Middle>(2).await;
+    assert!(matches!(result, Err(ReadError::PositionBeyondLimit { position: 8, limit: 2 })));
+}
+
+#[tokio::test]
+async fn test_rebase_backwards() {
+    let buf: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+    let mut reader = AsyncBinaryReader::from_le_bytes(&buf).with_base_offset(100);
+    assert_eq!(reader.u16().await.unwrap(), 0x0201);
+    let mut reader = reader.with_base_offset(10);
+    assert_eq!((reader.position(), reader.remaining()), (10, Some(6)));
+    reader.skip(5).await.unwrap();
+    assert!(matches!(
+        reader.u16().await,
+        Err(ReadError::NotEnoughBytes {
+            remaining: 1,
+            limit: 16,
+            offset: 15,
+            ..
+        })
+    ));
+    assert_eq!(reader.u8().await.unwrap(), 8);
+
+    let mut reader = AsyncBinaryReader::from_le_bytes(&buf);
+    reader.skip(4).await.unwrap();
+    reader.limit_bytes = Some(3);
+    let reader = reader.with_base_offset(2);
+    assert_eq!(reader.limit_bytes, Some(1));
+    assert_eq!(AsyncBinaryReader::from_le_bytes(&buf).with_base_offset(u64::MAX).remaining(), Some(0));
+}
+
+#[tokio::test]
+async fn test_large_position() {
+    let buf: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+    let mut reader = AsyncBinaryReader::from_le_bytes(&buf).with_base_offset(u32::MAX as u64 - 1);
+    assert_eq!(reader.u8().await.unwrap(), 1);
+    assert_eq!(reader.position(), u32::MAX as u64);
+    reader.skip_aligned(4).await.unwrap();
+    assert_eq!(reader.position(), 1 << 32);
+    assert_eq!(reader.remaining(), Some(6));
+    let value: u16 = reader.read_as_be().await.unwrap();
+    assert_eq!(value, 0x0304);
+    assert_eq!(reader.position(), (1 << 32) + 2);
+    reader.skip_to((1 << 32) + 4).await.unwrap();
+    assert_eq!(reader.u16().await.unwrap(), 0x0807);
+    assert!(matches!(
+        reader.u8().await,
+        Err(ReadError::NotEnoughBytes { remaining: 0, offset, .. }) if offset == (1 << 32) + 6
+    ));
+}
+
+#[tokio::test]
+async fn test_magic_mismatch() {
+    let buf = *b"RIFX";
+    let mut reader = AsyncBinaryReader::from_le_bytes(&buf);
+    let err = reader.magic(b"RIFF").await.unwrap_err();
+    assert!(matches!(err, ReadError::ExpectedMismatch { first_diff: 3, .. }));
+}
+
+#[tokio::test]
+async fn test_read_records() {
+    let buf: [u8; 12] = [0, 1, 0, 2, 0, 3, 0, 4, 0, 5, 0, 6];
+    let mut reader = AsyncBinaryReader::from_be_bytes(&buf);
+    reader.skip_records::<(u16, u16)>(1).await.unwrap();
+    let records: Vec<(u16, u16)> = reader.read_records(2).await.unwrap();
+    assert_eq!(records, vec![(3, 4), (5, 6)]);
+    assert!(matches!(reader.skip_records::<u8>(usize::MAX).await, Err(ReadError::InvalidArgument)));
+    assert!(matches!(reader.read_records::<u8>(1).await, Err(ReadError::NotEnoughBytes { .. })));
+}
+
+#[tokio::test]
+async fn test_small_capacity() {
+    let mut buf = vec![0xAAu8; 60];
+    buf.extend_from_slice(&0x0102030405060708u64.to_le_bytes());
+    buf.extend_from_slice(&[7u8; 100]);
+    let mut reader = AsyncBinaryReader::new_le_with_capacity(&buf[..], 64);
+    reader.skip(60).await.unwrap();
+    assert_eq!(reader.u64().await.unwrap(), 0x0102030405060708);
+    assert_eq!(reader.read_from_slice(100, |data| Ok(data.to_vec())).await.unwrap(), vec![7u8; 100]);
+}
+
+#[tokio::test]
+async fn test_read_frame() {
+    use crate::async_writer::AsyncBinaryWriter;
+    let (client, server) = tokio::io::duplex(1024);
+    let mut writer = AsyncBinaryWriter::new_le(client);
+    assert!(writer.write_frame(&(0x0102u16, 0x03040506u32)).await.is_ok());
+    assert!(writer.write_frame(&0x07u8).await.is_ok());
+    assert!(writer.write_frame(&[0u8; 64]).await.is_ok());
+    assert!(writer.flush().await.is_ok());
+
+    let mut reader = AsyncBinaryReader::new_le_with_capacity(server, 16);
+    assert_eq!(reader.read_frame::<(u16, u32)>().await.unwrap(), (0x0102, 0x03040506));
+    assert_eq!(reader.read_frame::<u8>().await.unwrap(), 0x07);
+    assert!(matches!(reader.read_frame_limited::<[u8; 64]>(32).await, Err(ReadError::InvalidDataFormat(_))));
+}
+
+#[tokio::test]
+async fn test_skip_large_unseekable() {
+    use tokio::io::AsyncReadExt;
+    const SKIPPED: u64 = 64 * 1024 * 1024;
+    let source = tokio::io::repeat(0).take(SKIPPED).chain(&[0x01u8, 0x02][..]);
+    let mut reader = AsyncBinaryReader::new_le(tokio::io::BufReader::new(source));
+    reader.skip(SKIPPED as usize).await.unwrap();
+    assert_eq!(reader.position(), SKIPPED);
+    assert_eq!(reader.u16().await.unwrap(), 0x0201);
+    assert!(matches!(reader.skip(1).await, Err(ReadError::IoError(_))));
+    assert_eq!(reader.position(), SKIPPED + 2);
+}
+
+#[tokio::test]
+async fn test_by_ref_take() {
+    let data = [1u8, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+    let mut reader = AsyncBinaryReader::new_le(&data[..]);
+    assert_eq!(reader.u8().await.unwrap(), 1);
+    {
+        let mut sub = reader.by_ref_take(4);
+        assert_eq!(sub.u16().await.unwrap(), 0x0302);
+        // widening the logical limit does not get past the `Take` underneath
+        assert!(matches!(sub.read_partial::<u64>(8).await, Err(ReadError::IoError(_))));
+    }
+    assert_eq!(reader.position(), 3);
+
+    // an unbounded take saturates instead of overflowing the end position
+    let mut reader = AsyncBinaryReader::new_le(&data[..]);
+    reader.u8().await.unwrap();
+    assert_eq!(reader.by_ref_take(u64::MAX).u8().await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn test_read_num() {
+    async fn load<T: Primitive + Send, R: ReaderBase>(reader: &mut AsyncBinaryReader<LittleEndian, R>, n: usize) -> Result<Vec<T>> {
+        let mut values = Vec::with_capacity(n);
+        for _ in 0..n {
+            values.push(reader.read_num::<T>().await?);
+        }
+        Ok(values)
+    }
+
+    let data = [1u8, 0, 2, 0, 0xfe, 0xff, 0xff, 0xff];
+    let mut reader = AsyncBinaryReader::from_le_bytes(&data);
+    assert_eq!(load::<u16, _>(&mut reader, 2).await.unwrap(), vec![1, 2]);
+    assert_eq!(load::<i32, _>(&mut reader, 1).await.unwrap(), vec![-2]);
+    let data = 1.5f64.to_le_bytes();
+    let mut reader = AsyncBinaryReader::from_le_bytes(&data);
+    assert_eq!(load::<f64, _>(&mut reader, 1).await.unwrap(), vec![1.5]);
+}
+
+#[tokio::test]
+async fn test_read_vec_validated() {
+    let data: Vec<u8> = (0..10u8).map(|i| i * 2).collect();
+    let mut reader = AsyncBinaryReader::from_le_bytes(&data);
+    let small = |_: usize, value: &u8| if *value < 6 { Ok(()) } else { Err(ReadError::invalid_data_format("too large")) };
+    assert!(matches!(reader.read_vec_validated::<u8>(10, small).await, Err(ReadError::InElement { index: 3, offset: 3, .. })));
+}
+
+#[tokio::test]
+async fn test_poisoned_after_short_read() {
+    // Serves each chunk from one `poll_read` call; an empty chunk reports EOF once.
+    struct Chunks(Vec<Vec<u8>>);
+    impl tokio::io::AsyncRead for Chunks {
+        fn poll_read(mut self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>, buf: &mut tokio::io::ReadBuf<'_>) -> std::task::Poll<std::io::Result<()>> {
+            if !self.0.is_empty() {
+                let chunk = self.0.remove(0);
+                buf.put_slice(&chunk);
+            }
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    let mut reader = AsyncBinaryReader::new_be_with_capacity(Chunks(vec![vec![1, 2], vec![], vec![0xaa]]), 16);
+    assert!(matches!(reader.u32().await, Err(ReadError::IoError(_))));
+    assert!(matches!(reader.read_as_le::<u8>().await, Err(ReadError::Desynchronized { at_offset: 0 })));
+    assert!(reader.is_poisoned());
+    reader.clear_poison();
+    assert_eq!(reader.u8().await.unwrap(), 0xaa);
+}
+
+#[tokio::test]
+async fn test_read_array() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Entry(Arc<u16>);
+    impl AsyncRead for Entry {
+        async fn read<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>) -> Result<Self> {
+            Ok(Entry(Arc::new(reader.u16().await?)))
+        }
+    }
+    impl Drop for Entry {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let data = [0u8, 1, 0, 2, 0, 3];
+    let mut reader = AsyncBinaryReader::from_be_bytes(&data);
+    let entries: [Entry; 3] = reader.read().await.unwrap();
+    assert_eq!(entries.iter().map(|entry| *entry.0).collect::<Vec<_>>(), vec![1, 2, 3]);
+    drop(entries);
+    assert_eq!(DROPS.swap(0, Ordering::SeqCst), 3);
+
+    let mut reader = AsyncBinaryReader::from_be_bytes(&data);
+    assert!(matches!(reader.read_array::<Entry, 4>().await, Err(ReadError::NotEnoughBytes { .. })));
+    assert_eq!(DROPS.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_read_slice_containers() {
+    let data = [1u8, 0, 2, 0, 3, 0];
+    let mut reader = AsyncBinaryReader::from_le_bytes(&data);
+    let boxed: Box<[u16]> = reader.read_with(2).await.unwrap();
+    assert_eq!(&*boxed, &[1, 2]);
+    let empty: Arc<[u16]> = reader.read_with(0).await.unwrap();
+    assert!(empty.is_empty());
+    let shared: Arc<[u16]> = reader.read_with(1).await.unwrap();
+    assert_eq!(&*shared, &[3]);
+}
+
+#[tokio::test]
+async fn test_seek_record() {
+    let table: Vec<u8> = (0..50u16).flat_map(|index| (index * 2).to_be_bytes()).collect();
+    let mut reader = AsyncBinaryReader::new_be(std::io::Cursor::new(table));
+    reader.seek_record::<u16>(0, 30).await.unwrap();
+    assert_eq!(reader.u16().await.unwrap(), 60);
+    reader.seek_record::<u16>(0, 2).await.unwrap();
+    assert_eq!(reader.position(), 4);
+    assert_eq!(reader.u16().await.unwrap(), 4);
+    reader.seek_relative(-4).await.unwrap();
+    assert_eq!(reader.u16().await.unwrap(), 2);
+    reader.seek_relative(90).await.unwrap();
+    assert_eq!(reader.u16().await.unwrap(), 94);
+    reader.seek_to(0).await.unwrap();
+    assert_eq!(reader.u16().await.unwrap(), 0);
+    assert!(matches!(reader.seek_relative(-3).await, Err(ReadError::InvalidArgument)));
+}
+
+#[tokio::test]
+async fn test_seek_file() {
+    let dir = std::env::temp_dir().join(format!("rwbin-async-seek-{}", std::process::id()));
+    tokio::fs::create_dir_all(&dir).await.unwrap();
+    let path = dir.join("archive.bin");
+    let mut data = vec![0u8; 1 << 16];
+    data[..4].copy_from_slice(&(1u32 << 16).to_be_bytes());
+    data.extend_from_slice(&7u16.to_be_bytes());
+    tokio::fs::write(&path, &data).await.unwrap();
+
+    let file = tokio::fs::File::open(&path).await.unwrap();
+    let mut reader = AsyncBinaryReader::new_be(tokio::io::BufReader::new(file));
+    reader.limit_bytes = Some(data.len() as u64);
+    let section = reader.u32().await.unwrap();
+    reader.seek_to(u64::from(section)).await.unwrap();
+    assert_eq!(reader.remaining(), Some(2));
+    assert_eq!(reader.u16().await.unwrap(), 7);
+    assert!(matches!(reader.seek_relative(1).await, Err(ReadError::PositionBeyondLimit { .. })));
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_read_into() {
+    let data = [2u8, 0, 1, 0, 2, 1, 0, 3];
+    let mut reader = AsyncBinaryReader::from_be_bytes(&data);
+    let mut values: Vec<u16> = Vec::with_capacity(4);
+    let buffer = values.as_ptr();
+    let count = reader.u8().await.unwrap() as usize;
+    reader.read_into_with(&mut values, count).await.unwrap();
+    assert_eq!(values, vec![1, 2]);
+    let count = reader.u8().await.unwrap() as usize;
+    reader.read_into_with(&mut values, count).await.unwrap();
+    assert_eq!(values, vec![3]);
+    assert_eq!(values.as_ptr(), buffer);
+
+    let mut reader = AsyncBinaryReader::from_be_bytes(&[0x12, 0x34]);
+    let mut value = 0u16;
+    reader.read_into(&mut value).await.unwrap();
+    assert_eq!(value, 0x1234);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_read_timeout() {
+    use tokio::io::AsyncWriteExt;
+    let (mut client, server) = tokio::io::duplex(64);
+    let mut reader = AsyncBinaryReader::new_be(tokio::io::BufReader::new(server));
+
+    // nothing arrives: the reader is left usable
+    assert!(matches!(reader.read_timeout::<u32>(Duration::from_secs(1)).await, Err(ReadError::TimedOut)));
+    assert!(!reader.is_poisoned());
+    client.write_all(&[0, 0, 0, 7]).await.unwrap();
+    assert_eq!(reader.read_timeout::<u32>(Duration::from_secs(1)).await.unwrap(), 7);
+
+    // a frame stalls halfway: the reader is poisoned
+    client.write_all(&[0, 0, 0, 4, 0xaa, 0xbb]).await.unwrap();
+    assert!(matches!(reader.read_frame_timeout::<u32>(Duration::from_secs(1)).await, Err(ReadError::TimedOut)));
+    assert!(reader.is_poisoned());
+    assert_eq!(reader.remaining(), None);
+    assert!(matches!(reader.u8().await, Err(ReadError::Desynchronized { at_offset: 8 })));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_read_timeout_restores_depth() {
+    use tokio::io::AsyncWriteExt;
+    let (mut client, server) = tokio::io::duplex(64);
+    let mut reader = AsyncBinaryReader::new_be(tokio::io::BufReader::new(server));
+    reader.set_max_depth(1);
+
+    // the frame's `read_partial` is cut off while nested
+    client.write_all(&[0, 0, 0, 4, 0xaa, 0xbb]).await.unwrap();
+    assert!(matches!(reader.read_frame_timeout::<u32>(Duration::from_secs(1)).await, Err(ReadError::TimedOut)));
+    reader.clear_poison();
+    client.write_all(&[0xcc, 0xdd, 0, 0, 0, 4, 0, 0, 0, 9]).await.unwrap();
+    reader.skip(2).await.unwrap();
+    assert_eq!(reader.read_frame_timeout::<u32>(Duration::from_secs(1)).await.unwrap(), 9);
+}
+
+#[tokio::test]
+async fn test_value_mismatch() {
+    let data = [0u8, 0, 0, 9];
+    let mut reader = AsyncBinaryReader::from_be_bytes(&data);
+    reader.value(&0u16).await.unwrap();
+    let err = reader.value(&8u8).await.unwrap_err();
+    assert!(matches!(err, ReadError::UnexpectedValue { ref expected, ref found, offset: 2 } if expected == "8" && found == "0"));
+
+    let mut reader = AsyncBinaryReader::from_be_bytes(&data);
+    let err = reader.values(&[0u8, 0, 0, 8]).await.unwrap_err();
+    assert!(matches!(err, ReadError::ExpectedMismatch { first_diff: 3, .. }));
+
+    let mut reader = AsyncBinaryReader::from_be_bytes(&data);
+    let err = reader.values(&[0u16, 8]).await.unwrap_err();
+    assert!(matches!(err, ReadError::InElement { index: 1, offset: 2, ref source } if matches!(**source, ReadError::UnexpectedValue { .. })));
+}
+
+#[tokio::test]
+async fn test_reserved_report() {
+    let mut data = vec![0xffu8; 600];
+    data[10] = 0;
+    data[590] = 0x42;
+    let mut reader = AsyncBinaryReader::from_le_bytes(&data);
+    assert_eq!(reader.reserved_report_len(600, 0xff).await.unwrap(), vec![(10, 0), (590, 0x42)]);
+}
+
+#[tokio::test]
+async fn test_scan_for() {
+    let data = b"\x00\x01\x02RI\x00RIFF\x07";
+    let mut reader = AsyncBinaryReader::new_le(tokio::io::BufReader::with_capacity(4, std::io::Cursor::new(&data[..])));
+    // the match spans the second and third refill
+    assert_eq!(reader.scan_for(b"RIFF", None).await.unwrap(), 6);
+    assert_eq!(reader.position(), 6);
+    reader.magic(b"RIFF").await.unwrap();
+    assert_eq!(reader.u8().await.unwrap(), 7);
+
+    let mut reader = AsyncBinaryReader::new_le(std::io::Cursor::new(&data[..]));
+    assert_eq!(reader.scan_for(b"\x00", None).await.unwrap(), 0);
+    assert_eq!(reader.scan_for(b"\x00", None).await.unwrap(), 0);
+    reader.skip(1).await.unwrap();
+    assert_eq!(reader.scan_for(b"\x00", None).await.unwrap(), 4);
+    assert_eq!(reader.position(), 5);
+    assert!(matches!(reader.scan_for(b"RIFF", Some(0)).await, Err(ReadError::PatternNotFound { offset: 5, scanned: 4 })));
+}
+
+#[tokio::test]
+async fn test_no_progress() {
+    /// Takes up no bytes and never matches a terminator, like a buggy parser.
+    #[derive(PartialEq)]
+    struct Nothing(u8);
+
+    impl AsyncRead for Nothing {
+        async fn read<E: Endian, R: ReaderBase>(_: &mut AsyncBinaryReader<E, R>) -> Result<Self> {
+            Ok(Nothing(0))
+        }
+    }
+
+    let mut reader = AsyncBinaryReader::from_le_bytes(&[5, 0, 6, 0]);
+    assert_eq!(reader.read_all::<u16>().await.unwrap(), vec![5, 6]);
+
+    let mut reader = AsyncBinaryReader::from_le_bytes(&[5]);
+    assert!(matches!(reader.read_all::<Nothing>().await, Err(ReadError::InvalidDataFormat(_))));
+    assert!(matches!(reader.read_until(&Nothing(1)).await, Err(ReadError::InvalidDataFormat(_))));
+    assert!(matches!(reader.read_while(|_: &[u8; 0]| Some(())).await, Err(ReadError::InvalidDataFormat(_))));
+    assert_eq!(reader.read_all::<u8>().await.unwrap(), vec![5]);
+}
+
+#[tokio::test]
+async fn test_read_with_cloned_args() {
+    #[derive(Clone, Copy)]
+    struct Scale(u16);
+
+    impl AsyncReadWith<Scale> for u16 {
+        async fn read_with<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>, scale: Scale) -> Result<Self> {
+            Ok(reader.u8().await? as u16 * scale.0)
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Label<'s>(&'s str, u8);
+
+    impl<'s> AsyncReadWith<&'s str> for Label<'s> {
+        async fn read_with<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>, prefix: &'s str) -> Result<Self> {
+            Ok(Label(prefix, reader.u8().await?))
+        }
+    }
+
+    let mut reader = AsyncBinaryReader::from_le_bytes(&[1, 2, 3, 4]);
+    assert_eq!(reader.read_with::<_, Vec<u16>>((2, Scale(10))).await.unwrap(), vec![10, 20]);
+    let prefix = String::from("#");
+    let labels: Arc<[Label]> = reader.read_with((2, prefix.as_str())).await.unwrap();
+    assert_eq!(&*labels, [Label("#", 3), Label("#", 4)]);
+}
+
+#[tokio::test]
+async fn test_copy_to() {
+    let data: Vec<u8> = (0..300u16).map(|i| i as u8).collect();
+    let mut reader = AsyncBinaryReader::new_be(tokio::io::BufReader::with_capacity(32, data.as_slice()));
+    reader.skip(10).await.unwrap();
+    let mut sink = Vec::new();
+    reader.copy_to(&mut sink, 250).await.unwrap();
+    assert_eq!(sink, &data[10..260]);
+    assert_eq!(reader.u8().await.unwrap(), 4);
+    assert!(reader.copy_to(&mut sink, 100).await.is_err());
+    assert_eq!(reader.position(), 300);
+
+    let mut reader = AsyncBinaryReader::new_be(tokio::io::BufReader::with_capacity(32, data.as_slice()));
+    let mut out = [0u8; 10];
+    assert!(matches!(reader.copy_to(&mut std::io::Cursor::new(&mut out[..]), 100).await, Err(ReadError::IoError(_))));
+    assert!(!reader.is_poisoned());
+    let mut out = [0u8; 40];
+    assert!(matches!(reader.copy_to(&mut std::io::Cursor::new(&mut out[..]), 100).await, Err(ReadError::IoError(_))));
+    assert!(matches!(reader.u8().await, Err(ReadError::Desynchronized { at_offset: 32 })));
+}
+
+#[test]
+fn test_read_blocking() {
+    #[derive(Debug, PartialEq)]
+    struct Hello {
+        version: u16,
+        name: String,
+        flags: [u8; 2],
+    }
+
+    impl AsyncRead for Hello {
+        async fn read<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>) -> Result<Self> {
+            reader.magic(b"HI").await?;
+            Ok(Hello {
+                version: reader.u16().await?,
+                name: reader.utf8_str(crate::string::StringMode::NullTerminated).await?,
+                flags: reader.read().await?,
+            })
+        }
+    }
+
+    let data = b"HI\x00\x02bob\x00\x01\x80";
+    let hello = read_blocking::<BigEndian, Hello>(data).unwrap();
+    assert_eq!(
+        hello,
+        Hello {
+            version: 2,
+            name: "bob".into(),
+            flags: [1, 0x80]
+        }
+    );
+    assert!(matches!(read_blocking::<LittleEndian, Hello>(&data[..6]), Err(ReadError::NotEnoughBytes { .. })));
+    assert!(matches!(read_blocking::<LittleEndian, Hello>(b"NO"), Err(ReadError::ExpectedMismatch { .. })));
+}
+
+#[tokio::test]
+async fn test_read_partial_endian_views() {
+    struct Window(u32);
+
+    impl AsyncRead for Window {
+        async fn read<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>) -> Result<Self> {
+            let len = reader.u8().await? as usize;
+            Ok(Window(reader.read_partial(len).await?))
+        }
+    }
+
+    let data = [0x02, 0x00, 0x00, 0x00, 0x01, 0x04, 0x00, 0x00, 0x00, 0x01];
+    let mut reader = AsyncBinaryReader::new_le(&data[..]);
+    assert!(matches!(
+        reader.read_as_be::<Window>().await,
+        Err(ReadError::NotEnoughBytes {
+            requested: 4,
+            remaining: 2,
+            offset: 1,
+            ..
+        })
+    ));
+    assert_eq!(reader.limit_bytes, None);
+    assert_eq!(reader.position(), 1);
+
+    let mut reader = AsyncBinaryReader::new_le(&data[5..]);
+    assert_eq!(reader.read_as_be::<Window>().await.unwrap().0, 1);
+    let mut reader = AsyncBinaryReader::new_be(&data[5..]);
+    assert_eq!(reader.read_as_le::<Window>().await.unwrap().0, 0x0100_0000);
+
+    let mut reader = AsyncBinaryReader::new_be(&data[..]);
+    reader.limit_bytes = Some(3);
+    assert!(matches!(reader.read_as_le::<u32>().await, Err(ReadError::NotEnoughBytes { requested: 4, remaining: 3, .. })));
+    assert!(matches!(reader.read_partial::<Window>(8).await, Err(ReadError::NotEnoughBytes { requested: 4, remaining: 2, .. })));
+    assert_eq!(reader.limit_bytes, Some(3));
+    assert_eq!(reader.position(), 1);
+    assert!(matches!(
+        reader.read_as_le_with::<Vec<u16>, _>(2).await,
+        Err(ReadError::NotEnoughBytes { requested: 2, remaining: 0, .. })
+    ));
+    assert_eq!(reader.position(), 3);
+}
+
+#[tokio::test]
+async fn test_read_from_slice_panic_poisons() {
+    use std::task::{Context, Waker};
+
+    let data = [0u8; 2000];
+    for len in [3, 1000] {
+        let mut reader = AsyncBinaryReader::from_le_bytes(&data);
+        reader.u16().await.unwrap();
+        {
+            let mut read = std::pin::pin!(reader.read_from_slice::<()>(len, |_| panic!("bad field")));
+            let poll = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| read.as_mut().poll(&mut Context::from_waker(Waker::noop()))));
+            assert!(poll.is_err());
+        }
+        assert_eq!(reader.position(), 2 + len as u64);
+        assert!(matches!(reader.u8().await, Err(ReadError::Desynchronized { at_offset: 2 })));
+        reader.clear_poison();
+        assert_eq!(reader.u8().await.unwrap(), 0);
+    }
+}
+
+#[tokio::test]
+async fn test_optional_tail() {
+    #[derive(Debug, Default, PartialEq)]
+    struct Tail(u32);
+
+    impl AsyncRead for Tail {
+        async fn read<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>) -> Result<Self> {
+            reader.read_remaining_or_default::<u32>().await.map(Tail)
+        }
+    }
+
+    let data = [2, 5, 0, 6, 5, 0, 1, 0, 0, 0];
+    let mut reader = AsyncBinaryReader::from_le_bytes(&data);
+    let mut tails = Vec::new();
+    for _ in 0..2 {
+        let len = reader.u8().await.unwrap() as usize;
+        tails.push(reader.read_partial::<(u16, Tail)>(len).await.unwrap().1);
+    }
+    assert_eq!(tails, [Tail(0), Tail(1)]);
+    let mut reader = AsyncBinaryReader::new_le(&data[..3]);
+    reader.read_partial::<(u8, u16)>(3).await.unwrap();
+    assert_eq!(reader.read_if_remaining::<u8>().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_read_indexed_iter() {
+    let data = [1, 0, 2, 0, 0, 3, 0, 0, 0, 9];
+    let mut reader = AsyncBinaryReader::from_be_bytes(&data);
+    let mut records = reader.read_indexed_iter::<(u8, u16)>();
+    let mut index = Vec::new();
+    while let Some(record) = records.next_record().await {
+        index.push(record.map(|(offset, value)| (offset, value.0)));
+    }
+    assert_eq!(index.len(), 4);
+    assert_eq!(index[..3].iter().map(|r| *r.as_ref().unwrap()).collect::<Vec<_>>(), [(0, 1), (3, 0), (6, 0)]);
+    assert!(matches!(index[3], Err(ReadError::NotEnoughBytes { .. })));
+    assert!(records.next_record().await.is_none());
+}
+
+#[tokio::test]
+async fn test_len_prefixed() {
+    use crate::async_writer::AsyncBinaryWriter;
+
+    let mut writer = AsyncBinaryWriter::new_le(Vec::new());
+    writer.write_len_prefixed::<u32, _>(&0xabcdu16, LenPolicy::InclusiveOfPrefix).await.unwrap();
+    writer.write_len_prefixed::<u8, _>(&7u8, LenPolicy::Exclusive).await.unwrap();
+    let data = writer.finish().await.unwrap();
+    assert_eq!(data, [6, 0, 0, 0, 0xcd, 0xab, 1, 7]);
+
+    let mut reader = AsyncBinaryReader::from_le_bytes(&data);
+    assert_eq!(reader.read_len_prefixed::<u32, u16>(LenPolicy::InclusiveOfPrefix).await.unwrap(), 0xabcd);
+    assert_eq!(reader.read_len_prefixed::<u8, u8>(LenPolicy::Exclusive).await.unwrap(), 7);
+    let mut reader = AsyncBinaryReader::from_le_bytes(&data);
+    assert!(matches!(
+        reader.read_len_prefixed::<u32, u16>(LenPolicy::InclusiveOfHeader(8)).await,
+        Err(ReadError::InvalidDataFormat(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_max_depth() {
+    struct Node(Option<Box<Node>>);
+
+    // boxed, as recursive async fns must be
+    impl AsyncRead for Node {
+        fn read<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>) -> impl Future<Output = Result<Self>> + Send {
+            let boxed: std::pin::Pin<Box<dyn Future<Output = Result<Self>> + Send + '_>> = Box::pin(async move {
+                match reader.u8().await? {
+                    0 => Ok(Node(None)),
+                    _ => Ok(Node(Some(Box::new(reader.read().await?)))),
+                }
+            });
+            boxed
+        }
+    }
+
+    let mut data = vec![1; 100_000];
+    data.push(0);
+    let mut reader = AsyncBinaryReader::from_le_bytes(&data[99_990..]);
+    reader.set_max_depth(11);
+    let (mut node, mut depth) = (Some(reader.read::<Node>().await.unwrap()), 0);
+    while let Some(Node(child)) = node {
+        (node, depth) = (child.map(|child| *child), depth + 1);
+    }
+    assert_eq!(depth, 11);
+    let mut reader = AsyncBinaryReader::from_le_bytes(&data);
+    reader.set_max_depth(32);
+    assert!(matches!(reader.read::<Node>().await, Err(ReadError::DepthLimitExceeded { max_depth: 32, offset: 32 })));
+}
+
+#[tokio::test]
+async fn test_version() {
+    let data = [3, 0, 1, 0, 0, 0, 0, 0];
+    let mut reader = AsyncBinaryReader::from_le_bytes(&data);
+    assert_eq!(reader.version(Version::new(3, 0)..=Version::new(3, 1)).await.unwrap(), Version::new(3, 1));
+    let err = reader.version(VersionRange::new(Version::new(1, 0), Version::new(3, 1))).await.unwrap_err();
+    assert!(matches!(err, ReadError::UnsupportedVersion { found, offset: 4, .. } if found == Version::new(0, 0)));
+}
+
+#[tokio::test]
+async fn test_trailing_checksum() {
+    use crate::{async_writer::AsyncBinaryWriter, checksum::ChecksumWriter};
+
+    let mut writer = AsyncBinaryWriter::new_be(ChecksumWriter::new(Vec::new(), ChecksumKind::Crc32));
+    writer.u16(0x0102).await.unwrap();
+    writer.u64(u64::MAX).await.unwrap();
+    let mut data = writer.finish_with_checksum().await.unwrap();
+    assert_eq!(data[10..], ChecksumKind::Crc32.of(&data[..10]).to_be_bytes());
+
+    let mut reader = AsyncBinaryReader::from_be_bytes(&data);
+    reader.verify_trailing_checksum(ChecksumKind::Crc32).await.unwrap();
+    data[0] ^= 1;
+    let mut reader = AsyncBinaryReader::from_be_bytes(&data);
+    let err = reader.verify_trailing_checksum(ChecksumKind::Crc32).await.unwrap_err();
+    assert!(matches!(err, ReadError::ChecksumMismatch { offset: 10, .. }));
+}
+
+#[tokio::test]
+async fn test_int_width() {
+    use crate::{async_writer::AsyncBinaryWriter, result::WriteError};
+
+    let mut writer = AsyncBinaryWriter::new_le(Vec::new());
+    for width in [IntWidth::U8, IntWidth::U16, IntWidth::U32, IntWidth::U64] {
+        writer.write_uint(width.max() - 1, width).await.unwrap();
+    }
+    assert!(matches!(writer.write_uint(300, IntWidth::U8).await, Err(WriteError::ValueTooLarge { value: 300, max: 255 })));
+    let data = writer.finish().await.unwrap();
+    assert_eq!(data.len(), 15);
+
+    let mut reader = AsyncBinaryReader::from_le_bytes(&data);
+    for width in [IntWidth::U8, IntWidth::U16, IntWidth::U32, IntWidth::U64] {
+        assert_eq!(reader.read_with::<_, u64>(width).await.unwrap(), width.max() - 1);
+    }
+    assert!(reader.read_uint(IntWidth::U8).await.is_err());
+}
+
+#[tokio::test]
+async fn test_native_endian() {
+    use crate::async_writer::AsyncBinaryWriter;
+
+    let value = (0x1234u16, -3i32, 1.5f32, u64::MAX - 1, u128::MAX / 3, i128::MIN + 1);
+    let mut writer = AsyncBinaryWriter::new_ne(Vec::new());
+    writer.write(&value).await.unwrap();
+    let data = writer.finish().await.unwrap();
+    assert_eq!(&data[..2], 0x1234u16.to_ne_bytes());
+    assert_eq!(AsyncBinaryReader::from_ne_bytes(&data).read::<(u16, i32, f32, u64, u128, i128)>().await.unwrap(), value);
+    assert_eq!(AsyncBinaryReader::new_ne(&data[..]).read::<(u16, i32, f32, u64, u128, i128)>().await.unwrap(), value);
+}
+
+#[tokio::test]
+async fn test_dyn_endian() {
+    use crate::async_writer::AsyncBinaryWriter;
+
+    for endianness in [Endianness::Little, Endianness::Big] {
+        let mut writer = AsyncBinaryWriter::new_dyn(Vec::new(), endianness);
+        writer.u16(0x0102).await.unwrap();
+        writer.write(&(-3i32, 1.5f64)).await.unwrap();
+        writer.write_num(7u64).await.unwrap();
+        assert_eq!(writer.endianness(), endianness);
+        let data = writer.finish().await.unwrap();
+        assert_eq!(data[0], if endianness == Endianness::Little { 0x02 } else { 0x01 });
+
+        let mut reader = AsyncBinaryReader::new_dyn(&data[..], endianness);
+        assert_eq!(reader.u16().await.unwrap(), 0x0102);
+        assert_eq!(reader.read::<(i32, f64)>().await.unwrap(), (-3, 1.5));
+        assert_eq!(reader.read_num::<u64>().await.unwrap(), 7);
+        assert_eq!(reader.position(), 22);
+    }
+}
+
+#[tokio::test]
+async fn test_either_endian_surface() {
+    let data = b"\x00\x02\x01\x02\x00\x03\x00\x04\xff\x00\x00\x00\x02hi\x05";
+    let mut reader = AsyncEitherEndianReader::Big(AsyncBinaryReader::from_be_bytes(data));
+    assert_eq!(reader.read_len_prefixed::<u16, u16>(LenPolicy::Exclusive).await.unwrap(), 0x0102);
+    assert_eq!(reader.read_with::<_, Vec<u16>>(2).await.unwrap(), [3, 4]);
+    reader.skip(1).await.unwrap();
+    assert_eq!(reader.read_os_string(crate::string::OsStrEncoding::Utf8Lossy).await.unwrap(), "hi");
+    assert_eq!((reader.position(), reader.remaining()), (15, Some(1)));
+    assert!(reader.ensure(2).is_err());
+    assert_eq!(reader.read_all::<u8>().await.unwrap(), [5]);
+}
+
+#[tokio::test]
+async fn test_detect_endian() {
+    let mut reader = AsyncEitherEndianReader::detect_endian(&b"MM\x00\x2a\x00\x00\x00\x08"[..], EndianMarker::Tiff).await.unwrap();
+    assert!(!reader.is_little_endian());
+    assert_eq!(reader.position(), 2);
+    assert_eq!(reader.u16().await.unwrap(), 42);
+    assert_eq!(reader.read::<u32>().await.unwrap(), 8);
+
+    let mut reader = AsyncEitherEndianReader::detect_endian(&b"II\x2a\x00"[..], EndianMarker::Tiff).await.unwrap();
+    assert_eq!(reader.endianness(), Endianness::Little);
+    assert_eq!(reader.u16().await.unwrap(), 42);
+
+    let mut reader = AsyncEitherEndianReader::detect_endian_with(&b"v2BE\x01\x02"[..], 4, |magic| {
+        magic.starts_with(b"v2").then(|| if magic.ends_with(b"BE") { Endianness::Big } else { Endianness::Little })
+    })
+    .await
+    .unwrap();
+    assert_eq!(reader.u16().await.unwrap(), 0x0102);
+
+    assert!(matches!(
+        AsyncEitherEndianReader::detect_endian(&b"XX\x00\x2a"[..], EndianMarker::Tiff).await,
+        Err(ReadError::InvalidDataFormat(_))
+    ));
+    assert!(matches!(
+        AsyncEitherEndianReader::detect_endian(&b"II"[..], EndianMarker::Custom(b"L", b"BE")).await,
+        Err(ReadError::InvalidArgument)
+    ));
+}
+
+#[tokio::test]
+async fn test_peek() {
+    let data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+    let mut reader = AsyncBinaryReader::from_be_bytes(&data);
+    assert_eq!(reader.peek_u8().await.unwrap(), 0x01);
+    assert_eq!(reader.peek_u16().await.unwrap(), 0x0102);
+    assert_eq!(reader.peek::<(u8, u16)>().await.unwrap(), (0x01, 0x0203));
+    assert_eq!(reader.u16().await.unwrap(), 0x0102);
+    assert_eq!(reader.peek_u32().await.unwrap(), 0x03040506);
+    assert_eq!(reader.position(), 2);
+    reader.skip(4).await.unwrap();
+    assert!(matches!(reader.peek_u8().await, Err(ReadError::NotEnoughBytes { requested: 1, remaining: 0, .. })));
+
+    let mut reader = AsyncBinaryReader::new_le(tokio::io::BufReader::with_capacity(4, &data[..]));
+    reader.u16().await.unwrap();
+    assert_eq!(reader.peek_u16().await.unwrap(), 0x0403);
+    assert_eq!(reader.peek_u32().await.unwrap(), 0x06050403);
+    assert_eq!(reader.u32().await.unwrap(), 0x06050403);
+
+    let long: Vec<u8> = (1..=40).collect();
+    let mut reader = AsyncBinaryReader::new_le(tokio::io::BufReader::with_capacity(4, &long[..]));
+    reader.u16().await.unwrap();
+    assert!(matches!(reader.peek::<[u8; 20]>().await, Err(ReadError::PeekBeyondBuffer { offset: 2, buffered: 16 })));
+    assert_eq!(reader.u32().await.unwrap(), 0x06050403);
+}
+
+#[tokio::test]
+async fn test_peek_across_refills() {
+    // The peeked u64 needs the 2 bytes left in the buffer and two more refills of 4.
+    let data: Vec<u8> = (1..=12).collect();
+    let mut reader = AsyncBinaryReader::new_le(tokio::io::BufReader::with_capacity(4, &data[..]));
+    reader.u16().await.unwrap();
+    assert_eq!(reader.peek::<u64>().await.unwrap(), 0x0a09080706050403);
+    assert_eq!(reader.position(), 2);
+    assert_eq!(reader.u16().await.unwrap(), 0x0403);
+    assert_eq!(reader.u64().await.unwrap(), 0x0c0b0a0908070605);
+
+    let mut reader = AsyncBinaryReader::new_le(tokio::io::BufReader::with_capacity(4, std::io::Cursor::new(&data)));
+    reader.u16().await.unwrap();
+    assert_eq!(reader.peek::<u64>().await.unwrap(), 0x0a09080706050403);
+    reader.skip(1).await.unwrap();
+    let mut copied = Vec::new();
+    reader.copy_to(&mut copied, 3).await.unwrap();
+    assert_eq!(copied, [0x04, 0x05, 0x06]);
+    reader.seek_relative(2).await.unwrap();
+    assert_eq!(reader.u32().await.unwrap(), 0x0c0b0a09);
+}
@@ -1,10 +1,30 @@
-use super::endian::{BigEndian, Endian, LittleEndian};
+use super::endian::{BigEndian, Endian, Endianness, LittleEndian, NativeEndian, Primitive};
+use crate::atomic::AsyncAtomicFile;
+use crate::checksum::ChecksumWriter;
+use crate::fixed_size::FixedSize;
+use crate::reader::{DEFAULT_MAX_FRAME_SIZE, DEFAULT_SMALL_BUF_SIZE, IntWidth, LenPolicy};
 use crate::result::{WriteError, WriteResult};
+use crate::retry;
+use crate::writer::{COPY_CHUNK_SIZE, PrefixOverflow, max_prefix_count, narrow};
+use std::borrow::{Borrow, Cow};
+use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
-use tokio::io::AsyncWriteExt;
+use std::sync::Arc;
+use tokio::io::AsyncSeekExt;
 
 pub struct AsyncBinaryWriter<E, R> {
     writer: R,
+    total_bytes_written: u64,
+    staging: Option<Vec<u8>>,
+    region_start: Option<u64>,
+    limit_bytes: Option<u64>,
+    /// Set once an alignment helper has run, so `write_repeated` knows an encoding may depend on its position.
+    padded: bool,
+    /// Stream offset of position 0, taken from the sink by `new_seekable` or else by the first
+    /// seek-aware call.
+    stream_base: Option<u64>,
+    #[cfg(feature = "stats")]
+    pub(crate) stats: Option<std::sync::Arc<crate::stats::Stats>>,
     _endian: PhantomData<fn() -> E>,
 }
 
@@ -13,22 +33,352 @@ pub type Result<T> = WriteResult<T>;
 pub trait WriterBase: tokio::io::AsyncWrite + Unpin + Send {}
 impl<T> WriterBase for T where T: tokio::io::AsyncWrite + Unpin + Send {}
 
+impl<E: Endian, W: WriterBase> AsyncBinaryWriter<E, W> {
+    pub fn new(writer: W) -> Self {
+        AsyncBinaryWriter {
+            writer,
+            total_bytes_written: 0,
+            staging: None,
+            region_start: None,
+            limit_bytes: None,
+            padded: false,
+            stream_base: None,
+            #[cfg(feature = "stats")]
+            stats: None,
+            _endian: PhantomData,
+        }
+    }
+    /// Returns an in-memory writer that encodes as if at `position` of this one, under the same limit.
+    fn scratch_at(&self, position: u64) -> AsyncBinaryWriter<E, Vec<u8>> {
+        let mut scratch = AsyncBinaryWriter::new(Vec::new());
+        (scratch.total_bytes_written, scratch.limit_bytes) = (position, self.limit_bytes);
+        scratch
+    }
+}
+
 impl<W: WriterBase> AsyncBinaryWriter<BigEndian, W> {
     pub fn new_be(writer: W) -> Self {
-        AsyncBinaryWriter { writer, _endian: PhantomData }
+        Self::new(writer)
     }
 }
 
 impl<W: WriterBase> AsyncBinaryWriter<LittleEndian, W> {
     pub fn new_le(writer: W) -> Self {
-        AsyncBinaryWriter { writer, _endian: PhantomData }
+        Self::new(writer)
+    }
+}
+
+impl<W: WriterBase> AsyncBinaryWriter<NativeEndian, W> {
+    pub fn new_ne(writer: W) -> Self {
+        Self::new(writer)
+    }
+}
+
+impl<W: WriterBase> AsyncBinaryWriter<LittleEndian, W> {
+    /// Creates a writer whose byte order `endianness` is chosen at runtime, see `AsyncEitherEndianWriter`.
+    pub fn new_dyn(writer: W, endianness: Endianness) -> AsyncEitherEndianWriter<W> {
+        AsyncEitherEndianWriter::new(writer, endianness)
+    }
+}
+
+/// The async counterpart of `EitherEndianWriter`: a writer whose byte order is decided at runtime.
+pub enum AsyncEitherEndianWriter<W> {
+    Little(AsyncBinaryWriter<LittleEndian, W>),
+    Big(AsyncBinaryWriter<BigEndian, W>),
+}
+
+macro_rules! forward_async_either_writer {
+    ( $( fn $name:ident(&mut self $(, $arg:ident: $ty:ty)*) -> $ret:ty; )* ) => {
+        $(
+            #[inline]
+            pub async fn $name(&mut self $(, $arg: $ty)*) -> $ret {
+                match self {
+                    AsyncEitherEndianWriter::Little(writer) => writer.$name($($arg),*).await,
+                    AsyncEitherEndianWriter::Big(writer) => writer.$name($($arg),*).await,
+                }
+            }
+        )*
+    };
+}
+
+impl<W: WriterBase> AsyncEitherEndianWriter<W> {
+    pub fn new(writer: W, endianness: Endianness) -> Self {
+        match endianness {
+            Endianness::Little => AsyncEitherEndianWriter::Little(AsyncBinaryWriter::new_le(writer)),
+            Endianness::Big => AsyncEitherEndianWriter::Big(AsyncBinaryWriter::new_be(writer)),
+        }
+    }
+
+    pub fn endianness(&self) -> Endianness {
+        match self {
+            AsyncEitherEndianWriter::Little(_) => Endianness::Little,
+            AsyncEitherEndianWriter::Big(_) => Endianness::Big,
+        }
+    }
+
+    pub fn position(&self) -> u64 {
+        match self {
+            AsyncEitherEndianWriter::Little(writer) => writer.position(),
+            AsyncEitherEndianWriter::Big(writer) => writer.position(),
+        }
+    }
+
+    pub fn remaining(&self) -> Option<u64> {
+        match self {
+            AsyncEitherEndianWriter::Little(writer) => writer.remaining(),
+            AsyncEitherEndianWriter::Big(writer) => writer.remaining(),
+        }
+    }
+
+    forward_async_either_writer! {
+        fn u8(&mut self, value: u8) -> Result<()>;
+        fn i8(&mut self, value: i8) -> Result<()>;
+        fn u16(&mut self, value: u16) -> Result<()>;
+        fn i16(&mut self, value: i16) -> Result<()>;
+        fn u32(&mut self, value: u32) -> Result<()>;
+        fn i32(&mut self, value: i32) -> Result<()>;
+        fn f32(&mut self, value: f32) -> Result<()>;
+        fn u64(&mut self, value: u64) -> Result<()>;
+        fn i64(&mut self, value: i64) -> Result<()>;
+        fn f64(&mut self, value: f64) -> Result<()>;
+        fn u128(&mut self, value: u128) -> Result<()>;
+        fn i128(&mut self, value: i128) -> Result<()>;
+        fn write_uint(&mut self, value: u64, width: IntWidth) -> Result<()>;
+        fn reserved(&mut self, value: u8, length: usize) -> Result<()>;
+        fn fill_aligned(&mut self, alignment: usize, offset: usize) -> Result<()>;
+        fn align_to(&mut self, alignment: usize) -> Result<()>;
+        fn flush(&mut self) -> Result<()>;
+    }
+
+    pub async fn utf8_str<T: AsRef<str>>(&mut self, value: T, mode: crate::string::StringMode) -> Result<()> {
+        match self {
+            AsyncEitherEndianWriter::Little(writer) => writer.utf8_str(value, mode).await,
+            AsyncEitherEndianWriter::Big(writer) => writer.utf8_str(value, mode).await,
+        }
+    }
+
+    pub async fn utf16_str<T: AsRef<str>>(&mut self, value: T, mode: crate::string::StringMode) -> Result<()> {
+        match self {
+            AsyncEitherEndianWriter::Little(writer) => writer.utf16_str(value, mode).await,
+            AsyncEitherEndianWriter::Big(writer) => writer.utf16_str(value, mode).await,
+        }
+    }
+
+    #[inline]
+    pub async fn write_num<T: Primitive + Send>(&mut self, value: T) -> Result<()> {
+        match self {
+            AsyncEitherEndianWriter::Little(writer) => writer.write_num(value).await,
+            AsyncEitherEndianWriter::Big(writer) => writer.write_num(value).await,
+        }
+    }
+
+    #[inline]
+    pub async fn write<T: AsyncWrite>(&mut self, value: &T) -> Result<()> {
+        match self {
+            AsyncEitherEndianWriter::Little(writer) => writer.write(value).await,
+            AsyncEitherEndianWriter::Big(writer) => writer.write(value).await,
+        }
+    }
+
+    #[inline]
+    pub async fn write_with<A, T: AsyncWriteWith<A> + ?Sized>(&mut self, value: &T, arg: &A) -> Result<()> {
+        match self {
+            AsyncEitherEndianWriter::Little(writer) => writer.write_with(value, arg).await,
+            AsyncEitherEndianWriter::Big(writer) => writer.write_with(value, arg).await,
+        }
+    }
+
+    pub async fn finish(self) -> Result<W> {
+        match self {
+            AsyncEitherEndianWriter::Little(writer) => writer.finish().await,
+            AsyncEitherEndianWriter::Big(writer) => writer.finish().await,
+        }
+    }
+}
+
+impl AsyncBinaryWriter<LittleEndian, AsyncAtomicFile> {
+    pub async fn create_atomic_le<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        AsyncAtomicFile::create(path).await.map(Self::new_le).map_err(WriteError::io_error)
+    }
+}
+
+impl AsyncBinaryWriter<BigEndian, AsyncAtomicFile> {
+    pub async fn create_atomic_be<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        AsyncAtomicFile::create(path).await.map(Self::new_be).map_err(WriteError::io_error)
+    }
+}
+
+impl<E: Endian> AsyncBinaryWriter<E, AsyncAtomicFile> {
+    pub async fn commit(mut self) -> Result<()> {
+        self.drain_staging().await?;
+        self.writer.commit().await.map_err(WriteError::io_error)
+    }
+}
+
+impl<E: Endian, W: WriterBase> AsyncBinaryWriter<E, ChecksumWriter<W>> {
+    pub async fn finish_with_checksum(mut self) -> Result<W> {
+        self.drain_staging().await?;
+        let digest = self.writer.checksum().value();
+        self.u32(digest).await?;
+        Ok(self.finish().await?.into_inner())
+    }
+}
+
+impl<E: Endian, W: WriterBase + tokio::io::AsyncSeek> AsyncBinaryWriter<E, W> {
+    /// Creates a writer whose position 0 is the sink's current offset, taken right away, see
+    /// `BinaryWriter::new_seekable`.
+    pub async fn new_seekable(mut writer: W) -> Result<Self> {
+        let base = writer.stream_position().await.map_err(WriteError::io_error)?;
+        let mut this = Self::new(writer);
+        this.stream_base = Some(base);
+        Ok(this)
+    }
+    pub async fn sync_position(&mut self) -> Result<u64> {
+        let base = self.stream_base().await?;
+        let stream_pos = self.writer.stream_position().await.map_err(WriteError::io_error)?;
+        self.total_bytes_written = stream_pos.checked_sub(base).ok_or(WriteError::InvalidArgument)?;
+        Ok(self.total_bytes_written)
+    }
+
+    pub async fn patch<T: AsyncWrite>(&mut self, pos: u64, value: &T) -> Result<()> {
+        let end = self.sync_position().await?;
+        // the limit applies to the bytes being replaced, not to the end of the output
+        let mut scratch = self.scratch_at(pos);
+        scratch.write(value).await?;
+        if pos.checked_add(scratch.writer.len() as u64).is_none_or(|patch_end| patch_end > end) {
+            return Err(WriteError::InvalidArgument);
+        }
+        let base = self.stream_base().await?;
+        self.writer.seek(std::io::SeekFrom::Start(base + pos)).await.map_err(WriteError::io_error)?;
+        self.total_bytes_written = pos;
+        let result = match self.write_bytes(&scratch.writer).await {
+            Ok(()) => self.drain_staging().await,
+            Err(e) => Err(e),
+        };
+        self.writer.seek(std::io::SeekFrom::Start(base + end)).await.map_err(WriteError::io_error)?;
+        result?;
+        self.sync_position().await.map(drop)
+    }
+
+    /// Flushes the staging buffer and returns the stream offset of position 0, asking the sink the first time.
+    async fn stream_base(&mut self) -> Result<u64> {
+        self.drain_staging().await?;
+        if let Some(base) = self.stream_base {
+            return Ok(base);
+        }
+        let stream_pos = self.writer.stream_position().await.map_err(WriteError::io_error)?;
+        let base = stream_pos.checked_sub(self.total_bytes_written).ok_or(WriteError::InvalidArgument)?;
+        self.stream_base = Some(base);
+        Ok(base)
+    }
+}
+
+impl<E, W> AsyncBinaryWriter<E, W> {
+    /// Starts counting calls and bytes into `stats`, replacing any collector attached before.
+    #[cfg(feature = "stats")]
+    pub fn attach_stats(&mut self, stats: &std::sync::Arc<crate::stats::Stats>) {
+        self.stats = Some(stats.clone());
+    }
+
+    /// Coalesces writes in an internal buffer of `capacity` bytes before handing them to the underlying writer.
+    ///
+    /// Buffered bytes reach the sink on overflow, `flush` or `finish`, and are lost if the writer is simply dropped.
+    pub fn with_buffer_capacity(mut self, capacity: usize) -> Self {
+        self.staging = (capacity > 0).then(|| Vec::with_capacity(capacity));
+        self
+    }
+
+    /// Returns the number of bytes written so far.
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.total_bytes_written
+    }
+
+    pub fn remaining(&self) -> Option<u64> {
+        self.limit_bytes.map(|limit| limit.saturating_sub(self.total_bytes_written))
+    }
+
+    pub(crate) fn with_options<S>(mut self, builder: &crate::builder::BinaryWriterBuilder<E, S>) -> Self {
+        self.limit_bytes = builder.limit;
+        #[cfg(feature = "stats")]
+        if let Some(stats) = &builder.stats {
+            self.attach_stats(stats);
+        }
+        self.with_buffer_capacity(builder.buffer_capacity)
+    }
+
+    #[inline]
+    fn check_limit(&self, len: usize) -> Result<()> {
+        match self.limit_bytes {
+            Some(limit) if self.total_bytes_written + len as u64 > limit => Err(WriteError::LimitExceeded {
+                requested: len,
+                position: self.total_bytes_written,
+                limit,
+            }),
+            _ => Ok(()),
+        }
     }
 }
 
 impl<E: Endian, W: WriterBase> AsyncBinaryWriter<E, W> {
+    #[inline]
+    pub(crate) async fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        self.check_limit(bytes.len())?;
+        match &mut self.staging {
+            Some(staging) if staging.len() + bytes.len() <= staging.capacity() => staging.extend_from_slice(bytes),
+            Some(_) => {
+                self.drain_staging().await?;
+                match &mut self.staging {
+                    Some(staging) if bytes.len() < staging.capacity() => staging.extend_from_slice(bytes),
+                    _ => retry::write_all(&mut self.writer, bytes).await.map_err(WriteError::io_error)?,
+                }
+            }
+            None => retry::write_all(&mut self.writer, bytes).await.map_err(WriteError::io_error)?,
+        }
+        self.total_bytes_written += bytes.len() as u64;
+        Ok(())
+    }
+    async fn drain_staging(&mut self) -> Result<()> {
+        if let Some(staging) = &mut self.staging
+            && !staging.is_empty()
+        {
+            retry::write_all(&mut self.writer, staging).await.map_err(WriteError::io_error)?;
+            staging.clear();
+        }
+        Ok(())
+    }
+
+    /// Runs `f` on a view of this writer that uses the byte order `F`, sharing the position and staging buffer.
+    async fn with_endian<F: Endian>(&mut self, f: impl AsyncFnOnce(&mut AsyncBinaryWriter<F, &mut W>) -> Result<()>) -> Result<()> {
+        let mut writer = AsyncBinaryWriter {
+            writer: &mut self.writer,
+            total_bytes_written: self.total_bytes_written,
+            staging: self.staging.take(),
+            region_start: self.region_start,
+            limit_bytes: self.limit_bytes,
+            padded: self.padded,
+            stream_base: self.stream_base,
+            #[cfg(feature = "stats")]
+            stats: self.stats.clone(),
+            _endian: PhantomData::<fn() -> F>,
+        };
+        let result = f(&mut writer).await;
+        (self.total_bytes_written, self.padded, self.stream_base) = (writer.total_bytes_written, writer.padded, writer.stream_base);
+        self.staging = writer.staging.take();
+        result
+    }
     #[inline]
     async fn write_value<const N: usize>(&mut self, value: &[u8; N]) -> Result<()> {
-        self.writer.write_all(value).await.map_err(WriteError::io_error)
+        self.write_bytes(value).await?;
+        record_stats!(self, Primitive, N);
+        Ok(())
+    }
+    #[inline]
+    pub async fn write_num<T: Primitive + Send>(&mut self, value: T) -> Result<()> {
+        let bytes = value.to_bytes::<E>();
+        self.write_bytes(bytes.as_ref()).await?;
+        record_stats!(self, Primitive, bytes.as_ref().len());
+        Ok(())
     }
     #[inline]
     pub async fn u8(&mut self, value: u8) -> Result<()> {
@@ -71,33 +421,129 @@ impl<E: Endian, W: WriterBase> AsyncBinaryWriter<E, W> {
         self.write_value(&E::f64_to_bytes(value)).await
     }
     #[inline]
+    pub async fn u128(&mut self, value: u128) -> Result<()> {
+        self.write_value(&E::u128_to_bytes(value)).await
+    }
+    #[inline]
+    pub async fn i128(&mut self, value: i128) -> Result<()> {
+        self.write_value(&E::i128_to_bytes(value)).await
+    }
+    #[inline]
+    pub async fn u8_from_usize(&mut self, value: usize) -> Result<()> {
+        self.u8(narrow(value)?).await
+    }
+    #[inline]
+    pub async fn u16_from_usize(&mut self, value: usize) -> Result<()> {
+        self.u16(narrow(value)?).await
+    }
+    #[inline]
+    pub async fn u32_from_usize(&mut self, value: usize) -> Result<()> {
+        self.u32(narrow(value)?).await
+    }
+    #[inline]
+    pub async fn u64_from_usize(&mut self, value: usize) -> Result<()> {
+        self.u64(narrow(value)?).await
+    }
+    #[inline]
+    pub async fn write_uint(&mut self, value: u64, width: IntWidth) -> Result<()> {
+        if value > width.max() {
+            return Err(WriteError::ValueTooLarge { value, max: width.max() });
+        }
+        match width {
+            IntWidth::U8 => self.u8(value as u8).await,
+            IntWidth::U16 => self.u16(value as u16).await,
+            IntWidth::U32 => self.u32(value as u32).await,
+            IntWidth::U64 => self.u64(value).await,
+        }
+    }
+    #[inline]
     pub async fn reserved(&mut self, value: u8, length: usize) -> Result<()> {
-        const POPULAR_BUF_SIZE: usize = 512;
-        if length <= POPULAR_BUF_SIZE {
-            let buf = [value; POPULAR_BUF_SIZE];
-            self.writer.write_all(&buf[..length]).await.map_err(WriteError::io_error)?;
+        if length <= DEFAULT_SMALL_BUF_SIZE {
+            let buf = [value; DEFAULT_SMALL_BUF_SIZE];
+            self.write_bytes(&buf[..length]).await?;
         } else {
             let buf = vec![value; length];
-            self.writer.write_all(&buf).await.map_err(WriteError::io_error)?;
+            self.write_bytes(&buf).await?;
         }
+        record_stats!(self, Skip, length);
         Ok(())
     }
     #[inline]
+    pub async fn reserved_for<T: FixedSize>(&mut self, value: u8) -> Result<()> {
+        self.reserved(value, T::SIZE).await
+    }
+    #[inline]
     pub async fn fill_aligned(&mut self, alignment: usize, offset: usize) -> Result<()> {
-        let remainder = alignment - (offset % alignment);
-        if remainder == 0 {
-            return Ok(());
+        if alignment == 0 {
+            return Err(WriteError::InvalidArgument);
+        }
+        self.padded = true;
+        // an offset that is already aligned needs no padding
+        let padding = (alignment - offset % alignment) % alignment;
+        self.reserved(0x00, padding).await
+    }
+    #[inline]
+    pub async fn align_to(&mut self, alignment: usize) -> Result<()> {
+        if alignment == 0 {
+            return Err(WriteError::InvalidArgument);
         }
-        self.reserved(0x00, remainder).await
+        self.padded = true;
+        let alignment = alignment as u64;
+        let padding = (alignment - self.total_bytes_written % alignment) % alignment;
+        self.reserved(0x00, padding as usize).await
     }
     #[inline]
+    pub async fn copy_from(&mut self, src: &mut (impl tokio::io::AsyncRead + Unpin + Send), len: Option<u64>) -> Result<u64> {
+        count_stats!(self, Bulk, async {
+            let mut buf = vec![0u8; len.map_or(COPY_CHUNK_SIZE, |len| len.min(COPY_CHUNK_SIZE as u64) as usize)];
+            let mut copied = 0u64;
+            loop {
+                let want = len.map_or(buf.len(), |len| (len - copied).min(buf.len() as u64) as usize);
+                if want == 0 {
+                    return Ok(copied);
+                }
+                let read = retry::read(src, &mut buf[..want]).await.map_err(WriteError::io_error)?;
+                if read == 0 {
+                    return match len {
+                        Some(_) => Err(WriteError::io_error(std::io::ErrorKind::UnexpectedEof.into())),
+                        None => Ok(copied),
+                    };
+                }
+                self.write_bytes(&buf[..read]).await?;
+                copied += read as u64;
+            }
+        })
+    }
     pub async fn flush(&mut self) -> Result<()> {
-        self.writer.flush().await.map_err(WriteError::io_error)
+        self.drain_staging().await?;
+        retry::flush(&mut self.writer).await.map_err(WriteError::io_error)
+    }
+    pub async fn finish(mut self) -> Result<W> {
+        self.flush().await?;
+        Ok(self.writer)
     }
 }
 
 pub trait AsyncWrite {
     fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> impl Future<Output = Result<()>> + Send;
+
+    /// Returns the number of bytes `write` will produce, if known up front.
+    fn size_hint(&self) -> Option<usize> {
+        None
+    }
+
+    /// Writes `values` in order, one by one unless overridden as `u8` and `i8` do to copy the slice at once.
+    fn write_slice<E: Endian, W: WriterBase>(values: &[Self], writer: &mut AsyncBinaryWriter<E, W>) -> impl Future<Output = Result<()>> + Send
+    where
+        Self: Sized + Sync,
+    {
+        async move {
+            for value in values {
+                value.write(writer).await?;
+            }
+            Ok(())
+        }
+    }
 }
 
 pub trait AsyncWriteWith<T> {
@@ -106,47 +552,234 @@ pub trait AsyncWriteWith<T> {
 
 impl<W: WriterBase> AsyncBinaryWriter<BigEndian, W> {
     pub async fn write_as_le<T: AsyncWrite>(&mut self, value: &T) -> Result<()> {
-        let mut writer = AsyncBinaryWriter {
-            writer: &mut self.writer,
-            _endian: PhantomData::<fn() -> LittleEndian>,
-        };
-        value.write(&mut writer).await?;
-        Ok(())
+        self.with_endian::<LittleEndian>(async |writer| value.write(writer).await).await
     }
-    pub async fn write_as_le_with<U, T: AsyncWriteWith<U>>(&mut self, value: T, with: U) -> Result<()> {
-        let mut writer = AsyncBinaryWriter {
-            writer: &mut self.writer,
-            _endian: PhantomData::<fn() -> LittleEndian>,
-        };
-        value.write_with(&mut writer, &with).await?;
-        Ok(())
+    pub async fn write_as_le_with<T: AsyncWriteWith<U>, U>(&mut self, value: &T, with: U) -> Result<()> {
+        self.with_endian::<LittleEndian>(async |writer| value.write_with(writer, &with).await).await
     }
 }
 
 impl<W: WriterBase> AsyncBinaryWriter<LittleEndian, W> {
     pub async fn write_as_be<T: AsyncWrite>(&mut self, value: &T) -> Result<()> {
-        let mut writer = AsyncBinaryWriter {
-            writer: &mut self.writer,
-            _endian: PhantomData::<fn() -> BigEndian>,
-        };
-        value.write(&mut writer).await?;
-        Ok(())
+        self.with_endian::<BigEndian>(async |writer| value.write(writer).await).await
     }
     pub async fn write_as_be_with<T: AsyncWriteWith<U>, U>(&mut self, value: &T, with: U) -> Result<()> {
-        let mut writer = AsyncBinaryWriter {
-            writer: &mut self.writer,
-            _endian: PhantomData::<fn() -> BigEndian>,
-        };
-        value.write_with(&mut writer, &with).await?;
-        Ok(())
+        self.with_endian::<BigEndian>(async |writer| value.write_with(writer, &with).await).await
     }
 }
 
+macro_rules! write_interleaved_fns {
+    ( $( fn $name:ident($first:ident: $F:ident $(, $col:ident: $C:ident)+); )* ) => {
+        $(
+            pub async fn $name<$F: AsyncWrite $(, $C: AsyncWrite)+>(&mut self, $first: &[$F] $(, $col: &[$C])+) -> Result<()> {
+                if $($col.len() != $first.len())||+ {
+                    return Err(WriteError::InvalidArgument);
+                }
+                for row in 0..$first.len() {
+                    self.write(&$first[row]).await?;
+                    $(self.write(&$col[row]).await?;)+
+                }
+                Ok(())
+            }
+        )*
+    };
+}
+
 impl<E: Endian, W: WriterBase> AsyncBinaryWriter<E, W> {
     #[inline]
     pub async fn write<T: AsyncWrite>(&mut self, value: &T) -> Result<()> {
         value.write(self).await
     }
+    pub async fn write_partial<T: AsyncWrite>(&mut self, value: &T) -> Result<()> {
+        let outer = self.region_start.replace(self.total_bytes_written);
+        let result = self.write(value).await;
+        self.region_start = outer;
+        result
+    }
+    pub async fn align_in_region(&mut self, align: usize, fill: u8) -> Result<()> {
+        let start = self.region_start.ok_or(WriteError::InvalidArgument)?;
+        if align == 0 {
+            return Err(WriteError::InvalidArgument);
+        }
+        self.padded = true;
+        let offset = ((self.total_bytes_written - start) % align as u64) as usize;
+        if offset != 0 {
+            self.reserved(fill, align - offset).await?;
+        }
+        Ok(())
+    }
+    write_interleaved_fns! {
+        fn write_interleaved2(a: A, b: B);
+        fn write_interleaved3(a: A, b: B, c: C);
+        fn write_interleaved4(a: A, b: B, c: C, d: D);
+    }
+    pub async fn write_map_iter<I: IntoIterator, T: AsyncWrite>(&mut self, iter: I, f: impl Fn(&I::Item) -> T) -> Result<()> {
+        for item in iter {
+            self.write(&f(&item)).await?;
+        }
+        Ok(())
+    }
+    pub async fn write_map_iter_counted<P, I, T>(&mut self, iter: I, f: impl Fn(&I::Item) -> T) -> Result<()>
+    where
+        P: AsyncWrite + TryFrom<usize>,
+        I: IntoIterator,
+        I::IntoIter: ExactSizeIterator,
+        T: AsyncWrite,
+    {
+        let iter = iter.into_iter();
+        let count = P::try_from(iter.len()).map_err(|_| WriteError::InvalidArgument)?;
+        self.write(&count).await?;
+        self.write_map_iter(iter, f).await
+    }
+    pub async fn write_vec_prefixed<P, T>(&mut self, values: &[T], policy: PrefixOverflow) -> Result<()>
+    where
+        P: AsyncWrite + TryFrom<usize> + Sync,
+        T: AsyncWrite + Send + Sync,
+    {
+        match policy {
+            PrefixOverflow::Error => self.write_prefixed_chunk::<P, T>(values).await,
+            PrefixOverflow::Truncate => self.write_prefixed_chunk::<P, T>(&values[..max_prefix_count::<P>(values.len())]).await,
+            PrefixOverflow::Chunked { continuation_flag } => {
+                let chunk_len = max_prefix_count::<P>(values.len());
+                if continuation_flag == 0 || (chunk_len == 0 && !values.is_empty()) {
+                    return Err(WriteError::InvalidArgument);
+                }
+                let mut chunks = values.chunks(chunk_len.max(1)).peekable();
+                if chunks.peek().is_none() {
+                    self.write_prefixed_chunk::<P, T>(&[]).await?;
+                    return self.u8(0).await;
+                }
+                while let Some(chunk) = chunks.next() {
+                    self.write_prefixed_chunk::<P, T>(chunk).await?;
+                    self.u8(if chunks.peek().is_some() { continuation_flag } else { 0 }).await?;
+                }
+                Ok(())
+            }
+        }
+    }
+    async fn write_prefixed_chunk<P, T>(&mut self, values: &[T]) -> Result<()>
+    where
+        P: AsyncWrite + TryFrom<usize> + Sync,
+        T: AsyncWrite + Send + Sync,
+    {
+        let count = P::try_from(values.len()).map_err(|_| WriteError::InvalidArgument)?;
+        self.write(&count).await?;
+        self.write(&values).await
+    }
+    pub async fn write_terminated<T: AsyncWrite + PartialEq + Send + Sync>(&mut self, items: &[T], terminator: &T) -> Result<()> {
+        if items.contains(terminator) {
+            return Err(WriteError::InvalidArgument);
+        }
+        self.write(&items).await?;
+        self.write(terminator).await
+    }
+    pub async fn write_terminated_iter<T, I>(&mut self, items: I, terminator: &T) -> Result<()>
+    where
+        T: AsyncWrite + PartialEq + Sync,
+        I: IntoIterator,
+        I::IntoIter: Send,
+        I::Item: Borrow<T> + Send,
+    {
+        for item in items {
+            let item = item.borrow();
+            if item == terminator {
+                return Err(WriteError::InvalidArgument);
+            }
+            self.write(item).await?;
+        }
+        self.write(terminator).await
+    }
+    pub async fn write_map_sorted<K, V, S>(&mut self, map: &HashMap<K, V, S>) -> Result<()>
+    where
+        K: Ord + AsyncWrite + Sync,
+        V: AsyncWrite + Sync,
+        S: Sync,
+    {
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+        for (key, value) in entries {
+            self.write(key).await?;
+            self.write(value).await?;
+        }
+        Ok(())
+    }
+    pub async fn write_set_sorted<T: Ord + AsyncWrite + Sync, S: Sync>(&mut self, set: &HashSet<T, S>) -> Result<()> {
+        let mut items: Vec<_> = set.iter().collect();
+        items.sort_unstable();
+        for item in items {
+            self.write(item).await?;
+        }
+        Ok(())
+    }
+    #[inline]
+    pub async fn write_frame<T: AsyncWrite + Sync>(&mut self, msg: &T) -> Result<()> {
+        self.write_frame_limited(msg, DEFAULT_MAX_FRAME_SIZE).await
+    }
+    pub async fn write_frame_limited<T: AsyncWrite + Sync>(&mut self, msg: &T, max_size: u32) -> Result<()> {
+        count_stats!(self, Bulk, async {
+            let mut payload = self.scratch_at(self.total_bytes_written + u32::SIZE as u64);
+            msg.write(&mut payload).await?;
+            let len = u32::try_from(payload.writer.len()).ok().filter(|len| *len <= max_size).ok_or(WriteError::InvalidArgument)?;
+            self.u32(len).await?;
+            self.write_bytes(&payload.writer).await
+        })
+    }
+    pub async fn write_len_prefixed<L: AsyncWrite + FixedSize + TryFrom<usize> + Sync, T: AsyncWrite + Sync>(&mut self, value: &T, policy: LenPolicy) -> Result<()> {
+        count_stats!(self, Bulk, async {
+            let mut payload = self.scratch_at(self.total_bytes_written + L::SIZE as u64);
+            payload.write_partial(value).await?;
+            let len = policy
+                .stored_len(payload.writer.len(), L::SIZE)
+                .and_then(|len| L::try_from(len).ok())
+                .ok_or(WriteError::InvalidArgument)?;
+            self.write(&len).await?;
+            self.write_bytes(&payload.writer).await
+        })
+    }
+    pub async fn write_len_prefixed_u32(&mut self, body: impl AsyncFnOnce(&mut AsyncBinaryWriter<E, Vec<u8>>) -> Result<()>) -> Result<()> {
+        count_stats!(self, Bulk, async {
+            let mut scratch = self.scratch_at(self.total_bytes_written + u32::SIZE as u64);
+            body(&mut scratch).await?;
+            self.u32_from_usize(scratch.writer.len()).await?;
+            self.write_bytes(&scratch.writer).await
+        })
+    }
+    /// Writes `value` `count` times, encoding it once at the current position and replicating the
+    /// bytes in chunks when every copy would come out the same, see `BinaryWriter::write_repeated`.
+    pub async fn write_repeated<T: AsyncWrite + Sync>(&mut self, value: &T, count: usize) -> Result<()> {
+        count_stats!(self, Bulk, async {
+            const CHUNK_SIZE: usize = 4096;
+            if count == 0 {
+                return Ok(());
+            }
+            let Some(encoded) = self.encode_repeatable(value).await? else {
+                for _ in 0..count {
+                    value.write(self).await?;
+                }
+                return Ok(());
+            };
+            if encoded.is_empty() {
+                return Ok(());
+            }
+            let per_chunk = (CHUNK_SIZE / encoded.len()).clamp(1, count.max(1));
+            let chunk = encoded.repeat(per_chunk);
+            for _ in 0..count / per_chunk {
+                self.write_bytes(&chunk).await?;
+            }
+            self.write_bytes(&chunk[..(count % per_chunk) * encoded.len()]).await
+        })
+    }
+    /// Encodes `value` as `write_repeated` would write its first copy, or returns `None` if later
+    /// copies might differ from it.
+    async fn encode_repeatable<T: AsyncWrite + Sync>(&self, value: &T) -> Result<Option<Vec<u8>>> {
+        let Some(hint) = value.size_hint() else {
+            return Ok(None);
+        };
+        let mut scratch = self.scratch_at(self.total_bytes_written);
+        scratch.region_start = self.region_start;
+        value.write(&mut scratch).await?;
+        Ok((scratch.writer.len() == hint && !scratch.padded).then_some(scratch.writer))
+    }
     #[inline]
     pub async fn write_with<A, T: AsyncWriteWith<A> + ?Sized>(&mut self, value: &T, arg: &A) -> Result<()> {
         value.write_with(self, arg).await
@@ -160,13 +793,14 @@ macro_rules! impl_writable_for_numeric_primitives {
                 async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
                     writer.$method(*self).await
                 }
+                fn size_hint(&self) -> Option<usize> {
+                    Some(std::mem::size_of::<$t>())
+                }
             }
         )*
     };
 }
 impl_writable_for_numeric_primitives!(
-    u8: u8,
-    i8: i8,
     u16: u16,
     i16: i16,
     u32: u32,
@@ -175,23 +809,61 @@ impl_writable_for_numeric_primitives!(
     u64: u64,
     i64: i64,
     f64: f64,
+    u128: u128,
+    i128: i128,
 );
 
+impl AsyncWrite for u8 {
+    async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
+        writer.u8(*self).await
+    }
+    fn size_hint(&self) -> Option<usize> {
+        Some(1)
+    }
+    async fn write_slice<E: Endian, W: WriterBase>(values: &[Self], writer: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
+        writer.write_bytes(values).await?;
+        record_stats!(writer, Bulk, values.len());
+        Ok(())
+    }
+}
+impl AsyncWrite for i8 {
+    async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
+        writer.i8(*self).await
+    }
+    fn size_hint(&self) -> Option<usize> {
+        Some(1)
+    }
+    async fn write_slice<E: Endian, W: WriterBase>(values: &[Self], writer: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
+        // SAFETY: `i8` and `u8` have the same size and alignment, and every bit pattern is a valid `u8`.
+        let bytes = unsafe { std::slice::from_raw_parts(values.as_ptr().cast::<u8>(), values.len()) };
+        u8::write_slice(bytes, writer).await
+    }
+}
+
 impl AsyncWrite for bool {
     async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
         writer.u8(if *self { 1 } else { 0 }).await
     }
+    fn size_hint(&self) -> Option<usize> {
+        Some(1)
+    }
 }
 impl AsyncWrite for char {
     async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
         writer.u32(*self as u32).await
     }
+    fn size_hint(&self) -> Option<usize> {
+        Some(4)
+    }
 }
 
 impl<T: AsyncWrite + Send + Sync> AsyncWrite for Option<T> {
     async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
         if let Some(value) = self { value.write(writer).await } else { Ok(()) }
     }
+    fn size_hint(&self) -> Option<usize> {
+        self.as_ref().map_or(Some(0), T::size_hint)
+    }
 }
 
 macro_rules! impl_writable_for_tuple {
@@ -205,6 +877,10 @@ macro_rules! impl_writable_for_tuple {
                 )+
                 Ok(())
             }
+            fn size_hint(&self) -> Option<usize> {
+                let ($(ref $name,)+) = *self;
+                [$( $name.size_hint()? ),+].into_iter().try_fold(0usize, usize::checked_add)
+            }
         }
     };
 }
@@ -216,16 +892,10 @@ impl_writable_for_tuple!(T1, T2, T3, T4, T5, T6);
 
 impl<T: AsyncWrite + Send + Sync> AsyncWrite for &[T] {
     async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
-        if std::mem::size_of::<T>() == 1 {
-            // SAFETY: We are assuming that T is a byte, so this is safe
-            let bytes = unsafe { std::slice::from_raw_parts(self.as_ptr() as *const u8, self.len()) };
-            writer.writer.write_all(bytes).await.map_err(WriteError::io_error)?;
-        } else {
-            for item in self.iter() {
-                item.write(writer).await?;
-            }
-        }
-        Ok(())
+        T::write_slice(self, writer).await
+    }
+    fn size_hint(&self) -> Option<usize> {
+        self.iter().try_fold(0usize, |sum, item| sum.checked_add(item.size_hint()?))
     }
 }
 
@@ -233,12 +903,45 @@ impl<T: AsyncWrite + Send + Sync, const N: usize> AsyncWrite for [T; N] {
     async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
         (&self[..]).write(writer).await
     }
+    fn size_hint(&self) -> Option<usize> {
+        (&self[..]).size_hint()
+    }
+}
+
+macro_rules! impl_async_writable_for_slice_containers {
+    ( $( $container:ident ),* ) => {
+        $(
+            impl<T: AsyncWrite + Send + Sync> AsyncWrite for $container<[T]> {
+                async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
+                    (&self[..]).write(writer).await
+                }
+                fn size_hint(&self) -> Option<usize> {
+                    (&self[..]).size_hint()
+                }
+            }
+        )*
+    };
+}
+
+// `Rc<[T]>` is left out: it is not `Sync`, so its write future could not be `Send`.
+impl_async_writable_for_slice_containers!(Box, Arc);
+
+impl<T: AsyncWrite + Send + Sync + Clone> AsyncWrite for Cow<'_, [T]> {
+    async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
+        (&self[..]).write(writer).await
+    }
+    fn size_hint(&self) -> Option<usize> {
+        (&self[..]).size_hint()
+    }
 }
 
 impl<T: AsyncWrite + Send + Sync> AsyncWrite for Vec<T> {
     async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
         self.as_slice().write(writer).await
     }
+    fn size_hint(&self) -> Option<usize> {
+        self.as_slice().size_hint()
+    }
 }
 
 #[tokio::test]
@@ -266,3 +969,473 @@ async fn test_async_binary_writer() {
     ];
     assert_eq!(&data[..], &expected[..]);
 }
+
+#[tokio::test]
+async fn test_fill_aligned_zero() {
+    let header = [0u8];
+    let mut data = Vec::new();
+    let mut writer = AsyncBinaryWriter::new_le(&mut data);
+    assert!(writer.u8(1).await.is_ok());
+    assert!(matches!(writer.fill_aligned(header[0] as usize, 1).await, Err(WriteError::InvalidArgument)));
+    assert_eq!(data, vec![1]);
+}
+
+#[tokio::test]
+async fn test_fill_aligned_boundaries() {
+    for (offset, padding) in [(0, 0), (7, 1), (8, 0), (9, 7), (16, 0)] {
+        let mut writer = AsyncBinaryWriter::new_le(Vec::new());
+        writer.fill_aligned(8, offset).await.unwrap();
+        assert_eq!(writer.finish().await.unwrap(), vec![0; padding], "offset {offset}");
+    }
+}
+
+#[tokio::test]
+async fn test_write_len_prefixed_u32() {
+    let mut writer = AsyncBinaryWriter::new_le(Vec::new());
+    writer
+        .write_len_prefixed_u32(async |chunk| {
+            chunk.u16(0x0102).await?;
+            chunk
+                .write_len_prefixed_u32(async |inner| {
+                    assert_eq!(inner.position(), 10);
+                    inner.u8(3).await
+                })
+                .await
+        })
+        .await
+        .unwrap();
+    assert_eq!(writer.position(), 11);
+    assert_eq!(writer.finish().await.unwrap(), [7, 0, 0, 0, 2, 1, 1, 0, 0, 0, 3]);
+}
+
+#[tokio::test]
+async fn test_align_to() {
+    let mut writer = AsyncBinaryWriter::new_le(Vec::new());
+    writer.write_as_be(&0x0102u16).await.unwrap();
+    writer.align_to(4).await.unwrap();
+    writer.reserved(0xff, 3).await.unwrap();
+    writer.align_to(4).await.unwrap();
+    writer.utf8_str("abc", crate::string::StringMode::NullTerminated).await.unwrap();
+    writer.align_to(8).await.unwrap();
+    assert_eq!(writer.position(), 16);
+    writer.align_to(8).await.unwrap();
+    assert!(matches!(writer.align_to(0).await, Err(WriteError::InvalidArgument)));
+    let data = writer.finish().await.unwrap();
+    assert_eq!(data, b"\x01\x02\0\0\xff\xff\xff\0abc\0\0\0\0\0");
+}
+
+#[tokio::test]
+async fn test_position() {
+    let mut data = Vec::new();
+    let mut writer = AsyncBinaryWriter::new_le(&mut data);
+    assert!(writer.write_as_be(&0x0102_u16).await.is_ok());
+    assert_eq!(writer.position(), 2);
+    assert!(writer.reserved(0, 3).await.is_ok());
+    assert!(writer.write(&[1u8, 2, 3]).await.is_ok());
+    assert!(writer.utf8_str("ab", crate::string::StringMode::NullTerminated).await.is_ok());
+    assert_eq!(writer.position(), 11);
+    assert_eq!(data.len(), 11);
+}
+
+#[test]
+fn test_size_hint() {
+    assert_eq!(AsyncWrite::size_hint(&(0x01u8, 0x0203i16, 'a')), Some(7));
+    assert_eq!(AsyncWrite::size_hint(&[1u32, 2, 3]), Some(12));
+    assert_eq!(AsyncWrite::size_hint(&vec![(1u16, true); 5]), Some(15));
+    assert_eq!(AsyncWrite::size_hint(&None::<u32>), Some(0));
+
+    struct Huge;
+    impl AsyncWrite for Huge {
+        async fn write<E: Endian, W: WriterBase>(&self, _: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
+            Ok(())
+        }
+        fn size_hint(&self) -> Option<usize> {
+            Some(usize::MAX)
+        }
+    }
+    assert_eq!(AsyncWrite::size_hint(&(1u8, Huge)), None);
+}
+
+#[tokio::test]
+async fn test_write_repeated() {
+    let record = (0x0102_u16, 0x03040506_u32);
+    let mut data = Vec::new();
+    let mut writer = AsyncBinaryWriter::new_be(&mut data);
+    assert!(writer.write_repeated(&record, 100_000).await.is_ok());
+    assert_eq!(writer.position(), 600_000);
+    assert!(writer.flush().await.is_ok());
+    assert_eq!(data.len(), 600_000);
+    assert_eq!(&data[6 * 54_321..6 * 54_322], &[1, 2, 3, 4, 5, 6]);
+
+    // an aligned value at an unaligned offset pads differently for every copy
+    struct Aligned(u8);
+    impl AsyncWrite for Aligned {
+        async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
+            writer.align_to(4).await?;
+            writer.u8(self.0).await
+        }
+        fn size_hint(&self) -> Option<usize> {
+            Some(1)
+        }
+    }
+    let mut data = Vec::new();
+    let mut writer = AsyncBinaryWriter::new_le(&mut data);
+    assert!(writer.u8(0xEE).await.is_ok());
+    assert!(writer.write_repeated(&Aligned(7), 3).await.is_ok());
+    assert_eq!(writer.position(), 13);
+    assert!(writer.flush().await.is_ok());
+    assert_eq!(data, [0xEE, 0, 0, 0, 7, 0, 0, 0, 7, 0, 0, 0, 7]);
+}
+
+#[tokio::test]
+async fn test_atomic_commit_and_discard() {
+    let dir = std::env::temp_dir().join(format!("rwbin-async-atomic-{}", std::process::id()));
+    tokio::fs::create_dir_all(&dir).await.unwrap();
+    let path = dir.join("save.bin");
+
+    let mut writer = AsyncBinaryWriter::create_atomic_be(&path).await.unwrap();
+    assert!(writer.u16(0x0102).await.is_ok());
+    assert!(writer.commit().await.is_ok());
+    assert_eq!(tokio::fs::read(&path).await.unwrap(), vec![1, 2]);
+
+    let mut writer = AsyncBinaryWriter::create_atomic_le(&path).await.unwrap();
+    assert!(writer.u16(0x0304).await.is_ok());
+    drop(writer);
+    assert_eq!(tokio::fs::read(&path).await.unwrap(), vec![1, 2]);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+}
+
+#[cfg(test)]
+#[derive(Default)]
+struct CountingSink {
+    data: Vec<u8>,
+    writes: usize,
+}
+
+#[cfg(test)]
+impl tokio::io::AsyncWrite for CountingSink {
+    fn poll_write(mut self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>, buf: &[u8]) -> std::task::Poll<std::io::Result<usize>> {
+        self.writes += 1;
+        self.data.extend_from_slice(buf);
+        std::task::Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<()>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<()>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+}
+
+#[tokio::test]
+async fn test_buffer_capacity() {
+    let mut writer = AsyncBinaryWriter::new_le(CountingSink::default()).with_buffer_capacity(64);
+    for i in 0..32u16 {
+        assert!(writer.u8(i as u8).await.is_ok());
+        assert!(writer.u16(i).await.is_ok());
+    }
+    assert!(writer.write_as_be(&0x0102u16).await.is_ok());
+    assert!(writer.reserved(0xff, 100).await.is_ok());
+    assert_eq!(writer.position(), 32 * 3 + 2 + 100);
+    let sink = writer.finish().await.unwrap();
+    assert!(sink.writes < 8);
+    assert_eq!(sink.data.len(), 32 * 3 + 2 + 100);
+    assert_eq!(&sink.data[..6], &[0, 0, 0, 1, 1, 0]);
+    assert_eq!(&sink.data[96..98], &[0x01, 0x02]);
+}
+
+#[tokio::test]
+async fn test_write_map_iter() {
+    let names = ["ab", "cde", "f"];
+    let mut data = Vec::new();
+    let mut writer = AsyncBinaryWriter::new_le(&mut data);
+    assert!(writer.write_map_iter_counted::<u16, _, _>(&names, |name| name.len() as u8).await.is_ok());
+    assert!(writer.write_map_iter(names.iter().rev(), |name| name.as_bytes()[0]).await.is_ok());
+    assert_eq!(data, vec![3, 0, 2, 3, 1, b'f', b'c', b'a']);
+}
+
+#[tokio::test]
+async fn test_write_sorted() {
+    use std::collections::hash_map::RandomState;
+    let mut first = HashMap::with_hasher(RandomState::new());
+    let mut second = HashMap::with_hasher(RandomState::new());
+    for key in 0..32u8 {
+        first.insert(key, u16::from(key) * 2);
+        second.insert(31 - key, u16::from(31 - key) * 2);
+    }
+    let mut first_bytes = Vec::new();
+    AsyncBinaryWriter::new_be(&mut first_bytes).write_map_sorted(&first).await.unwrap();
+    let mut second_bytes = Vec::new();
+    AsyncBinaryWriter::new_be(&mut second_bytes).write_map_sorted(&second).await.unwrap();
+    assert_eq!(first_bytes, second_bytes);
+    assert_eq!(&first_bytes[..6], &[0, 0, 0, 1, 0, 2]);
+
+    let set: HashSet<u8> = (0..8).rev().collect();
+    let mut set_bytes = Vec::new();
+    AsyncBinaryWriter::new_be(&mut set_bytes).write_set_sorted(&set).await.unwrap();
+    assert_eq!(set_bytes, (0..8).collect::<Vec<u8>>());
+}
+
+#[tokio::test]
+async fn test_write_slice_containers() {
+    let mut data = Vec::new();
+    let mut writer = AsyncBinaryWriter::new_le(&mut data);
+    writer.write(&Box::<[u8]>::from([1u8, 2])).await.unwrap();
+    writer.write(&Arc::<[u8]>::from([3u8])).await.unwrap();
+    writer.write(&Cow::Borrowed(&[4u8][..])).await.unwrap();
+    writer.write(&Box::<[u32]>::from([5u32])).await.unwrap();
+    writer.write(&Arc::<[u32]>::from([6u32])).await.unwrap();
+    writer.write(&Cow::<[u32]>::Owned(vec![7])).await.unwrap();
+    assert_eq!(data, vec![1, 2, 3, 4, 5, 0, 0, 0, 6, 0, 0, 0, 7, 0, 0, 0]);
+}
+
+#[tokio::test]
+async fn test_write_vec_prefixed_chunked() {
+    let values: Vec<u8> = (0..=255).chain(0..10).collect();
+    let mut data = Vec::new();
+    let mut writer = AsyncBinaryWriter::new_le(&mut data);
+    writer.write_vec_prefixed::<u8, u8>(&values, PrefixOverflow::Chunked { continuation_flag: 1 }).await.unwrap();
+    assert!(matches!(writer.write_vec_prefixed::<u8, u8>(&values, PrefixOverflow::Error).await, Err(WriteError::InvalidArgument)));
+    assert_eq!(data.len(), 1 + 255 + 1 + 1 + 11 + 1);
+
+    let mut reader = crate::async_reader::AsyncBinaryReader::from_le_bytes(&data);
+    assert_eq!(reader.read_vec_prefixed_chunked::<u8, u8>(1).await.unwrap(), values);
+}
+
+#[tokio::test]
+async fn test_write_as_nested_position() {
+    use crate::string::StringMode;
+    struct Record;
+    impl AsyncWrite for Record {
+        async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
+            writer.utf8_str("xy", StringMode::NullTerminated).await?;
+            writer.fill_aligned(4, writer.position() as usize).await?;
+            writer.u16(0x0102).await
+        }
+    }
+
+    let mut data = Vec::new();
+    let mut writer = AsyncBinaryWriter::new_be(&mut data);
+    writer.u16(0xaabb).await.unwrap();
+    writer.write_as_le(&Record).await.unwrap();
+    assert_eq!(writer.position(), 10);
+    writer.u16(0x0304).await.unwrap();
+    assert_eq!(writer.position(), 12);
+    assert_eq!(data, vec![0xaa, 0xbb, b'x', b'y', 0, 0, 0, 0, 2, 1, 3, 4]);
+}
+
+#[tokio::test]
+async fn test_reserved_for() {
+    let mut data = Vec::new();
+    let mut writer = AsyncBinaryWriter::new_be(&mut data);
+    writer.reserved_for::<u64>(0xee).await.unwrap();
+    writer.reserved_for::<(u16, u16)>(0x00).await.unwrap();
+    assert_eq!(data, [[0xee; 8].as_slice(), &[0; 4]].concat());
+
+    let mut reader = crate::async_reader::AsyncBinaryReader::from_be_bytes(&data);
+    reader.reserved_for::<u64>(0xee).await.unwrap();
+    reader.reserved_for::<(u16, u16)>(0x00).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_byte_sized_custom_write() {
+    struct Doubled(u8);
+    impl AsyncWrite for Doubled {
+        async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
+            writer.u8(self.0).await?;
+            writer.u8(self.0).await
+        }
+    }
+
+    let mut data = Vec::new();
+    let mut writer = AsyncBinaryWriter::new_le(&mut data);
+    writer.write(&[Doubled(7), Doubled(8)]).await.unwrap();
+    writer.write(&vec![-2i8, 3]).await.unwrap();
+    assert_eq!(data, vec![7, 7, 8, 8, 0xfe, 3]);
+}
+
+#[tokio::test]
+async fn test_write_terminated() {
+    let mut data = Vec::new();
+    let mut writer = AsyncBinaryWriter::new_le(&mut data);
+    writer.write_terminated(&[1u16, 2], &0xffff).await.unwrap();
+    writer.write_terminated_iter(vec![b'a', b'b'], &0).await.unwrap();
+    assert!(matches!(writer.write_terminated(&[b'x', 0], &0).await, Err(WriteError::InvalidArgument)));
+    assert_eq!(data, vec![1, 0, 2, 0, 0xff, 0xff, b'a', b'b', 0]);
+
+    let mut reader = crate::async_reader::AsyncBinaryReader::from_le_bytes(&data);
+    assert_eq!(reader.read_until(&0xffffu16).await.unwrap(), vec![1, 2]);
+    assert_eq!(reader.read_until(&0u8).await.unwrap(), b"ab");
+}
+
+#[tokio::test]
+async fn test_patch_and_sync_position() {
+    let mut cursor = std::io::Cursor::new(vec![0xee; 5]);
+    cursor.set_position(5);
+    let mut writer = AsyncBinaryWriter::new_be(cursor).with_buffer_capacity(8);
+    writer.u16(0).await.unwrap();
+    writer.utf8_str("abc", crate::string::StringMode::NullTerminated).await.unwrap();
+    writer.align_to(4).await.unwrap();
+    writer.patch(0, &6u16).await.unwrap();
+    assert_eq!(writer.sync_position().await.unwrap(), 8);
+    assert!(matches!(writer.patch(7, &0u16).await, Err(WriteError::InvalidArgument)));
+    assert!(matches!(writer.patch(8, &0u8).await, Err(WriteError::InvalidArgument)));
+    writer.patch(6, &0u16).await.unwrap();
+    assert_eq!(writer.position(), 8);
+    writer.u8(9).await.unwrap();
+    let buf = writer.finish().await.unwrap().into_inner();
+    assert_eq!(buf, [0xee, 0xee, 0xee, 0xee, 0xee, 0, 6, b'a', b'b', b'c', 0, 0, 0, 9]);
+}
+
+#[tokio::test]
+async fn test_sync_position_after_external_seek() {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let path = std::env::temp_dir().join(format!("rwbin-async-sync-position-{}.bin", std::process::id()));
+    let mut handle = std::fs::File::options().read(true).write(true).create(true).truncate(true).open(&path).unwrap();
+    handle.seek(SeekFrom::Start(4)).unwrap();
+    let file = tokio::fs::File::from_std(handle.try_clone().unwrap());
+    let mut writer = AsyncBinaryWriter::<LittleEndian, _>::new_seekable(file).await.unwrap();
+    writer.u16(0x0102).await.unwrap();
+    writer.flush().await.unwrap();
+
+    // Moved through the other handle before the writer ever looked at the stream.
+    handle.seek(SeekFrom::Start(10)).unwrap();
+    assert_eq!(writer.sync_position().await.unwrap(), 6);
+    writer.u8(3).await.unwrap();
+    writer.patch(0, &0x0405u16).await.unwrap();
+    assert_eq!(writer.position(), 7);
+    writer.finish().await.unwrap();
+
+    let mut data = Vec::new();
+    handle.seek(SeekFrom::Start(0)).unwrap();
+    handle.read_to_end(&mut data).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(data, [0, 0, 0, 0, 5, 4, 0, 0, 0, 0, 3]);
+}
+
+#[tokio::test]
+async fn test_write_len_prefixed_under_limit() {
+    let mut writer = crate::builder::BinaryWriterBuilder::new().limit(6).endian_le().build_async(Vec::new());
+    assert!(matches!(
+        writer.write_len_prefixed::<u16, _>(&[0u8; 5], LenPolicy::Exclusive).await,
+        Err(WriteError::LimitExceeded { .. })
+    ));
+    assert_eq!(writer.position(), 0);
+    writer.u8(0xaa).await.unwrap();
+    writer.write_len_prefixed::<u8, _>(&0x11u8, LenPolicy::Exclusive).await.unwrap();
+    assert_eq!(writer.finish().await.unwrap(), [0xaa, 1, 0x11]);
+}
+
+#[tokio::test]
+async fn test_write_frame_under_limit() {
+    let mut writer = crate::builder::BinaryWriterBuilder::new().limit(6).endian_be().build_async(Vec::new());
+    assert!(matches!(writer.write_frame(&0x1122_3344u32).await, Err(WriteError::LimitExceeded { position: 4, limit: 6, .. })));
+    assert_eq!(writer.position(), 0);
+    writer.write_frame(&0x1122u16).await.unwrap();
+    assert_eq!(writer.finish().await.unwrap(), [0, 0, 0, 2, 0x11, 0x22]);
+}
+
+#[tokio::test]
+async fn test_copy_from() {
+    let blob = vec![0x5a; 2 * 1024 * 1024 + 3];
+    let mut out = Vec::new();
+    let mut writer = AsyncBinaryWriter::new_be(&mut out);
+    writer.u32(7).await.unwrap();
+    assert_eq!(writer.copy_from(&mut blob.as_slice(), Some(blob.len() as u64)).await.unwrap(), blob.len() as u64);
+    writer.fill_aligned(16, writer.position() as usize).await.unwrap();
+    assert_eq!(writer.position(), 2 * 1024 * 1024 + 16);
+    assert!(writer.copy_from(&mut &blob[..2], Some(3)).await.is_err());
+    assert_eq!(writer.copy_from(&mut &blob[..0], None).await.unwrap(), 0);
+    writer.flush().await.unwrap();
+    assert_eq!(out.len(), 2 * 1024 * 1024 + 18);
+    assert_eq!(&out[4..4 + blob.len()], blob);
+}
+
+#[tokio::test]
+async fn test_write_interleaved() {
+    use crate::async_reader::AsyncBinaryReader;
+
+    let xs = [1.0f32, 2.0, 3.0];
+    let ys = [-1.0f32, -2.0, -3.0];
+    let zs = [0.5f32, 0.25, 0.125];
+    let mut writer = AsyncBinaryWriter::new_be(Vec::new());
+    writer.write_interleaved3(&xs, &ys, &zs).await.unwrap();
+    let data = writer.finish().await.unwrap();
+
+    let mut reference = AsyncBinaryWriter::new_be(Vec::new());
+    for row in 0..3 {
+        reference.write(&[xs[row], ys[row], zs[row]]).await.unwrap();
+    }
+    assert_eq!(data, reference.finish().await.unwrap());
+
+    let (mut rx, mut ry, mut rz) = (Vec::new(), Vec::new(), Vec::new());
+    let mut reader = AsyncBinaryReader::from_be_bytes(&data);
+    reader.read_deinterleaved3(3, &mut rx, &mut ry, &mut rz).await.unwrap();
+    assert_eq!((&rx[..], &ry[..], &rz[..]), (&xs[..], &ys[..], &zs[..]));
+    let mut writer = AsyncBinaryWriter::new_le(Vec::new());
+    assert!(matches!(writer.write_interleaved2(&[1u8, 2], &[3u8]).await, Err(WriteError::InvalidArgument)));
+}
+
+#[tokio::test]
+async fn test_checked_narrowing() {
+    use crate::async_reader::AsyncBinaryReader;
+
+    let mut writer = AsyncBinaryWriter::new_le(Vec::new());
+    writer.u32_from_usize(3).await.unwrap();
+    writer.write(&vec![7u8; 3]).await.unwrap();
+    assert!(matches!(writer.u8_from_usize(300).await, Err(WriteError::ValueTooLarge { value: 300, max: 255 })));
+    let data = writer.finish().await.unwrap();
+    assert_eq!(data, [3, 0, 0, 0, 7, 7, 7]);
+
+    let mut reader = AsyncBinaryReader::from_le_bytes(&data);
+    assert_eq!(reader.usize_from_u32().await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn test_align_in_region() {
+    struct Aligned(u8, u32);
+
+    impl AsyncWrite for Aligned {
+        async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> Result<()> {
+            writer.u8(self.0).await?;
+            writer.align_in_region(8, 0).await?;
+            writer.u32(self.1).await
+        }
+    }
+
+    let mut writer = AsyncBinaryWriter::new_be(Vec::new());
+    writer.u8(0xff).await.unwrap();
+    writer.write_len_prefixed::<u8, _>(&Aligned(1, 2), LenPolicy::InclusiveOfPrefix).await.unwrap();
+    writer.write_partial(&Aligned(3, 4)).await.unwrap();
+    assert!(matches!(writer.align_in_region(8, 0).await, Err(WriteError::InvalidArgument)));
+    let data = writer.finish().await.unwrap();
+    assert_eq!(data, [0xff, 13, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4]);
+}
+
+#[tokio::test]
+async fn test_128_bit_integers() {
+    let hash = 0x0102_0304_0506_0708_090A_0B0C_0D0E_0F10u128;
+
+    let mut writer = AsyncBinaryWriter::new_le(Vec::new());
+    writer.u128(hash).await.unwrap();
+    writer.write(&-2i128).await.unwrap();
+    let data = writer.finish().await.unwrap();
+    assert_eq!(&data[..16], hash.to_le_bytes());
+    let mut reader = crate::async_reader::AsyncBinaryReader::from_le_bytes(&data);
+    assert_eq!(reader.read::<u128>().await.unwrap(), hash);
+    assert_eq!(reader.i128().await.unwrap(), -2);
+
+    let mut writer = AsyncBinaryWriter::new_be(Vec::new());
+    writer.write(&hash).await.unwrap();
+    writer.i128(i128::MIN).await.unwrap();
+    let data = writer.finish().await.unwrap();
+    assert_eq!(&data[..16], hash.to_be_bytes());
+    let mut reader = crate::async_reader::AsyncBinaryReader::from_be_bytes(&data);
+    assert_eq!(reader.u128().await.unwrap(), hash);
+    assert_eq!(reader.read::<i128>().await.unwrap(), i128::MIN);
+}
@@ -0,0 +1,151 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Picks a temporary path next to `target`, so the final rename stays on one filesystem.
+fn temp_path_for(target: &Path) -> std::io::Result<PathBuf> {
+    let name = target
+        .file_name()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "atomic write target has no file name"))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(name);
+    temp_name.push(format!(".{}.{}.tmp", std::process::id(), TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)));
+    Ok(target.with_file_name(temp_name))
+}
+
+/// Makes the rename itself durable by syncing the containing directory where supported.
+fn sync_parent_dir(target: &Path) {
+    #[cfg(unix)]
+    if let Some(parent) = target.parent() {
+        let dir = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = target;
+}
+
+/// A file sink that only replaces its target once `commit` succeeds.
+///
+/// Bytes go to a temporary file in the target's directory. `commit` flushes, fsyncs and
+/// renames it over the target; dropping an uncommitted `AtomicFile` deletes the temporary
+/// file and leaves the target untouched.
+pub struct AtomicFile {
+    file: Option<BufWriter<File>>,
+    temp_path: PathBuf,
+    target: PathBuf,
+}
+
+impl AtomicFile {
+    pub fn create<P: AsRef<Path>>(target: P) -> std::io::Result<Self> {
+        let target = target.as_ref().to_path_buf();
+        let temp_path = temp_path_for(&target)?;
+        let file = File::options().write(true).create_new(true).open(&temp_path)?;
+        Ok(AtomicFile {
+            file: Some(BufWriter::new(file)),
+            temp_path,
+            target,
+        })
+    }
+
+    /// Returns the path that `commit` will replace.
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    pub fn commit(mut self) -> std::io::Result<()> {
+        let file = self.file.take().expect("AtomicFile used after commit");
+        let file = file.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        drop(file);
+        std::fs::rename(&self.temp_path, &self.target)?;
+        sync_parent_dir(&self.target);
+        Ok(())
+    }
+}
+
+impl std::io::Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.file.as_mut().expect("AtomicFile used after commit").write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.as_mut().expect("AtomicFile used after commit").flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        // `file` is only still present if `commit` never ran or failed before the rename.
+        if self.file.take().is_some() || self.temp_path.exists() {
+            let _ = std::fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+/// Asynchronous twin of `AtomicFile` built on `tokio::fs`.
+pub struct AsyncAtomicFile {
+    file: Option<tokio::io::BufWriter<tokio::fs::File>>,
+    temp_path: PathBuf,
+    target: PathBuf,
+}
+
+impl AsyncAtomicFile {
+    pub async fn create<P: AsRef<Path>>(target: P) -> std::io::Result<Self> {
+        let target = target.as_ref().to_path_buf();
+        let temp_path = temp_path_for(&target)?;
+        let file = tokio::fs::File::options().write(true).create_new(true).open(&temp_path).await?;
+        Ok(AsyncAtomicFile {
+            file: Some(tokio::io::BufWriter::new(file)),
+            temp_path,
+            target,
+        })
+    }
+
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    pub async fn commit(mut self) -> std::io::Result<()> {
+        use tokio::io::AsyncWriteExt;
+        let mut file = self.file.take().expect("AsyncAtomicFile used after commit");
+        file.flush().await?;
+        let file = file.into_inner();
+        file.sync_all().await?;
+        drop(file);
+        tokio::fs::rename(&self.temp_path, &self.target).await?;
+        sync_parent_dir(&self.target);
+        Ok(())
+    }
+}
+
+impl tokio::io::AsyncWrite for AsyncAtomicFile {
+    fn poll_write(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>, buf: &[u8]) -> std::task::Poll<std::io::Result<usize>> {
+        let file = self.get_mut().file.as_mut().expect("AsyncAtomicFile used after commit");
+        std::pin::Pin::new(file).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<()>> {
+        let file = self.get_mut().file.as_mut().expect("AsyncAtomicFile used after commit");
+        std::pin::Pin::new(file).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<()>> {
+        let file = self.get_mut().file.as_mut().expect("AsyncAtomicFile used after commit");
+        std::pin::Pin::new(file).poll_shutdown(cx)
+    }
+}
+
+impl Drop for AsyncAtomicFile {
+    fn drop(&mut self) {
+        if self.file.take().is_some() || self.temp_path.exists() {
+            let _ = std::fs::remove_file(&self.temp_path);
+        }
+    }
+}
@@ -0,0 +1,554 @@
+use crate::{
+    async_reader::AsyncBinaryReader,
+    async_writer::{AsyncBinaryWriter, WriterBase},
+    atomic::{AsyncAtomicFile, AtomicFile},
+    checksum::{ChecksumKind, ChecksumWriter},
+    endian::{BigEndian, Endian, LittleEndian},
+    reader::{BinaryReader, DEFAULT_SMALL_BUF_SIZE},
+    result::WriteError,
+    writer::BinaryWriter,
+};
+use std::{io::BufReader, marker::PhantomData};
+
+/// Collects reader options in one place and builds a `BinaryReader` or `AsyncBinaryReader` with
+/// all of them applied.
+///
+/// `new_le`, `from_le_bytes` and friends remain the shortcuts for a reader with default options;
+/// every other combination goes through the builder instead of another constructor:
+///
+/// ```
+/// use rwbin::builder::BinaryReaderBuilder;
+///
+/// let data = [0, 0, 0, 2, 0xff];
+/// let mut reader = BinaryReaderBuilder::new().endian_be().limit(4).max_alloc(1 << 20).build_from_slice(&data);
+/// assert_eq!(reader.u32().unwrap(), 2);
+/// assert!(reader.u8().is_err());
+/// ```
+///
+/// The builder starts out little-endian with no options set, and can be reused for any number of
+/// readers.
+pub struct BinaryReaderBuilder<E> {
+    pub(crate) limit: Option<u64>,
+    pub(crate) capacity: Option<usize>,
+    pub(crate) base_offset: u64,
+    pub(crate) small_buf_size: usize,
+    pub(crate) max_alloc: Option<usize>,
+    pub(crate) max_depth: Option<u32>,
+    pub(crate) strict_strings: bool,
+    #[cfg(feature = "stats")]
+    pub(crate) stats: Option<std::sync::Arc<crate::stats::Stats>>,
+    _endian: PhantomData<fn() -> E>,
+}
+
+impl BinaryReaderBuilder<LittleEndian> {
+    pub fn new() -> Self {
+        BinaryReaderBuilder {
+            limit: None,
+            capacity: None,
+            base_offset: 0,
+            small_buf_size: DEFAULT_SMALL_BUF_SIZE,
+            max_alloc: None,
+            max_depth: None,
+            strict_strings: false,
+            #[cfg(feature = "stats")]
+            stats: None,
+            _endian: PhantomData,
+        }
+    }
+}
+
+impl Default for BinaryReaderBuilder<LittleEndian> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> Clone for BinaryReaderBuilder<E> {
+    fn clone(&self) -> Self {
+        BinaryReaderBuilder {
+            #[cfg(feature = "stats")]
+            stats: self.stats.clone(),
+            _endian: PhantomData,
+            ..*self
+        }
+    }
+}
+
+impl<E> BinaryReaderBuilder<E> {
+    /// Switches the readers to the byte order `F`, e.g. `MiddleEndian`.
+    pub fn endian<F: Endian>(self) -> BinaryReaderBuilder<F> {
+        BinaryReaderBuilder {
+            limit: self.limit,
+            capacity: self.capacity,
+            base_offset: self.base_offset,
+            small_buf_size: self.small_buf_size,
+            max_alloc: self.max_alloc,
+            max_depth: self.max_depth,
+            strict_strings: self.strict_strings,
+            #[cfg(feature = "stats")]
+            stats: self.stats,
+            _endian: PhantomData,
+        }
+    }
+
+    pub fn endian_le(self) -> BinaryReaderBuilder<LittleEndian> {
+        self.endian()
+    }
+
+    pub fn endian_be(self) -> BinaryReaderBuilder<BigEndian> {
+        self.endian()
+    }
+
+    /// Lets the reader consume at most `limit` bytes. Slice-backed readers are limited to the
+    /// slice regardless.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Sets the buffer size `build` and `build_async` wrap their source in.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Reports the start of the stream as position `offset`, see `BinaryReader::with_base_offset`.
+    /// The limit still counts from the start.
+    pub fn base_offset(mut self, offset: u64) -> Self {
+        self.base_offset = offset;
+        self
+    }
+
+    /// See `BinaryReader::with_small_buf_size`. Async readers have no stack buffers and ignore it.
+    pub fn small_buf_size(mut self, size: usize) -> Self {
+        self.small_buf_size = size;
+        self
+    }
+
+    /// Fails reads that would allocate more than `max_alloc` bytes up front, such as a
+    /// `Vec<T>` of an untrusted length or a large `read_from_slice`, with
+    /// `ReadError::AllocationLimitExceeded` before anything is allocated or consumed.
+    pub fn max_alloc(mut self, max_alloc: usize) -> Self {
+        self.max_alloc = Some(max_alloc);
+        self
+    }
+
+    /// See `BinaryReader::set_max_depth`.
+    pub fn max_depth(mut self, max_depth: u32) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Makes `utf8_str` and `utf16_str` fail with `ReadError::InvalidDataFormat` on invalid
+    /// sequences instead of replacing them with U+FFFD.
+    pub fn strict_strings(mut self, strict: bool) -> Self {
+        self.strict_strings = strict;
+        self
+    }
+
+    /// Counts calls and bytes of every reader built into `stats`.
+    #[cfg(feature = "stats")]
+    pub fn stats(mut self, stats: &std::sync::Arc<crate::stats::Stats>) -> Self {
+        self.stats = Some(stats.clone());
+        self
+    }
+
+    /// Combines the configured limit with the one a reader already has, e.g. from its slice.
+    pub(crate) fn limit_for(&self, current: Option<u64>) -> Option<u64> {
+        match (self.limit, current) {
+            (Some(limit), Some(len)) => Some(limit.min(len)),
+            (limit, current) => limit.or(current),
+        }
+    }
+}
+
+impl<E: Endian> BinaryReaderBuilder<E> {
+    /// Builds a reader over `reader`, buffered with `capacity` bytes if set.
+    pub fn build<R: std::io::Read>(&self, reader: R) -> BinaryReader<E, BufReader<R>> {
+        let reader = match self.capacity {
+            Some(capacity) => BufReader::with_capacity(capacity, reader),
+            None => BufReader::new(reader),
+        };
+        self.build_buf_read(reader)
+    }
+
+    /// Builds a reader over a source that is already buffered, ignoring `capacity`.
+    pub fn build_buf_read<R: std::io::BufRead>(&self, reader: R) -> BinaryReader<E, R> {
+        BinaryReader::new(reader).with_options(self)
+    }
+
+    /// Builds a reader over an in-memory byte slice.
+    pub fn build_from_slice<'a>(&self, data: &'a [u8]) -> BinaryReader<E, &'a [u8]> {
+        BinaryReader::from_bytes(data).with_options(self)
+    }
+
+    /// Builds an async reader over `reader`, buffered with `capacity` bytes if set.
+    pub fn build_async<R: tokio::io::AsyncRead + Unpin + Send>(&self, reader: R) -> AsyncBinaryReader<E, tokio::io::BufReader<R>> {
+        let reader = match self.capacity {
+            Some(capacity) => tokio::io::BufReader::with_capacity(capacity, reader),
+            None => tokio::io::BufReader::new(reader),
+        };
+        self.build_async_buf_read(reader)
+    }
+
+    /// Builds an async reader over a source that is already buffered, ignoring `capacity`.
+    pub fn build_async_buf_read<R: crate::async_reader::ReaderBase>(&self, reader: R) -> AsyncBinaryReader<E, R> {
+        AsyncBinaryReader::new(reader).with_options(self)
+    }
+
+    /// Builds an async reader over an in-memory byte slice.
+    pub fn build_async_from_slice<'a>(&self, data: &'a [u8]) -> AsyncBinaryReader<E, &'a [u8]> {
+        AsyncBinaryReader::from_bytes(data).with_options(self)
+    }
+}
+
+/// How a `BinaryWriterBuilder` wraps the sinks it builds writers over.
+pub trait WrapSink {
+    type Sink<W>;
+
+    fn wrap<W>(&self, sink: W) -> Self::Sink<W>;
+}
+
+/// Leaves sinks as they are; the wrapping of a new `BinaryWriterBuilder`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlainSink;
+
+impl WrapSink for PlainSink {
+    type Sink<W> = W;
+
+    fn wrap<W>(&self, sink: W) -> W {
+        sink
+    }
+}
+
+/// Wraps sinks in a `ChecksumWriter` of this kind, set with `BinaryWriterBuilder::checksum`.
+impl WrapSink for ChecksumKind {
+    type Sink<W> = ChecksumWriter<W>;
+
+    fn wrap<W>(&self, sink: W) -> ChecksumWriter<W> {
+        ChecksumWriter::new(sink, *self)
+    }
+}
+
+/// Collects writer options in one place and builds a `BinaryWriter` or `AsyncBinaryWriter` with
+/// all of them applied, over any sink, a `Vec`, a fixed slice or an atomically replaced file.
+///
+/// `new_le`, `create_atomic_be` and friends remain the shortcuts for a writer with default
+/// options:
+///
+/// ```
+/// use rwbin::{builder::BinaryWriterBuilder, checksum::ChecksumKind};
+///
+/// let mut writer = BinaryWriterBuilder::new().endian_be().limit(8).checksum(ChecksumKind::Crc32).build_vec();
+/// writer.u32(0xcafe).unwrap();
+/// assert!(writer.u64(0).is_err());
+/// let bytes = writer.finish_with_checksum().unwrap();
+/// assert_eq!(bytes.len(), 8);
+/// ```
+///
+/// `checksum` changes the sink type to a `ChecksumWriter`, whose writers end with
+/// `finish_with_checksum`. The limit covers every byte written, including such a digest.
+pub struct BinaryWriterBuilder<E, S = PlainSink> {
+    pub(crate) limit: Option<u64>,
+    pub(crate) buffer_capacity: usize,
+    #[cfg(feature = "debug-tools")]
+    pub(crate) validate_roundtrip: bool,
+    #[cfg(feature = "stats")]
+    pub(crate) stats: Option<std::sync::Arc<crate::stats::Stats>>,
+    sink: S,
+    _endian: PhantomData<fn() -> E>,
+}
+
+impl BinaryWriterBuilder<LittleEndian> {
+    pub fn new() -> Self {
+        BinaryWriterBuilder {
+            limit: None,
+            buffer_capacity: 0,
+            #[cfg(feature = "debug-tools")]
+            validate_roundtrip: false,
+            #[cfg(feature = "stats")]
+            stats: None,
+            sink: PlainSink,
+            _endian: PhantomData,
+        }
+    }
+}
+
+impl Default for BinaryWriterBuilder<LittleEndian> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E, S: Clone> Clone for BinaryWriterBuilder<E, S> {
+    fn clone(&self) -> Self {
+        BinaryWriterBuilder {
+            limit: self.limit,
+            buffer_capacity: self.buffer_capacity,
+            #[cfg(feature = "debug-tools")]
+            validate_roundtrip: self.validate_roundtrip,
+            #[cfg(feature = "stats")]
+            stats: self.stats.clone(),
+            sink: self.sink.clone(),
+            _endian: PhantomData,
+        }
+    }
+}
+
+impl<E, S> BinaryWriterBuilder<E, S> {
+    fn map<F, T>(self, wrap: impl FnOnce(S) -> T) -> BinaryWriterBuilder<F, T> {
+        BinaryWriterBuilder {
+            limit: self.limit,
+            buffer_capacity: self.buffer_capacity,
+            #[cfg(feature = "debug-tools")]
+            validate_roundtrip: self.validate_roundtrip,
+            #[cfg(feature = "stats")]
+            stats: self.stats,
+            sink: wrap(self.sink),
+            _endian: PhantomData,
+        }
+    }
+
+    /// Switches the writers to the byte order `F`, e.g. `MiddleEndian`.
+    pub fn endian<F: Endian>(self) -> BinaryWriterBuilder<F, S> {
+        self.map(|sink| sink)
+    }
+
+    pub fn endian_le(self) -> BinaryWriterBuilder<LittleEndian, S> {
+        self.endian()
+    }
+
+    pub fn endian_be(self) -> BinaryWriterBuilder<BigEndian, S> {
+        self.endian()
+    }
+
+    /// Lets the writer emit at most `limit` bytes. A write that would pass it fails with
+    /// `WriteError::LimitExceeded` and writes nothing. Writers over a slice are limited to the
+    /// slice regardless.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Coalesces writes in a buffer of `capacity` bytes, see `BinaryWriter::with_buffer_capacity`.
+    pub fn buffer_capacity(mut self, capacity: usize) -> Self {
+        self.buffer_capacity = capacity;
+        self
+    }
+
+    /// Wraps sinks in a `ChecksumWriter`, so that `finish_with_checksum` appends the `kind` digest
+    /// of everything written.
+    pub fn checksum(self, kind: ChecksumKind) -> BinaryWriterBuilder<E, ChecksumKind> {
+        self.map(|_| kind)
+    }
+
+    /// Switches the checks of writers built with `build_validating` on or off, see
+    /// `ValidatingWriter`. Off by default.
+    #[cfg(feature = "debug-tools")]
+    pub fn validate_roundtrip(mut self, enabled: bool) -> Self {
+        self.validate_roundtrip = enabled;
+        self
+    }
+
+    /// Counts calls and bytes of every writer built into `stats`.
+    #[cfg(feature = "stats")]
+    pub fn stats(mut self, stats: &std::sync::Arc<crate::stats::Stats>) -> Self {
+        self.stats = Some(stats.clone());
+        self
+    }
+}
+
+impl<E: Endian, S: WrapSink> BinaryWriterBuilder<E, S> {
+    /// Builds a writer over `sink`.
+    pub fn build<W: std::io::Write>(&self, sink: W) -> BinaryWriter<E, S::Sink<W>>
+    where
+        S::Sink<W>: std::io::Write,
+    {
+        BinaryWriter::new(self.sink.wrap(sink)).with_options(self)
+    }
+
+    /// Builds a writer over a new `Vec`.
+    pub fn build_vec(&self) -> BinaryWriter<E, S::Sink<Vec<u8>>>
+    where
+        S::Sink<Vec<u8>>: std::io::Write,
+    {
+        self.build(Vec::new())
+    }
+
+    /// Builds a writer that fills `buf` from the start. Writes past its end fail with
+    /// `WriteError::LimitExceeded`; `position` tells how much of it was filled.
+    pub fn build_slice<'a>(&self, buf: &'a mut [u8]) -> BinaryWriter<E, S::Sink<&'a mut [u8]>>
+    where
+        S::Sink<&'a mut [u8]>: std::io::Write,
+    {
+        let len = buf.len() as u64;
+        let mut writer = self.build(buf);
+        writer.limit_to(len);
+        writer
+    }
+
+    /// Builds a writer whose output replaces `path` only when committed, see `AtomicFile`.
+    pub fn build_atomic<P: AsRef<std::path::Path>>(&self, path: P) -> Result<BinaryWriter<E, S::Sink<AtomicFile>>, WriteError>
+    where
+        S::Sink<AtomicFile>: std::io::Write,
+    {
+        AtomicFile::create(path).map(|file| self.build(file)).map_err(WriteError::io_error)
+    }
+
+    /// Builds a `ValidatingWriter` over `sink`, checking values only if `validate_roundtrip` is set.
+    #[cfg(feature = "debug-tools")]
+    pub fn build_validating<W: std::io::Write>(&self, sink: W) -> crate::validate::ValidatingWriter<E, S::Sink<W>>
+    where
+        S::Sink<W>: std::io::Write,
+    {
+        crate::validate::ValidatingWriter::with_checks(self.build(sink), self.validate_roundtrip)
+    }
+
+    /// Builds an async writer over `sink`.
+    pub fn build_async<W: WriterBase>(&self, sink: W) -> AsyncBinaryWriter<E, S::Sink<W>>
+    where
+        S::Sink<W>: WriterBase,
+    {
+        AsyncBinaryWriter::new(self.sink.wrap(sink)).with_options(self)
+    }
+
+    /// Builds an async writer whose output replaces `path` only when committed, see `AsyncAtomicFile`.
+    pub async fn build_async_atomic<P: AsRef<std::path::Path>>(&self, path: P) -> Result<AsyncBinaryWriter<E, S::Sink<AsyncAtomicFile>>, WriteError>
+    where
+        S::Sink<AsyncAtomicFile>: WriterBase,
+    {
+        AsyncAtomicFile::create(path).await.map(|file| self.build_async(file)).map_err(WriteError::io_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{result::ReadError, string::StringMode};
+
+    #[test]
+    fn test_builder_options() {
+        let data = [0, 1, 0, 2, 0xff, 0xfe, 0, 0, 0, 0, 0, 0];
+        let builder = BinaryReaderBuilder::new().endian_be().limit(5).base_offset(0x100);
+        let mut reader = builder.build_from_slice(&data);
+        assert_eq!((reader.position(), reader.remaining()), (0x100, Some(5)));
+        assert_eq!(reader.u32().unwrap(), 0x0001_0002);
+        assert!(matches!(reader.u16(), Err(ReadError::NotEnoughBytes { offset: 0x104, .. })));
+
+        // the slice still bounds a limit beyond it
+        let mut reader = BinaryReaderBuilder::new().limit(100).capacity(2).build(&data[..4]);
+        assert_eq!(reader.u32().unwrap(), 0x0200_0100);
+        assert_eq!(BinaryReaderBuilder::new().limit(100).build_from_slice(&data).remaining(), Some(12));
+
+        let mut reader = BinaryReaderBuilder::new().max_alloc(4).small_buf_size(0).build_from_slice(&data);
+        assert_eq!(reader.read_with::<Vec<u16>, _>(2).unwrap(), [0x0100, 0x0200]);
+        let err = reader.read_with::<Vec<u16>, _>(3).unwrap_err();
+        assert!(matches!(
+            err,
+            ReadError::AllocationLimitExceeded {
+                requested: 6,
+                max_alloc: 4,
+                offset: 4
+            }
+        ));
+        assert_eq!(err.to_string(), "allocation of 6 bytes at offset 0x4 (4) exceeds the limit of 4 bytes");
+        assert!(matches!(reader.read_from_slice(5, |b| Ok(b.len())), Err(ReadError::AllocationLimitExceeded { requested: 5, .. })));
+        assert_eq!(reader.position(), 4);
+
+        let mut reader = BinaryReaderBuilder::new().max_depth(1).build_from_slice(&data);
+        assert_eq!(reader.read::<u8>().unwrap(), 0);
+        assert!(matches!(reader.read_with::<Vec<u8>, _>(1), Err(ReadError::DepthLimitExceeded { max_depth: 1, offset: 1 })));
+
+        let bad = b"ok\xff\0";
+        let lossy = BinaryReaderBuilder::new().build_from_slice(bad).utf8_str(StringMode::NullTerminated).unwrap();
+        assert_eq!(lossy, "ok\u{FFFD}");
+        let strict = BinaryReaderBuilder::new().strict_strings(true);
+        assert!(matches!(strict.build_from_slice(bad).utf8_str(StringMode::NullTerminated), Err(ReadError::InvalidDataFormat(_))));
+        assert!(matches!(strict.build_from_slice(bad).utf8_str(StringMode::FixedChars(4)), Err(ReadError::InvalidDataFormat(_))));
+        assert!(matches!(
+            strict.build_from_slice(b"\0\xd8\0\0").utf16_str(StringMode::NullTerminated),
+            Err(ReadError::InvalidDataFormat(_))
+        ));
+        assert_eq!(strict.build_from_slice(b"fine\0").utf8_str(StringMode::NullTerminated).unwrap(), "fine");
+    }
+
+    #[test]
+    fn test_writer_limit_and_checksum() {
+        let builder = BinaryWriterBuilder::new().endian_be().limit(10).buffer_capacity(4).checksum(ChecksumKind::Crc32);
+        let mut writer = builder.build_vec();
+        writer.u32(0xdead_beef).unwrap();
+        writer.u16(7).unwrap();
+        assert_eq!(writer.remaining(), Some(4));
+        let err = writer.u64(1).unwrap_err();
+        assert!(matches!(err, WriteError::LimitExceeded { requested: 8, position: 6, limit: 10 }));
+        assert_eq!(writer.position(), 6);
+        let bytes = writer.finish_with_checksum().unwrap();
+        assert_eq!(bytes.len(), 10);
+        assert_eq!(bytes[..6], [0xde, 0xad, 0xbe, 0xef, 0, 7]);
+
+        let mut reader = BinaryReader::from_be_bytes(&bytes);
+        reader.verify_trailing_checksum(ChecksumKind::Crc32).unwrap();
+
+        // the digest counts toward the limit
+        let mut writer = builder.clone().limit(9).build_vec();
+        writer.u32(0).unwrap();
+        writer.u16(0).unwrap();
+        assert!(matches!(writer.finish_with_checksum(), Err(WriteError::LimitExceeded { requested: 4, .. })));
+    }
+
+    #[test]
+    fn test_writer_slice_sink() {
+        let mut buf = [0u8; 6];
+        let mut writer = BinaryWriterBuilder::new().limit(100).build_slice(&mut buf);
+        writer.u32(1).unwrap();
+        assert!(matches!(writer.u32(2), Err(WriteError::LimitExceeded { limit: 6, .. })));
+        writer.u16(3).unwrap();
+        assert_eq!(writer.position(), 6);
+        writer.finish().unwrap();
+        assert_eq!(buf, [1, 0, 0, 0, 3, 0]);
+    }
+
+    #[cfg(feature = "debug-tools")]
+    #[test]
+    fn test_writer_validate_roundtrip() {
+        #[derive(Debug, PartialEq)]
+        struct Lossy(u16);
+        impl crate::writer::Write for Lossy {
+            fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> crate::writer::Result<()> {
+                writer.u8(self.0 as u8)
+            }
+        }
+        impl crate::reader::Read for Lossy {
+            fn read<E: Endian, R: std::io::BufRead>(reader: &mut BinaryReader<E, R>) -> crate::reader::Result<Self> {
+                reader.u8().map(|v| Lossy(v as u16))
+            }
+        }
+
+        let mut writer = BinaryWriterBuilder::new().validate_roundtrip(true).build_validating(Vec::new());
+        writer.write(&Lossy(1)).unwrap();
+        if cfg!(debug_assertions) {
+            assert!(matches!(writer.write(&Lossy(0x100)), Err(WriteError::RoundTripMismatch { .. })));
+        }
+        let mut writer = BinaryWriterBuilder::new().build_validating(Vec::new());
+        writer.write(&Lossy(0x100)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_async_builder_options() {
+        let data = [0xd8, 0, 0, 0, 1, 2, 3];
+        let builder = BinaryReaderBuilder::new().endian_be().limit(4).strict_strings(true).max_alloc(2);
+        let mut reader = builder.build_async_from_slice(&data);
+        assert!(matches!(reader.utf16_str(StringMode::NullTerminated).await, Err(ReadError::InvalidDataFormat(_))));
+        let mut reader = builder.build_async(&data[2..]);
+        assert_eq!(reader.u16().await.unwrap(), 0);
+        assert!(matches!(reader.read_with::<_, Vec<u8>>(3).await, Err(ReadError::AllocationLimitExceeded { requested: 3, .. })));
+        assert_eq!(reader.read_with::<_, Vec<u8>>(2).await.unwrap(), [1, 2]);
+        assert!(reader.u8().await.is_err());
+
+        let mut writer = BinaryWriterBuilder::new().limit(6).checksum(ChecksumKind::Adler32).build_async(Vec::new());
+        writer.u16(1).await.unwrap();
+        assert!(matches!(writer.u64(2).await, Err(WriteError::LimitExceeded { requested: 8, position: 2, limit: 6 })));
+        let bytes = writer.finish_with_checksum().await.unwrap();
+        assert_eq!(bytes, [1, 0, 2, 0, 4, 0]);
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{endian::Endian, reader::BinaryReader, writer::BinaryWriter};
+use std::io::{BufRead, Result};
+
+pub use crate::endian::{BigEndian, LittleEndian, NativeEndian};
+
+/// `byteorder`'s short name for `LittleEndian`.
+pub type LE = LittleEndian;
+/// `byteorder`'s short name for `BigEndian`.
+pub type BE = BigEndian;
+/// Network byte order, which is big-endian.
+pub type NetworkEndian = BigEndian;
+
+/// `byteorder`'s `ReadBytesExt` methods on `BinaryReader`, for porting code one call site at a time.
+pub use RwbinByteorderCompat as ReadBytesExt;
+/// `byteorder`'s `WriteBytesExt` methods on `BinaryWriter`.
+pub use RwbinByteorderCompatWrite as WriteBytesExt;
+
+/// Generates the `$name::<B>()` readers of `RwbinByteorderCompat`.
+macro_rules! compat_read_fns {
+    ($($name:ident: $t:ty),* $(,)?) => {
+        $(
+            #[doc = concat!("Reads a `", stringify!($t), "` in the byte order `B`.")]
+            fn $name<B: Endian>(&mut self) -> Result<$t>;
+        )*
+    };
+}
+
+/// Generates the `$name::<B>(n)` writers of `RwbinByteorderCompatWrite`.
+macro_rules! compat_write_fns {
+    ($($name:ident: $t:ty),* $(,)?) => {
+        $(
+            #[doc = concat!("Writes a `", stringify!($t), "` in the byte order `B`.")]
+            fn $name<B: Endian>(&mut self, n: $t) -> Result<()>;
+        )*
+    };
+}
+
+/// Implements endian-generic compat methods with `read_num_as` / `write_num_as`.
+macro_rules! impl_compat_fns {
+    (read: $($name:ident: $t:ty),* $(,)?) => {
+        $(
+            #[inline]
+            fn $name<B: Endian>(&mut self) -> Result<$t> {
+                Ok(self.read_num_as::<B, $t>()?)
+            }
+        )*
+    };
+    (write: $($name:ident: $t:ty),* $(,)?) => {
+        $(
+            #[inline]
+            fn $name<B: Endian>(&mut self, n: $t) -> Result<()> {
+                Ok(self.write_num_as::<B, $t>(n)?)
+            }
+        )*
+    };
+}
+
+/// The reading half of `byteorder`'s API: `read_u16::<BigEndian>()` and friends, failing with a
+/// `std::io::Error` as `byteorder` does.
+///
+/// Import it as `ReadBytesExt` together with the byte order types of this module, and a parser
+/// written against `byteorder` compiles against a `BinaryReader` unchanged. The type parameter
+/// picks the byte order of each call; the reader's own byte order is not used. Running out of
+/// bytes, including at the reader's limit, fails with `ErrorKind::UnexpectedEof`.
+pub trait RwbinByteorderCompat {
+    /// Reads a `u8`.
+    fn read_u8(&mut self) -> Result<u8>;
+    /// Reads an `i8`.
+    fn read_i8(&mut self) -> Result<i8>;
+    /// Fills `buf` with the next bytes, like `std::io::Read::read_exact`.
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()>;
+
+    compat_read_fns! {
+        read_u16: u16, read_i16: i16,
+        read_u32: u32, read_i32: i32,
+        read_u64: u64, read_i64: i64,
+        read_u128: u128, read_i128: i128,
+        read_f32: f32, read_f64: f64,
+    }
+}
+
+impl<E: Endian, R: BufRead> RwbinByteorderCompat for BinaryReader<E, R> {
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.u8()?)
+    }
+
+    fn read_i8(&mut self) -> Result<i8> {
+        Ok(self.i8()?)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        Ok(self.read_from_slice(buf.len(), |bytes| {
+            buf.copy_from_slice(bytes);
+            Ok(())
+        })?)
+    }
+
+    impl_compat_fns! { read:
+        read_u16: u16, read_i16: i16,
+        read_u32: u32, read_i32: i32,
+        read_u64: u64, read_i64: i64,
+        read_u128: u128, read_i128: i128,
+        read_f32: f32, read_f64: f64,
+    }
+}
+
+/// The writing half of `byteorder`'s API: `write_u16::<BigEndian>(n)` and friends, see
+/// `RwbinByteorderCompat`.
+pub trait RwbinByteorderCompatWrite {
+    /// Writes a `u8`.
+    fn write_u8(&mut self, n: u8) -> Result<()>;
+    /// Writes an `i8`.
+    fn write_i8(&mut self, n: i8) -> Result<()>;
+    /// Writes all of `buf`, like `std::io::Write::write_all`.
+    fn write_all(&mut self, buf: &[u8]) -> Result<()>;
+
+    compat_write_fns! {
+        write_u16: u16, write_i16: i16,
+        write_u32: u32, write_i32: i32,
+        write_u64: u64, write_i64: i64,
+        write_u128: u128, write_i128: i128,
+        write_f32: f32, write_f64: f64,
+    }
+}
+
+impl<E: Endian, W: std::io::Write> RwbinByteorderCompatWrite for BinaryWriter<E, W> {
+    fn write_u8(&mut self, n: u8) -> Result<()> {
+        Ok(self.u8(n)?)
+    }
+
+    fn write_i8(&mut self, n: i8) -> Result<()> {
+        Ok(self.i8(n)?)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        Ok(self.write_bytes(buf)?)
+    }
+
+    impl_compat_fns! { write:
+        write_u16: u16, write_i16: i16,
+        write_u32: u32, write_i32: i32,
+        write_u64: u64, write_i64: i64,
+        write_u128: u128, write_i128: i128,
+        write_f32: f32, write_f64: f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Ported from a `byteorder`-based parser; only the `use` line changed.
+    use super::{BigEndian, LittleEndian, NetworkEndian, ReadBytesExt, WriteBytesExt};
+    use std::io;
+
+    #[derive(Debug, PartialEq)]
+    struct Header {
+        magic: [u8; 4],
+        version: u16,
+        flags: u32,
+        scale: f32,
+        offsets: Vec<i64>,
+    }
+
+    fn read_header<R: ReadBytesExt>(rdr: &mut R) -> io::Result<Header> {
+        let mut magic = [0; 4];
+        rdr.read_exact(&mut magic)?;
+        let version = rdr.read_u16::<BigEndian>()?;
+        let flags = rdr.read_u32::<LittleEndian>()?;
+        let scale = rdr.read_f32::<LittleEndian>()?;
+        let count = rdr.read_u8()?;
+        let mut offsets = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            offsets.push(rdr.read_i64::<NetworkEndian>()?);
+        }
+        Ok(Header {
+            magic,
+            version,
+            flags,
+            scale,
+            offsets,
+        })
+    }
+
+    fn write_header<W: WriteBytesExt>(wtr: &mut W, header: &Header) -> io::Result<()> {
+        wtr.write_all(&header.magic)?;
+        wtr.write_u16::<BigEndian>(header.version)?;
+        wtr.write_u32::<LittleEndian>(header.flags)?;
+        wtr.write_f32::<LittleEndian>(header.scale)?;
+        wtr.write_u8(header.offsets.len() as u8)?;
+        for &offset in &header.offsets {
+            wtr.write_i64::<NetworkEndian>(offset)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_ported_parser() {
+        use crate::{reader::BinaryReader, writer::BinaryWriter};
+
+        let header = Header {
+            magic: *b"RWB\0",
+            version: 0x0102,
+            flags: 0x0a0b0c0d,
+            scale: 1.5,
+            offsets: vec![-1, 0x10],
+        };
+        // the writer's own byte order does not matter, each call picks one
+        let mut wtr = BinaryWriter::new_le(Vec::new());
+        write_header(&mut wtr, &header).unwrap();
+        let data = wtr.finish().unwrap();
+        assert_eq!(&data[..14], b"RWB\0\x01\x02\x0d\x0c\x0b\x0a\x00\x00\xc0\x3f");
+        assert_eq!(&data[23..], [0, 0, 0, 0, 0, 0, 0, 0x10]);
+
+        let mut rdr = BinaryReader::from_be_bytes(&data);
+        assert_eq!(read_header(&mut rdr).unwrap(), header);
+
+        let mut rdr = BinaryReader::from_be_bytes(&data[..20]);
+        assert_eq!(read_header(&mut rdr).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        let mut rdr = BinaryReader::new_be(&data[..1]);
+        assert_eq!(rdr.read_u16::<BigEndian>().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,190 @@
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The digest algorithms available for trailing checksums.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChecksumKind {
+    /// The CRC-32 of zlib, PNG and ZIP (reflected polynomial `0xEDB88320`).
+    Crc32,
+    /// The Adler-32 of zlib streams.
+    Adler32,
+}
+
+impl ChecksumKind {
+    /// Returns the size of the stored digest in bytes.
+    pub const fn digest_len(self) -> usize {
+        match self {
+            ChecksumKind::Crc32 | ChecksumKind::Adler32 => 4,
+        }
+    }
+
+    /// Returns the digest of `bytes`.
+    pub fn of(self, bytes: &[u8]) -> u32 {
+        let mut checksum = Checksum::new(self);
+        checksum.update(bytes);
+        checksum.value()
+    }
+}
+
+impl fmt::Display for ChecksumKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ChecksumKind::Crc32 => "CRC-32",
+            ChecksumKind::Adler32 => "Adler-32",
+        })
+    }
+}
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+const ADLER32_MOD: u32 = 65521;
+
+/// A running digest, fed chunk by chunk with `update`.
+#[derive(Debug, Clone)]
+pub struct Checksum {
+    kind: ChecksumKind,
+    state: u32,
+}
+
+impl Checksum {
+    pub fn new(kind: ChecksumKind) -> Self {
+        let state = match kind {
+            ChecksumKind::Crc32 => !0,
+            ChecksumKind::Adler32 => 1,
+        };
+        Checksum { kind, state }
+    }
+
+    pub fn kind(&self) -> ChecksumKind {
+        self.kind
+    }
+
+    /// Adds `bytes` to the digest.
+    pub fn update(&mut self, bytes: &[u8]) {
+        match self.kind {
+            ChecksumKind::Crc32 => {
+                for &byte in bytes {
+                    self.state = CRC32_TABLE[((self.state ^ byte as u32) & 0xff) as usize] ^ (self.state >> 8);
+                }
+            }
+            ChecksumKind::Adler32 => {
+                let (mut a, mut b) = (self.state & 0xffff, self.state >> 16);
+                // 5552 bytes is the longest run before `b` could overflow a u32
+                for chunk in bytes.chunks(5552) {
+                    for &byte in chunk {
+                        a += byte as u32;
+                        b += a;
+                    }
+                    a %= ADLER32_MOD;
+                    b %= ADLER32_MOD;
+                }
+                self.state = (b << 16) | a;
+            }
+        }
+    }
+
+    /// Returns the digest of everything added so far.
+    pub fn value(&self) -> u32 {
+        match self.kind {
+            ChecksumKind::Crc32 => !self.state,
+            ChecksumKind::Adler32 => self.state,
+        }
+    }
+}
+
+/// A sink that passes bytes through to `inner` while adding them to a running `Checksum`.
+///
+/// Wrap the sink of a `BinaryWriter` or `AsyncBinaryWriter` in it and end with
+/// `finish_with_checksum` to append the digest of everything written. Only bytes the inner sink
+/// accepted are counted.
+pub struct ChecksumWriter<W> {
+    inner: W,
+    checksum: Checksum,
+}
+
+impl<W> ChecksumWriter<W> {
+    pub fn new(inner: W, kind: ChecksumKind) -> Self {
+        ChecksumWriter { inner, checksum: Checksum::new(kind) }
+    }
+
+    /// Returns the digest of everything written so far.
+    pub fn checksum(&self) -> &Checksum {
+        &self.checksum
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: std::io::Write> std::io::Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.checksum.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: tokio::io::AsyncWrite + Unpin> tokio::io::AsyncWrite for ChecksumWriter<W> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        let written = std::task::ready!(Pin::new(&mut this.inner).poll_write(cx, buf))?;
+        this.checksum.update(&buf[..written]);
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_digests() {
+        assert_eq!(ChecksumKind::Crc32.of(b"123456789"), 0xCBF4_3926);
+        assert_eq!(ChecksumKind::Crc32.of(b""), 0);
+        assert_eq!(ChecksumKind::Adler32.of(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(ChecksumKind::Adler32.of(b""), 1);
+
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i * 7) as u8).collect();
+        for kind in [ChecksumKind::Crc32, ChecksumKind::Adler32] {
+            let mut checksum = Checksum::new(kind);
+            for chunk in data.chunks(333) {
+                checksum.update(chunk);
+            }
+            assert_eq!(checksum.value(), kind.of(&data), "{kind}");
+        }
+    }
+}
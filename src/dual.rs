@@ -0,0 +1,198 @@
+use crate::{
+    endian::{BigEndian, LittleEndian},
+    string::StringMode,
+    writer::{BinaryWriter, Result, Write},
+};
+
+/// Writes the same data as a little-endian and a big-endian stream in one pass.
+///
+/// Every call is carried out on both writers, each encoding primitives in its own byte order, so a
+/// converter walks its source data once and gets both files. Structural calls such as `reserved`
+/// or `fill_aligned` produce the same bytes on both sides, and since both streams hold the same
+/// values they stay the same length. A failed call may have reached only the little-endian side.
+pub struct DualWriter<WL, WB> {
+    le: BinaryWriter<LittleEndian, WL>,
+    be: BinaryWriter<BigEndian, WB>,
+}
+
+/// Forwards `$name` to both writers.
+macro_rules! dual_fns {
+    ($($(#[$doc:meta])* $name:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            $(#[$doc])*
+            #[inline]
+            pub fn $name(&mut self, $($arg: $ty),*) -> Result<()> {
+                self.le.$name($($arg),*)?;
+                self.be.$name($($arg),*)
+            }
+        )*
+    };
+}
+
+impl<WL: std::io::Write, WB: std::io::Write> DualWriter<WL, WB> {
+    /// Writes little-endian output to `le` and big-endian output to `be`.
+    pub fn new(le: WL, be: WB) -> Self {
+        Self {
+            le: BinaryWriter::new_le(le),
+            be: BinaryWriter::new_be(be),
+        }
+    }
+
+    /// Returns the number of bytes written to each stream.
+    pub fn position(&self) -> u64 {
+        self.le.position()
+    }
+
+    dual_fns! {
+        /// Writes an unsigned 8‑bit integer.
+        u8(value: u8);
+        /// Writes a signed 8‑bit integer.
+        i8(value: i8);
+        /// Writes an unsigned 16‑bit integer.
+        u16(value: u16);
+        /// Writes a signed 16‑bit integer.
+        i16(value: i16);
+        /// Writes an unsigned 32‑bit integer.
+        u32(value: u32);
+        /// Writes a signed 32‑bit integer.
+        i32(value: i32);
+        /// Writes a 32‑bit float.
+        f32(value: f32);
+        /// Writes an unsigned 64‑bit integer.
+        u64(value: u64);
+        /// Writes a signed 64‑bit integer.
+        i64(value: i64);
+        /// Writes a 64‑bit float.
+        f64(value: f64);
+        /// Writes an unsigned 128‑bit integer.
+        u128(value: u128);
+        /// Writes a signed 128‑bit integer.
+        i128(value: i128);
+        /// Writes `value` as a `u8`, or fails with `WriteError::ValueTooLarge`.
+        u8_from_usize(value: usize);
+        /// Writes `value` as a `u16`, or fails with `WriteError::ValueTooLarge`.
+        u16_from_usize(value: usize);
+        /// Writes `value` as a `u32`, or fails with `WriteError::ValueTooLarge`.
+        u32_from_usize(value: usize);
+        /// Writes `value` as a `u64`, or fails with `WriteError::ValueTooLarge`.
+        u64_from_usize(value: usize);
+        /// Writes `len` bytes of the given value (reserved space).
+        reserved(value: u8, len: usize);
+        /// Pads both streams with zeros up to the next multiple of `alignment`.
+        fill_aligned(alignment: usize, offset: usize);
+        /// Pads both streams with zeros until their position is a multiple of `alignment`.
+        align_to(alignment: usize);
+    }
+
+    /// Writes a value implementing `Write` to both streams.
+    ///
+    /// The value is encoded twice, but it is only produced once by the caller.
+    #[inline]
+    pub fn write<T: Write>(&mut self, value: &T) -> Result<()> {
+        self.le.write(value)?;
+        self.be.write(value)
+    }
+
+    pub fn utf8_str<T: AsRef<str>>(&mut self, value: T, mode: StringMode) -> Result<()> {
+        self.le.utf8_str(value.as_ref(), same_mode(&mode))?;
+        self.be.utf8_str(value, mode)
+    }
+
+    pub fn utf16_str<T: AsRef<str>>(&mut self, value: T, mode: StringMode) -> Result<()> {
+        self.le.utf16_str(value.as_ref(), same_mode(&mode))?;
+        self.be.utf16_str(value, mode)
+    }
+
+    /// Flushes both writers.
+    pub fn flush(&mut self) -> Result<()> {
+        self.le.flush()?;
+        self.be.flush()
+    }
+
+    /// Flushes everything and returns the little- and big-endian sinks.
+    pub fn finish(self) -> Result<(WL, WB)> {
+        Ok((self.le.finish()?, self.be.finish()?))
+    }
+}
+
+impl<WL, WB> DualWriter<WL, WB> {
+    /// Returns the little-endian and big-endian writers.
+    pub fn into_inner(self) -> (BinaryWriter<LittleEndian, WL>, BinaryWriter<BigEndian, WB>) {
+        (self.le, self.be)
+    }
+}
+
+fn same_mode(mode: &StringMode) -> StringMode {
+    match mode {
+        StringMode::FixedChars(len) => StringMode::FixedChars(*len),
+        StringMode::NullTerminated => StringMode::NullTerminated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endian::Endian;
+
+    #[derive(Debug, PartialEq)]
+    struct Sample {
+        id: u16,
+        values: Vec<i32>,
+        scale: f64,
+    }
+
+    impl Write for Sample {
+        fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+            writer.u16(self.id)?;
+            writer.u8_from_usize(self.values.len())?;
+            writer.write(&self.values)?;
+            writer.f64(self.scale)
+        }
+    }
+
+    /// The converter's single pass over its source, generic over the writer it feeds.
+    macro_rules! emit {
+        ($writer:expr, $samples:expr) => {{
+            let writer = &mut $writer;
+            writer.u32(0x5241_5742).unwrap();
+            writer.utf8_str("demo", StringMode::NullTerminated).unwrap();
+            writer.utf16_str("ab", StringMode::FixedChars(6)).unwrap();
+            writer.fill_aligned(4, 15).unwrap();
+            writer.u32_from_usize($samples.len()).unwrap();
+            for sample in $samples {
+                writer.write(sample).unwrap();
+                writer.i8(-1).unwrap();
+            }
+        }};
+    }
+
+    #[test]
+    fn test_dual_matches_single() {
+        let samples = [
+            Sample {
+                id: 1,
+                values: vec![-2, 70_000],
+                scale: 0.25,
+            },
+            Sample {
+                id: 0x0203,
+                values: vec![],
+                scale: -1e9,
+            },
+        ];
+        let mut dual = DualWriter::new(Vec::new(), Vec::new());
+        emit!(dual, &samples);
+        let position = dual.position();
+        let (le, be) = dual.finish().unwrap();
+
+        let mut single_le = BinaryWriter::new_le(Vec::new());
+        emit!(single_le, &samples);
+        let mut single_be = BinaryWriter::new_be(Vec::new());
+        emit!(single_be, &samples);
+        assert_eq!(le, single_le.finish().unwrap());
+        assert_eq!(be, single_be.finish().unwrap());
+        assert_eq!((le.len() as u64, be.len() as u64), (position, position));
+        assert_eq!(&le[..4], b"BWAR");
+        assert_eq!(&be[..4], b"RAWB");
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{
+    endian::Endian,
+    reader::{BinaryReader, Read, Result},
+};
+use std::{cell::RefCell, fmt};
+
+/// A value read at `offset`, spanning `len` bytes, with the values it was read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DumpNode {
+    pub offset: u64,
+    pub len: u64,
+    pub type_name: &'static str,
+    pub children: Vec<DumpNode>,
+}
+
+/// The nested reads made while parsing a value with `dump`, together with the parsed bytes.
+///
+/// Only reads that go through `BinaryReader::read` or `read_with` show up as nodes; bytes taken
+/// with primitive methods such as `u32` belong to the enclosing node without a node of their own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DumpTree {
+    pub root: DumpNode,
+    data: Vec<u8>,
+}
+
+/// How many bytes of a node are shown after it before the excerpt is cut off with `...`.
+const EXCERPT_LEN: usize = 16;
+
+impl DumpTree {
+    fn fmt_node(&self, f: &mut fmt::Formatter<'_>, node: &DumpNode, depth: usize) -> fmt::Result {
+        let start = node.offset as usize;
+        let end = start + (node.len as usize).min(EXCERPT_LEN);
+        write!(f, "{:indent$}{:#06x} +{} {}:", "", node.offset, node.len, node.type_name, indent = depth * 2)?;
+        for byte in &self.data[start..end] {
+            write!(f, " {byte:02X}")?;
+        }
+        if node.len as usize > EXCERPT_LEN {
+            f.write_str(" ...")?;
+        }
+        writeln!(f)?;
+        for child in &node.children {
+            self.fmt_node(f, child, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DumpTree {
+    /// Renders one line per node, indented by depth: offset, length, type name and a hex excerpt.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_node(f, &self.root, 0)
+    }
+}
+
+/// A node whose read has started but not finished.
+struct Open {
+    offset: u64,
+    type_name: &'static str,
+    children: Vec<DumpNode>,
+}
+
+thread_local! {
+    /// The reads in progress on this thread while `dump` runs, innermost last; `None` otherwise.
+    static RECORDER: RefCell<Option<Vec<Open>>> = const { RefCell::new(None) };
+}
+
+/// Opens a node for a read of `type_name` starting at `offset`, if `dump` is recording.
+pub(crate) fn enter(offset: u64, type_name: &'static str) {
+    RECORDER.with_borrow_mut(|recorder| {
+        if let Some(stack) = recorder {
+            stack.push(Open {
+                offset,
+                type_name,
+                children: Vec::new(),
+            });
+        }
+    });
+}
+
+/// Closes the innermost node at `end` and attaches it to its parent.
+pub(crate) fn exit(end: u64) {
+    RECORDER.with_borrow_mut(|recorder| {
+        if let Some(stack) = recorder
+            && stack.len() > 1
+            && let Some(open) = stack.pop()
+        {
+            let node = DumpNode {
+                offset: open.offset,
+                len: end.saturating_sub(open.offset),
+                type_name: open.type_name,
+                children: open.children,
+            };
+            stack.last_mut().expect("the sentinel stays at the bottom").children.push(node);
+        }
+    });
+}
+
+/// Parses a `T` from `bytes` and returns it with a tree of the nested reads it was made of.
+///
+/// Meant for debugging unknown files: `println!("{tree}")` shows which bytes belonged to which
+/// field. Bytes after the value are ignored.
+pub fn dump<E: Endian, T: Read>(bytes: &[u8]) -> Result<(T, DumpTree)> {
+    let sentinel = Open {
+        offset: 0,
+        type_name: "",
+        children: Vec::new(),
+    };
+    RECORDER.set(Some(vec![sentinel]));
+    let result = BinaryReader::<E, _>::from_bytes(bytes).read::<T>();
+    let mut stack = RECORDER.take().expect("recorder is set for the whole read");
+    let value = result?;
+    let root = stack.pop().and_then(|mut sentinel| sentinel.children.pop()).expect("read::<T> records a node");
+    Ok((value, DumpTree { root, data: bytes.to_vec() }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endian::BigEndian;
+    use std::any::type_name;
+
+    #[derive(Debug, PartialEq)]
+    struct Point {
+        x: u16,
+        y: u16,
+    }
+
+    impl Read for Point {
+        fn read<E: Endian, R: std::io::BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+            Ok(Point { x: reader.u16()?, y: reader.u16()? })
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Shape {
+        id: u8,
+        origin: Point,
+        points: Vec<Point>,
+    }
+
+    impl Read for Shape {
+        fn read<E: Endian, R: std::io::BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+            let id = reader.u8()?;
+            let origin = reader.read()?;
+            let count = reader.u8()? as usize;
+            let points = reader.read_with(count)?;
+            Ok(Shape { id, origin, points })
+        }
+    }
+
+    fn node(offset: u64, len: u64, type_name: &'static str, children: Vec<DumpNode>) -> DumpNode {
+        DumpNode { offset, len, type_name, children }
+    }
+
+    #[test]
+    fn test_dump_tree() {
+        let data = [7, 0, 1, 0, 2, 2, 0, 3, 0, 4, 0, 5, 0, 6, 0xee];
+        let (shape, tree) = dump::<BigEndian, Shape>(&data).unwrap();
+        assert_eq!(shape.id, 7);
+        assert_eq!(shape.origin, Point { x: 1, y: 2 });
+        assert_eq!(shape.points.len(), 2);
+
+        let points = (0..2).map(|i| node(6 + 4 * i, 4, type_name::<Point>(), vec![])).collect();
+        let expected = node(
+            0,
+            14,
+            type_name::<Shape>(),
+            vec![node(1, 4, type_name::<Point>(), vec![]), node(6, 8, type_name::<Vec<Point>>(), points)],
+        );
+        assert_eq!(tree.root, expected);
+
+        let rendered = tree.to_string();
+        let lines: Vec<_> = rendered.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], format!("0x0000 +14 {}: 07 00 01 00 02 02 00 03 00 04 00 05 00 06", type_name::<Shape>()));
+        assert_eq!(lines[1], format!("  0x0001 +4 {}: 00 01 00 02", type_name::<Point>()));
+        assert_eq!(lines[4], format!("    0x000a +4 {}: 00 05 00 06", type_name::<Point>()));
+    }
+
+    #[test]
+    fn test_dump_error() {
+        assert!(dump::<BigEndian, Shape>(&[7, 0, 1]).is_err());
+        let (value, tree) = dump::<BigEndian, u16>(&[1, 2]).unwrap();
+        assert_eq!(value, 0x0102);
+        assert_eq!(tree.root, node(0, 2, "u16", vec![]));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::fixed_size::FixedSize;
 use std::mem::size_of;
 
 pub trait Endian {
@@ -9,6 +10,8 @@ pub trait Endian {
     fn u64_to_bytes(value: u64) -> [u8; size_of::<u64>()];
     fn i64_to_bytes(value: i64) -> [u8; size_of::<i64>()];
     fn f64_to_bytes(value: f64) -> [u8; size_of::<f64>()];
+    fn u128_to_bytes(value: u128) -> [u8; size_of::<u128>()];
+    fn i128_to_bytes(value: i128) -> [u8; size_of::<i128>()];
 
     fn u16iter_to_bytes<T: Iterator<Item = u16>>(iter: T, capacity: usize) -> Vec<u8> {
         let mut result = Vec::with_capacity(capacity);
@@ -26,9 +29,11 @@ pub trait Endian {
     fn u64_from_bytes(bytes: &[u8; size_of::<u64>()]) -> u64;
     fn i64_from_bytes(bytes: &[u8; size_of::<i64>()]) -> i64;
     fn f64_from_bytes(bytes: &[u8; size_of::<f64>()]) -> f64;
+    fn u128_from_bytes(bytes: &[u8; size_of::<u128>()]) -> u128;
+    fn i128_from_bytes(bytes: &[u8; size_of::<i128>()]) -> i128;
 
     fn u16vec_from_bytes(bytes: &[u8]) -> Vec<u16> {
-        assert!(bytes.len() % 2 == 0, "Invalid length for u16 array: {}", bytes.len());
+        assert!(bytes.len().is_multiple_of(2), "Invalid length for u16 array: {}", bytes.len());
         let mut result = Vec::with_capacity(bytes.len() / 2);
         for chunk in bytes.chunks_exact(2) {
             let value = Self::u16_from_bytes(chunk.try_into().expect("slice with incorrect length"));
@@ -38,73 +43,404 @@ pub trait Endian {
     }
 }
 
-macro_rules! impl_to_bytes {
-    ($($fn:ident: ($t:ty, $conv:ident)),* $(,)?) => {
-        $(
-            #[inline]
-            fn $fn(value: $t) -> [u8; size_of::<$t>()] {
-                value.$conv()
-            }
-        )*
+/// Implements the conversions of a fixed byte order as inherent `const fn`s, usable in `const`
+/// items such as baked headers, and implements `Endian` on top of them.
+macro_rules! impl_byte_order {
+    ($endian:ident, $to_conv:ident, $from_conv:ident; $($to:ident, $from:ident: $t:ty),* $(,)?) => {
+        impl $endian {
+            $(
+                #[inline]
+                pub const fn $to(value: $t) -> [u8; size_of::<$t>()] {
+                    value.$to_conv()
+                }
+
+                #[inline]
+                pub const fn $from(bytes: &[u8; size_of::<$t>()]) -> $t {
+                    <$t>::$from_conv(*bytes)
+                }
+            )*
+        }
+
+        impl Endian for $endian {
+            $(
+                #[inline]
+                fn $to(value: $t) -> [u8; size_of::<$t>()] {
+                    $endian::$to(value)
+                }
+
+                #[inline]
+                fn $from(bytes: &[u8; size_of::<$t>()]) -> $t {
+                    $endian::$from(bytes)
+                }
+            )*
+        }
     };
 }
 
-macro_rules! impl_from_bytes {
-    ($($fn:ident: ($t:ty, $conv:ident)),* $(,)?) => {
+pub struct LittleEndian;
+impl_byte_order! {
+    LittleEndian, to_le_bytes, from_le_bytes;
+    u16_to_bytes, u16_from_bytes: u16,
+    i16_to_bytes, i16_from_bytes: i16,
+    u32_to_bytes, u32_from_bytes: u32,
+    i32_to_bytes, i32_from_bytes: i32,
+    f32_to_bytes, f32_from_bytes: f32,
+    u64_to_bytes, u64_from_bytes: u64,
+    i64_to_bytes, i64_from_bytes: i64,
+    f64_to_bytes, f64_from_bytes: f64,
+    u128_to_bytes, u128_from_bytes: u128,
+    i128_to_bytes, i128_from_bytes: i128,
+}
+
+pub struct BigEndian;
+impl_byte_order! {
+    BigEndian, to_be_bytes, from_be_bytes;
+    u16_to_bytes, u16_from_bytes: u16,
+    i16_to_bytes, i16_from_bytes: i16,
+    u32_to_bytes, u32_from_bytes: u32,
+    i32_to_bytes, i32_from_bytes: i32,
+    f32_to_bytes, f32_from_bytes: f32,
+    u64_to_bytes, u64_from_bytes: u64,
+    i64_to_bytes, i64_from_bytes: i64,
+    f64_to_bytes, f64_from_bytes: f64,
+    u128_to_bytes, u128_from_bytes: u128,
+    i128_to_bytes, i128_from_bytes: i128,
+}
+
+/// The byte order of the host, for data produced and consumed on the same machine such as
+/// memory-mapped caches. Encodes exactly like `LittleEndian` or `BigEndian`, whichever is native.
+pub struct NativeEndian;
+impl_byte_order! {
+    NativeEndian, to_ne_bytes, from_ne_bytes;
+    u16_to_bytes, u16_from_bytes: u16,
+    i16_to_bytes, i16_from_bytes: i16,
+    u32_to_bytes, u32_from_bytes: u32,
+    i32_to_bytes, i32_from_bytes: i32,
+    f32_to_bytes, f32_from_bytes: f32,
+    u64_to_bytes, u64_from_bytes: u64,
+    i64_to_bytes, i64_from_bytes: i64,
+    f64_to_bytes, f64_from_bytes: f64,
+    u128_to_bytes, u128_from_bytes: u128,
+    i128_to_bytes, i128_from_bytes: i128,
+}
+
+/// A byte order chosen at runtime, e.g. from a flag in a file header.
+///
+/// `BinaryReader::new_dyn` and `BinaryWriter::new_dyn` (and their async counterparts) turn it into
+/// an `EitherEndianReader` / `EitherEndianWriter`, which forward each call to a reader or writer of
+/// the matching `Endian` type, so `Read` and `Write` implementations work unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+impl Endianness {
+    /// Returns the byte order of the host.
+    pub const fn native() -> Self {
+        if cfg!(target_endian = "little") { Endianness::Little } else { Endianness::Big }
+    }
+}
+
+/// Assembles a fixed `[u8; N]` from a few fields in a `const` context, e.g. a header template:
+///
+/// ```
+/// use rwbin::endian::{ConstBytes, LittleEndian};
+///
+/// const HEADER: [u8; 12] = ConstBytes::new().push(b"RIFF").push(&LittleEndian::u32_to_bytes(4)).push(b"WAVE").finish();
+/// assert_eq!(&HEADER[..4], b"RIFF");
+/// ```
+///
+/// Pushing past `N` bytes, or finishing short of it, panics, which fails the build when evaluated in a `const` item.
+#[derive(Debug, Clone, Copy)]
+pub struct ConstBytes<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> ConstBytes<N> {
+    pub const fn new() -> Self {
+        Self { bytes: [0; N], len: 0 }
+    }
+
+    /// Appends `field` after the bytes pushed so far.
+    pub const fn push(mut self, field: &[u8]) -> Self {
+        assert!(self.len + field.len() <= N, "fields exceed the array length");
+        let mut i = 0;
+        while i < field.len() {
+            self.bytes[self.len + i] = field[i];
+            i += 1;
+        }
+        self.len += field.len();
+        self
+    }
+
+    /// Returns the array, which must have been filled exactly.
+    pub const fn finish(self) -> [u8; N] {
+        assert!(self.len == N, "fields do not fill the array");
+        self.bytes
+    }
+}
+
+impl<const N: usize> Default for ConstBytes<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// PDP-11 "middle-endian" byte order.
+///
+/// 16-bit values are little-endian. Wider values are split into 16-bit words stored most
+/// significant word first, each word little-endian, so `0x0A0B0C0D` is laid out as
+/// `0B 0A 0D 0C`. 64-bit and 128-bit values follow the same rule with four and eight words. Floats are IEEE 754
+/// values whose bits are reordered like the integer of the same width; the native PDP-11
+/// floating point format is not emulated.
+pub struct MiddleEndian;
+
+/// Swaps each pair of bytes while reversing the word order of a little-endian array.
+#[inline]
+fn le_to_middle<const N: usize>(le: [u8; N]) -> [u8; N] {
+    let mut out = [0u8; N];
+    for (i, word) in le.chunks_exact(2).rev().enumerate() {
+        out[2 * i] = word[0];
+        out[2 * i + 1] = word[1];
+    }
+    out
+}
+
+/// Inverse of `le_to_middle`; the word reversal is its own inverse.
+#[inline]
+fn middle_to_le<const N: usize>(middle: &[u8; N]) -> [u8; N] {
+    le_to_middle(*middle)
+}
+
+impl Endian for MiddleEndian {
+    #[inline]
+    fn u16_to_bytes(value: u16) -> [u8; 2] {
+        value.to_le_bytes()
+    }
+    #[inline]
+    fn i16_to_bytes(value: i16) -> [u8; 2] {
+        value.to_le_bytes()
+    }
+    #[inline]
+    fn u32_to_bytes(value: u32) -> [u8; 4] {
+        le_to_middle(value.to_le_bytes())
+    }
+    #[inline]
+    fn i32_to_bytes(value: i32) -> [u8; 4] {
+        le_to_middle(value.to_le_bytes())
+    }
+    #[inline]
+    fn f32_to_bytes(value: f32) -> [u8; 4] {
+        le_to_middle(value.to_le_bytes())
+    }
+    #[inline]
+    fn u64_to_bytes(value: u64) -> [u8; 8] {
+        le_to_middle(value.to_le_bytes())
+    }
+    #[inline]
+    fn i64_to_bytes(value: i64) -> [u8; 8] {
+        le_to_middle(value.to_le_bytes())
+    }
+    #[inline]
+    fn f64_to_bytes(value: f64) -> [u8; 8] {
+        le_to_middle(value.to_le_bytes())
+    }
+    #[inline]
+    fn u128_to_bytes(value: u128) -> [u8; 16] {
+        le_to_middle(value.to_le_bytes())
+    }
+    #[inline]
+    fn i128_to_bytes(value: i128) -> [u8; 16] {
+        le_to_middle(value.to_le_bytes())
+    }
+
+    #[inline]
+    fn u16_from_bytes(bytes: &[u8; 2]) -> u16 {
+        u16::from_le_bytes(*bytes)
+    }
+    #[inline]
+    fn i16_from_bytes(bytes: &[u8; 2]) -> i16 {
+        i16::from_le_bytes(*bytes)
+    }
+    #[inline]
+    fn u32_from_bytes(bytes: &[u8; 4]) -> u32 {
+        u32::from_le_bytes(middle_to_le(bytes))
+    }
+    #[inline]
+    fn i32_from_bytes(bytes: &[u8; 4]) -> i32 {
+        i32::from_le_bytes(middle_to_le(bytes))
+    }
+    #[inline]
+    fn f32_from_bytes(bytes: &[u8; 4]) -> f32 {
+        f32::from_le_bytes(middle_to_le(bytes))
+    }
+    #[inline]
+    fn u64_from_bytes(bytes: &[u8; 8]) -> u64 {
+        u64::from_le_bytes(middle_to_le(bytes))
+    }
+    #[inline]
+    fn i64_from_bytes(bytes: &[u8; 8]) -> i64 {
+        i64::from_le_bytes(middle_to_le(bytes))
+    }
+    #[inline]
+    fn f64_from_bytes(bytes: &[u8; 8]) -> f64 {
+        f64::from_le_bytes(middle_to_le(bytes))
+    }
+    #[inline]
+    fn u128_from_bytes(bytes: &[u8; 16]) -> u128 {
+        u128::from_le_bytes(middle_to_le(bytes))
+    }
+    #[inline]
+    fn i128_from_bytes(bytes: &[u8; 16]) -> i128 {
+        i128::from_le_bytes(middle_to_le(bytes))
+    }
+}
+
+/// Numeric types whose encoding is a fixed number of bytes in the byte order `E`.
+///
+/// Lets generic code read and write elements with `read_num::<T>()` / `write_num(value)`
+/// instead of matching on the element type.
+pub trait Primitive: FixedSize + Copy {
+    /// The encoded form, always `[u8; Self::SIZE]`.
+    type Bytes: AsRef<[u8]> + AsMut<[u8]> + Default + Send;
+
+    fn from_bytes<E: Endian>(bytes: Self::Bytes) -> Self;
+    fn to_bytes<E: Endian>(self) -> Self::Bytes;
+}
+
+impl Primitive for u8 {
+    type Bytes = [u8; 1];
+
+    #[inline]
+    fn from_bytes<E: Endian>(bytes: Self::Bytes) -> Self {
+        bytes[0]
+    }
+    #[inline]
+    fn to_bytes<E: Endian>(self) -> Self::Bytes {
+        [self]
+    }
+}
+
+impl Primitive for i8 {
+    type Bytes = [u8; 1];
+
+    #[inline]
+    fn from_bytes<E: Endian>(bytes: Self::Bytes) -> Self {
+        bytes[0] as i8
+    }
+    #[inline]
+    fn to_bytes<E: Endian>(self) -> Self::Bytes {
+        [self as u8]
+    }
+}
+
+macro_rules! impl_primitive {
+    ($($t:ty: ($from:ident, $to:ident)),* $(,)?) => {
         $(
-            #[inline]
-            fn $fn(bytes: &[u8; size_of::<$t>()]) -> $t {
-                <$t>::$conv(*bytes)
+            impl Primitive for $t {
+                type Bytes = [u8; size_of::<$t>()];
+
+                #[inline]
+                fn from_bytes<E: Endian>(bytes: Self::Bytes) -> Self {
+                    E::$from(&bytes)
+                }
+                #[inline]
+                fn to_bytes<E: Endian>(self) -> Self::Bytes {
+                    E::$to(self)
+                }
             }
         )*
     };
 }
-pub struct LittleEndian;
-impl Endian for LittleEndian {
-    impl_to_bytes! {
-        u16_to_bytes: (u16, to_le_bytes),
-        i16_to_bytes: (i16, to_le_bytes),
-        u32_to_bytes: (u32, to_le_bytes),
-        i32_to_bytes: (i32, to_le_bytes),
-        f32_to_bytes: (f32, to_le_bytes),
-        u64_to_bytes: (u64, to_le_bytes),
-        i64_to_bytes: (i64, to_le_bytes),
-        f64_to_bytes: (f64, to_le_bytes),
-    }
-
-    impl_from_bytes! {
-        u16_from_bytes: (u16, from_le_bytes),
-        i16_from_bytes: (i16, from_le_bytes),
-        u32_from_bytes: (u32, from_le_bytes),
-        i32_from_bytes: (i32, from_le_bytes),
-        f32_from_bytes: (f32, from_le_bytes),
-        u64_from_bytes: (u64, from_le_bytes),
-        i64_from_bytes: (i64, from_le_bytes),
-        f64_from_bytes: (f64, from_le_bytes),
-    }
+
+impl_primitive! {
+    u16: (u16_from_bytes, u16_to_bytes),
+    i16: (i16_from_bytes, i16_to_bytes),
+    u32: (u32_from_bytes, u32_to_bytes),
+    i32: (i32_from_bytes, i32_to_bytes),
+    f32: (f32_from_bytes, f32_to_bytes),
+    u64: (u64_from_bytes, u64_to_bytes),
+    i64: (i64_from_bytes, i64_to_bytes),
+    f64: (f64_from_bytes, f64_to_bytes),
+    u128: (u128_from_bytes, u128_to_bytes),
+    i128: (i128_from_bytes, i128_to_bytes),
 }
 
-pub struct BigEndian;
-impl Endian for BigEndian {
-    impl_to_bytes! {
-        u16_to_bytes: (u16, to_be_bytes),
-        i16_to_bytes: (i16, to_be_bytes),
-        u32_to_bytes: (u32, to_be_bytes),
-        i32_to_bytes: (i32, to_be_bytes),
-        f32_to_bytes: (f32, to_be_bytes),
-        u64_to_bytes: (u64, to_be_bytes),
-        i64_to_bytes: (i64, to_be_bytes),
-        f64_to_bytes: (f64, to_be_bytes),
-    }
-
-    impl_from_bytes! {
-        u16_from_bytes: (u16, from_be_bytes),
-        i16_from_bytes: (i16, from_be_bytes),
-        u32_from_bytes: (u32, from_be_bytes),
-        i32_from_bytes: (i32, from_be_bytes),
-        f32_from_bytes: (f32, from_be_bytes),
-        u64_from_bytes: (u64, from_be_bytes),
-        i64_from_bytes: (i64, from_be_bytes),
-        f64_from_bytes: (f64, from_be_bytes),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::BinaryReader;
+    use crate::writer::BinaryWriter;
+
+    #[test]
+    fn test_middle_endian_layout() {
+        assert_eq!(MiddleEndian::u16_to_bytes(0x0A0B), [0x0B, 0x0A]);
+        assert_eq!(MiddleEndian::u32_to_bytes(0x0A0B_0C0D), [0x0B, 0x0A, 0x0D, 0x0C]);
+        // 1,000,000 = 0x000F4240 as stored by PDP-11 32-bit longs.
+        assert_eq!(MiddleEndian::u32_to_bytes(1_000_000), [0x0F, 0x00, 0x40, 0x42]);
+        assert_eq!(MiddleEndian::i32_to_bytes(-2), [0xFF, 0xFF, 0xFE, 0xFF]);
+        assert_eq!(MiddleEndian::u64_to_bytes(0x0102_0304_0506_0708), [0x02, 0x01, 0x04, 0x03, 0x06, 0x05, 0x08, 0x07]);
+        assert_eq!(MiddleEndian::u32_from_bytes(&[0x0B, 0x0A, 0x0D, 0x0C]), 0x0A0B_0C0D);
+    }
+
+    #[test]
+    fn test_native_endian_matches_host() {
+        let native: fn(u64) -> [u8; 8] = if cfg!(target_endian = "little") { LittleEndian::u64_to_bytes } else { BigEndian::u64_to_bytes };
+        assert_eq!(NativeEndian::u64_to_bytes(0x0102_0304_0506_0708), native(0x0102_0304_0506_0708));
+        assert_eq!(NativeEndian::u16_to_bytes(0x0A0B), 0x0A0Bu16.to_ne_bytes());
+        assert_eq!(NativeEndian::i128_from_bytes(&(-5i128).to_ne_bytes()), -5);
+    }
+
+    #[test]
+    fn test_native_endian_reader_writer() {
+        let small = (0xABu8, -1i8, 0x1234u16, -2i16, 0x0A0B_0C0Du32, -3i32);
+        let wide = (1.5f32, u64::MAX - 1, -4i64, -0.25f64, u128::MAX / 3, i128::MIN + 1);
+        let mut buf = Vec::new();
+        BinaryWriter::new_ne(&mut buf).write(&(small, wide)).unwrap();
+        assert_eq!(&buf[2..4], 0x1234u16.to_ne_bytes());
+        assert_eq!(BinaryReader::from_ne_bytes(&buf).read::<(_, (f32, u64, i64, f64, u128, i128))>().unwrap(), (small, wide));
+        assert_eq!(BinaryReader::new_ne(&buf[..]).read::<((u8, i8, u16, i16, u32, i32), _)>().unwrap(), (small, wide));
+    }
+
+    #[test]
+    fn test_const_header() {
+        const VERSION: u16 = 3;
+        const LE_HEADER: [u8; 14] = ConstBytes::new()
+            .push(b"HDR\0")
+            .push(&LittleEndian::u16_to_bytes(VERSION))
+            .push(&LittleEndian::f32_to_bytes(0.5))
+            .push(&LittleEndian::i32_to_bytes(-7))
+            .finish();
+        const BE_MAGIC: u32 = BigEndian::u32_from_bytes(&BigEndian::u32_to_bytes(0xCAFE_BABE));
+        const BE_HEADER: [u8; 12] = ConstBytes::new().push(&BigEndian::u32_to_bytes(BE_MAGIC)).push(&BigEndian::u64_to_bytes(1 << 40)).finish();
+
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_le(&mut buf);
+        writer.write(&b"HDR\0".as_slice()).unwrap();
+        writer.write(&(VERSION, 0.5f32, -7i32)).unwrap();
+        assert_eq!(buf, LE_HEADER);
+
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_be(&mut buf);
+        writer.write(&(0xCAFE_BABEu32, 1u64 << 40)).unwrap();
+        assert_eq!(buf, BE_HEADER);
+    }
+
+    #[test]
+    #[should_panic(expected = "fields do not fill the array")]
+    fn test_const_header_too_short() {
+        ConstBytes::<4>::new().push(&[1, 2]).finish();
+    }
+
+    #[test]
+    fn test_middle_endian_reader_writer() {
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::<MiddleEndian, _>::new(&mut buf);
+        writer.write(&(0x1234u16, 0x0A0B_0C0Du32, -3i64, 1.5f32)).unwrap();
+        let mut reader = BinaryReader::<MiddleEndian, _>::new(&buf[..]);
+        assert_eq!(&buf[..6], &[0x34, 0x12, 0x0B, 0x0A, 0x0D, 0x0C]);
+        assert_eq!(reader.read::<(u16, u32, i64, f32)>().unwrap(), (0x1234, 0x0A0B_0C0D, -3, 1.5));
     }
 }
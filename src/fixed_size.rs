@@ -0,0 +1,127 @@
+/// Types whose encoded form always occupies the same number of bytes.
+///
+/// `SIZE` must equal the number of bytes the type's `Read`/`Write` impls consume and produce,
+/// regardless of endianness.
+pub trait FixedSize {
+    const SIZE: usize;
+}
+
+macro_rules! impl_fixed_size_for_primitives {
+    ( $( $t:ty ),* $(,)? ) => {
+        $(
+            impl FixedSize for $t {
+                const SIZE: usize = std::mem::size_of::<$t>();
+            }
+        )*
+    };
+}
+impl_fixed_size_for_primitives! {
+    u8, i8, u16, i16, u32, i32, f32, u64, i64, f64, u128, i128,
+}
+
+impl FixedSize for bool {
+    const SIZE: usize = 1;
+}
+
+impl FixedSize for char {
+    const SIZE: usize = 4;
+}
+
+impl<T: FixedSize, const N: usize> FixedSize for [T; N] {
+    const SIZE: usize = T::SIZE * N;
+}
+
+macro_rules! impl_fixed_size_for_tuples {
+    ( $( ( $( $T:ident ),+ ), )+ ) => {
+        $(
+            impl<$($T: FixedSize),+> FixedSize for ( $( $T, )+ ) {
+                const SIZE: usize = 0 $( + $T::SIZE )+;
+            }
+        )+
+    }
+}
+impl_fixed_size_for_tuples! {
+    (T1, T2),
+    (T1, T2, T3),
+    (T1, T2, T3, T4),
+    (T1, T2, T3, T4, T5),
+    (T1, T2, T3, T4, T5, T6),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endian::Endian;
+    use crate::reader::{self, BinaryReader, Read};
+    use crate::result::ReadError;
+    use crate::writer::{self, BinaryWriter, Write};
+    use std::io::BufRead;
+
+    #[derive(Debug, PartialEq)]
+    struct Record {
+        id: u32,
+        pos: [i16; 3],
+        flag: bool,
+        tag: char,
+    }
+    impl FixedSize for Record {
+        const SIZE: usize = <(u32, [i16; 3], bool, char)>::SIZE;
+    }
+    impl Read for Record {
+        fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> reader::Result<Self> {
+            let (id, pos, flag, tag) = reader.read()?;
+            Ok(Record { id, pos, flag, tag })
+        }
+    }
+    impl Write for Record {
+        fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> writer::Result<()> {
+            writer.write(&(self.id, self.pos, self.flag, self.tag))
+        }
+    }
+
+    #[test]
+    fn test_size_matches_encoding() {
+        let record = Record {
+            id: 7,
+            pos: [1, -2, 3],
+            flag: true,
+            tag: 'x',
+        };
+        let mut buf = Vec::new();
+        BinaryWriter::new_le(&mut buf).write(&record).unwrap();
+        assert_eq!(Record::SIZE, 15);
+        assert_eq!(buf.len(), Record::SIZE);
+    }
+
+    #[test]
+    fn test_skip_and_read_records() {
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_be(&mut buf);
+        for id in 0..4 {
+            writer
+                .write(&Record {
+                    id,
+                    pos: [0; 3],
+                    flag: false,
+                    tag: 'a',
+                })
+                .unwrap();
+        }
+        let mut reader = BinaryReader::from_be_bytes(&buf);
+        reader.skip_records::<Record>(2).unwrap();
+        assert_eq!(reader.position(), 2 * Record::SIZE as u64);
+        let records: Vec<Record> = reader.read_records(2).unwrap();
+        assert_eq!(records[0].id, 2);
+        assert_eq!(records[1].id, 3);
+        assert!(matches!(reader.skip_records::<u8>(usize::MAX), Err(ReadError::InvalidArgument)));
+    }
+
+    #[test]
+    fn test_read_records_checks_size_first() {
+        let buf = [0u8; 20];
+        let mut reader = BinaryReader::from_le_bytes(&buf);
+        let result = reader.read_records::<Record>(2);
+        assert!(matches!(result, Err(ReadError::NotEnoughBytes { requested: 30, .. })));
+        assert_eq!(reader.position(), 0);
+    }
+}
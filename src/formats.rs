@@ -0,0 +1,2 @@
+pub mod bmp;
+pub mod wav;
@@ -0,0 +1,213 @@
+use crate::{
+    endian::Endian,
+    reader::{BinaryReader, Read, Result},
+    result::ReadError,
+    tag::Tag,
+    writer::{self, BinaryWriter, Write},
+};
+use std::io::BufRead;
+
+const SIGNATURE: Tag<2> = Tag::new(*b"BM");
+/// Size of a `BITMAPINFOHEADER`, the smallest DIB header this module understands.
+const INFO_HEADER_SIZE: u32 = 40;
+
+/// The 14-byte `BITMAPFILEHEADER` that starts every `.bmp` file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileHeader {
+    /// Size of the whole file in bytes.
+    pub file_size: u32,
+    /// Offset of the pixel array from the start of the file.
+    pub pixel_offset: u32,
+}
+
+impl Read for FileHeader {
+    fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+        reader.magic(SIGNATURE)?;
+        let file_size = reader.u32()?;
+        reader.reserved::<4>(0)?;
+        let pixel_offset = reader.u32()?;
+        Ok(FileHeader { file_size, pixel_offset })
+    }
+}
+
+impl Write for FileHeader {
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> writer::Result<()> {
+        writer.write(&SIGNATURE)?;
+        writer.u32(self.file_size)?;
+        writer.reserved(0, 4)?;
+        writer.u32(self.pixel_offset)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(14)
+    }
+}
+
+/// How the pixel array is encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Rgb,
+    Rle8,
+    Rle4,
+    Bitfields,
+    /// Any other `biCompression` value, kept as stored.
+    Other(u32),
+}
+
+impl From<u32> for Compression {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => Compression::Rgb,
+            1 => Compression::Rle8,
+            2 => Compression::Rle4,
+            3 => Compression::Bitfields,
+            other => Compression::Other(other),
+        }
+    }
+}
+
+impl From<Compression> for u32 {
+    fn from(value: Compression) -> Self {
+        match value {
+            Compression::Rgb => 0,
+            Compression::Rle8 => 1,
+            Compression::Rle4 => 2,
+            Compression::Bitfields => 3,
+            Compression::Other(other) => other,
+        }
+    }
+}
+
+/// The fields of a `BITMAPINFOHEADER`.
+///
+/// Newer, larger DIB headers (`BITMAPV4HEADER`, `BITMAPV5HEADER`) start with the same fields;
+/// their extra bytes are skipped when reading, and writing always produces the 40-byte form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DibHeader {
+    pub width: i32,
+    /// Positive for bottom-up images, negative for top-down ones.
+    pub height: i32,
+    pub bits_per_pixel: u16,
+    pub compression: Compression,
+    /// Size of the pixel array in bytes; may be 0 for uncompressed images.
+    pub image_size: u32,
+    pub x_pixels_per_meter: i32,
+    pub y_pixels_per_meter: i32,
+    pub colors_used: u32,
+    pub colors_important: u32,
+}
+
+impl Read for DibHeader {
+    fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+        let start = reader.position();
+        let header_size = reader.u32()?;
+        if header_size < INFO_HEADER_SIZE {
+            return Err(ReadError::invalid_data_format(format!("unsupported DIB header of {header_size} bytes")));
+        }
+        let width = reader.i32()?;
+        let height = reader.i32()?;
+        reader.value(&1u16)?; // color planes
+        let header = DibHeader {
+            width,
+            height,
+            bits_per_pixel: reader.u16()?,
+            compression: reader.u32()?.into(),
+            image_size: reader.u32()?,
+            x_pixels_per_meter: reader.i32()?,
+            y_pixels_per_meter: reader.i32()?,
+            colors_used: reader.u32()?,
+            colors_important: reader.u32()?,
+        };
+        reader.skip_to(start + header_size as u64)?;
+        Ok(header)
+    }
+}
+
+impl Write for DibHeader {
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> writer::Result<()> {
+        writer.u32(INFO_HEADER_SIZE)?;
+        writer.i32(self.width)?;
+        writer.i32(self.height)?;
+        writer.u16(1)?;
+        writer.u16(self.bits_per_pixel)?;
+        writer.u32(self.compression.into())?;
+        writer.u32(self.image_size)?;
+        writer.i32(self.x_pixels_per_meter)?;
+        writer.i32(self.y_pixels_per_meter)?;
+        writer.u32(self.colors_used)?;
+        writer.u32(self.colors_important)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(INFO_HEADER_SIZE as usize)
+    }
+}
+
+/// Both headers of a `.bmp` file, leaving the reader just after the DIB header.
+///
+/// The pixel array starts at `file.pixel_offset`, possibly after a color table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BmpHeader {
+    pub file: FileHeader,
+    pub dib: DibHeader,
+}
+
+impl Read for BmpHeader {
+    fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+        Ok(BmpHeader {
+            file: reader.read()?,
+            dib: reader.read()?,
+        })
+    }
+}
+
+impl Write for BmpHeader {
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> writer::Result<()> {
+        writer.write(&self.file)?;
+        writer.write(&self.dib)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(14 + INFO_HEADER_SIZE as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endian::LittleEndian;
+
+    const SAMPLE: &[u8] = include_bytes!("../../testdata/sample.bmp");
+
+    #[test]
+    fn test_sample_bmp() {
+        let mut reader = BinaryReader::from_le_bytes(SAMPLE);
+        let header: BmpHeader = reader.read().unwrap();
+        assert_eq!(
+            header.file,
+            FileHeader {
+                file_size: SAMPLE.len() as u32,
+                pixel_offset: 54
+            }
+        );
+        assert_eq!((header.dib.width, header.dib.height, header.dib.bits_per_pixel), (2, 2, 24));
+        assert_eq!(header.dib.compression, Compression::Rgb);
+        assert_eq!(header.dib.image_size, 16);
+        reader.skip_to(header.file.pixel_offset as u64).unwrap();
+        assert_eq!(reader.read_array::<u8, 3>().unwrap(), [0, 0, 255]);
+
+        let written = BinaryWriter::<LittleEndian, _>::to_vec(&header).unwrap();
+        assert_eq!(written, &SAMPLE[..54]);
+    }
+
+    #[test]
+    fn test_bmp_rejects_other_files() {
+        let mut reader = BinaryReader::from_le_bytes(b"RIFF\0\0\0\0\0\0\0\0\0\0");
+        assert!(matches!(reader.read::<FileHeader>(), Err(ReadError::ExpectedMismatch { .. })));
+
+        let mut truncated = SAMPLE[..54].to_vec();
+        truncated[14] = 12; // a BITMAPCOREHEADER
+        let mut reader = BinaryReader::from_le_bytes(&truncated);
+        assert!(matches!(reader.read::<BmpHeader>(), Err(ReadError::InvalidDataFormat(_))));
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{
+    endian::Endian,
+    reader::{BinaryReader, Read, Result},
+    result::ReadError,
+    tag::Tag,
+    writer::{self, BinaryWriter, Write},
+};
+use std::io::BufRead;
+
+const RIFF: Tag<4> = Tag::new(*b"RIFF");
+const WAVE: Tag<4> = Tag::new(*b"WAVE");
+const FMT: Tag<4> = Tag::new(*b"fmt ");
+const DATA: Tag<4> = Tag::new(*b"data");
+/// Size of a plain `fmt ` chunk body, without the `cbSize` extension.
+const FMT_SIZE: u32 = 16;
+
+/// The sample encoding named by a `fmt ` chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+    Pcm,
+    IeeeFloat,
+    Extensible,
+    /// Any other `wFormatTag`, kept as stored.
+    Other(u16),
+}
+
+impl From<u16> for AudioFormat {
+    fn from(value: u16) -> Self {
+        match value {
+            1 => AudioFormat::Pcm,
+            3 => AudioFormat::IeeeFloat,
+            0xfffe => AudioFormat::Extensible,
+            other => AudioFormat::Other(other),
+        }
+    }
+}
+
+impl From<AudioFormat> for u16 {
+    fn from(value: AudioFormat) -> Self {
+        match value {
+            AudioFormat::Pcm => 1,
+            AudioFormat::IeeeFloat => 3,
+            AudioFormat::Extensible => 0xfffe,
+            AudioFormat::Other(other) => other,
+        }
+    }
+}
+
+/// The body of a `fmt ` chunk. Bytes past the first 16, such as a `WAVE_FORMAT_EXTENSIBLE`
+/// extension, are skipped when reading and not written back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Format {
+    pub audio_format: AudioFormat,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub byte_rate: u32,
+    pub block_align: u16,
+    pub bits_per_sample: u16,
+}
+
+impl Read for Format {
+    fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+        Ok(Format {
+            audio_format: reader.u16()?.into(),
+            channels: reader.u16()?,
+            sample_rate: reader.u32()?,
+            byte_rate: reader.u32()?,
+            block_align: reader.u16()?,
+            bits_per_sample: reader.u16()?,
+        })
+    }
+}
+
+impl Write for Format {
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> writer::Result<()> {
+        writer.u16(self.audio_format.into())?;
+        writer.u16(self.channels)?;
+        writer.u32(self.sample_rate)?;
+        writer.u32(self.byte_rate)?;
+        writer.u16(self.block_align)?;
+        writer.u16(self.bits_per_sample)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(FMT_SIZE as usize)
+    }
+}
+
+/// The RIFF header of a `.wav` file up to the start of its sample data.
+///
+/// Reading walks the chunks after `WAVE`, skipping any it does not know (`LIST`, `fact`, ...),
+/// and stops at the start of the `data` chunk's samples. Writing produces the canonical 44-byte
+/// header, so `riff_size` should be `36 + data_len` for a file without other chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WavHeader {
+    /// Size of everything after the `RIFF` size field.
+    pub riff_size: u32,
+    pub format: Format,
+    /// Size of the sample data in bytes.
+    pub data_len: u32,
+}
+
+impl Read for WavHeader {
+    fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+        reader.magic(RIFF)?;
+        let riff_size = reader.u32()?;
+        reader.magic(WAVE)?;
+        let mut format = None;
+        loop {
+            let id: Tag<4> = reader.read()?;
+            let len = reader.u32()?;
+            if id == DATA {
+                let format = format.ok_or_else(|| ReadError::invalid_data_format("data chunk before fmt chunk"))?;
+                return Ok(WavHeader { riff_size, format, data_len: len });
+            }
+            let end = reader.position() + len as u64;
+            if id == FMT {
+                if len < FMT_SIZE {
+                    return Err(ReadError::invalid_data_format(format!("fmt chunk of {len} bytes")));
+                }
+                format = Some(reader.read_partial(len as usize)?);
+            }
+            // chunks are padded to an even length
+            reader.skip_to(end + (len & 1) as u64)?;
+        }
+    }
+}
+
+impl Write for WavHeader {
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> writer::Result<()> {
+        writer.write(&RIFF)?;
+        writer.u32(self.riff_size)?;
+        writer.write(&WAVE)?;
+        writer.write(&FMT)?;
+        writer.u32(FMT_SIZE)?;
+        writer.write(&self.format)?;
+        writer.write(&DATA)?;
+        writer.u32(self.data_len)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(44)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endian::LittleEndian;
+
+    const SAMPLE: &[u8] = include_bytes!("../../testdata/sample.wav");
+
+    #[test]
+    fn test_sample_wav() {
+        let mut reader = BinaryReader::from_le_bytes(SAMPLE);
+        let header: WavHeader = reader.read().unwrap();
+        assert_eq!(header.riff_size as usize, SAMPLE.len() - 8);
+        assert_eq!(header.format.audio_format, AudioFormat::Pcm);
+        assert_eq!((header.format.channels, header.format.sample_rate, header.format.bits_per_sample), (1, 8000, 16));
+        assert_eq!(header.data_len, 8);
+        assert_eq!(reader.read_with::<Vec<i16>, _>(4).unwrap(), vec![0, 1000, -1000, 0]);
+
+        let written = BinaryWriter::<LittleEndian, _>::to_vec(&header).unwrap();
+        assert_eq!(written, &SAMPLE[..44]);
+    }
+
+    #[test]
+    fn test_wav_skips_unknown_chunks() {
+        // a three-byte LIST chunk plus its pad byte, between fmt and data
+        let mut data = SAMPLE[..36].to_vec();
+        data.extend_from_slice(b"LIST\x03\0\0\0abc\0");
+        data.extend_from_slice(&SAMPLE[36..]);
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        let header: WavHeader = reader.read().unwrap();
+        assert_eq!(header.data_len, 8);
+        assert_eq!(reader.remaining(), Some(8));
+
+        let mut reader = BinaryReader::from_le_bytes(b"RIFF\x0c\0\0\0WAVEdata\0\0\0\0");
+        assert!(matches!(reader.read::<WavHeader>(), Err(ReadError::InvalidDataFormat(_))));
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{
+    endian::Endian,
+    fixed_size::FixedSize,
+    reader::{BinaryReader, Read, Result},
+    result::ReadError,
+    span::Span,
+};
+use std::{
+    io::{BufRead, Seek},
+    marker::PhantomData,
+};
+
+/// Where the elements of a `LazyVec` are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Layout {
+    /// Element `i` starts at `base + i * stride` and spans at most `stride` bytes.
+    Strided { base: u64, stride: u64, len: usize },
+    /// Element `i` occupies exactly `spans[i]`.
+    Spans(Vec<Span>),
+}
+
+/// A table of `T`s whose locations are known but which are only parsed when asked for.
+///
+/// Keeps its own seekable reader over the data, so a file with a huge element table can be opened
+/// without parsing every element. Build one with `strided`, `fixed` or `from_spans`, or with
+/// `BinaryReader::read_lazy_vec` on a slice-backed reader. Offsets are positions as reported by
+/// the retained reader. Each element is read within its own region, so a `T` that reads past it
+/// fails with `ReadError::NotEnoughBytes`.
+pub struct LazyVec<E, R, T> {
+    reader: BinaryReader<E, R>,
+    layout: Layout,
+    _element: PhantomData<fn() -> T>,
+}
+
+impl<E: Endian, R: BufRead + Seek, T: Read> LazyVec<E, R, T> {
+    /// A table of `len` elements starting at `base`, one every `stride` bytes.
+    pub fn strided(reader: BinaryReader<E, R>, base: u64, stride: u64, len: usize) -> Self {
+        Self::with_layout(reader, Layout::Strided { base, stride, len })
+    }
+
+    /// A table of elements at the given regions, e.g. taken from an index of `Span`s.
+    pub fn from_spans(reader: BinaryReader<E, R>, spans: Vec<Span>) -> Self {
+        Self::with_layout(reader, Layout::Spans(spans))
+    }
+
+    fn with_layout(reader: BinaryReader<E, R>, layout: Layout) -> Self {
+        Self {
+            reader,
+            layout,
+            _element: PhantomData,
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        match &self.layout {
+            Layout::Strided { len, .. } => *len,
+            Layout::Spans(spans) => spans.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the region of element `index`, or `None` if it is out of bounds or its offset does
+    /// not fit in a `u64`.
+    pub fn span(&self, index: usize) -> Option<Span> {
+        match &self.layout {
+            Layout::Strided { base, stride, len } if index < *len => {
+                let offset = (index as u64).checked_mul(*stride).and_then(|offset| offset.checked_add(*base))?;
+                Some(Span::new(offset, *stride))
+            }
+            Layout::Strided { .. } => None,
+            Layout::Spans(spans) => spans.get(index).copied(),
+        }
+    }
+
+    /// Parses element `index`. Fails with `ReadError::InvalidArgument` if it is out of bounds or
+    /// its offset overflows.
+    pub fn get(&mut self, index: usize) -> Result<T> {
+        let span = self.span(index).ok_or(ReadError::InvalidArgument)?;
+        let len = usize::try_from(span.len).map_err(|_| ReadError::InvalidArgument)?;
+        self.reader.seek_to(span.offset)?;
+        self.reader.read_partial(len).map_err(|e| ReadError::InElement {
+            index,
+            offset: span.offset,
+            source: Box::new(e),
+        })
+    }
+
+    /// Parses the elements one by one as the iterator is advanced.
+    pub fn iter(&mut self) -> impl Iterator<Item = Result<T>> + '_ {
+        (0..self.len()).map(|index| self.get(index))
+    }
+
+    /// Returns the retained reader.
+    pub fn into_reader(self) -> BinaryReader<E, R> {
+        self.reader
+    }
+}
+
+impl<E: Endian, R: BufRead + Seek, T: Read + FixedSize> LazyVec<E, R, T> {
+    /// A table of `len` fixed-size elements packed back to back from `base`.
+    pub fn fixed(reader: BinaryReader<E, R>, base: u64, len: usize) -> Self {
+        Self::strided(reader, base, T::SIZE as u64, len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endian::LittleEndian;
+    use std::{
+        io::Cursor,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    static PARSED: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct Counted(u32);
+
+    impl Read for Counted {
+        fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+            PARSED.fetch_add(1, Ordering::Relaxed);
+            reader.u32().map(Counted)
+        }
+    }
+
+    impl FixedSize for Counted {
+        const SIZE: usize = 4;
+    }
+
+    #[test]
+    fn test_lazy_vec_sparse() {
+        let mut data = vec![0xaa, 0xbb];
+        data.extend((0..100_000u32).flat_map(|i| (i * 3).to_le_bytes()));
+        data.push(0xcc);
+
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        reader.u16().unwrap();
+        let mut table = reader.read_lazy_vec::<Counted>(100_000).unwrap();
+        assert_eq!(reader.u8().unwrap(), 0xcc);
+        assert_eq!(table.len(), 100_000);
+
+        PARSED.store(0, Ordering::Relaxed);
+        for index in [99_999, 0, 51_234, 7] {
+            assert_eq!(table.get(index).unwrap(), Counted(index as u32 * 3));
+        }
+        assert_eq!(PARSED.load(Ordering::Relaxed), 4);
+        assert_eq!(table.span(7), Some(Span::new(2 + 28, 4)));
+        assert!(matches!(table.get(100_000), Err(ReadError::InvalidArgument)));
+
+        let firsts: Vec<_> = table.iter().take(3).map(|r| r.unwrap().0).collect();
+        assert_eq!(firsts, [0, 3, 6]);
+        assert_eq!(PARSED.load(Ordering::Relaxed), 7);
+    }
+
+    #[test]
+    fn test_lazy_vec_spans() {
+        let data = b"\x01\x00\x00\x00pad\x02\x00\x00\x00\x03\x00";
+        let reader = BinaryReader::new_le(Cursor::new(&data[..]));
+        let spans = vec![Span::new(7, 4), Span::new(0, 4), Span::new(11, 2)];
+        let mut table = LazyVec::<LittleEndian, _, u32>::from_spans(reader, spans);
+        assert_eq!(table.get(0).unwrap(), 2);
+        assert_eq!(table.get(1).unwrap(), 1);
+        let err = table.get(2).unwrap_err();
+        assert!(matches!(err, ReadError::InElement { index: 2, offset: 11, ref source } if matches!(**source, ReadError::NotEnoughBytes { .. })));
+
+        let reader = BinaryReader::new_le(Cursor::new(&data[..]));
+        let mut strided = LazyVec::<LittleEndian, _, u8>::strided(reader, 0, 7, 2);
+        assert_eq!(strided.iter().collect::<Result<Vec<_>>>().unwrap(), [1, 2]);
+
+        // Offsets past u64::MAX from a hostile header are rejected instead of wrapping around.
+        let reader = BinaryReader::new_le(Cursor::new(&data[..]));
+        let mut overflowing = LazyVec::<LittleEndian, _, u8>::strided(reader, u64::MAX - 8, u64::MAX / 2, 3);
+        assert_eq!(overflowing.span(0), Some(Span::new(u64::MAX - 8, u64::MAX / 2)));
+        assert_eq!(overflowing.span(1), None);
+        assert!(matches!(overflowing.get(1), Err(ReadError::InvalidArgument)));
+        assert!(matches!(overflowing.get(2), Err(ReadError::InvalidArgument)));
+    }
+}
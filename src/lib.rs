@@ -39,6 +39,161 @@
 //!     assert_eq!(y, 2);
 //! }
 //! ```
+//!
+//! # Extending readers
+//!
+//! Domain-specific reads such as `guid()` or `vec3()` belong in an extension trait implemented
+//! for `BinaryReader<E, R>` or `AsyncBinaryReader<E, R>`. The methods such traits build on form
+//! the stable extension surface; they are public, not `#[doc(hidden)]`, and change only with a
+//! semver-major release:
+//!
+//! - `read_from_array` and `read_from_slice`, which consume a fixed or runtime number of bytes
+//!   and hand them to a parse closure
+//! - `position`, `remaining` and `ensure`, which report and check progress against the limit
+//! - `read_num` and `read_num_as`, the endian hooks, which decode a `Primitive` in the reader's
+//!   byte order or an explicit one, together with the `Endian` trait behind the `E` parameter
+//!
+//! Errors from these methods carry offsets and poison the reader as the built-in reads do, so
+//! an extension needs no access to the reader's internals:
+//!
+//! ```rust
+//! use rwbin::async_reader::{AsyncBinaryReader, ReaderBase};
+//! use rwbin::endian::{BigEndian, Endian};
+//! use rwbin::reader::{BinaryReader, Result};
+//! use std::future::Future;
+//! use std::io::BufRead;
+//!
+//! pub trait ReadGeometry {
+//!     /// A GUID, whose first three fields follow the reader's byte order.
+//!     fn guid(&mut self) -> Result<[u8; 16]>;
+//!     /// Three `f32` coordinates, read all or nothing.
+//!     fn vec3(&mut self) -> Result<[f32; 3]>;
+//! }
+//!
+//! impl<E: Endian, R: BufRead> ReadGeometry for BinaryReader<E, R> {
+//!     fn guid(&mut self) -> Result<[u8; 16]> {
+//!         let data1 = self.read_num::<u32>()?;
+//!         let (data2, data3) = (self.read_num::<u16>()?, self.read_num::<u16>()?);
+//!         let tail = self.read_from_array(|bytes: &[u8; 8]| *bytes)?;
+//!         let mut guid = [0; 16];
+//!         guid[..4].copy_from_slice(&data1.to_be_bytes());
+//!         guid[4..6].copy_from_slice(&data2.to_be_bytes());
+//!         guid[6..8].copy_from_slice(&data3.to_be_bytes());
+//!         guid[8..].copy_from_slice(&tail);
+//!         Ok(guid)
+//!     }
+//!
+//!     fn vec3(&mut self) -> Result<[f32; 3]> {
+//!         self.ensure(12)?;
+//!         Ok([self.read_num()?, self.read_num()?, self.read_num()?])
+//!     }
+//! }
+//!
+//! pub trait AsyncReadGeometry {
+//!     fn vec3(&mut self) -> impl Future<Output = Result<[f32; 3]>> + Send;
+//!     fn be_tag(&mut self) -> impl Future<Output = Result<u32>> + Send;
+//! }
+//!
+//! impl<E: Endian, R: ReaderBase> AsyncReadGeometry for AsyncBinaryReader<E, R> {
+//!     async fn vec3(&mut self) -> Result<[f32; 3]> {
+//!         self.ensure(12)?;
+//!         Ok([self.read_num().await?, self.read_num().await?, self.read_num().await?])
+//!     }
+//!
+//!     async fn be_tag(&mut self) -> Result<u32> {
+//!         self.read_num_as::<BigEndian, u32>().await
+//!     }
+//! }
+//!
+//! let data = [0x33, 0x22, 0x11, 0x00, 0x55, 0x44, 0x77, 0x66, 8, 9, 10, 11, 12, 13, 14, 15];
+//! let mut reader = BinaryReader::from_le_bytes(&data);
+//! assert_eq!(reader.guid().unwrap(), [0, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 8, 9, 10, 11, 12, 13, 14, 15]);
+//! assert!(reader.vec3().is_err());
+//! assert_eq!(reader.position(), 16);
+//!
+//! let data = [b'V', b'E', b'C', b'3', 0, 0, 0x80, 0x3f, 0, 0, 0, 0x40, 0, 0, 0x40, 0x40];
+//! let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+//! runtime.block_on(async {
+//!     let mut reader = AsyncBinaryReader::from_le_bytes(&data);
+//!     assert_eq!(reader.be_tag().await.unwrap(), u32::from_be_bytes(*b"VEC3"));
+//!     assert_eq!(reader.vec3().await.unwrap(), [1.0, 2.0, 3.0]);
+//!     assert_eq!(reader.remaining(), Some(0));
+//! });
+//! ```
+/// Records one `$category` call that moved `$bytes` bytes on the collector attached to `$this`, if any.
+macro_rules! record_stats {
+    ($this:ident, $category:ident, $bytes:expr) => {
+        #[cfg(feature = "stats")]
+        if let Some(stats) = &$this.stats {
+            stats.record($crate::stats::Category::$category, $bytes as u64);
+        }
+    };
+}
+
+/// Runs `$body` as a single `$category` call: every byte it moves is attributed to that category,
+/// and nothing is recorded for the primitive or bulk operations it is built from.
+macro_rules! count_stats {
+    ($this:ident, $category:ident, async $body:block) => {{
+        #[cfg(feature = "stats")]
+        let (stats, start) = ($this.stats.take(), $this.position());
+        let result = async $body.await;
+        #[cfg(feature = "stats")]
+        {
+            if let Some(stats) = &stats {
+                stats.record($crate::stats::Category::$category, $this.position() - start);
+            }
+            $this.stats = stats;
+        }
+        result
+    }};
+    ($this:ident, $category:ident, $body:block) => {{
+        #[cfg(feature = "stats")]
+        let (stats, start) = ($this.stats.take(), $this.position());
+        #[allow(clippy::redundant_closure_call)] // gives `?` inside `$body` somewhere to return to
+        let result = (|| $body)();
+        #[cfg(feature = "stats")]
+        {
+            if let Some(stats) = &stats {
+                stats.record($crate::stats::Category::$category, $this.position() - start);
+            }
+            $this.stats = stats;
+        }
+        result
+    }};
+}
+
+/// Runs `$body` with `$view` bound to a `$reader` over `$this`'s stream that uses the byte order
+/// `$endian`, then takes over the position and poison it ends with.
+///
+/// The view starts from `$this`'s position, limit, poison and stats, so a value read through it
+/// is held to exactly the same limit, e.g. it cannot escape an enclosing `read_partial`. Shared by
+/// the `read_as_*` methods of the sync and async readers; fields only one of them has are listed
+/// in braces after the reader type, copied into the view and taken back from it afterwards. A
+/// view type given after `as`, such as a `DynReader`, erases the stream type.
+macro_rules! with_endian {
+    ($this:ident, $reader:ident $({ $($field:ident),* })?, $endian:ty $(as $view_ty:ty)?, |$view:ident| $body:expr) => {{
+        let mut $view $(: $view_ty)? = $reader {
+            $($($field: $this.$field,)*)?
+            reader: &mut $this.reader,
+            total_bytes_read: $this.total_bytes_read,
+            limit_bytes: $this.limit_bytes,
+            poisoned_at: $this.poisoned_at,
+            max_depth: $this.max_depth,
+            depth: $this.depth,
+            max_alloc: $this.max_alloc,
+            strict_strings: $this.strict_strings,
+            #[cfg(feature = "stats")]
+            stats: $this.stats.clone(),
+            _endian: std::marker::PhantomData::<fn() -> $endian>,
+        };
+        let result = $body;
+        $this.total_bytes_read = $view.total_bytes_read;
+        $this.poisoned_at = $view.poisoned_at;
+        $($($this.$field = $view.$field;)*)?
+        result
+    }};
+}
+
 /// Asynchronous binary reader supporting futures-based I/O.
 ///
 /// Construct with `AsyncBinaryReader::<Endian, _>::new_le`, `new_be`, `from_le_bytes`, or
@@ -49,11 +204,72 @@ pub mod async_reader;
 /// Construct with `AsyncBinaryWriter::<Endian, _>::new_le`, `new_be`, then call methods like
 /// `.u8()`, `.write::<T>()`, or implement `AsyncWrite` for your types.
 pub mod async_writer;
+/// Crash-safe file sinks that replace their target only on commit.
+///
+/// Contains `AtomicFile` and `AsyncAtomicFile`, used by `BinaryWriter::create_atomic_le` and friends.
+pub mod atomic;
+/// One place to configure readers and writers.
+///
+/// Contains `BinaryReaderBuilder`, which sets the byte order, limit, buffer capacity, allocation
+/// and depth caps and string strictness before building a `BinaryReader` or `AsyncBinaryReader`,
+/// and `BinaryWriterBuilder`, which does the same for writers with a limit, staging buffer,
+/// checksum and round-trip validation over any sink, a `Vec`, a slice or an atomic file.
+pub mod builder;
+/// Drop-in `ReadBytesExt` / `WriteBytesExt` methods for code written against `byteorder`
+/// (`byteorder-compat` feature).
+///
+/// Contains `RwbinByteorderCompat` and `RwbinByteorderCompatWrite`, with per-call byte orders and
+/// `std::io::Error` results.
+#[cfg(feature = "byteorder-compat")]
+pub mod byteorder_compat;
+/// Digests for file-level integrity checks.
+///
+/// Contains `ChecksumKind`, `Checksum` and the `ChecksumWriter` sink behind `finish_with_checksum`
+/// and `verify_trailing_checksum`.
+pub mod checksum;
+/// One-pass output in both byte orders.
+///
+/// Contains `DualWriter`, which mirrors every write into a little-endian and a big-endian stream.
+pub mod dual;
+/// Annotated parse trees for debugging unknown files (`debug-tools` feature).
+///
+/// Contains `dump`, which parses a value and returns a `DumpTree` of the offsets and lengths of its
+/// nested reads.
+#[cfg(feature = "debug-tools")]
+pub mod dump;
 /// Endianness utilities for byte conversions.
 ///
-/// Contains `LittleEndian` and `BigEndian` types implementing the `Endian` trait,
-/// which converts primitives to/from byte arrays.
+/// Contains `LittleEndian`, `BigEndian`, the host's `NativeEndian` and the PDP-11 `MiddleEndian`
+/// types implementing the `Endian` trait, which converts primitives to/from byte arrays. The
+/// little-, big- and native-endian conversions are also `const fn`s, and `ConstBytes` assembles
+/// them into header arrays at compile time.
 pub mod endian;
+/// Encoded-size information for fixed-width types.
+///
+/// Contains the `FixedSize` trait, implemented for primitives, tuples and arrays, which
+/// enables size pre-checks and record skipping without parsing.
+pub mod fixed_size;
+/// Headers of simple real-world formats (`formats` feature).
+///
+/// Contains `bmp::BmpHeader` and `wav::WavHeader`, with `Read`/`Write` built only on the public
+/// API. Both formats are little-endian on disk, so use them with little-endian readers and writers.
+#[cfg(feature = "formats")]
+pub mod formats;
+/// Element tables parsed on demand.
+///
+/// Contains `LazyVec`, which records where the elements of a table are and parses one only when
+/// it is indexed, from a retained seekable reader.
+pub mod lazy;
+/// Zero-copy views of plain-old-data types (`pod` feature).
+///
+/// Re-exports the `zerocopy` traits that bound `BinaryReader::read_pod_ref`.
+#[cfg(feature = "pod")]
+pub mod pod;
+/// Typed offsets to values elsewhere in the stream.
+///
+/// Contains `Ptr<T>`, which `resolve`s against a seekable reader, `NullablePtr<T>` for offsets where
+/// 0 means none, and the `PtrSlot` placeholders `Ptr::reserve` writes for targets not written yet.
+pub mod ptr;
 /// Synchronous binary reader wrapping any `BufRead`.
 ///
 /// Construct with `BinaryReader::<Endian, _>::new_le`, `new_be`, `from_le_bytes`, or `from_be_bytes`,
@@ -63,11 +279,59 @@ pub mod reader;
 ///
 /// Includes `ReadError`, `WriteError`, and the `ReadResult` / `WriteResult` aliases.
 pub mod result;
+mod retry;
+/// Offset/length and start/end region types for span-based formats.
+///
+/// Contains `Span` and the `SpanEncoding` argument used to read and write it, or a `Range<u64>`,
+/// with `read_with`/`write_with`.
+pub mod span;
+/// Per-category call and byte counters for profiling parsers (`stats` feature).
+///
+/// Contains `Stats`, attached with `attach_stats` on readers and writers, and its `StatsReport`
+/// snapshot.
+#[cfg(feature = "stats")]
+pub mod stats;
 /// String utilities for reading and writing UTF-8 and UTF-16 data.
 ///
 /// Provides `utf8_str` and `utf16_str` methods on readers/writers for fixed-length or
 /// null-terminated strings in sync and async contexts.
 pub mod string;
+/// Fixed-width byte tags such as FourCC chunk identifiers.
+///
+/// Contains `Tag<N>`, read and written as raw bytes and accepted by `magic`.
+pub mod tag;
+/// Tag-dispatched unions without hand-written matches.
+///
+/// Contains `TagTable`, mapping tags to parse functions for `BinaryReader::read_tagged`, and the
+/// type-erased `DynReader` those functions receive. The writer side is `BinaryWriter::write_tagged`.
+pub mod tagged;
+/// Golden-file snapshot tests for `Write` impls (`test-util` feature).
+///
+/// Contains `assert_golden`, which compares a value's encoding with a checked-in file and
+/// rewrites the file when `RWBIN_BLESS` is set.
+#[cfg(feature = "test-util")]
+pub mod test_util;
+/// Byte-level decoding and encoding layers for obfuscated data.
+///
+/// Contains `TransformReader` and `TransformWriter`, which apply an offset-keyed transform such as
+/// `xor` to every chunk that flows through them.
+pub mod transform;
+/// Round-trip checks for `Read`/`Write` impls (`debug-tools` feature).
+///
+/// Contains `ValidatingWriter`, which re-parses every value it writes in debug builds and reports
+/// values that do not read back as themselves.
+#[cfg(feature = "debug-tools")]
+pub mod validate;
+/// Variable-length integers.
+///
+/// Adds the LEB128 `varint_u64` and zigzag-encoded `varint_i64` used by protobuf and WebAssembly,
+/// and the MIDI-style big-endian `vlq_u32`, to the sync and async readers and writers.
+pub mod varint;
+/// Format versions for file headers.
+///
+/// Contains `Version`, a `major.minor` pair of `u16`s, and the `VersionRange` checked by
+/// `BinaryReader::version`.
+pub mod version;
 /// Synchronous binary writer wrapping any `Write`.
 ///
 /// Construct with `BinaryWriter::<Endian, _>::new_le`, `new_be`, then call methods like
@@ -0,0 +1,8 @@
+//! The `zerocopy` traits bounding `BinaryReader::read_pod_ref`.
+//!
+//! A type viewed in place must be valid for every bit pattern (`FromBytes`), have a known layout
+//! (`KnownLayout`), contain no interior mutability (`Immutable`) and have an alignment of 1
+//! (`Unaligned`). Derive them with `zerocopy`'s derive macros. The bytes are reinterpreted
+//! as-is, so multi-byte fields are in the buffer's byte order rather than the reader's `Endian`;
+//! store them as byte arrays or `zerocopy::byteorder` types when that matters.
+pub use zerocopy::{FromBytes, Immutable, KnownLayout, Unaligned};
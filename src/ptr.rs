@@ -0,0 +1,279 @@
+use crate::{
+    endian::Endian,
+    fixed_size::FixedSize,
+    reader::{self, BinaryReader, Read},
+    result::WriteError,
+    writer::{self, BinaryWriter, Write},
+};
+use std::{
+    fmt,
+    io::{BufRead, Seek},
+    marker::PhantomData,
+};
+
+/// The integer types a pointer can be stored as.
+pub trait PtrWidth: Read + Write + FixedSize + Copy + Into<u64> + TryFrom<u64> {}
+
+impl PtrWidth for u8 {}
+impl PtrWidth for u16 {}
+impl PtrWidth for u32 {}
+impl PtrWidth for u64 {}
+
+/// An offset to a `T` elsewhere in the stream, stored as an `O`.
+///
+/// Reading a `Ptr` only reads the offset; `resolve` follows it. Offsets are positions as reported
+/// by the reader's `position`, i.e. absolute unless the reader was rebased.
+pub struct Ptr<T, O = u32> {
+    pub offset: u64,
+    _target: PhantomData<fn() -> (T, O)>,
+}
+
+impl<T, O> Ptr<T, O> {
+    pub const fn new(offset: u64) -> Self {
+        Self { offset, _target: PhantomData }
+    }
+}
+
+impl<T: Read, O> Ptr<T, O> {
+    /// Reads the `T` this pointer points to, leaving `reader` where it was.
+    pub fn resolve<E: Endian, R: BufRead + Seek>(&self, reader: &mut BinaryReader<E, R>) -> reader::Result<T> {
+        reader.read_at(self.offset)
+    }
+}
+
+impl<T, O: PtrWidth> Ptr<T, O> {
+    /// Writes a null pointer as a placeholder and returns the slot to fill in once the target
+    /// has been written. Also works for `NullablePtr` fields, which share the encoding.
+    pub fn reserve<E: Endian, W: std::io::Write + Seek>(writer: &mut BinaryWriter<E, W>) -> writer::Result<PtrSlot<T, O>> {
+        let at = writer.sync_position()?;
+        writer.write(&Self::new(0))?;
+        Ok(PtrSlot { at, _target: PhantomData })
+    }
+}
+
+impl<T, O> Clone for Ptr<T, O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, O> Copy for Ptr<T, O> {}
+
+impl<T, O> PartialEq for Ptr<T, O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.offset == other.offset
+    }
+}
+
+impl<T, O> Eq for Ptr<T, O> {}
+
+impl<T, O> fmt::Debug for Ptr<T, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ptr({:#x})", self.offset)
+    }
+}
+
+impl<T, O: PtrWidth> FixedSize for Ptr<T, O> {
+    const SIZE: usize = O::SIZE;
+}
+
+impl<T, O: PtrWidth> Read for Ptr<T, O> {
+    fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> reader::Result<Self> {
+        reader.read::<O>().map(|offset| Self::new(offset.into()))
+    }
+}
+
+impl<T, O: PtrWidth> Write for Ptr<T, O> {
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> writer::Result<()> {
+        let offset = O::try_from(self.offset).map_err(|_| WriteError::InvalidArgument)?;
+        writer.write(&offset)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(O::SIZE)
+    }
+}
+
+/// A `Ptr` for which offset 0 means "no target".
+pub struct NullablePtr<T, O = u32>(pub Option<Ptr<T, O>>);
+
+impl<T: Read, O> NullablePtr<T, O> {
+    /// Reads the `T` this pointer points to, if any, leaving `reader` where it was.
+    pub fn resolve<E: Endian, R: BufRead + Seek>(&self, reader: &mut BinaryReader<E, R>) -> reader::Result<Option<T>> {
+        self.0.map(|ptr| ptr.resolve(reader)).transpose()
+    }
+}
+
+impl<T, O> Clone for NullablePtr<T, O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, O> Copy for NullablePtr<T, O> {}
+
+impl<T, O> PartialEq for NullablePtr<T, O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T, O> Eq for NullablePtr<T, O> {}
+
+impl<T, O> fmt::Debug for NullablePtr<T, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(ptr) => ptr.fmt(f),
+            None => f.write_str("Null"),
+        }
+    }
+}
+
+impl<T, O: PtrWidth> FixedSize for NullablePtr<T, O> {
+    const SIZE: usize = O::SIZE;
+}
+
+impl<T, O: PtrWidth> Read for NullablePtr<T, O> {
+    fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> reader::Result<Self> {
+        let ptr = reader.read::<Ptr<T, O>>()?;
+        Ok(Self((ptr.offset != 0).then_some(ptr)))
+    }
+}
+
+impl<T, O: PtrWidth> Write for NullablePtr<T, O> {
+    /// Writes the offset, or 0 for `None`. A `Some` pointer to offset 0 cannot be told apart
+    /// from `None` and is rejected with `WriteError::InvalidArgument`.
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> writer::Result<()> {
+        match self.0 {
+            Some(ptr) if ptr.offset == 0 => Err(WriteError::InvalidArgument),
+            Some(ptr) => writer.write(&ptr),
+            None => writer.write(&Ptr::<T, O>::new(0)),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(O::SIZE)
+    }
+}
+
+/// A placeholder pointer written by `Ptr::reserve`, waiting for the position of its target.
+#[must_use = "the placeholder stays null until `point_here` is called"]
+pub struct PtrSlot<T, O = u32> {
+    at: u64,
+    _target: PhantomData<fn() -> (T, O)>,
+}
+
+impl<T, O: PtrWidth> PtrSlot<T, O> {
+    /// Returns the writer position of the placeholder.
+    pub fn position(&self) -> u64 {
+        self.at
+    }
+
+    /// Points the placeholder at the writer's current position, where the target is about to be
+    /// written, and returns the pointer that was patched in.
+    pub fn point_here<E: Endian, W: std::io::Write + Seek>(self, writer: &mut BinaryWriter<E, W>) -> writer::Result<Ptr<T, O>> {
+        let ptr = Ptr::new(writer.sync_position()?);
+        writer.patch(self.at, &ptr)?;
+        Ok(ptr)
+    }
+}
+
+impl<T, O> fmt::Debug for PtrSlot<T, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PtrSlot({:#x})", self.at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[derive(Debug, PartialEq)]
+    struct Leaf {
+        value: u16,
+    }
+
+    impl Read for Leaf {
+        fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> reader::Result<Self> {
+            Ok(Leaf { value: reader.u16()? })
+        }
+    }
+
+    impl Write for Leaf {
+        fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> writer::Result<()> {
+            writer.u16(self.value)
+        }
+    }
+
+    struct Node {
+        tag: u8,
+        leaf: Ptr<Leaf>,
+        next: NullablePtr<Node, u16>,
+    }
+
+    impl Read for Node {
+        fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> reader::Result<Self> {
+            Ok(Node {
+                tag: reader.u8()?,
+                leaf: reader.read()?,
+                next: reader.read()?,
+            })
+        }
+    }
+
+    #[test]
+    fn test_follow_pointer_chain() {
+        // root node at 0 -> node at 7 -> no next; each node points at its own leaf
+        let data = [
+            1, 0x0e, 0, 0, 0, 0x07, 0, //
+            2, 0x10, 0, 0, 0, 0, 0, //
+            0x01, 0x11, 0x02, 0x22,
+        ];
+        let mut reader = BinaryReader::new_le(Cursor::new(&data[..]));
+        let root: Node = reader.read().unwrap();
+        assert_eq!((root.tag, root.leaf), (1, Ptr::new(0x0e)));
+        let second = root.next.resolve(&mut reader).unwrap().unwrap();
+        assert_eq!(reader.position(), 7);
+        assert_eq!(second.tag, 2);
+        assert_eq!(second.next, NullablePtr(None));
+        assert!(second.next.resolve(&mut reader).unwrap().is_none());
+        assert_eq!(root.leaf.resolve(&mut reader).unwrap(), Leaf { value: 0x1101 });
+        assert_eq!(second.leaf.resolve(&mut reader).unwrap(), Leaf { value: 0x2202 });
+        assert_eq!(reader.position(), 7);
+
+        let dangling = Ptr::<Leaf>::new(17);
+        assert!(dangling.resolve(&mut reader).is_err());
+        assert_eq!(reader.position(), 7);
+    }
+
+    #[test]
+    fn test_write_backpatched_pointers() {
+        let mut writer = BinaryWriter::new_be(Cursor::new(Vec::new()));
+        writer.u8(1).unwrap();
+        let leaf = Ptr::<Leaf>::reserve(&mut writer).unwrap();
+        let next = Ptr::<Node, u16>::reserve(&mut writer).unwrap();
+        assert_eq!((leaf.position(), next.position()), (1, 5));
+
+        assert_eq!(next.point_here(&mut writer).unwrap(), Ptr::new(7));
+        writer.u8(2).unwrap();
+        let second_leaf = Ptr::<Leaf>::reserve(&mut writer).unwrap();
+        writer.write(&NullablePtr::<Node, u16>(None)).unwrap();
+        assert_eq!(leaf.point_here(&mut writer).unwrap(), Ptr::new(14));
+        writer.write(&Leaf { value: 0x0111 }).unwrap();
+        second_leaf.point_here(&mut writer).unwrap();
+        writer.write(&Leaf { value: 0x0222 }).unwrap();
+        let data = writer.finish().unwrap().into_inner();
+        assert_eq!(data, [1, 0, 0, 0, 14, 0, 7, 2, 0, 0, 0, 16, 0, 0, 0x01, 0x11, 0x02, 0x22]);
+
+        let mut reader = BinaryReader::new_be(Cursor::new(&data[..]));
+        let root: Node = reader.read().unwrap();
+        let second = root.next.resolve(&mut reader).unwrap().unwrap();
+        assert_eq!(root.leaf.resolve(&mut reader).unwrap().value, 0x0111);
+        assert_eq!(second.leaf.resolve(&mut reader).unwrap().value, 0x0222);
+
+        let mut writer = BinaryWriter::new_le(Vec::new());
+        assert!(matches!(writer.write(&Ptr::<Leaf, u8>::new(256)), Err(WriteError::InvalidArgument)));
+        assert!(matches!(writer.write(&NullablePtr(Some(Ptr::<Leaf>::new(0)))), Err(WriteError::InvalidArgument)));
+    }
+}
@@ -1,103 +1,643 @@
+#[cfg(feature = "pod")]
+use crate::pod::{FromBytes, Immutable, KnownLayout, Unaligned};
 use crate::{
-    endian::{BigEndian, Endian, LittleEndian},
+    checksum::{ChecksumKind, ChecksumWriter},
+    endian::{BigEndian, Endian, Endianness, LittleEndian, NativeEndian, Primitive},
+    fixed_size::FixedSize,
     result::{ReadError, ReadResult},
+    retry,
+    version::{Version, VersionRange},
 };
 use std::{
+    any::Any,
+    borrow::Cow,
     fmt::Debug,
-    io::{BufRead, BufReader},
+    hash::Hash,
+    io::{BufRead, BufReader, Cursor, Seek, SeekFrom},
     marker::PhantomData,
+    mem::{ManuallyDrop, MaybeUninit},
+    sync::Arc,
 };
 
 pub struct BinaryReader<E, R> {
     reader: R,
-    total_bytes_read: usize,
-    limit_bytes: Option<usize>,
+    total_bytes_read: u64,
+    limit_bytes: Option<u64>,
+    poisoned_at: Option<u64>,
+    max_depth: Option<u32>,
+    depth: u32,
+    max_alloc: Option<usize>,
+    strict_strings: bool,
+    small_buf_size: usize,
+    pending: Pending,
+    #[cfg(feature = "stats")]
+    pub(crate) stats: Option<std::sync::Arc<crate::stats::Stats>>,
     _endian: PhantomData<fn() -> E>,
 }
 
 pub type Result<T> = ReadResult<T>;
 
-impl<R> BinaryReader<LittleEndian, R> {
-    /// Creates a new little-endian `BinaryReader` wrapping the given reader.
-    pub fn new_le(reader: R) -> Self {
+/// Largest payload accepted by `read_frame` / `write_frame` unless another cap is given.
+pub const DEFAULT_MAX_FRAME_SIZE: u32 = 16 * 1024 * 1024;
+
+/// What the length field read by `read_len_prefixed` (and written by `write_len_prefixed`) counts
+/// besides the payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LenPolicy {
+    /// Only the payload.
+    Exclusive,
+    /// The payload and the length field itself.
+    InclusiveOfPrefix,
+    /// The payload and a header of this many bytes that ends with the length field, e.g. 8 for
+    /// a 4-byte tag followed by a `u32` length.
+    InclusiveOfHeader(usize),
+}
+
+impl LenPolicy {
+    /// Bytes counted by the length besides the payload, for a length field of `prefix_size` bytes.
+    fn overhead(self, prefix_size: usize) -> usize {
+        match self {
+            LenPolicy::Exclusive => 0,
+            LenPolicy::InclusiveOfPrefix => prefix_size,
+            LenPolicy::InclusiveOfHeader(header_size) => header_size,
+        }
+    }
+
+    /// Returns the payload size for a stored length, failing if the length is smaller than what it includes.
+    pub(crate) fn payload_len(self, stored: u64, prefix_size: usize) -> Result<usize> {
+        let overhead = self.overhead(prefix_size);
+        let len = stored
+            .checked_sub(overhead as u64)
+            .ok_or_else(|| ReadError::invalid_data_format(format!("length {stored} is smaller than the {overhead} bytes it includes")))?;
+        usize::try_from(len).map_err(|_| ReadError::invalid_data_format(format!("length {stored} does not fit in usize")))
+    }
+
+    /// Returns the length to store for a payload of `payload` bytes, or `None` on overflow.
+    pub(crate) fn stored_len(self, payload: usize, prefix_size: usize) -> Option<usize> {
+        payload.checked_add(self.overhead(prefix_size))
+    }
+}
+
+/// The width of an unsigned integer whose size a format decides at run time, e.g. offsets that are
+/// 4 bytes when a header flag is set and 8 otherwise. Read with `read_uint`, or as a `u64` through
+/// `read_with`, and written with `write_uint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntWidth {
+    U8,
+    U16,
+    U32,
+    U64,
+}
+
+impl IntWidth {
+    /// Returns the encoded size in bytes.
+    pub const fn size(self) -> usize {
+        match self {
+            IntWidth::U8 => 1,
+            IntWidth::U16 => 2,
+            IntWidth::U32 => 4,
+            IntWidth::U64 => 8,
+        }
+    }
+
+    /// Returns the largest value that fits.
+    pub const fn max(self) -> u64 {
+        match self {
+            IntWidth::U8 => u8::MAX as u64,
+            IntWidth::U16 => u16::MAX as u64,
+            IntWidth::U32 => u32::MAX as u64,
+            IntWidth::U64 => u64::MAX,
+        }
+    }
+}
+
+/// Reads of up to this many bytes go through a stack buffer instead of a fresh `Vec`, unless
+/// changed with `BinaryReader::with_small_buf_size`. Also used by the async reader and writer.
+pub const DEFAULT_SMALL_BUF_SIZE: usize = 512;
+
+/// Largest threshold `with_small_buf_size` accepts; bigger values are clamped to it.
+pub const MAX_SMALL_BUF_SIZE: usize = 16 * 1024;
+
+/// Bytes taken from the source ahead of the position. Either what a fixed-size read got before
+/// its source reported `WouldBlock`, kept so that retrying the read can complete it, or the
+/// lookahead a `peek` staged to get past the end of the source's buffer, which reads consume
+/// before the source. Values up to 16 bytes, i.e. every primitive, are resumable and peekable.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Pending {
+    bytes: [u8; Pending::CAPACITY],
+    len: usize,
+    /// Size of the interrupted read; only a retry of the same size picks the bytes up. Zero for
+    /// staged lookahead.
+    want: usize,
+}
+
+impl Pending {
+    pub(crate) const CAPACITY: usize = 16;
+    pub(crate) const EMPTY: Pending = Pending {
+        bytes: [0; Pending::CAPACITY],
+        len: 0,
+        want: 0,
+    };
+
+    /// Returns the staged lookahead, empty unless a `peek` left some.
+    pub(crate) fn lookahead(&self) -> &[u8] {
+        match self.want {
+            0 => &self.bytes[..self.len],
+            _ => &[],
+        }
+    }
+
+    /// Drops the first `amt` bytes of the staged lookahead.
+    pub(crate) fn consume(&mut self, amt: usize) {
+        self.bytes.copy_within(amt..self.len, 0);
+        self.len -= amt;
+    }
+
+    /// Moves up to `buf.len()` bytes of the staged lookahead into `buf`, returning how many.
+    pub(crate) fn take_into(&mut self, buf: &mut [u8]) -> usize {
+        let n = self.lookahead().len().min(buf.len());
+        buf[..n].copy_from_slice(&self.bytes[..n]);
+        self.consume(n);
+        n
+    }
+
+    /// Returns the free space after the staged lookahead, up to `max` bytes of lookahead in all.
+    pub(crate) fn spare(&mut self, max: usize) -> &mut [u8] {
+        let end = max.clamp(self.len, self.bytes.len());
+        &mut self.bytes[self.len..end]
+    }
+
+    /// Stages `n` more bytes, written into `spare` before.
+    pub(crate) fn grow(&mut self, n: usize) {
+        self.len += n;
+    }
+}
+
+/// The stream as a reader sees it: the lookahead a `peek` staged in `pending` first, then the
+/// source itself.
+pub(crate) struct Staged<'a, R: ?Sized> {
+    pub(crate) pending: &'a mut Pending,
+    pub(crate) reader: &'a mut R,
+}
+
+impl<'a, R: BufRead + ?Sized> Staged<'a, R> {
+    /// Like `fill_buf`, keeping the buffer borrowed for as long as the stream.
+    fn into_buf(self) -> std::io::Result<&'a [u8]> {
+        let pending: &'a Pending = self.pending;
+        if pending.lookahead().is_empty() {
+            return self.reader.fill_buf();
+        }
+        Ok(pending.lookahead())
+    }
+}
+
+impl<R: BufRead + ?Sized> std::io::Read for Staged<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.pending.take_into(buf) {
+            0 => self.reader.read(buf),
+            n => Ok(n),
+        }
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> std::io::Result<()> {
+        let n = self.pending.take_into(buf);
+        self.reader.read_exact(&mut buf[n..])
+    }
+}
+
+impl<R: BufRead + ?Sized> BufRead for Staged<'_, R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if self.pending.lookahead().is_empty() {
+            return self.reader.fill_buf();
+        }
+        Ok(self.pending.lookahead())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if self.pending.lookahead().is_empty() {
+            self.reader.consume(amt);
+        } else {
+            self.pending.consume(amt);
+        }
+    }
+}
+
+impl<E: Endian, R> BinaryReader<E, R> {
+    /// Creates a new `BinaryReader` using the byte order `E`, e.g. `BinaryReader::<MiddleEndian, _>::new(reader)`.
+    pub fn new(reader: R) -> Self {
         BinaryReader {
             reader,
             total_bytes_read: 0,
             limit_bytes: None,
+            poisoned_at: None,
+            max_depth: None,
+            depth: 0,
+            max_alloc: None,
+            strict_strings: false,
+            small_buf_size: DEFAULT_SMALL_BUF_SIZE,
+            pending: Pending::EMPTY,
+            #[cfg(feature = "stats")]
+            stats: None,
             _endian: PhantomData,
         }
     }
 }
-impl<'a> BinaryReader<LittleEndian, BufReader<&'a [u8]>> {
+impl<'a, E: Endian> BinaryReader<E, &'a [u8]> {
+    /// Creates a new `BinaryReader` using the byte order `E` from an in-memory byte slice.
+    pub fn from_bytes(data: &'a [u8]) -> Self {
+        let mut reader = Self::new(data);
+        reader.limit_bytes = Some(data.len() as u64);
+        reader
+    }
+}
+impl<R> BinaryReader<LittleEndian, R> {
+    /// Creates a new little-endian `BinaryReader` wrapping the given reader.
+    pub fn new_le(reader: R) -> Self {
+        Self::new(reader)
+    }
+}
+impl<R: std::io::Read> BinaryReader<LittleEndian, BufReader<R>> {
+    /// Creates a new little-endian `BinaryReader` that buffers `reader` with `capacity` bytes.
+    ///
+    /// Values larger than the buffer are still read correctly, but anything that inspects the
+    /// buffered data without consuming it can see at most `capacity` bytes.
+    pub fn new_le_with_capacity(reader: R, capacity: usize) -> Self {
+        Self::new_le(BufReader::with_capacity(capacity, reader))
+    }
+}
+impl<'a> BinaryReader<LittleEndian, &'a [u8]> {
     /// Creates a new little-endian `BinaryReader` from an in-memory byte slice.
     pub fn from_le_bytes(data: &'a [u8]) -> Self {
-        BinaryReader {
-            reader: BufReader::new(data),
-            total_bytes_read: 0,
-            limit_bytes: Some(data.len()),
-            _endian: PhantomData,
-        }
+        Self::from_bytes(data)
     }
 }
 impl<R> BinaryReader<BigEndian, R> {
     /// Creates a new big-endian `BinaryReader` wrapping the given reader.
     pub fn new_be(reader: R) -> Self {
-        BinaryReader {
-            reader,
-            total_bytes_read: 0,
-            limit_bytes: None,
-            _endian: PhantomData,
-        }
+        Self::new(reader)
     }
 }
-impl<'a> BinaryReader<BigEndian, BufReader<&'a [u8]>> {
+impl<R: std::io::Read> BinaryReader<BigEndian, BufReader<R>> {
+    /// Creates a new big-endian `BinaryReader` that buffers `reader` with `capacity` bytes.
+    ///
+    /// Values larger than the buffer are still read correctly, but anything that inspects the
+    /// buffered data without consuming it can see at most `capacity` bytes.
+    pub fn new_be_with_capacity(reader: R, capacity: usize) -> Self {
+        Self::new_be(BufReader::with_capacity(capacity, reader))
+    }
+}
+impl<'a> BinaryReader<BigEndian, &'a [u8]> {
     /// Creates a new big-endian `BinaryReader` from an in-memory byte slice.
     pub fn from_be_bytes(data: &'a [u8]) -> Self {
-        BinaryReader {
-            reader: BufReader::new(data),
-            total_bytes_read: 0,
-            limit_bytes: Some(data.len()),
-            _endian: PhantomData,
+        Self::from_bytes(data)
+    }
+}
+impl<R> BinaryReader<NativeEndian, R> {
+    /// Creates a new native-endian `BinaryReader` wrapping the given reader.
+    pub fn new_ne(reader: R) -> Self {
+        Self::new(reader)
+    }
+}
+impl<'a> BinaryReader<NativeEndian, &'a [u8]> {
+    /// Creates a new native-endian `BinaryReader` from an in-memory byte slice.
+    pub fn from_ne_bytes(data: &'a [u8]) -> Self {
+        Self::from_bytes(data)
+    }
+}
+impl<'c, E: Endian, T: AsRef<[u8]>> BinaryReader<E, &'c mut Cursor<T>> {
+    /// Creates a new `BinaryReader` using the byte order `E` that continues from `cursor`'s position.
+    ///
+    /// The position starts at `cursor.position()` and the limit at the end of its data. Reads go
+    /// through the cursor, so its position follows the reader's and `cursor` can be used again,
+    /// e.g. after `into_inner`, from where the reader stopped.
+    pub fn from_cursor(cursor: &'c mut Cursor<T>) -> Self {
+        let position = cursor.position();
+        let len = cursor.get_ref().as_ref().len() as u64;
+        let mut reader = BinaryReader::<E, _>::new(cursor).with_base_offset(position);
+        reader.limit_bytes = Some(len);
+        reader
+    }
+}
+impl<'c, T: AsRef<[u8]>> BinaryReader<LittleEndian, &'c mut Cursor<T>> {
+    /// Creates a new little-endian `BinaryReader` that continues from `cursor`'s position, see `from_cursor`.
+    pub fn from_cursor_le(cursor: &'c mut Cursor<T>) -> Self {
+        Self::from_cursor(cursor)
+    }
+}
+impl<'c, T: AsRef<[u8]>> BinaryReader<BigEndian, &'c mut Cursor<T>> {
+    /// Creates a new big-endian `BinaryReader` that continues from `cursor`'s position, see `from_cursor`.
+    pub fn from_cursor_be(cursor: &'c mut Cursor<T>) -> Self {
+        Self::from_cursor(cursor)
+    }
+}
+
+impl<E, R> BinaryReader<E, R> {
+    /// Returns the wrapped stream, without the bytes a `peek` may have staged from it.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Starts counting calls and bytes into `stats`, replacing any collector attached before.
+    #[cfg(feature = "stats")]
+    pub fn attach_stats(&mut self, stats: &std::sync::Arc<crate::stats::Stats>) {
+        self.stats = Some(stats.clone());
+    }
+
+    /// Rebases the reader so that its current position is reported as `offset`.
+    ///
+    /// Useful when the wrapped stream starts in the middle of a larger file. `offset` may be below
+    /// the current position. An existing limit is shifted by the same amount, so it still covers
+    /// the same bytes, saturating at `u64::MAX` and `0`.
+    pub fn with_base_offset(mut self, offset: u64) -> Self {
+        self.limit_bytes = self.limit_bytes.map(|limit| rebase_limit(limit, self.total_bytes_read, offset));
+        self.total_bytes_read = offset;
+        self
+    }
+
+    /// Sets the largest read, in bytes, that `read_from_slice` and the string readers serve from a
+    /// stack buffer rather than a heap allocation. Defaults to `DEFAULT_SMALL_BUF_SIZE` and is
+    /// clamped to `MAX_SMALL_BUF_SIZE`.
+    ///
+    /// Raise it when a format is dominated by fields of a few KiB, so they stop allocating; lower
+    /// it, down to `0` for no stack buffer at all, when a deeply recursive parse should not keep a
+    /// buffer in every frame. Reads above `DEFAULT_SMALL_BUF_SIZE` use separate 4 KiB and 16 KiB
+    /// buffers that only occupy the stack while such a read runs.
+    pub fn with_small_buf_size(mut self, size: usize) -> Self {
+        self.small_buf_size = size.min(MAX_SMALL_BUF_SIZE);
+        self
+    }
+
+    /// Applies the options collected in `builder`.
+    pub(crate) fn with_options(mut self, builder: &crate::builder::BinaryReaderBuilder<E>) -> Self {
+        self.limit_bytes = builder.limit_for(self.limit_bytes);
+        self.max_alloc = builder.max_alloc;
+        self.max_depth = builder.max_depth;
+        self.strict_strings = builder.strict_strings;
+        #[cfg(feature = "stats")]
+        if let Some(stats) = &builder.stats {
+            self.attach_stats(stats);
+        }
+        self.with_small_buf_size(builder.small_buf_size).with_base_offset(builder.base_offset)
+    }
+
+    /// Returns the number of bytes consumed so far.
+    ///
+    /// Part of the stable extension surface, see the crate documentation.
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.total_bytes_read
+    }
+
+    /// Returns the number of bytes left before the active limit, or `None` if unlimited.
+    ///
+    /// Part of the stable extension surface, see the crate documentation.
+    #[inline]
+    pub fn remaining(&self) -> Option<u64> {
+        self.limit_bytes.map(|limit| limit.saturating_sub(self.total_bytes_read))
+    }
+
+    /// Returns `true` if an earlier I/O failure left the stream out of step with the position.
+    ///
+    /// While poisoned, every read fails with `ReadError::Desynchronized`.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned_at.is_some()
+    }
+
+    /// Lets reads proceed again after a failure, e.g. once a recovery parser has resynced on a magic.
+    ///
+    /// The position is left as it was before the failed read, which may lag behind the stream.
+    pub fn clear_poison(&mut self) {
+        self.poisoned_at = None;
+        if self.pending.lookahead().is_empty() {
+            self.pending = Pending::EMPTY;
+        }
+    }
+
+    /// Limits how deeply `read`, `read_with` and `read_partial` calls may nest, so that a
+    /// self-referential format parsed from hostile input fails with `ReadError::DepthLimitExceeded`
+    /// instead of overflowing the stack. Each of those calls counts as one level while it runs.
+    pub fn set_max_depth(&mut self, max_depth: u32) {
+        self.max_depth = Some(max_depth);
+    }
+
+    /// Returns `true` if the string readers reject invalid sequences, see
+    /// `BinaryReaderBuilder::strict_strings`.
+    pub(crate) fn strict_strings(&self) -> bool {
+        self.strict_strings
+    }
+
+    /// Fails if allocating `bytes` up front would exceed the `max_alloc` the reader was built with.
+    #[inline]
+    pub(crate) fn check_alloc(&self, bytes: usize) -> Result<()> {
+        match self.max_alloc {
+            Some(max_alloc) if bytes > max_alloc => Err(ReadError::AllocationLimitExceeded {
+                requested: bytes,
+                max_alloc,
+                offset: self.total_bytes_read,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Enters one nesting level, or fails if that would exceed `max_depth`.
+    #[inline]
+    fn enter_level(&mut self) -> Result<()> {
+        if let Some(max_depth) = self.max_depth {
+            if self.depth >= max_depth {
+                return Err(ReadError::DepthLimitExceeded {
+                    max_depth,
+                    offset: self.total_bytes_read,
+                });
+            }
+            self.depth += 1;
+        }
+        Ok(())
+    }
+
+    /// Leaves the level entered by `enter_level`.
+    #[inline]
+    fn exit_level(&mut self) {
+        if self.max_depth.is_some() {
+            self.depth = self.depth.saturating_sub(1);
         }
     }
 }
 
+/// Defines `read_deinterleavedN`. A failing row is reported as `ReadError::InElement`; the columns
+/// then hold the rows before it, and possibly some fields of the failing one.
+macro_rules! read_deinterleaved_fns {
+    ( $( $(#[$meta:meta])* fn $name:ident($($col:ident: $C:ident),+); )* ) => {
+        $(
+            $(#[$meta])*
+            pub fn $name<$($C: Read),+>(&mut self, rows: usize, $($col: &mut Vec<$C>),+) -> Result<()> {
+                $($col.reserve(rows);)+
+                for index in 0..rows {
+                    let offset = self.total_bytes_read;
+                    (|| {
+                        $($col.push(self.read()?);)+
+                        Ok(())
+                    })()
+                    .map_err(|e| ReadError::InElement { index, offset, source: Box::new(e) })?;
+                }
+                Ok(())
+            }
+        )*
+    };
+}
+
 impl<E: Endian, R: BufRead> BinaryReader<E, R> {
+    /// Marks the stream as desynchronized: a failed read may have consumed part of a value.
+    fn poison(&mut self, error: std::io::Error) -> ReadError {
+        self.poisoned_at = Some(self.total_bytes_read);
+        self.pending = Pending::EMPTY;
+        ReadError::io_error(error)
+    }
+
+    /// Returns the stream as reads see it, starting with any lookahead a `peek` staged.
+    fn source(&mut self) -> Staged<'_, R> {
+        Staged {
+            pending: &mut self.pending,
+            reader: &mut self.reader,
+        }
+    }
+
+    /// Fills `buf` for a fixed-size read, resuming a read of the same size that stopped on
+    /// `WouldBlock` before.
+    ///
+    /// If the source reports `WouldBlock` after part of the value was consumed, those bytes are
+    /// kept and the reader is poisoned at the value's offset, so only a retry of the same read
+    /// gets past it. With nothing consumed the reader is left as it was.
+    fn fill_resumable(&mut self, buf: &mut [u8]) -> Result<()> {
+        let mut filled = 0;
+        if self.pending.len > 0 && self.pending.want == buf.len() && self.poisoned_at == Some(self.total_bytes_read) {
+            filled = self.pending.len;
+            buf[..filled].copy_from_slice(&self.pending.bytes[..filled]);
+            self.poisoned_at = None;
+            self.pending = Pending::EMPTY;
+        }
+        self.check_size(buf.len())?;
+        while filled < buf.len() {
+            match std::io::Read::read(&mut self.source(), &mut buf[filled..]) {
+                Ok(0) => return Err(self.poison(std::io::ErrorKind::UnexpectedEof.into())),
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock && buf.len() <= self.pending.bytes.len() => {
+                    let offset = self.total_bytes_read;
+                    if filled > 0 {
+                        self.pending.bytes[..filled].copy_from_slice(&buf[..filled]);
+                        (self.pending.len, self.pending.want) = (filled, buf.len());
+                        self.poisoned_at = Some(offset);
+                    }
+                    return Err(ReadError::WouldBlock { offset, buffered: filled });
+                }
+                Err(e) => return Err(self.poison(e)),
+            }
+        }
+        self.total_bytes_read += buf.len() as u64;
+        Ok(())
+    }
+
+    /// Fails, without reading anything, unless `len` more bytes may be read before the limit.
+    ///
+    /// Fails with `ReadError::NotEnoughBytes` past the limit and `ReadError::Desynchronized` on
+    /// a poisoned reader, exactly like a read of `len` bytes would. Lets a multi-part read bail
+    /// out before consuming its first part.
+    ///
+    /// Part of the stable extension surface, see the crate documentation.
+    #[inline]
+    pub fn ensure(&self, len: usize) -> Result<()> {
+        self.check_size(len)
+    }
+
     /// Checks if `len` bytes can be read without exceeding the limit.
     #[inline]
     fn check_size(&self, len: usize) -> Result<()> {
+        self.check_len(len as u64)
+    }
+
+    /// Like `check_size`, for lengths that need not fit in memory.
+    #[inline]
+    fn check_len(&self, len: u64) -> Result<()> {
+        if let Some(at_offset) = self.poisoned_at {
+            return Err(ReadError::Desynchronized { at_offset });
+        }
         if let Some(limit_bytes) = self.limit_bytes {
-            if self.total_bytes_read + len > limit_bytes {
-                return Err(ReadError::not_enough_bytes(len, limit_bytes - self.total_bytes_read));
+            if self.total_bytes_read > limit_bytes {
+                // A nested `read_partial` declared more bytes than the enclosing limit allowed.
+                return Err(ReadError::PositionBeyondLimit {
+                    position: self.total_bytes_read,
+                    limit: limit_bytes,
+                });
+            }
+            if self.total_bytes_read.saturating_add(len) > limit_bytes {
+                return Err(ReadError::not_enough_bytes(usize::try_from(len).unwrap_or(usize::MAX), limit_bytes, self.total_bytes_read));
             }
         }
         Ok(())
     }
 
+    /// The error for a copy that failed: once part of it was consumed, the reader is poisoned.
+    fn copy_error(&mut self, error: std::io::Error, consumed: bool) -> ReadError {
+        if consumed { self.poison(error) } else { ReadError::io_error(error) }
+    }
+
     /// Reads `len` bytes, applies `parse`, and returns the parsed value.
+    ///
+    /// `parse` gets a detached copy of the bytes and is called once; it cannot reach the reader,
+    /// which is borrowed for the whole call. If it panics, the bytes are already consumed, so the
+    /// reader is poisoned at the start of the slice before the panic continues, and later reads
+    /// fail with `ReadError::Desynchronized` until `clear_poison`.
+    ///
+    /// Part of the stable extension surface, see the crate documentation.
     #[inline]
-    pub fn read_from_slice<T>(&mut self, len: usize, parse: impl Fn(&[u8]) -> Result<T>) -> Result<T> {
+    pub fn read_from_slice<T>(&mut self, len: usize, parse: impl FnOnce(&[u8]) -> Result<T>) -> Result<T> {
         self.check_size(len)?;
-        const POPULAR_BUF_SIZE: usize = 512;
-        if len <= POPULAR_BUF_SIZE {
-            let mut buf = [0u8; POPULAR_BUF_SIZE];
-            self.reader.read_exact(&mut buf[..len]).map_err(ReadError::io_error)?;
-            self.total_bytes_read += len;
-            parse(&buf[..len])
-        } else {
+        if len > self.small_buf_size {
+            self.check_alloc(len)?;
             let mut buf = vec![0u8; len];
-            self.reader.read_exact(&mut buf).map_err(ReadError::io_error)?;
-            self.total_bytes_read += len;
-            parse(&buf)
+            self.fill_checked(&mut buf)?;
+            parse_or_poison(&mut self.poisoned_at, self.total_bytes_read - len as u64, &buf, parse)
+        } else if len <= DEFAULT_SMALL_BUF_SIZE {
+            self.read_from_stack::<DEFAULT_SMALL_BUF_SIZE, T>(len, parse)
+        } else if len <= 4096 {
+            self.read_from_stack::<4096, T>(len, parse)
+        } else {
+            self.read_from_stack::<MAX_SMALL_BUF_SIZE, T>(len, parse)
         }
     }
 
+    /// Reads `len <= N` bytes into a stack buffer of `N` bytes and applies `parse`.
+    ///
+    /// Kept out of line so the buffer only takes up stack space while such a read runs.
+    #[inline(never)]
+    fn read_from_stack<const N: usize, T>(&mut self, len: usize, parse: impl FnOnce(&[u8]) -> Result<T>) -> Result<T> {
+        let mut buf = [0u8; N];
+        self.fill_checked(&mut buf[..len])?;
+        parse_or_poison(&mut self.poisoned_at, self.total_bytes_read - len as u64, &buf[..len], parse)
+    }
+
+    /// Fills `buf` from the stream, already checked against the limit, and advances the position.
+    #[inline]
+    fn fill_checked(&mut self, buf: &mut [u8]) -> Result<()> {
+        std::io::Read::read_exact(&mut self.source(), buf).map_err(|e| self.poison(e))?;
+        self.total_bytes_read += buf.len() as u64;
+        record_stats!(self, Bulk, buf.len());
+        Ok(())
+    }
+
     /// Reads exactly `N` bytes into an array and applies `parse`.
-    pub fn read_from_array<T, const N: usize>(&mut self, parse: impl Fn(&[u8; N]) -> T) -> Result<T> {
-        self.check_size(N)?;
+    ///
+    /// This and the primitive reads built on it can be resumed over non-blocking sources: if the
+    /// source reports `WouldBlock` midway, the call fails with `ReadError::WouldBlock` and keeps
+    /// the bytes it got, and calling it again once data is available completes the value. Until
+    /// then every other read fails with `ReadError::Desynchronized`. Other reads are not
+    /// resumable and poison the reader on `WouldBlock` like on any other I/O error.
+    ///
+    /// Part of the stable extension surface, see the crate documentation.
+    pub fn read_from_array<T, const N: usize>(&mut self, parse: impl FnOnce(&[u8; N]) -> T) -> Result<T> {
         let mut buf = [0u8; N];
-        self.reader.read_exact(&mut buf).map_err(ReadError::io_error)?;
-        self.total_bytes_read += N;
+        self.fill_resumable(&mut buf)?;
+        record_stats!(self, Primitive, N);
         Ok(parse(&buf))
     }
 
@@ -108,16 +648,179 @@ impl<E: Endian, R: BufRead> BinaryReader<E, R> {
         let mut buf = [0u8; N];
         loop {
             self.check_size(N)?;
-            self.reader.read_exact(&mut buf).map_err(ReadError::io_error)?;
-            self.total_bytes_read += N;
+            std::io::Read::read_exact(&mut self.source(), &mut buf).map_err(|e| self.poison(e))?;
+            self.total_bytes_read += N as u64;
             match try_parse(&buf) {
+                Some(_) if N == 0 => return Err(ReadError::no_progress()),
                 Some(v) => values.push(v),
                 None => break,
             }
         }
+        record_stats!(self, Bulk, (values.len() + 1) * N);
+        Ok(values)
+    }
+
+    /// Reads values of `T` until one equals `terminator`, which is consumed but not returned.
+    ///
+    /// Returns `ReadError::InvalidDataFormat` if a value other than the terminator takes up no bytes.
+    pub fn read_until<T: Read + PartialEq>(&mut self, terminator: &T) -> Result<Vec<T>> {
+        let mut values = Vec::new();
+        loop {
+            let start = self.total_bytes_read;
+            let value = T::read(self)?;
+            if value == *terminator {
+                return Ok(values);
+            }
+            if self.total_bytes_read == start {
+                return Err(ReadError::no_progress());
+            }
+            values.push(value);
+        }
+    }
+
+    /// Returns `true` if the limit is reached or the stream has ended.
+    fn at_end(&mut self) -> Result<bool> {
+        if self.remaining() == Some(0) {
+            return Ok(true);
+        }
+        retry::retry(|| self.source().fill_buf().map(|buf| buf.is_empty())).map_err(|e| self.poison(e))
+    }
+
+    /// Reads a `T` unless nothing is left before the limit or, without one, the end of the stream.
+    ///
+    /// Meant for fields that newer versions of a format append to a record: inside `read_partial`
+    /// over the record length, an older, shorter record yields `None`. A tail that is present but
+    /// cut short fails as usual.
+    pub fn read_if_remaining<T: Read>(&mut self) -> Result<Option<T>> {
+        self.check_size(0)?;
+        if self.at_end()? {
+            return Ok(None);
+        }
+        self.read().map(Some)
+    }
+
+    /// Like `read_if_remaining`, returning `T::default()` for an absent tail.
+    pub fn read_remaining_or_default<T: Read + Default>(&mut self) -> Result<T> {
+        self.read_if_remaining().map(Option::unwrap_or_default)
+    }
+
+    /// Reads a `T` from the data the source has buffered without consuming it, so the next read
+    /// starts at the same position, e.g. to look at a tag before choosing how to parse a record.
+    ///
+    /// A `BufRead` cannot be refilled without consuming what it holds, so a `T` of up to 16 bytes
+    /// that runs past the buffered bytes is staged in the reader: they are taken out of the
+    /// buffer along with enough of the next refill, and the following reads are served from them
+    /// first. Larger values fail with `ReadError::PeekBeyondBuffer` if they run past the buffer,
+    /// even if the stream goes on; give a `BufReader` at least the capacity of the largest peek,
+    /// see `new_le_with_capacity`. Past the limit or the end of the stream it fails like the read
+    /// would, but leaves the reader usable.
+    pub fn peek<T: Read>(&mut self) -> Result<T> {
+        self.check_size(0)?;
+        let offset = self.total_bytes_read;
+        let remaining = self.remaining();
+        let settings = (self.max_depth, self.depth, self.max_alloc, self.strict_strings, self.small_buf_size);
+        let peek_in = |window: &[u8]| {
+            let mut peeker = BinaryReader::<E, &[u8]>::from_bytes(window);
+            peeker.total_bytes_read = offset;
+            peeker.limit_bytes = Some(offset + window.len() as u64);
+            (peeker.max_depth, peeker.depth, peeker.max_alloc, peeker.strict_strings, peeker.small_buf_size) = settings;
+            T::read(&mut peeker)
+        };
+        match retry::retry(|| self.source().fill_buf().map(drop)) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => return Err(ReadError::WouldBlock { offset, buffered: 0 }),
+            Err(e) => return Err(self.poison(e)),
+        }
+        let buf = match self.source().into_buf() {
+            Ok(buf) => buf,
+            Err(e) => return Err(self.poison(e)),
+        };
+        let (window, at_limit) = peek_window(buf, remaining);
+        match peek_in(window).map_err(|e| peek_error(e, offset, window.len(), at_limit)) {
+            Err(ReadError::PeekBeyondBuffer { buffered, .. }) if buffered < Pending::CAPACITY => {}
+            result => return result,
+        }
+        // The value straddles the buffer: stage it and read on into the next refill.
+        let max = remaining.map_or(usize::MAX, |remaining| usize::try_from(remaining).unwrap_or(usize::MAX));
+        let mut ended = false;
+        while !ended && !self.pending.spare(max).is_empty() {
+            match std::io::Read::read(&mut self.reader, self.pending.spare(max)) {
+                Ok(0) => ended = true,
+                Ok(n) => self.pending.grow(n),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    return Err(ReadError::WouldBlock { offset, buffered: self.pending.len });
+                }
+                Err(e) => return Err(self.poison(e)),
+            }
+        }
+        let (window, at_limit) = peek_window(self.pending.lookahead(), remaining);
+        peek_in(window).map_err(|e| match e {
+            ReadError::NotEnoughBytes { limit, .. } if ended && limit == offset + window.len() as u64 => ReadError::io_error(std::io::ErrorKind::UnexpectedEof.into()),
+            e => peek_error(e, offset, window.len(), at_limit),
+        })
+    }
+
+    /// Returns the next unsigned 8‑bit integer without consuming it, see `peek`.
+    #[inline]
+    pub fn peek_u8(&mut self) -> Result<u8> {
+        self.peek()
+    }
+
+    /// Returns the next unsigned 16‑bit integer without consuming it, see `peek`.
+    #[inline]
+    pub fn peek_u16(&mut self) -> Result<u16> {
+        self.peek()
+    }
+
+    /// Returns the next unsigned 32‑bit integer without consuming it, see `peek`.
+    #[inline]
+    pub fn peek_u32(&mut self) -> Result<u32> {
+        self.peek()
+    }
+
+    /// Reads values of `T` until the limit or the end of the stream.
+    ///
+    /// A value cut off by the end fails with the usual error. Returns `ReadError::InvalidDataFormat`
+    /// if a value takes up no bytes, since the end would never be reached.
+    pub fn read_all<T: Read>(&mut self) -> Result<Vec<T>> {
+        let mut values = Vec::new();
+        while let Some((_, value)) = self.read_next_indexed()? {
+            values.push(value);
+        }
         Ok(values)
     }
 
+    /// Returns an iterator over the values of `T` up to the limit or the end of the stream, each
+    /// with the position it started at.
+    ///
+    /// Meant for building an index of variable-length records while reading them. Ends like
+    /// `read_all`: cleanly at the end, or after yielding the first error.
+    pub fn read_indexed_iter<T: Read>(&mut self) -> impl Iterator<Item = Result<(u64, T)>> + '_ {
+        let mut done = false;
+        std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            let next = self.read_next_indexed().transpose();
+            done = !matches!(next, Some(Ok(_)));
+            next
+        })
+    }
+
+    /// Reads the next value for `read_all`, or `None` at the end.
+    fn read_next_indexed<T: Read>(&mut self) -> Result<Option<(u64, T)>> {
+        if self.at_end()? {
+            return Ok(None);
+        }
+        let start = self.total_bytes_read;
+        let value = T::read(self)?;
+        if self.total_bytes_read == start {
+            return Err(ReadError::no_progress());
+        }
+        Ok(Some((start, value)))
+    }
+
     /// Reads an unsigned 8‑bit integer.
     #[inline]
     pub fn u8(&mut self) -> Result<u8> {
@@ -178,32 +881,144 @@ impl<E: Endian, R: BufRead> BinaryReader<E, R> {
         self.read_from_array(E::f64_from_bytes)
     }
 
+    /// Reads an unsigned 128‑bit integer according to the configured endian.
+    #[inline]
+    pub fn u128(&mut self) -> Result<u128> {
+        self.read_from_array(E::u128_from_bytes)
+    }
+
+    /// Reads a signed 128‑bit integer according to the configured endian.
+    #[inline]
+    pub fn i128(&mut self) -> Result<i128> {
+        self.read_from_array(E::i128_from_bytes)
+    }
+
+    /// Reads a `u16` as a `usize`.
+    #[inline]
+    pub fn usize_from_u16(&mut self) -> Result<usize> {
+        self.u16().map(usize::from)
+    }
+
+    /// Reads a `u32` as a `usize`, e.g. a length to allocate or index with. Fails with
+    /// `ReadError::InvalidDataFormat` on targets where it does not fit.
+    #[inline]
+    pub fn usize_from_u32(&mut self) -> Result<usize> {
+        let offset = self.total_bytes_read;
+        widen(self.u32()?, offset)
+    }
+
+    /// Reads a `u64` as a `usize`, failing with `ReadError::InvalidDataFormat` if it does not fit.
+    #[inline]
+    pub fn usize_from_u64(&mut self) -> Result<usize> {
+        let offset = self.total_bytes_read;
+        widen(self.u64()?, offset)
+    }
+
+    /// Reads an unsigned integer of the given `width` according to the configured endian.
+    #[inline]
+    pub fn read_uint(&mut self, width: IntWidth) -> Result<u64> {
+        match width {
+            IntWidth::U8 => self.u8().map(u64::from),
+            IntWidth::U16 => self.u16().map(u64::from),
+            IntWidth::U32 => self.u32().map(u64::from),
+            IntWidth::U64 => self.u64(),
+        }
+    }
+
+    /// Reads a numeric value of type `T` according to the configured endian.
+    ///
+    /// Part of the stable extension surface, see the crate documentation.
+    #[inline]
+    pub fn read_num<T: Primitive>(&mut self) -> Result<T> {
+        self.read_num_as::<E, T>()
+    }
+
+    /// Reads a numeric value of type `T` in the byte order `B`, whatever the reader's own, e.g.
+    /// a big-endian field inside a little-endian format.
+    ///
+    /// Part of the stable extension surface, see the crate documentation.
+    #[inline]
+    pub fn read_num_as<B: Endian, T: Primitive>(&mut self) -> Result<T> {
+        let mut bytes = T::Bytes::default();
+        let buf = bytes.as_mut();
+        self.fill_resumable(buf)?;
+        record_stats!(self, Primitive, buf.len());
+        Ok(T::from_bytes::<B>(bytes))
+    }
+
     /// Reads any value implementing the `Read` trait.
     #[inline]
     pub fn read<T: Read>(&mut self) -> Result<T> {
-        T::read(self)
+        self.enter_level()?;
+        #[cfg(feature = "debug-tools")]
+        crate::dump::enter(self.total_bytes_read, std::any::type_name::<T>());
+        let result = T::read(self);
+        #[cfg(feature = "debug-tools")]
+        crate::dump::exit(self.total_bytes_read);
+        self.exit_level();
+        result
+    }
+
+    read_deinterleaved_fns! {
+        /// Reads `rows` rows of `(A, B)` and appends their fields to `a` and `b`.
+        fn read_deinterleaved2(a: A, b: B);
+        /// Reads `rows` rows of `(A, B, C)` and appends their fields to `a`, `b` and `c`, e.g. to
+        /// keep the x, y and z of a vertex table in separate `Vec`s.
+        fn read_deinterleaved3(a: A, b: B, c: C);
+        /// Reads `rows` rows of `(A, B, C, D)` and appends their fields to `a`, `b`, `c` and `d`.
+        fn read_deinterleaved4(a: A, b: B, c: C, d: D);
     }
 
     /// Reads any value implementing the `ReadWith` trait with an argument.
     #[inline]
     pub fn read_with<T: ReadWith<U>, U>(&mut self, arg: U) -> Result<T> {
-        T::read_with(self, arg)
+        self.enter_level()?;
+        #[cfg(feature = "debug-tools")]
+        crate::dump::enter(self.total_bytes_read, std::any::type_name::<T>());
+        let result = T::read_with(self, arg);
+        #[cfg(feature = "debug-tools")]
+        crate::dump::exit(self.total_bytes_read);
+        self.exit_level();
+        result
+    }
+
+    /// Reads over an existing value in place, letting it reuse the allocations it already owns.
+    #[inline]
+    pub fn read_into<T: ReadInto>(&mut self, target: &mut T) -> Result<()> {
+        target.read_into(self)
+    }
+
+    /// Reads over an existing value in place with an argument, such as an element count.
+    #[inline]
+    pub fn read_into_with<T: ReadIntoWith<U>, U>(&mut self, target: &mut T, arg: U) -> Result<()> {
+        target.read_into_with(self, arg)
     }
 
     /// Verifies the next value equals `value`.
+    ///
+    /// A mismatch is reported as `ReadError::UnexpectedValue` with both values and the offset read from.
     #[inline]
     pub fn value<T: PartialEq + Read + Debug>(&mut self, value: &T) -> Result<()> {
-        if self.read::<T>()? != *value {
-            return Err(ReadError::invalid_data_format(format!("Expected value to be {:?}, but got something else", value)));
+        let offset = self.total_bytes_read;
+        let found = self.read::<T>()?;
+        if found != *value {
+            return Err(ReadError::unexpected_value(value, &found, offset));
         }
         Ok(())
     }
 
     /// Verifies the next sequence equals `values`.
-    #[inline]
-    pub fn values<T: PartialEq + Read + Debug>(&mut self, values: &[T]) -> Result<()> {
-        for value in values {
-            self.value(value)?;
+    ///
+    /// A run of `u8` or `i8` is read whole and a mismatch is reported as `ReadError::ExpectedMismatch`,
+    /// like `magic`. For other types the first mismatching element is reported as
+    /// `ReadError::InElement` around the `ReadError::UnexpectedValue`.
+    pub fn values<T: PartialEq + Read + Debug + 'static>(&mut self, values: &[T]) -> Result<()> {
+        if let Some(expected) = byte_run(values) {
+            return self.magic(expected);
+        }
+        for (index, value) in values.iter().enumerate() {
+            let offset = self.total_bytes_read;
+            self.value(value).map_err(|e| mismatch_in_element(e, index, offset))?;
         }
         Ok(())
     }
@@ -212,47 +1027,347 @@ impl<E: Endian, R: BufRead> BinaryReader<E, R> {
     #[inline]
     pub fn reserved<const N: usize>(&mut self, expected_value: u8) -> Result<()> {
         self.read_from_array(|data: &[u8; N]| {
-            for &byte in data.iter() {
-                if byte != expected_value {
-                    return Err(ReadError::invalid_data_format(
-                        format!("Expected reserved byte to be 0x{:02X}, but got 0x{:02X}", expected_value, byte,),
-                    ));
-                }
+            if data.iter().any(|&byte| byte != expected_value) {
+                return Err(ReadError::expected_mismatch(&[expected_value; N], data));
             }
             Ok(())
         })?
     }
 
-    /// Reads a sub-structure of length `len`.
+    /// Reads `N` reserved bytes and returns every `(relative offset, actual byte)` that differs from
+    /// `expected_value`, instead of failing on the first one like `reserved`.
+    #[inline]
+    pub fn reserved_report<const N: usize>(&mut self, expected_value: u8) -> Result<Vec<(usize, u8)>> {
+        self.reserved_report_len(N, expected_value)
+    }
+
+    /// Like `reserved_report`, for a region whose length is only known at runtime.
+    pub fn reserved_report_len(&mut self, len: usize, expected_value: u8) -> Result<Vec<(usize, u8)>> {
+        self.read_from_slice(len, |data| Ok(reserved_violations(data, expected_value)))
+    }
+
+    /// Verifies the next `T::SIZE` bytes all equal `expected_value`, for reserved regions sized as a whole type.
+    #[inline]
+    pub fn reserved_for<T: FixedSize>(&mut self, expected_value: u8) -> Result<()> {
+        self.read_from_slice(T::SIZE, |data| {
+            if data.iter().any(|&byte| byte != expected_value) {
+                return Err(ReadError::expected_mismatch(&vec![expected_value; data.len()], data));
+            }
+            Ok(())
+        })
+    }
+
+    /// Verifies the next bytes equal the signature `expected`.
+    #[inline]
+    pub fn magic(&mut self, expected: impl AsRef<[u8]>) -> Result<()> {
+        let expected = expected.as_ref();
+        self.read_from_slice(expected.len(), |data| {
+            if data != expected {
+                return Err(ReadError::expected_mismatch(expected, data));
+            }
+            Ok(())
+        })
+    }
+
+    /// Reads a `Version` and checks that it is one of the `supported` versions, e.g.
+    /// `reader.version(Version::new(1, 0)..=Version::new(1, 4))`.
+    ///
+    /// Fails with `ReadError::UnsupportedVersion`, naming both, if it is not.
+    pub fn version(&mut self, supported: impl Into<VersionRange>) -> Result<Version> {
+        let (supported, offset) = (supported.into(), self.total_bytes_read);
+        let found = self.read::<Version>()?;
+        if !supported.contains(found) {
+            return Err(ReadError::UnsupportedVersion { found, supported, offset });
+        }
+        Ok(found)
+    }
+
+    /// Verifies a file-level checksum stored as a `u32` in the last `kind.digest_len()` bytes
+    /// before the limit, as written by `BinaryWriter::finish_with_checksum`.
+    ///
+    /// Hashes everything from the current position up to the digest, so call it at offset 0 to
+    /// cover the whole file, then reads the digest and compares. Leaves the reader at the limit on
+    /// success and fails with `ReadError::ChecksumMismatch` otherwise. The reader needs a limit to
+    /// know where the stream ends, such as a slice-backed reader or a `by_ref_take` view, and
+    /// fails with `ReadError::InvalidArgument` without one.
+    pub fn verify_trailing_checksum(&mut self, kind: ChecksumKind) -> Result<()> {
+        let remaining = self.remaining().ok_or(ReadError::InvalidArgument)?;
+        let mut hasher = ChecksumWriter::new(std::io::sink(), kind);
+        self.copy_to(&mut hasher, remaining.saturating_sub(kind.digest_len() as u64))?;
+        let offset = self.total_bytes_read;
+        let (stored, computed) = (self.u32()?, hasher.checksum().value());
+        if stored != computed {
+            return Err(ReadError::ChecksumMismatch { kind, stored, computed, offset });
+        }
+        Ok(())
+    }
+
+    /// Reads a tag, then the body of the tagged union variant it selects in `table`.
+    ///
+    /// The parse functions get this reader with its stream type erased, at the same position and
+    /// under the same limit.
+    pub fn read_tagged<Tag: Read + Eq + Hash + Debug, T>(&mut self, table: &crate::tagged::TagTable<E, Tag, T>) -> Result<T> {
+        let tag = self.read::<Tag>()?;
+        with_endian!(self, BinaryReader { small_buf_size, pending }, E as crate::tagged::DynReader<'_, E>, |view| table
+            .dispatch(tag, &mut view))
+    }
+
+    /// Reads a sub-structure of length `len`.
     #[inline]
     pub fn read_partial<T: Read>(&mut self, len: usize) -> Result<T> {
+        self.enter_level()?;
         let original_limit = self.limit_bytes;
-        self.limit_bytes = Some(self.total_bytes_read + len);
-        let result = T::read(self)?;
+        self.limit_bytes = Some(self.total_bytes_read + len as u64);
+        let result = T::read(self);
         self.limit_bytes = original_limit;
-        Ok(result)
+        self.exit_level();
+        result
     }
 
     /// Reads a sub-structure with argument `arg` and length `len`.
     #[inline]
     pub fn read_partial_with<U, T: ReadWith<U>>(&mut self, len: usize, arg: U) -> Result<T> {
+        self.enter_level()?;
         let original_limit = self.limit_bytes;
-        self.limit_bytes = Some(self.total_bytes_read + len);
-        let result = T::read_with(self, arg)?;
+        self.limit_bytes = Some(self.total_bytes_read + len as u64);
+        let result = T::read_with(self, arg);
         self.limit_bytes = original_limit;
+        self.exit_level();
+        result
+    }
+
+    /// Returns a sub-reader that can read at most `len` more bytes, enforced by `std::io::Take`
+    /// underneath as well as by the logical limit.
+    ///
+    /// The sub-reader derefs to a plain `BinaryReader`, so it can be handed to code expecting
+    /// `&mut BinaryReader<E, impl BufRead>`. This reader's position catches up when it is dropped.
+    pub fn by_ref_take(&mut self, len: u64) -> TakeReader<'_, E, R> {
+        let end = self.total_bytes_read.saturating_add(len);
+        TakeReader {
+            inner: BinaryReader {
+                // staged lookahead is served first and counts toward `len`
+                reader: std::io::Read::take(&mut self.reader, len.saturating_sub(self.pending.lookahead().len() as u64)),
+                total_bytes_read: self.total_bytes_read,
+                limit_bytes: Some(self.limit_bytes.map_or(end, |limit| limit.min(end))),
+                poisoned_at: self.poisoned_at,
+                max_depth: self.max_depth,
+                depth: self.depth,
+                max_alloc: self.max_alloc,
+                strict_strings: self.strict_strings,
+                small_buf_size: self.small_buf_size,
+                pending: self.pending,
+                #[cfg(feature = "stats")]
+                stats: self.stats.clone(),
+                _endian: PhantomData,
+            },
+            parent_position: &mut self.total_bytes_read,
+            parent_poisoned_at: &mut self.poisoned_at,
+            parent_pending: &mut self.pending,
+        }
+    }
+
+    /// Lends the underlying stream as a `BufRead` that serves at most `remaining()` bytes.
+    ///
+    /// Whatever the consumer actually reads is added to this reader's position when the adapter
+    /// is dropped, so partially consuming the region and then resuming with rwbin reads works.
+    pub fn as_bufread_limited(&mut self) -> impl BufRead + '_ {
+        LimitedBufRead {
+            left: self.remaining().unwrap_or(u64::MAX),
+            reader: Staged {
+                pending: &mut self.pending,
+                reader: &mut self.reader,
+            },
+            consumed: 0,
+            position: &mut self.total_bytes_read,
+        }
+    }
+
+    /// Reads a `u32` length-prefixed frame and parses its payload as `T`.
+    #[inline]
+    pub fn read_frame<T: Read>(&mut self) -> Result<T> {
+        self.read_frame_limited(DEFAULT_MAX_FRAME_SIZE)
+    }
+
+    /// Reads a `u32` length-prefixed frame of at most `max_size` bytes and parses its payload as `T`.
+    ///
+    /// Fails if `T` does not consume the whole payload.
+    pub fn read_frame_limited<T: Read>(&mut self, max_size: u32) -> Result<T> {
+        let len = self.u32()?;
+        if len > max_size {
+            return Err(ReadError::invalid_data_format(format!("frame of {len} bytes exceeds the maximum of {max_size}")));
+        }
+        self.check_size(len as usize)?;
+        let end = self.total_bytes_read + len as u64;
+        let result = self.read_partial(len as usize)?;
+        if self.total_bytes_read != end {
+            return Err(ReadError::invalid_data_format(format!("frame of {len} bytes left {} bytes unread", end - self.total_bytes_read)));
+        }
+        Ok(result)
+    }
+
+    /// Reads an `L` length followed by a payload parsed as `T`, with `policy` saying whether the
+    /// length includes the length field or a longer header.
+    ///
+    /// Fails with `ReadError::InvalidDataFormat` if the length is smaller than what it includes,
+    /// or if `T` does not consume the whole payload.
+    pub fn read_len_prefixed<L: Read + FixedSize + Into<u64>, T: Read>(&mut self, policy: LenPolicy) -> Result<T> {
+        let len = policy.payload_len(self.read::<L>()?.into(), L::SIZE)?;
+        self.check_size(len)?;
+        let end = self.total_bytes_read + len as u64;
+        let result = self.read_partial(len)?;
+        if self.total_bytes_read != end {
+            return Err(ReadError::invalid_data_format(format!("payload of {len} bytes left {} bytes unread", end - self.total_bytes_read)));
+        }
         Ok(result)
     }
 
     /// Skips `bytes` bytes.
-    #[inline]
+    ///
+    /// Consumes straight out of the reader's buffer, so memory use does not grow with `bytes`.
+    /// Readers over a seekable stream can use `seek_relative` to skip without reading.
     pub fn skip(&mut self, bytes: usize) -> Result<()> {
-        self.read_from_slice(bytes, |_| Ok(()))
+        count_stats!(self, Skip, {
+            self.check_size(bytes)?;
+            let mut left = bytes;
+            while left > 0 {
+                let available = match retry::retry(|| self.source().fill_buf().map(|buf| buf.len())) {
+                    Ok(available) => available,
+                    Err(e) => return Err(self.poison(e)),
+                };
+                if available == 0 {
+                    return Err(self.poison(std::io::ErrorKind::UnexpectedEof.into()));
+                }
+                let chunk = available.min(left);
+                self.source().consume(chunk);
+                left -= chunk;
+            }
+            self.total_bytes_read += bytes as u64;
+            Ok(())
+        })
+    }
+
+    /// Streams the next `len` bytes into `dst` straight from the read buffer, e.g. to extract an
+    /// embedded blob to a file without holding it in memory.
+    ///
+    /// The position advances with every chunk handed to `dst`, so after a failed read or write it
+    /// still counts exactly the bytes that were copied. If any were, the reader is poisoned there.
+    pub fn copy_to(&mut self, dst: &mut impl std::io::Write, len: u64) -> Result<()> {
+        count_stats!(self, Bulk, {
+            self.check_len(len)?;
+            let mut left = len;
+            while left > 0 {
+                let consumed = left < len;
+                let available = retry::retry(|| self.source().fill_buf().map(|buf| buf.len())).map_err(|e| self.copy_error(e, consumed))?;
+                if available == 0 {
+                    return Err(self.copy_error(std::io::ErrorKind::UnexpectedEof.into(), consumed));
+                }
+                let chunk = (available as u64).min(left) as usize;
+                let buf = match self.source().into_buf() {
+                    Ok(buf) => &buf[..chunk],
+                    Err(e) => return Err(self.copy_error(e, consumed)),
+                };
+                if let Err(e) = dst.write_all(buf) {
+                    return Err(self.copy_error(e, consumed));
+                }
+                self.source().consume(chunk);
+                self.total_bytes_read += chunk as u64;
+                left -= chunk as u64;
+            }
+            Ok(())
+        })
+    }
+
+    /// Skips forward to the absolute position `pos`.
+    ///
+    /// Returns `ReadError::InvalidArgument` if `pos` lies behind the current position.
+    pub fn skip_to(&mut self, pos: u64) -> Result<()> {
+        if pos < self.total_bytes_read {
+            return Err(ReadError::InvalidArgument);
+        }
+        count_stats!(self, Skip, {
+            let mut left = pos - self.total_bytes_read;
+            while left > 0 {
+                let chunk = left.min(usize::MAX as u64) as usize;
+                self.skip(chunk)?;
+                left -= chunk as u64;
+            }
+            Ok(())
+        })
+    }
+
+    /// Skips `n` records of the fixed-size type `T` without parsing them.
+    pub fn skip_records<T: FixedSize>(&mut self, n: usize) -> Result<()> {
+        let len = (n as u64).checked_mul(T::SIZE as u64).ok_or(ReadError::InvalidArgument)?;
+        self.skip_to(self.total_bytes_read.checked_add(len).ok_or(ReadError::InvalidArgument)?)
+    }
+
+    /// Reads `N` values of `T` straight into an array, without a `Default` bound or a heap allocation.
+    ///
+    /// If an element fails to read, the elements read before it are dropped.
+    pub fn read_array<T: Read, const N: usize>(&mut self) -> Result<[T; N]> {
+        let mut array = ArrayBuilder::new();
+        for _ in 0..N {
+            array.push(T::read(self)?);
+        }
+        Ok(array.finish())
+    }
+
+    /// Reads `len` values of `T`, passing each to `validate` before the next one is read.
+    ///
+    /// A parse or validation failure stops the read and is reported as `ReadError::InElement`
+    /// with the element's index and the offset it started at.
+    pub fn read_vec_validated<T: Read>(&mut self, len: usize, validate: impl Fn(usize, &T) -> Result<()>) -> Result<Vec<T>> {
+        self.check_alloc(len.saturating_mul(size_of::<T>()))?;
+        let mut values = Vec::with_capacity(len);
+        for index in 0..len {
+            let offset = self.total_bytes_read;
+            let value = T::read(self)
+                .and_then(|value| validate(index, &value).map(|()| value))
+                .map_err(|e| ReadError::InElement { index, offset, source: Box::new(e) })?;
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    /// Reads values written by `write_vec_prefixed` with `PrefixOverflow::Chunked`: chunks of a
+    /// `P` count and that many values, each followed by `continuation_flag` or a final `0`.
+    pub fn read_vec_prefixed_chunked<P, T>(&mut self, continuation_flag: u8) -> Result<Vec<T>>
+    where
+        P: Read + TryInto<usize>,
+        T: Read,
+    {
+        let mut values = Vec::new();
+        loop {
+            let count = self.read::<P>()?.try_into().map_err(|_| ReadError::invalid_data_format("chunk count does not fit in usize"))?;
+            for _ in 0..count {
+                values.push(T::read(self)?);
+            }
+            match self.u8()? {
+                0 => return Ok(values),
+                flag if flag == continuation_flag => continue,
+                flag => return Err(ReadError::invalid_data_format(format!("unexpected chunk flag {flag:#04x}"))),
+            }
+        }
+    }
+
+    /// Reads `n` records of the fixed-size type `T`, failing before consuming anything if the
+    /// limit cannot hold all of them.
+    pub fn read_records<T: Read + FixedSize>(&mut self, n: usize) -> Result<Vec<T>> {
+        let len = n.checked_mul(T::SIZE).ok_or(ReadError::InvalidArgument)?;
+        self.check_size(len)?;
+        count_stats!(self, Bulk, { self.read_with(n) })
     }
 
     /// Skips up to alignment boundary by padding.
+    ///
+    /// Returns `ReadError::InvalidArgument` if `align` is zero. Alignments that are not
+    /// a power of two are accepted and treated as plain multiples.
     #[inline]
     pub fn skip_aligned(&mut self, align: usize) -> Result<()> {
-        let offset = self.total_bytes_read % align;
+        if align == 0 {
+            return Err(ReadError::InvalidArgument);
+        }
+        let offset = (self.total_bytes_read % align as u64) as usize;
         if offset != 0 {
             self.skip(align - offset)?;
         }
@@ -260,230 +1375,1177 @@ impl<E: Endian, R: BufRead> BinaryReader<E, R> {
     }
 }
 
-pub trait Read {
-    fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self>
-    where
-        Self: Sized;
-}
-
-pub trait ReadWith<A> {
-    fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, arg: A) -> Result<Self>
-    where
-        Self: Sized;
-}
-
-impl<R: BufRead> BinaryReader<LittleEndian, R> {
-    pub fn read_as_be<T: Read>(&mut self) -> Result<T> {
-        let mut be_reader = BinaryReader {
-            reader: &mut self.reader,
-            total_bytes_read: self.total_bytes_read,
-            limit_bytes: self.limit_bytes,
-            _endian: PhantomData::<fn() -> BigEndian>,
-        };
-        let v = T::read(&mut be_reader)?;
-        self.total_bytes_read = be_reader.total_bytes_read;
-        Ok(v)
+impl<E: Endian, R: BufRead + Seek> BinaryReader<E, R> {
+    /// Seeks to record `index` of a table of fixed-size `T` records starting at position `base`.
+    ///
+    /// Positions are the ones reported by `position`, so a rebased reader seeks relative to its
+    /// base offset. A successful seek puts the reader back in step with the stream, clearing any poison.
+    pub fn seek_record<T: FixedSize>(&mut self, base: u64, index: u64) -> Result<()> {
+        let target = index.checked_mul(T::SIZE as u64).and_then(|offset| offset.checked_add(base)).ok_or(ReadError::InvalidArgument)?;
+        self.seek_to(target)
     }
-    pub fn read_as_be_with<T: ReadWith<U>, U>(&mut self, arg: U) -> Result<T> {
-        let mut be_reader = BinaryReader {
-            reader: &mut self.reader,
-            total_bytes_read: self.total_bytes_read,
-            limit_bytes: self.limit_bytes,
-            _endian: PhantomData::<fn() -> BigEndian>,
-        };
-        let v = T::read_with(&mut be_reader, arg)?;
-        self.total_bytes_read = be_reader.total_bytes_read;
-        Ok(v)
+
+    /// Seeks to `target`, a position as reported by `position`, clearing any poison.
+    ///
+    /// Fails with `ReadError::PositionBeyondLimit` if `target` lies past the active limit.
+    pub fn seek_to(&mut self, target: u64) -> Result<()> {
+        if let Some(limit) = self.limit_bytes
+            && target > limit
+        {
+            return Err(ReadError::PositionBeyondLimit { position: target, limit });
+        }
+        // the stream is ahead of the position by whatever a peek staged
+        let stream_pos = i128::from(self.total_bytes_read) + self.pending.lookahead().len() as i128;
+        let delta = i64::try_from(i128::from(target) - stream_pos).map_err(|_| ReadError::InvalidArgument)?;
+        self.reader.seek(SeekFrom::Current(delta)).map_err(|e| self.poison(e))?;
+        self.total_bytes_read = target;
+        self.poisoned_at = None;
+        self.pending = Pending::EMPTY;
+        Ok(())
     }
-}
 
-impl<R: BufRead> BinaryReader<BigEndian, R> {
-    pub fn read_as_le<T: Read>(&mut self) -> Result<T> {
-        let mut le_reader = BinaryReader {
-            reader: &mut self.reader,
-            total_bytes_read: self.total_bytes_read,
-            limit_bytes: self.limit_bytes,
-            _endian: PhantomData::<fn() -> LittleEndian>,
-        };
-        let v = T::read(&mut le_reader)?;
-        self.total_bytes_read = le_reader.total_bytes_read;
-        Ok(v)
+    /// Moves `delta` bytes forward or back from the current position, see `seek_to`.
+    ///
+    /// Unlike `skip`, which reads the bytes through the buffer, this seeks the inner stream, so
+    /// passing over large sections costs no I/O. Fails with `ReadError::InvalidArgument` if the
+    /// target position would be negative.
+    pub fn seek_relative(&mut self, delta: i64) -> Result<()> {
+        let target = self.total_bytes_read.checked_add_signed(delta).ok_or(ReadError::InvalidArgument)?;
+        self.seek_to(target)
     }
-    pub fn read_as_le_with<T: ReadWith<U>, U>(&mut self, arg: U) -> Result<T> {
-        let mut le_reader: BinaryReader<LittleEndian, &mut R> = BinaryReader {
-            reader: &mut self.reader,
-            total_bytes_read: self.total_bytes_read,
-            limit_bytes: self.limit_bytes,
-            _endian: PhantomData::<fn() -> LittleEndian>,
-        };
-        let v = T::read_with(&mut le_reader, arg)?;
-        self.total_bytes_read = le_reader.total_bytes_read;
-        Ok(v)
+
+    /// Reads a `T` at position `pos` and returns to the current position, e.g. to follow an offset
+    /// stored in a header. The reader is back where it was even if the read fails.
+    pub fn read_at<T: Read>(&mut self, pos: u64) -> Result<T> {
+        let here = self.total_bytes_read;
+        self.seek_to(pos)?;
+        let result = self.read::<T>();
+        self.seek_to(here)?;
+        result
     }
-}
 
-// Read trait implementations for primitive types
-macro_rules! impl_readable_for_numeric_primitives {
-    ( $( $t:ty: $func:ident ),* $(,)? ) => {
-        $(
-            impl Read for $t {
-                #[inline]
-                fn read<E: Endian, R: BufRead>(
-                    reader: &mut BinaryReader<E, R>
-                ) -> Result<Self> {
-                    reader.$func()
+    /// Skips forward to the next occurrence of `pattern` and returns the number of bytes skipped,
+    /// leaving the reader at the first byte of the match.
+    ///
+    /// Meant for resynchronizing on a magic number in a damaged file. At most `max_scan` bytes are
+    /// skipped; `None` scans up to the limit. A match inside one buffer refill is reached by
+    /// consuming only the bytes before it. A match straddling two refills has partly been consumed
+    /// when it is found, so the reader seeks back over it. Without a match, returns
+    /// `ReadError::PatternNotFound` and leaves the reader after the scanned bytes.
+    pub fn scan_for(&mut self, pattern: &[u8], max_scan: Option<usize>) -> Result<u64> {
+        if pattern.is_empty() {
+            return Err(ReadError::InvalidArgument);
+        }
+        self.check_size(0)?;
+        let start = self.total_bytes_read;
+        let mut end = max_scan.map_or(u64::MAX, |max| start.saturating_add(max as u64).saturating_add(pattern.len() as u64));
+        if let Some(limit) = self.limit_bytes {
+            end = end.min(limit);
+        }
+        count_stats!(self, Skip, {
+            let mut scan = PatternScan::new(pattern);
+            loop {
+                let available = retry::retry(|| self.source().fill_buf().map(|buf| buf.len())).map_err(|e| self.poison(e))?;
+                let window = (end - self.total_bytes_read).min(available as u64) as usize;
+                if window == 0 {
+                    return Err(ReadError::PatternNotFound {
+                        offset: start,
+                        scanned: self.total_bytes_read - start,
+                    });
+                }
+                let step = match self.source().fill_buf() {
+                    Ok(buf) => scan.step(&buf[..window]),
+                    Err(e) => return Err(self.poison(e)),
+                };
+                match step {
+                    ScanStep::At(offset) => {
+                        self.source().consume(offset);
+                        self.total_bytes_read += offset as u64;
+                        return Ok(self.total_bytes_read - start);
+                    }
+                    ScanStep::Straddling(back) => {
+                        self.reader.seek(SeekFrom::Current(-(back as i64))).map_err(|e| self.poison(e))?;
+                        self.total_bytes_read -= back as u64;
+                        return Ok(self.total_bytes_read - start);
+                    }
+                    ScanStep::Continue => {
+                        self.source().consume(window);
+                        self.total_bytes_read += window as u64;
+                    }
                 }
             }
-        )*
-    };
+        })
+    }
 }
-impl_readable_for_numeric_primitives! {
-    u8: u8,
-    i8: i8,
-    u16: u16,
-    i16: i16,
-    u32: u32,
-    i32: i32,
-    f32: f32,
-    u64: u64,
-    i64: i64,
-    f64: f64,
+
+/// Converts `value`, read at `offset`, to a `usize`, or fails with `ReadError::InvalidDataFormat`.
+pub(crate) fn widen<T: Copy + Into<u64> + TryInto<usize>>(value: T, offset: u64) -> Result<usize> {
+    value
+        .try_into()
+        .map_err(|_| ReadError::invalid_data_format(format!("value {} at offset {offset:#X} does not fit in usize", value.into())))
 }
 
-impl Read for bool {
-    fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
-        match reader.u8()? {
-            0 => Ok(false),
-            1 => Ok(true),
-            _ => Err(ReadError::invalid_data_format("Expected 0 or 1 for boolean")),
+/// Calls `parse` on bytes already consumed at offset `at`, poisoning the reader at `at` if it panics.
+pub(crate) fn parse_or_poison<T>(poisoned_at: &mut Option<u64>, at: u64, bytes: &[u8], parse: impl FnOnce(&[u8]) -> Result<T>) -> Result<T> {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| parse(bytes))) {
+        Ok(result) => result,
+        Err(payload) => {
+            *poisoned_at = Some(at);
+            std::panic::resume_unwind(payload)
         }
     }
 }
 
-impl Read for char {
-    fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
-        let value = reader.u32()?;
-        std::char::from_u32(value).ok_or_else(|| ReadError::invalid_data_format(format!("Invalid char value: {}", value)))
-    }
+/// Searches for a pattern across successive buffer refills, for `scan_for`.
+pub(crate) struct PatternScan<'p> {
+    pattern: &'p [u8],
+    /// The last consumed bytes that may still begin a match, at most `pattern.len() - 1`.
+    carry: Vec<u8>,
 }
 
-macro_rules! impl_readable_for_tuples {
-    ( $( ( $( $T:ident ),+ ), )+ ) => {
-        $(
-            impl<$($T: Read),+> Read for ( $( $T, )+ ) {
-                fn read<E: Endian, R: BufRead>(
-                    reader: &mut BinaryReader<E, R>
-                ) -> Result<Self> {
-                    Ok(( $( $T::read(reader)?, )+ ))
-                }
-            }
-        )+
-    }
-}
-impl_readable_for_tuples! {
-    (T1, T2),
-    (T1, T2, T3),
-    (T1, T2, T3, T4),
-    (T1, T2, T3, T4, T5),
-    (T1, T2, T3, T4, T5, T6),
+pub(crate) enum ScanStep {
+    /// The pattern starts this many bytes before the chunk, in bytes already consumed.
+    Straddling(usize),
+    /// The pattern starts at this offset within the chunk.
+    At(usize),
+    /// No match yet; the whole chunk may be consumed.
+    Continue,
 }
 
-impl<T: Read> ReadWith<bool> for Option<T> {
-    fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, cond: bool) -> Result<Self> {
-        if cond { Ok(Some(T::read(reader)?)) } else { Ok(None) }
+impl<'p> PatternScan<'p> {
+    pub(crate) fn new(pattern: &'p [u8]) -> Self {
+        Self { pattern, carry: Vec::new() }
     }
-}
 
-impl<T: Read + Default, const N: usize> Read for [T; N] {
-    fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
-        let mut arr = std::array::from_fn(|_| T::default());
-        for e in arr.iter_mut() {
-            *e = T::read(reader)?;
+    /// Looks for the pattern in `chunk`, which directly follows everything passed in before.
+    pub(crate) fn step(&mut self, chunk: &[u8]) -> ScanStep {
+        let len = self.pattern.len();
+        if !self.carry.is_empty() {
+            let mut joined = self.carry.clone();
+            joined.extend_from_slice(&chunk[..chunk.len().min(len - 1)]);
+            if let Some(start) = joined.windows(len).position(|window| window == self.pattern)
+                && start < self.carry.len()
+            {
+                return ScanStep::Straddling(self.carry.len() - start);
+            }
         }
-        Ok(arr)
+        if let Some(start) = chunk.windows(len).position(|window| window == self.pattern) {
+            return ScanStep::At(start);
+        }
+        self.carry.extend_from_slice(chunk);
+        let excess = self.carry.len().saturating_sub(len - 1);
+        self.carry.drain(..excess);
+        ScanStep::Continue
     }
 }
 
-impl<T: Read> ReadWith<usize> for Vec<T> {
-    fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, len: usize) -> Result<Self> {
-        let mut vec = Vec::with_capacity(len);
-        for _ in 0..len {
-            vec.push(T::read(reader)?);
-        }
-        Ok(vec)
+/// Returns `values` as raw bytes if `T` is `u8` or `i8`, so `values` can compare them as one run.
+pub(crate) fn byte_run<T: 'static>(values: &[T]) -> Option<Vec<u8>> {
+    values
+        .iter()
+        .map(|value| {
+            let value = value as &dyn Any;
+            value.downcast_ref::<u8>().copied().or_else(|| value.downcast_ref::<i8>().map(|&byte| byte as u8))
+        })
+        .collect()
+}
+
+/// Wraps an `UnexpectedValue` from element `index` of `values` in `ReadError::InElement`.
+pub(crate) fn mismatch_in_element(error: ReadError, index: usize, offset: u64) -> ReadError {
+    match error {
+        ReadError::UnexpectedValue { .. } => ReadError::InElement {
+            index,
+            offset,
+            source: Box::new(error),
+        },
+        error => error,
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Lists the bytes of a reserved region that differ from `expected_value`, by offset within the region.
+pub(crate) fn reserved_violations(data: &[u8], expected_value: u8) -> Vec<(usize, u8)> {
+    data.iter().enumerate().filter(|&(_, &byte)| byte != expected_value).map(|(offset, &byte)| (offset, byte)).collect()
+}
 
-    #[test]
-    fn test_integers() {
-        let data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+/// A sub-reader returned by `BinaryReader::by_ref_take`.
+pub struct TakeReader<'p, E, R> {
+    inner: BinaryReader<E, std::io::Take<&'p mut R>>,
+    parent_position: &'p mut u64,
+    parent_poisoned_at: &'p mut Option<u64>,
+    parent_pending: &'p mut Pending,
+}
 
-        assert_eq!(BinaryReader::from_le_bytes(&data).u8().unwrap(), 0x01);
-        assert_eq!(BinaryReader::from_le_bytes(&data).u16().unwrap(), 0x0201);
-        assert_eq!(BinaryReader::from_le_bytes(&data).u32().unwrap(), 0x04030201);
-        assert_eq!(BinaryReader::from_le_bytes(&data).u64().unwrap(), 0x0807060504030201);
+impl<'p, E, R> std::ops::Deref for TakeReader<'p, E, R> {
+    type Target = BinaryReader<E, std::io::Take<&'p mut R>>;
 
-        assert_eq!(BinaryReader::from_le_bytes(&data).i8().unwrap(), 0x01);
-        assert_eq!(BinaryReader::from_le_bytes(&data).i16().unwrap(), 0x0201);
-        assert_eq!(BinaryReader::from_le_bytes(&data).i32().unwrap(), 0x04030201);
-        assert_eq!(BinaryReader::from_le_bytes(&data).i64().unwrap(), 0x0807060504030201);
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
 
-        assert_eq!(BinaryReader::from_be_bytes(&data).u8().unwrap(), 0x01);
-        assert_eq!(BinaryReader::from_be_bytes(&data).u16().unwrap(), 0x0102);
-        assert_eq!(BinaryReader::from_be_bytes(&data).u32().unwrap(), 0x01020304);
-        assert_eq!(BinaryReader::from_be_bytes(&data).u64().unwrap(), 0x0102030405060708);
+impl<E, R> std::ops::DerefMut for TakeReader<'_, E, R> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
 
-        assert_eq!(BinaryReader::from_be_bytes(&data).i8().unwrap(), 0x01);
-        assert_eq!(BinaryReader::from_be_bytes(&data).i16().unwrap(), 0x0102);
-        assert_eq!(BinaryReader::from_be_bytes(&data).i32().unwrap(), 0x01020304);
-        assert_eq!(BinaryReader::from_be_bytes(&data).i64().unwrap(), 0x0102030405060708);
+impl<E, R> Drop for TakeReader<'_, E, R> {
+    fn drop(&mut self) {
+        *self.parent_position = self.inner.total_bytes_read;
+        *self.parent_poisoned_at = self.inner.poisoned_at;
+        *self.parent_pending = self.inner.pending;
     }
+}
 
-    #[test]
-    fn test_floats() {
-        let data = [0x00, 0x00, 0x80, 0x3F, 0x00, 0x00, 0x00, 0x40];
-        assert_eq!(BinaryReader::from_le_bytes(&data).f32().unwrap(), 1.0);
+/// The adapter behind `BinaryReader::as_bufread_limited`.
+struct LimitedBufRead<'p, R> {
+    reader: Staged<'p, R>,
+    left: u64,
+    consumed: u64,
+    position: &'p mut u64,
+}
 
-        let data = [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xF0, 0x3F];
-        assert_eq!(BinaryReader::from_le_bytes(&data).f64().unwrap(), 1.0);
+impl<R: BufRead> std::io::Read for LimitedBufRead<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
 
-        let data = [0x3F, 0x80, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00];
-        assert_eq!(BinaryReader::from_be_bytes(&data).f32().unwrap(), 1.0);
+impl<R: BufRead> BufRead for LimitedBufRead<'_, R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if self.left == 0 {
+            return Ok(&[]);
+        }
+        let buf = self.reader.fill_buf()?;
+        let len = (buf.len() as u64).min(self.left) as usize;
+        Ok(&buf[..len])
+    }
 
-        let data = [0x3F, 0xF0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
-        assert_eq!(BinaryReader::from_be_bytes(&data).f64().unwrap(), 1.0);
+    fn consume(&mut self, amt: usize) {
+        let amt = (amt as u64).min(self.left);
+        self.reader.consume(amt as usize);
+        self.left -= amt;
+        self.consumed += amt;
     }
+}
 
-    #[test]
-    fn test_skip() {
-        let data = [0x01, 0x02, 0x03, 0x04];
-        let mut reader = BinaryReader::from_le_bytes(&data);
-        assert_eq!(reader.u8().unwrap(), 0x01);
-        reader.skip(2).unwrap();
-        assert_eq!(reader.u8().unwrap(), 0x04);
+impl<R> Drop for LimitedBufRead<'_, R> {
+    fn drop(&mut self) {
+        *self.position += self.consumed;
     }
+}
 
-    #[test]
-    fn test_skip_aligned() {
-        let data = [0x01, 0x02, 0x03, 0x04, 0x05];
-        let mut reader = BinaryReader::from_le_bytes(&data);
-        assert_eq!(reader.u8().unwrap(), 0x01);
-        reader.skip_aligned(4).unwrap();
-        assert_eq!(reader.u8().unwrap(), 0x05);
+/// A `BufRead` that can hand out the next bytes as a `Cow`.
+///
+/// In-memory slices return `Cow::Borrowed` pointing into the original buffer; other sources
+/// fall back to copying into `Cow::Owned`. Parsers generic over `R: ByteSource<'a>` can
+/// return borrowed payloads when possible and still work against files.
+pub trait ByteSource<'a>: BufRead {
+    fn read_cow(&mut self, len: usize) -> std::io::Result<Cow<'a, [u8]>> {
+        let mut buf = vec![0u8; len];
+        self.read_exact(&mut buf)?;
+        Ok(Cow::Owned(buf))
     }
+}
 
-    #[test]
-    fn test_reserved() {
-        let data = [0x01, 0x01, 0x01, 0x01];
-        let mut reader = BinaryReader::from_le_bytes(&data);
-        reader.reserved::<4>(0x01).unwrap();
+impl<'a> ByteSource<'a> for &'a [u8] {
+    fn read_cow(&mut self, len: usize) -> std::io::Result<Cow<'a, [u8]>> {
+        if len > self.len() {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        let (head, tail) = self.split_at(len);
+        *self = tail;
+        Ok(Cow::Borrowed(head))
     }
+}
+impl<'a, R: ByteSource<'a> + ?Sized> ByteSource<'a> for &mut R {
+    fn read_cow(&mut self, len: usize) -> std::io::Result<Cow<'a, [u8]>> {
+        (**self).read_cow(len)
+    }
+}
+impl<R: std::io::Read> ByteSource<'_> for BufReader<R> {}
+impl<T: AsRef<[u8]>> ByteSource<'_> for Cursor<T> {}
+impl<R: BufRead + ?Sized> ByteSource<'_> for Staged<'_, R> {}
+impl<R: BufRead + ?Sized> ByteSource<'_> for Box<R> {}
 
-    #[test]
+impl<'a, E: Endian, R: ByteSource<'a>> BinaryReader<E, R> {
+    /// Reads `len` bytes, borrowing them from the source buffer when the reader is slice-backed.
+    pub fn read_cow_bytes(&mut self, len: usize) -> Result<Cow<'a, [u8]>> {
+        self.check_size(len)?;
+        let bytes = if self.pending.lookahead().is_empty() {
+            self.reader.read_cow(len)
+        } else {
+            // bytes a peek staged are copied out, the source no longer holds them
+            self.source().read_cow(len)
+        };
+        let bytes = bytes.map_err(|e| self.poison(e))?;
+        self.total_bytes_read += len as u64;
+        record_stats!(self, Bulk, len);
+        Ok(bytes)
+    }
+}
+
+impl<'a, E: Endian> BinaryReader<E, &'a [u8]> {
+    /// Skips over a table of `len` fixed-size `T`s and returns a `LazyVec` that parses them on demand.
+    ///
+    /// The `LazyVec` reads from the table's own bytes, so it stays usable while this reader moves on,
+    /// and reports the same positions as this reader.
+    pub fn read_lazy_vec<T: Read + FixedSize>(&mut self, len: usize) -> Result<crate::lazy::LazyVec<E, Cursor<&'a [u8]>, T>> {
+        let size = len.checked_mul(T::SIZE).ok_or(ReadError::InvalidArgument)?;
+        self.check_size(size)?;
+        if self.reader.len() < size {
+            return Err(ReadError::io_error(std::io::ErrorKind::UnexpectedEof.into()));
+        }
+        let base = self.total_bytes_read;
+        let (table, rest) = self.reader.split_at(size);
+        let mut table_reader = BinaryReader::<E, _>::new(Cursor::new(table)).with_base_offset(base);
+        table_reader.limit_bytes = Some(base + size as u64);
+        self.reader = rest;
+        self.total_bytes_read += size as u64;
+        record_stats!(self, Skip, size);
+        Ok(crate::lazy::LazyVec::fixed(table_reader, base, len))
+    }
+}
+
+#[cfg(feature = "pod")]
+impl<'a, E: Endian> BinaryReader<E, &'a [u8]> {
+    /// Returns a reference to the next `size_of::<T>()` bytes viewed as `T`, without copying.
+    ///
+    /// The reference points into the source slice and outlives the reader. Only slice-backed
+    /// readers have this method, and `T` must implement the `zerocopy` traits re-exported by
+    /// `rwbin::pod`, which guarantee any bytes form a valid, unaligned `T`.
+    pub fn read_pod_ref<T: FromBytes + KnownLayout + Immutable + Unaligned>(&mut self) -> Result<&'a T> {
+        let len = std::mem::size_of::<T>();
+        self.check_size(len)?;
+        let (value, tail) = T::ref_from_prefix(self.reader).map_err(|_| ReadError::io_error(std::io::ErrorKind::UnexpectedEof.into()))?;
+        self.reader = tail;
+        self.total_bytes_read += len as u64;
+        record_stats!(self, Primitive, len);
+        Ok(value)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<E: Endian> BinaryReader<E, &[u8]> {
+    /// Reads `n` fixed-size records on the rayon thread pool, preserving their order.
+    ///
+    /// Every record is parsed from its own slice of the region with `into_par_iter`. On failure the
+    /// error of the lowest failing record is returned as `ReadError::InElement` and the
+    /// reader's position is left unchanged.
+    pub fn read_records_par<T: Read + FixedSize + Send>(&mut self, n: usize) -> Result<Vec<T>> {
+        use rayon::prelude::*;
+
+        let len = n.checked_mul(T::SIZE).ok_or(ReadError::InvalidArgument)?;
+        self.check_size(len)?;
+        if self.reader.len() < len {
+            return Err(ReadError::io_error(std::io::ErrorKind::UnexpectedEof.into()));
+        }
+        let region = &self.reader[..len];
+        let base = self.total_bytes_read;
+        let parse = |index: usize| {
+            let offset = base + (index * T::SIZE) as u64;
+            let mut reader = BinaryReader::<E, &[u8]>::from_bytes(&region[index * T::SIZE..(index + 1) * T::SIZE]).with_base_offset(offset);
+            T::read(&mut reader).map_err(|e| ReadError::InElement { index, offset, source: Box::new(e) })
+        };
+        let records = match (0..n).into_par_iter().map(parse).collect::<Result<Vec<T>>>() {
+            Ok(records) => records,
+            // rayon stops at whichever failure it meets first, so look for the lowest one
+            Err(e) => return Err((0..n).into_par_iter().find_map_first(|index| parse(index).err()).unwrap_or(e)),
+        };
+        self.reader = &self.reader[len..];
+        self.total_bytes_read += len as u64;
+        record_stats!(self, Bulk, len);
+        Ok(records)
+    }
+}
+
+/// Moves `limit` along with a position rebased from `position` to `offset`, so it stays the same
+/// distance away.
+pub(crate) fn rebase_limit(limit: u64, position: u64, offset: u64) -> u64 {
+    match limit.checked_sub(position) {
+        Some(ahead) => offset.saturating_add(ahead),
+        None => offset.saturating_sub(position - limit),
+    }
+}
+
+/// Cuts the buffered bytes `buf` down to the `remaining` ones before the limit, and tells whether
+/// the limit, rather than the end of the buffer, ends the window.
+pub(crate) fn peek_window(buf: &[u8], remaining: Option<u64>) -> (&[u8], bool) {
+    match remaining {
+        Some(remaining) if remaining <= buf.len() as u64 => (&buf[..remaining as usize], true),
+        _ => (buf, false),
+    }
+}
+
+/// Turns the error of a peek over a window of `buffered` bytes at `offset` into the one the read
+/// would give: running off a window that ends with the buffer is `PeekBeyondBuffer`, unless
+/// nothing was buffered because the stream has ended.
+pub(crate) fn peek_error(error: ReadError, offset: u64, buffered: usize, at_limit: bool) -> ReadError {
+    match error {
+        ReadError::NotEnoughBytes { limit, .. } if !at_limit && limit == offset + buffered as u64 => match buffered {
+            0 => ReadError::io_error(std::io::ErrorKind::UnexpectedEof.into()),
+            _ => ReadError::PeekBeyondBuffer { offset, buffered },
+        },
+        error => error,
+    }
+}
+
+pub trait Read {
+    fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self>
+    where
+        Self: Sized;
+}
+
+pub trait ReadWith<A> {
+    fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, arg: A) -> Result<Self>
+    where
+        Self: Sized;
+}
+
+/// Types that can be read over an existing value, e.g. to refill a scratch record every frame.
+///
+/// Every `Read` type gets this by overwriting itself; implement it by hand to update fields in
+/// place, typically via `read_into_with` on its `Vec` and `String` fields.
+pub trait ReadInto {
+    fn read_into<E: Endian, R: BufRead>(&mut self, reader: &mut BinaryReader<E, R>) -> Result<()>;
+}
+
+/// Like `ReadInto`, with an argument such as an element count or `StringMode`.
+pub trait ReadIntoWith<A> {
+    fn read_into_with<E: Endian, R: BufRead>(&mut self, reader: &mut BinaryReader<E, R>, arg: A) -> Result<()>;
+}
+
+impl<T: Read> ReadInto for T {
+    fn read_into<E: Endian, R: BufRead>(&mut self, reader: &mut BinaryReader<E, R>) -> Result<()> {
+        *self = T::read(reader)?;
+        Ok(())
+    }
+}
+
+impl<T: Read> ReadIntoWith<usize> for Vec<T> {
+    fn read_into_with<E: Endian, R: BufRead>(&mut self, reader: &mut BinaryReader<E, R>, len: usize) -> Result<()> {
+        self.clear();
+        self.reserve(len);
+        for _ in 0..len {
+            self.push(T::read(reader)?);
+        }
+        Ok(())
+    }
+}
+
+/// A byte-order marker recognised by `EitherEndianReader::detect_endian`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndianMarker<'m> {
+    /// TIFF convention: `II` for little-endian, `MM` for big-endian.
+    Tiff,
+    /// UTF-16 byte-order mark: `FF FE` for little-endian, `FE FF` for big-endian.
+    Utf16Bom,
+    /// A custom pair of markers `(little, big)` of the same length.
+    Custom(&'m [u8], &'m [u8]),
+}
+
+impl EndianMarker<'_> {
+    pub(crate) fn patterns(&self) -> (&[u8], &[u8]) {
+        match self {
+            EndianMarker::Tiff => (b"II", b"MM"),
+            EndianMarker::Utf16Bom => (&[0xff, 0xfe], &[0xfe, 0xff]),
+            EndianMarker::Custom(le, be) => (le, be),
+        }
+    }
+}
+
+impl<R> BinaryReader<LittleEndian, R> {
+    /// Creates a reader whose byte order `endianness` is chosen at runtime, e.g. from a header flag.
+    ///
+    /// Equivalent to `EitherEndianReader::new`.
+    pub fn new_dyn(reader: R, endianness: Endianness) -> EitherEndianReader<R> {
+        EitherEndianReader::new(reader, endianness)
+    }
+}
+
+/// A reader whose byte order is decided at runtime, by `EitherEndianReader::detect_endian` or
+/// `BinaryReader::new_dyn`.
+///
+/// It offers the `BinaryReader` methods, reading in the chosen byte order. Those whose result
+/// carries the byte order in its type, like `by_ref_take` or `read_tagged`, need a match on the
+/// variants.
+pub enum EitherEndianReader<R> {
+    Little(BinaryReader<LittleEndian, R>),
+    Big(BinaryReader<BigEndian, R>),
+}
+
+impl<R> EitherEndianReader<R> {
+    /// Wraps `reader` in a reader using the byte order `endianness`, e.g. one chosen from a header flag.
+    pub fn new(reader: R, endianness: Endianness) -> Self {
+        match endianness {
+            Endianness::Little => EitherEndianReader::Little(BinaryReader::new_le(reader)),
+            Endianness::Big => EitherEndianReader::Big(BinaryReader::new_be(reader)),
+        }
+    }
+
+    /// Returns the byte order in use.
+    pub fn endianness(&self) -> Endianness {
+        match self {
+            EitherEndianReader::Little(_) => Endianness::Little,
+            EitherEndianReader::Big(_) => Endianness::Big,
+        }
+    }
+
+    /// Returns `true` if the byte order in use is little-endian.
+    pub fn is_little_endian(&self) -> bool {
+        matches!(self, EitherEndianReader::Little(_))
+    }
+
+    /// Consumes the reader and returns the wrapped one.
+    pub fn into_inner(self) -> R {
+        match self {
+            EitherEndianReader::Little(reader) => reader.into_inner(),
+            EitherEndianReader::Big(reader) => reader.into_inner(),
+        }
+    }
+}
+
+impl<R: BufRead> EitherEndianReader<R> {
+    /// Reads a byte-order marker from the start of `reader` and returns a reader using that order.
+    ///
+    /// The marker bytes count toward the position. Returns `ReadError::InvalidDataFormat` if
+    /// neither marker matches, and `ReadError::InvalidArgument` if a custom pair differs in length.
+    pub fn detect_endian(reader: R, marker: EndianMarker<'_>) -> Result<Self> {
+        let (le, be) = marker.patterns();
+        if le.len() != be.len() {
+            return Err(ReadError::InvalidArgument);
+        }
+        Self::detect_endian_with(reader, le.len(), |data| match data {
+            _ if data == le => Some(Endianness::Little),
+            _ if data == be => Some(Endianness::Big),
+            _ => None,
+        })
+    }
+
+    /// Reads the first `len` bytes of `reader` and returns a reader using the byte order `decide`
+    /// picks for them, for markers that `EndianMarker` does not describe.
+    ///
+    /// `decide` sees the consumed magic, so it can validate the rest of it too; returning `None`
+    /// fails with `ReadError::InvalidDataFormat`. The magic counts toward the position.
+    pub fn detect_endian_with(reader: R, len: usize, decide: impl FnOnce(&[u8]) -> Option<Endianness>) -> Result<Self> {
+        let mut reader = BinaryReader::new_le(reader);
+        let endianness = reader.read_from_slice(len, |data| {
+            decide(data).ok_or_else(|| ReadError::invalid_data_format(format!("unrecognized byte-order marker {:02x?}", data)))
+        })?;
+        Ok(if endianness == Endianness::Little {
+            EitherEndianReader::Little(reader)
+        } else {
+            EitherEndianReader::Big(BinaryReader {
+                reader: reader.reader,
+                total_bytes_read: reader.total_bytes_read,
+                limit_bytes: reader.limit_bytes,
+                poisoned_at: reader.poisoned_at,
+                max_depth: reader.max_depth,
+                depth: reader.depth,
+                max_alloc: reader.max_alloc,
+                strict_strings: reader.strict_strings,
+                small_buf_size: reader.small_buf_size,
+                pending: reader.pending,
+                #[cfg(feature = "stats")]
+                stats: reader.stats,
+                _endian: PhantomData,
+            })
+        })
+    }
+}
+
+/// Defines `EitherEndianReader` methods that call the `BinaryReader` method of the same name on
+/// either variant. Generic parameters go in `[...]`, followed by a turbofish for those the
+/// arguments do not determine. Methods taking `&self` are written out.
+macro_rules! forward_either_reader {
+    ( $( $(#[$meta:meta])* fn $name:ident $([$($generics:tt)*] $(::<$($turbofish:ty),*>)?)? (&mut $self:ident $(, $arg:ident: $ty:ty)*) $(-> $ret:ty)?; )* ) => {
+        $(
+            $(#[$meta])*
+            #[inline]
+            pub fn $name $(<$($generics)*>)? (&mut $self $(, $arg: $ty)*) $(-> $ret)? {
+                match $self {
+                    EitherEndianReader::Little(reader) => reader.$name $($(::<$($turbofish),*>)?)? ($($arg),*),
+                    EitherEndianReader::Big(reader) => reader.$name $($(::<$($turbofish),*>)?)? ($($arg),*),
+                }
+            }
+        )*
+    };
+}
+
+impl<R: BufRead> EitherEndianReader<R> {
+    /// Returns the number of bytes consumed so far, a detected marker included.
+    pub fn position(&self) -> u64 {
+        match self {
+            EitherEndianReader::Little(reader) => reader.position(),
+            EitherEndianReader::Big(reader) => reader.position(),
+        }
+    }
+
+    /// Returns the number of bytes left before the active limit, or `None` if unlimited.
+    pub fn remaining(&self) -> Option<u64> {
+        match self {
+            EitherEndianReader::Little(reader) => reader.remaining(),
+            EitherEndianReader::Big(reader) => reader.remaining(),
+        }
+    }
+
+    /// Returns `true` if a failed read left the stream desynchronized.
+    pub fn is_poisoned(&self) -> bool {
+        match self {
+            EitherEndianReader::Little(reader) => reader.is_poisoned(),
+            EitherEndianReader::Big(reader) => reader.is_poisoned(),
+        }
+    }
+
+    /// Fails, without reading anything, unless `len` more bytes may be read before the limit.
+    pub fn ensure(&self, len: usize) -> Result<()> {
+        match self {
+            EitherEndianReader::Little(reader) => reader.ensure(len),
+            EitherEndianReader::Big(reader) => reader.ensure(len),
+        }
+    }
+
+    forward_either_reader! {
+        /// Lets reads proceed again after a failure.
+        fn clear_poison(&mut self);
+        /// Limits how deeply `read`, `read_with` and `read_partial` calls may nest.
+        fn set_max_depth(&mut self, max_depth: u32);
+        /// Reads `len` bytes, applies `parse`, and returns the parsed value.
+        fn read_from_slice[T](&mut self, len: usize, parse: impl FnOnce(&[u8]) -> Result<T>) -> Result<T>;
+        /// Reads exactly `N` bytes into an array and applies `parse`.
+        fn read_from_array[T, const N: usize](&mut self, parse: impl FnOnce(&[u8; N]) -> T) -> Result<T>;
+        /// Reads items of size `N` while `try_parse` returns `Some`.
+        fn read_while[T, const N: usize](&mut self, try_parse: impl Fn(&[u8; N]) -> Option<T>) -> Result<Vec<T>>;
+        /// Reads values of `T` until one equals `terminator`, which is consumed but not returned.
+        fn read_until[T: Read + PartialEq](&mut self, terminator: &T) -> Result<Vec<T>>;
+        /// Reads a `T` unless nothing is left before the limit or the end of the stream.
+        fn read_if_remaining[T: Read](&mut self) -> Result<Option<T>>;
+        /// Like `read_if_remaining`, returning `T::default()` for an absent tail.
+        fn read_remaining_or_default[T: Read + Default](&mut self) -> Result<T>;
+        /// Reads values of `T` until the limit or the end of the stream.
+        fn read_all[T: Read](&mut self) -> Result<Vec<T>>;
+        /// Reads a `T` from the buffered data without consuming it.
+        fn peek[T: Read](&mut self) -> Result<T>;
+        /// Returns the next unsigned 8‑bit integer without consuming it.
+        fn peek_u8(&mut self) -> Result<u8>;
+        /// Returns the next unsigned 16‑bit integer in the detected byte order without consuming it.
+        fn peek_u16(&mut self) -> Result<u16>;
+        /// Returns the next unsigned 32‑bit integer in the detected byte order without consuming it.
+        fn peek_u32(&mut self) -> Result<u32>;
+        /// Reads an unsigned 8‑bit integer.
+        fn u8(&mut self) -> Result<u8>;
+        /// Reads a signed 8‑bit integer.
+        fn i8(&mut self) -> Result<i8>;
+        /// Reads an unsigned 16‑bit integer in the detected byte order.
+        fn u16(&mut self) -> Result<u16>;
+        /// Reads a signed 16‑bit integer in the detected byte order.
+        fn i16(&mut self) -> Result<i16>;
+        /// Reads an unsigned 32‑bit integer in the detected byte order.
+        fn u32(&mut self) -> Result<u32>;
+        /// Reads a signed 32‑bit integer in the detected byte order.
+        fn i32(&mut self) -> Result<i32>;
+        /// Reads a 32‑bit floating point value in the detected byte order.
+        fn f32(&mut self) -> Result<f32>;
+        /// Reads an unsigned 64‑bit integer in the detected byte order.
+        fn u64(&mut self) -> Result<u64>;
+        /// Reads a signed 64‑bit integer in the detected byte order.
+        fn i64(&mut self) -> Result<i64>;
+        /// Reads a 64‑bit floating point value in the detected byte order.
+        fn f64(&mut self) -> Result<f64>;
+        /// Reads an unsigned 128‑bit integer in the detected byte order.
+        fn u128(&mut self) -> Result<u128>;
+        /// Reads a signed 128‑bit integer in the detected byte order.
+        fn i128(&mut self) -> Result<i128>;
+        /// Reads a `u16` in the detected byte order as a `usize`.
+        fn usize_from_u16(&mut self) -> Result<usize>;
+        /// Reads a `u32` in the detected byte order as a `usize`.
+        fn usize_from_u32(&mut self) -> Result<usize>;
+        /// Reads a `u64` in the detected byte order as a `usize`.
+        fn usize_from_u64(&mut self) -> Result<usize>;
+        /// Reads an unsigned integer of the given `width` in the detected byte order.
+        fn read_uint(&mut self, width: IntWidth) -> Result<u64>;
+        /// Reads a numeric value of type `T` in the detected byte order.
+        fn read_num[T: Primitive](&mut self) -> Result<T>;
+        /// Reads a numeric value of type `T` in the byte order `B`, whatever the detected one.
+        fn read_num_as[B: Endian, T: Primitive]::<B, T>(&mut self) -> Result<T>;
+        /// Reads any value implementing the `Read` trait in the detected byte order.
+        fn read[T: Read](&mut self) -> Result<T>;
+        /// Reads any value implementing the `ReadWith` trait in the detected byte order.
+        fn read_with[T: ReadWith<U>, U](&mut self, arg: U) -> Result<T>;
+        /// Reads over an existing value in place, in the detected byte order.
+        fn read_into[T: ReadInto](&mut self, target: &mut T) -> Result<()>;
+        /// Reads over an existing value in place with an argument, in the detected byte order.
+        fn read_into_with[T: ReadIntoWith<U>, U](&mut self, target: &mut T, arg: U) -> Result<()>;
+        /// Verifies the next value equals `value`.
+        fn value[T: PartialEq + Read + Debug](&mut self, value: &T) -> Result<()>;
+        /// Verifies the next sequence equals `values`.
+        fn values[T: PartialEq + Read + Debug + 'static](&mut self, values: &[T]) -> Result<()>;
+        /// Ensures the next `N` bytes all equal `expected_value`.
+        fn reserved[const N: usize]::<N>(&mut self, expected_value: u8) -> Result<()>;
+        /// Reads `N` reserved bytes and returns every `(relative offset, actual byte)` that differs from `expected_value`.
+        fn reserved_report[const N: usize]::<N>(&mut self, expected_value: u8) -> Result<Vec<(usize, u8)>>;
+        /// Like `reserved_report`, for a region whose length is only known at runtime.
+        fn reserved_report_len(&mut self, len: usize, expected_value: u8) -> Result<Vec<(usize, u8)>>;
+        /// Verifies the next `T::SIZE` bytes all equal `expected_value`.
+        fn reserved_for[T: FixedSize]::<T>(&mut self, expected_value: u8) -> Result<()>;
+        /// Verifies the next bytes equal the signature `expected`.
+        fn magic(&mut self, expected: impl AsRef<[u8]>) -> Result<()>;
+        /// Reads a `Version` in the detected byte order and checks that it is `supported`.
+        fn version(&mut self, supported: impl Into<VersionRange>) -> Result<Version>;
+        /// Verifies a trailing checksum stored in the detected byte order before the limit.
+        fn verify_trailing_checksum(&mut self, kind: ChecksumKind) -> Result<()>;
+        /// Reads a sub-structure of length `len`.
+        fn read_partial[T: Read](&mut self, len: usize) -> Result<T>;
+        /// Reads a sub-structure of length `len` with an argument.
+        fn read_partial_with[U, T: ReadWith<U>](&mut self, len: usize, arg: U) -> Result<T>;
+        /// Reads a `u32` length-prefixed frame and parses its payload as `T`.
+        fn read_frame[T: Read](&mut self) -> Result<T>;
+        /// Reads a `u32` length-prefixed frame of at most `max_size` bytes and parses its payload as `T`.
+        fn read_frame_limited[T: Read](&mut self, max_size: u32) -> Result<T>;
+        /// Reads an `L` length followed by a payload parsed as `T`, see `BinaryReader::read_len_prefixed`.
+        fn read_len_prefixed[L: Read + FixedSize + Into<u64>, T: Read]::<L, T>(&mut self, policy: LenPolicy) -> Result<T>;
+        /// Skips `bytes` bytes.
+        fn skip(&mut self, bytes: usize) -> Result<()>;
+        /// Streams the next `len` bytes into `dst`.
+        fn copy_to(&mut self, dst: &mut impl std::io::Write, len: u64) -> Result<()>;
+        /// Skips forward to the absolute position `pos`.
+        fn skip_to(&mut self, pos: u64) -> Result<()>;
+        /// Skips `n` records of the fixed-size type `T` without parsing them.
+        fn skip_records[T: FixedSize]::<T>(&mut self, n: usize) -> Result<()>;
+        /// Skips up to alignment boundary by padding.
+        fn skip_aligned(&mut self, align: usize) -> Result<()>;
+        /// Reads `N` values of `T` straight into an array.
+        fn read_array[T: Read, const N: usize](&mut self) -> Result<[T; N]>;
+        /// Reads `len` values of `T`, passing each to `validate` before the next one is read.
+        fn read_vec_validated[T: Read](&mut self, len: usize, validate: impl Fn(usize, &T) -> Result<()>) -> Result<Vec<T>>;
+        /// Reads values written in chunks of a `P` count, see `BinaryReader::read_vec_prefixed_chunked`.
+        fn read_vec_prefixed_chunked[P: Read + TryInto<usize>, T: Read]::<P, T>(&mut self, continuation_flag: u8) -> Result<Vec<T>>;
+        /// Reads `n` records of the fixed-size type `T`.
+        fn read_records[T: Read + FixedSize](&mut self, n: usize) -> Result<Vec<T>>;
+        /// Reads a UTF-8 string.
+        fn utf8_str(&mut self, mode: crate::string::StringMode) -> Result<String>;
+        /// Reads a UTF-16 string in the detected byte order.
+        fn utf16_str(&mut self, mode: crate::string::StringMode) -> Result<String>;
+        /// Reads NUL-terminated UTF-8 strings until an empty one, consuming at most `max_bytes` bytes.
+        fn utf8_str_list(&mut self, max_bytes: usize) -> Result<Vec<String>>;
+        /// Reads NUL-terminated UTF-16 strings until an empty one, consuming at most `max_bytes` bytes.
+        fn utf16_str_list(&mut self, max_bytes: usize) -> Result<Vec<String>>;
+        /// Reads an `OsString` stored with `encoding`.
+        fn read_os_string(&mut self, encoding: crate::string::OsStrEncoding) -> Result<std::ffi::OsString>;
+        /// Reads a `PathBuf` stored with `encoding`.
+        fn read_path_buf(&mut self, encoding: crate::string::OsStrEncoding) -> Result<std::path::PathBuf>;
+        /// Reads an unsigned LEB128 varint.
+        fn varint_u64(&mut self) -> Result<u64>;
+        /// Reads a zigzag-encoded signed LEB128 varint, as protobuf's `sint64`.
+        fn varint_i64(&mut self) -> Result<i64>;
+        /// Reads a MIDI-style big-endian variable-length quantity.
+        fn vlq_u32(&mut self) -> Result<u32>;
+    }
+}
+
+impl<R: BufRead + Seek> EitherEndianReader<R> {
+    forward_either_reader! {
+        /// Seeks to record `index` of a table of fixed-size `T` records starting at position `base`.
+        fn seek_record[T: FixedSize]::<T>(&mut self, base: u64, index: u64) -> Result<()>;
+        /// Seeks to the absolute position `target`.
+        fn seek_to(&mut self, target: u64) -> Result<()>;
+        /// Seeks `delta` bytes forward or backward from the current position.
+        fn seek_relative(&mut self, delta: i64) -> Result<()>;
+        /// Reads a `T` at position `pos` and returns to the current position.
+        fn read_at[T: Read](&mut self, pos: u64) -> Result<T>;
+        /// Skips forward to the next occurrence of `pattern`, leaving the reader at its first byte.
+        fn scan_for(&mut self, pattern: &[u8], max_scan: Option<usize>) -> Result<u64>;
+    }
+}
+
+impl<R: BufRead> BinaryReader<LittleEndian, R> {
+    /// Reads a value as big-endian without changing this reader’s endian.
+    pub fn read_as_be<T: Read>(&mut self) -> Result<T> {
+        with_endian!(self, BinaryReader { small_buf_size, pending }, BigEndian, |view| T::read(&mut view))
+    }
+
+    /// Reads a value with parameter as big-endian.
+    pub fn read_as_be_with<T: ReadWith<U>, U>(&mut self, arg: U) -> Result<T> {
+        with_endian!(self, BinaryReader { small_buf_size, pending }, BigEndian, |view| T::read_with(&mut view, arg))
+    }
+}
+
+impl<R: BufRead> BinaryReader<BigEndian, R> {
+    /// Reads a value as little-endian without changing this reader’s endian.
+    pub fn read_as_le<T: Read>(&mut self) -> Result<T> {
+        with_endian!(self, BinaryReader { small_buf_size, pending }, LittleEndian, |view| T::read(&mut view))
+    }
+
+    /// Reads a value with parameter as little-endian.
+    pub fn read_as_le_with<T: ReadWith<U>, U>(&mut self, arg: U) -> Result<T> {
+        with_endian!(self, BinaryReader { small_buf_size, pending }, LittleEndian, |view| T::read_with(&mut view, arg))
+    }
+}
+
+// Read trait implementations for primitive types
+macro_rules! impl_readable_for_numeric_primitives {
+    ( $( $t:ty: $func:ident ),* $(,)? ) => {
+        $(
+            impl Read for $t {
+                #[inline]
+                fn read<E: Endian, R: BufRead>(
+                    reader: &mut BinaryReader<E, R>
+                ) -> Result<Self> {
+                    reader.$func()
+                }
+            }
+        )*
+    };
+}
+impl_readable_for_numeric_primitives! {
+    u8: u8,
+    i8: i8,
+    u16: u16,
+    i16: i16,
+    u32: u32,
+    i32: i32,
+    f32: f32,
+    u64: u64,
+    i64: i64,
+    f64: f64,
+    u128: u128,
+    i128: i128,
+}
+
+impl Read for bool {
+    fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+        match reader.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(ReadError::invalid_data_format("Expected 0 or 1 for boolean")),
+        }
+    }
+}
+
+impl Read for char {
+    fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+        let value = reader.u32()?;
+        std::char::from_u32(value).ok_or_else(|| ReadError::invalid_data_format(format!("Invalid char value: {}", value)))
+    }
+}
+
+macro_rules! impl_readable_for_tuples {
+    ( $( ( $( $T:ident ),+ ), )+ ) => {
+        $(
+            impl<$($T: Read),+> Read for ( $( $T, )+ ) {
+                fn read<E: Endian, R: BufRead>(
+                    reader: &mut BinaryReader<E, R>
+                ) -> Result<Self> {
+                    Ok(( $( $T::read(reader)?, )+ ))
+                }
+            }
+        )+
+    }
+}
+impl_readable_for_tuples! {
+    (T1, T2),
+    (T1, T2, T3),
+    (T1, T2, T3, T4),
+    (T1, T2, T3, T4, T5),
+    (T1, T2, T3, T4, T5, T6),
+}
+
+impl<T: Read> ReadWith<bool> for Option<T> {
+    fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, cond: bool) -> Result<Self> {
+        if cond { Ok(Some(T::read(reader)?)) } else { Ok(None) }
+    }
+}
+
+/// An array being filled front to back, which drops only the elements written so far if abandoned.
+pub(crate) struct ArrayBuilder<T, const N: usize> {
+    elements: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> ArrayBuilder<T, N> {
+    pub(crate) fn new() -> Self {
+        Self {
+            elements: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+        }
+    }
+
+    pub(crate) fn push(&mut self, value: T) {
+        self.elements[self.len].write(value);
+        self.len += 1;
+    }
+
+    pub(crate) fn finish(self) -> [T; N] {
+        assert_eq!(self.len, N, "array finished before every element was written");
+        let this = ManuallyDrop::new(self);
+        // SAFETY: all `N` elements are initialized, and `MaybeUninit<T>` has the layout of `T`.
+        // `this` is never dropped, so ownership of the elements moves to the returned array.
+        unsafe { std::ptr::read(this.elements.as_ptr().cast::<[T; N]>()) }
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayBuilder<T, N> {
+    fn drop(&mut self) {
+        for element in &mut self.elements[..self.len] {
+            // SAFETY: the first `len` elements were initialized by `push`.
+            unsafe { element.assume_init_drop() };
+        }
+    }
+}
+
+impl<T: Read, const N: usize> Read for [T; N] {
+    fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+        reader.read_array()
+    }
+}
+
+impl ReadWith<IntWidth> for u64 {
+    fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, width: IntWidth) -> Result<Self> {
+        reader.read_uint(width)
+    }
+}
+
+impl<T: Read> ReadWith<usize> for Vec<T> {
+    fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, len: usize) -> Result<Self> {
+        reader.check_alloc(len.saturating_mul(size_of::<T>()))?;
+        let mut vec = Vec::with_capacity(len);
+        for _ in 0..len {
+            vec.push(reader.read::<T>()?);
+        }
+        Ok(vec)
+    }
+}
+
+/// Reads `len` elements, handing each a clone of `arg`.
+///
+/// Borrowed arguments need no higher-ranked bounds: `(len, &header)` works for any element
+/// type implementing `ReadWith<&Header>`, since references are `Clone`.
+impl<A: Clone, T: ReadWith<A>> ReadWith<(usize, A)> for Vec<T> {
+    fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, (len, arg): (usize, A)) -> Result<Self> {
+        reader.check_alloc(len.saturating_mul(size_of::<T>()))?;
+        let mut vec = Vec::with_capacity(len);
+        for _ in 0..len {
+            vec.push(T::read_with(reader, arg.clone())?);
+        }
+        Ok(vec)
+    }
+}
+
+macro_rules! impl_readable_for_slice_containers {
+    ( $( $container:ident ),* ) => {
+        $(
+            impl<T: Read> ReadWith<usize> for $container<[T]> {
+                fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, len: usize) -> Result<Self> {
+                    Vec::<T>::read_with(reader, len).map(Into::into)
+                }
+            }
+
+            impl<A: Clone, T: ReadWith<A>> ReadWith<(usize, A)> for $container<[T]> {
+                fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, arg: (usize, A)) -> Result<Self> {
+                    Vec::<T>::read_with(reader, arg).map(Into::into)
+                }
+            }
+        )*
+    };
+}
+
+impl_readable_for_slice_containers!(Box, Arc);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integers() {
+        let data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+
+        assert_eq!(BinaryReader::from_le_bytes(&data).u8().unwrap(), 0x01);
+        assert_eq!(BinaryReader::from_le_bytes(&data).u16().unwrap(), 0x0201);
+        assert_eq!(BinaryReader::from_le_bytes(&data).u32().unwrap(), 0x04030201);
+        assert_eq!(BinaryReader::from_le_bytes(&data).u64().unwrap(), 0x0807060504030201);
+
+        assert_eq!(BinaryReader::from_le_bytes(&data).i8().unwrap(), 0x01);
+        assert_eq!(BinaryReader::from_le_bytes(&data).i16().unwrap(), 0x0201);
+        assert_eq!(BinaryReader::from_le_bytes(&data).i32().unwrap(), 0x04030201);
+        assert_eq!(BinaryReader::from_le_bytes(&data).i64().unwrap(), 0x0807060504030201);
+
+        assert_eq!(BinaryReader::from_be_bytes(&data).u8().unwrap(), 0x01);
+        assert_eq!(BinaryReader::from_be_bytes(&data).u16().unwrap(), 0x0102);
+        assert_eq!(BinaryReader::from_be_bytes(&data).u32().unwrap(), 0x01020304);
+        assert_eq!(BinaryReader::from_be_bytes(&data).u64().unwrap(), 0x0102030405060708);
+
+        assert_eq!(BinaryReader::from_be_bytes(&data).i8().unwrap(), 0x01);
+        assert_eq!(BinaryReader::from_be_bytes(&data).i16().unwrap(), 0x0102);
+        assert_eq!(BinaryReader::from_be_bytes(&data).i32().unwrap(), 0x01020304);
+        assert_eq!(BinaryReader::from_be_bytes(&data).i64().unwrap(), 0x0102030405060708);
+    }
+
+    #[test]
+    fn test_floats() {
+        let data = [0x00, 0x00, 0x80, 0x3F, 0x00, 0x00, 0x00, 0x40];
+        assert_eq!(BinaryReader::from_le_bytes(&data).f32().unwrap(), 1.0);
+
+        let data = [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xF0, 0x3F];
+        assert_eq!(BinaryReader::from_le_bytes(&data).f64().unwrap(), 1.0);
+
+        let data = [0x3F, 0x80, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00];
+        assert_eq!(BinaryReader::from_be_bytes(&data).f32().unwrap(), 1.0);
+
+        let data = [0x3F, 0xF0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(BinaryReader::from_be_bytes(&data).f64().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_128_bit_integers() {
+        let hash = 0x0102_0304_0506_0708_090A_0B0C_0D0E_0F10u128;
+
+        let mut data = Vec::new();
+        let mut writer = crate::writer::BinaryWriter::new_le(&mut data);
+        writer.u128(hash).unwrap();
+        writer.write(&-2i128).unwrap();
+        assert_eq!(&data[..16], hash.to_le_bytes());
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        assert_eq!(reader.read::<u128>().unwrap(), hash);
+        assert_eq!(reader.i128().unwrap(), -2);
+
+        let mut data = Vec::new();
+        let mut writer = crate::writer::BinaryWriter::new_be(&mut data);
+        writer.write(&hash).unwrap();
+        writer.i128(i128::MIN).unwrap();
+        assert_eq!(&data[..16], hash.to_be_bytes());
+        let mut reader = BinaryReader::from_be_bytes(&data);
+        assert_eq!(reader.u128().unwrap(), hash);
+        assert_eq!(reader.read::<i128>().unwrap(), i128::MIN);
+    }
+
+    #[test]
+    fn test_skip() {
+        let data = [0x01, 0x02, 0x03, 0x04];
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        assert_eq!(reader.u8().unwrap(), 0x01);
+        reader.skip(2).unwrap();
+        assert_eq!(reader.u8().unwrap(), 0x04);
+    }
+
+    #[test]
+    fn test_skip_aligned() {
+        let data = [0x01, 0x02, 0x03, 0x04, 0x05];
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        assert_eq!(reader.u8().unwrap(), 0x01);
+        reader.skip_aligned(4).unwrap();
+        assert_eq!(reader.u8().unwrap(), 0x05);
+    }
+
+    #[test]
+    fn test_skip_aligned_zero() {
+        let data = [0x00, 0x02, 0x03, 0x04];
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        let align = reader.u8().unwrap() as usize;
+        assert!(matches!(reader.skip_aligned(align), Err(ReadError::InvalidArgument)));
+        assert_eq!(reader.u8().unwrap(), 0x02);
+    }
+
+    #[test]
+    fn test_large_position() {
+        let data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+        let mut reader = BinaryReader::from_le_bytes(&data).with_base_offset(u32::MAX as u64 - 1);
+        assert_eq!(reader.u8().unwrap(), 0x01);
+        assert_eq!(reader.position(), u32::MAX as u64);
+        reader.skip_aligned(4).unwrap();
+        assert_eq!(reader.position(), 1 << 32);
+        assert_eq!(reader.remaining(), Some(6));
+        reader.skip_to((1 << 32) + 4).unwrap();
+        assert_eq!(reader.u16().unwrap(), 0x0807);
+        assert!(matches!(
+            reader.u8(),
+            Err(ReadError::NotEnoughBytes { remaining: 0, offset, .. }) if offset == (1 << 32) + 6
+        ));
+    }
+
+    #[test]
+    fn test_rebase_backwards() {
+        let data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+        let mut reader = BinaryReader::from_le_bytes(&data).with_base_offset(100);
+        assert_eq!(reader.u16().unwrap(), 0x0201);
+        let mut reader = reader.with_base_offset(10);
+        assert_eq!((reader.position(), reader.remaining()), (10, Some(6)));
+        reader.skip(5).unwrap();
+        assert!(matches!(
+            reader.u16(),
+            Err(ReadError::NotEnoughBytes {
+                remaining: 1,
+                limit: 16,
+                offset: 15,
+                ..
+            })
+        ));
+        assert_eq!(reader.u8().unwrap(), 0x08);
+
+        // A cursor already moved forward, rebased to an earlier file offset.
+        let mut cursor = Cursor::new(&data[..]);
+        cursor.set_position(6);
+        let mut reader = BinaryReader::from_cursor_le(&mut cursor).with_base_offset(2);
+        assert_eq!((reader.position(), reader.remaining()), (2, Some(2)));
+        assert_eq!(reader.u16().unwrap(), 0x0807);
+
+        // A limit already behind the position stays behind it.
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        reader.skip(4).unwrap();
+        reader.limit_bytes = Some(3);
+        let reader = reader.with_base_offset(2);
+        assert_eq!(reader.limit_bytes, Some(1));
+        assert_eq!(BinaryReader::from_le_bytes(&data).with_base_offset(u64::MAX).remaining(), Some(0));
+    }
+
+    #[test]
+    fn test_reserved() {
+        let data = [0x01, 0x01, 0x01, 0x01];
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        reader.reserved::<4>(0x01).unwrap();
+    }
+
+    #[test]
+    fn test_magic_mismatch() {
+        let data = *b"RIFX";
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        let err = reader.magic(b"RIFF").unwrap_err();
+        assert!(matches!(err, ReadError::ExpectedMismatch { first_diff: 3, .. }));
+        assert_eq!(
+            err.to_string(),
+            "unexpected bytes, first difference at byte 3:\n\
+             expected: 52 49 46 46\n\
+             \x20 actual: 52 49 46 58\n\
+             \x20                  ^^"
+        );
+    }
+
+    #[test]
+    fn test_magic_mismatch_truncated() {
+        let expected: Vec<u8> = (0..64).collect();
+        let mut data = expected.clone();
+        data[40] = 0xFF;
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        let err = reader.magic(&expected).unwrap_err();
+        let message = err.to_string();
+        let lines: Vec<&str> = message.lines().collect();
+        assert_eq!(lines[1], "expected: ... 20 21 22 23 24 25 26 27 28 29 2A 2B 2C 2D 2E 2F ...");
+        assert_eq!(lines[2], "  actual: ... 20 21 22 23 24 25 26 27 FF 29 2A 2B 2C 2D 2E 2F ...");
+        assert_eq!(lines[3].find('^'), lines[2].find("FF"));
+    }
+
+    #[test]
+    fn test_reserved_mismatch() {
+        let data = [0x00, 0x00, 0x07, 0x00];
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        let err = reader.reserved::<4>(0x00).unwrap_err();
+        assert!(matches!(err, ReadError::ExpectedMismatch { first_diff: 2, .. }));
+    }
+
+    #[test]
     fn test_child() {
         let data = [0x01, 0x02, 0x03, 0x04, 0x05];
         let mut reader = BinaryReader::from_le_bytes(&data);
@@ -494,112 +2556,1402 @@ mod tests {
     }
 
     #[test]
-    fn test_read() {
-        let data = [0x01, 0x02, 0x03, 0x04];
-        let mut reader = BinaryReader::from_le_bytes(&data);
-        assert_eq!(reader.read::<u8>().unwrap(), 0x01);
-        assert_eq!(reader.read::<u16>().unwrap(), 0x302);
-        assert_eq!(reader.read::<u8>().unwrap(), 0x04);
+    fn test_partial_overshoot() {
+        struct Inner;
+        impl Read for Inner {
+            fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+                reader.read::<(u32, u32)>()?;
+                Ok(Inner)
+            }
+        }
+        struct Middle;
+        impl Read for Middle {
+            fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+                // The inner block claims 8 bytes although only 2 are left in this one.
+                reader.read_partial::<Inner>(8)?;
+                reader.u8()?;
+                Ok(Middle)
+            }
+        }
+        let data = [0u8; 16];
+        let mut reader = BinaryReader::new_le(&data[..]);
+        let result = reader.read_partial::<Middle>(2);
+        assert!(matches!(result, Err(ReadError::PositionBeyondLimit { position: 8, limit: 2 })));
+    }
+
+    #[test]
+    fn test_read_cow_bytes() {
+        let data = [0x01, 0x02, 0x03, 0x04, 0x05];
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        reader.u8().unwrap();
+        let payload = reader.read_cow_bytes(3).unwrap();
+        assert!(matches!(payload, Cow::Borrowed(_)));
+        assert_eq!(payload.as_ptr(), data[1..].as_ptr());
+        assert_eq!(reader.position(), 4);
+        assert!(matches!(reader.read_cow_bytes(2), Err(ReadError::NotEnoughBytes { .. })));
+
+        let mut reader = BinaryReader::new_le(BufReader::new(&data[..]));
+        let payload = reader.read_cow_bytes(3).unwrap();
+        assert!(matches!(payload, Cow::Owned(_)));
+        assert_eq!(&payload[..], &[0x01, 0x02, 0x03]);
+    }
+
+    #[cfg(feature = "pod")]
+    #[test]
+    fn test_read_pod_ref() {
+        #[derive(zerocopy::FromBytes, zerocopy::KnownLayout, zerocopy::Immutable, zerocopy::Unaligned)]
+        #[repr(C)]
+        struct Header {
+            magic: [u8; 4],
+            version: [u8; 2],
+            flags: u8,
+            kind: i8,
+        }
+
+        let data = [b'R', b'W', b'B', b'N', 0x02, 0x01, 0x80, 0xff, 0xaa];
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        let header: &Header = reader.read_pod_ref().unwrap();
+        assert_eq!(reader.position(), 8);
+        let last: &u8 = reader.read_pod_ref().unwrap();
+        assert_eq!(*last, 0xaa);
+        assert!(matches!(reader.read_pod_ref::<u8>(), Err(ReadError::NotEnoughBytes { .. })));
+
+        let mut copying = BinaryReader::from_le_bytes(&data);
+        assert_eq!(header.magic, copying.read::<[u8; 4]>().unwrap());
+        assert_eq!(u16::from_le_bytes(header.version), copying.u16().unwrap());
+        assert_eq!(header.flags, copying.u8().unwrap());
+        assert_eq!(header.kind, copying.i8().unwrap());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_read_records_par() {
+        let mut data = Vec::new();
+        for i in 0..10_000u32 {
+            data.extend_from_slice(&i.to_le_bytes());
+            data.extend_from_slice(&(i as u16).wrapping_mul(3).to_le_bytes());
+            data.push((i % 2) as u8);
+        }
+        let mut sequential = BinaryReader::from_le_bytes(&data);
+        let expected: Vec<(u32, u16, bool)> = sequential.read_records(10_000).unwrap();
+        let mut parallel = BinaryReader::from_le_bytes(&data);
+        parallel.u8().unwrap();
+        parallel.skip(6).unwrap();
+        let records: Vec<(u32, u16, bool)> = parallel.read_records_par(9_999).unwrap();
+        assert_eq!(records, expected[1..]);
+        assert_eq!(parallel.position(), sequential.position());
+
+        data[7 * 7_777 + 6] = 2;
+        let mut sequential = BinaryReader::from_le_bytes(&data);
+        assert!(matches!(sequential.read_records::<(u32, u16, bool)>(10_000), Err(ReadError::InvalidDataFormat(_))));
+        assert_eq!(sequential.position(), 7 * 7_778);
+        let mut parallel = BinaryReader::from_le_bytes(&data);
+        let err = parallel.read_records_par::<(u32, u16, bool)>(10_000).unwrap_err();
+        assert!(matches!(err, ReadError::InElement { index: 7_777, offset: 54_439, ref source } if matches!(**source, ReadError::InvalidDataFormat(_))));
+        assert_eq!(parallel.position(), 0);
+    }
+
+    #[test]
+    fn test_small_capacity() {
+        let mut data = vec![0xAAu8; 60];
+        data.extend_from_slice(&0x0102030405060708u64.to_be_bytes());
+        data.extend_from_slice(&[b'x'; 100]);
+        let mut reader = BinaryReader::new_be_with_capacity(&data[..], 64);
+        reader.skip(60).unwrap();
+        assert_eq!(reader.u64().unwrap(), 0x0102030405060708);
+        let text = reader.utf8_str(crate::string::StringMode::FixedChars(100)).unwrap();
+        assert_eq!(text, "x".repeat(100));
+        assert_eq!(reader.position(), 168);
+    }
+
+    #[test]
+    fn test_read() {
+        let data = [0x01, 0x02, 0x03, 0x04];
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        assert_eq!(reader.read::<u8>().unwrap(), 0x01);
+        assert_eq!(reader.read::<u16>().unwrap(), 0x302);
+        assert_eq!(reader.read::<u8>().unwrap(), 0x04);
+    }
+
+    #[test]
+    fn test_read_with() {
+        struct Test {
+            a: u8,
+            b: u16,
+            opt: Option<u8>,
+        }
+        impl ReadWith<u8> for Test {
+            fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, arg: u8) -> Result<Self> {
+                let (a, b) = reader.read::<(u8, u16)>()?;
+                Ok(Test { a, b, opt: Some(arg) })
+            }
+        }
+        let data = [0x01, 0x02, 0x03, 0x04];
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        let test: Test = reader.read_with(0x10).unwrap();
+        assert_eq!(test.a, 0x01);
+        assert_eq!(test.b, 0x302);
+        assert_eq!(test.opt, Some(0x10));
+    }
+
+    #[test]
+    fn test_read_vec() {
+        let data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        let vec: Vec<u16> = reader.read_with(3).unwrap();
+        assert_eq!(vec, vec![0x0201, 0x0403, 0x0605]);
+    }
+
+    #[test]
+    fn test_read_if() {
+        let data = [0x01, 0x02, 0x03, 0x04];
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        let a: Option<u8> = reader.read_with(true).unwrap();
+        assert_eq!(a, Some(0x01));
+        let b: Option<u16> = reader.read_with(false).unwrap();
+        assert_eq!(b, None);
+        let c: Option<u16> = reader.read_with(true).unwrap();
+        assert_eq!(c, Some(0x0302));
+        let _ = reader.u8().unwrap();
+        assert!(matches!(reader.check_size(1), Err(ReadError::NotEnoughBytes { .. })));
+    }
+
+    #[test]
+    fn test_not_enough_bytes_in_partial() {
+        let data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        reader.u8().unwrap();
+        let result = reader.read_partial::<(u16, u32)>(3);
+        assert!(matches!(
+            result,
+            Err(ReadError::NotEnoughBytes {
+                requested: 4,
+                remaining: 1,
+                limit: 4,
+                offset: 3,
+            })
+        ));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "not enough bytes: requested 4 at offset 0x3 (3), but only 1 remain before the limit at 0x4 (4)"
+        );
+    }
+
+    #[test]
+    fn test_values() {
+        let data = [0x01, 0x02, 0x03, 0x04];
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        reader.values::<u8>(&[0x01, 0x02]).unwrap();
+        reader.values::<u8>(&[0x03, 0x04]).unwrap();
+        assert!(matches!(reader.check_size(1), Err(ReadError::NotEnoughBytes { .. })));
+    }
+
+    #[test]
+    fn test_values_mismatch() {
+        let data = *b"\x89PNG\r\n\x1a\x0b";
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        let err = reader.values(b"\x89PNG\r\n\x1a\n").unwrap_err();
+        assert!(matches!(err, ReadError::ExpectedMismatch { first_diff: 7, .. }));
+        assert_eq!(err.to_string().lines().last().unwrap().find('^'), err.to_string().lines().nth(2).unwrap().find("0B"));
+
+        let data = [0x01, 0x00, 0x02, 0x00, 0x07, 0x00];
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        let err = reader.values(&[1u16, 2, 3]).unwrap_err();
+        assert!(matches!(err, ReadError::InElement { index: 2, offset: 4, ref source } if matches!(**source, ReadError::UnexpectedValue { .. })));
+    }
+
+    #[test]
+    fn test_read_as_be() {
+        let data = [0x01, 0x02, 0x03, 0x04];
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        let a: u16 = reader.read_as_be().unwrap();
+        assert_eq!(a, 0x0102);
+        let b: u16 = reader.read().unwrap();
+        assert_eq!(b, 0x0403);
+        assert!(matches!(reader.check_size(1), Err(ReadError::NotEnoughBytes { .. })));
+    }
+
+    #[test]
+    fn test_read_as_be_with() {
+        struct Test {
+            a: u8,
+            b: u16,
+            opt: Option<u8>,
+        }
+        impl ReadWith<u8> for Test {
+            fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, arg: u8) -> Result<Self> {
+                let (a, b) = reader.read::<(u8, u16)>()?;
+                Ok(Test { a, b, opt: Some(arg) })
+            }
+        }
+        let data = [0x01, 0x02, 0x03, 0x04];
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        let test: Test = reader.read_as_be_with(0x10).unwrap();
+        assert_eq!(test.a, 0x01);
+        assert_eq!(test.b, 0x0203);
+        assert_eq!(test.opt, Some(0x10));
+    }
+
+    #[test]
+    fn test_read_tuple() {
+        let data = [0x01, 0x02, 0x03, 0x04];
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        let (a, b): (u8, u16) = reader.read().unwrap();
+        assert_eq!(a, 0x01);
+        assert_eq!(b, 0x0302);
+    }
+
+    #[test]
+    fn test_read_array() {
+        let data = [0x01, 0x02, 0x03, 0x04];
+        let mut reader = BinaryReader::from_be_bytes(&data);
+        let array: [u16; 2] = reader.read().unwrap();
+        assert_eq!(array, [0x0102, 0x0304]);
+    }
+
+    #[test]
+    fn test_read_frame() {
+        let mut buf = Vec::new();
+        let mut writer = crate::writer::BinaryWriter::new_be(&mut buf);
+        writer.write_frame(&(0x0102u16, 0x03040506u32)).unwrap();
+        writer.write_frame(&0x07u8).unwrap();
+        writer.write_frame(&[0u8; 8]).unwrap();
+        assert!(matches!(writer.write_frame_limited(&[0u8; 9], 8), Err(crate::result::WriteError::InvalidArgument)));
+        assert_eq!(&buf[..10], &[0, 0, 0, 6, 1, 2, 3, 4, 5, 6]);
+
+        let mut reader = BinaryReader::from_be_bytes(&buf);
+        assert_eq!(reader.read_frame::<(u16, u32)>().unwrap(), (0x0102, 0x03040506));
+        assert_eq!(reader.read_frame::<u8>().unwrap(), 0x07);
+        assert!(matches!(reader.read_frame::<u32>(), Err(ReadError::InvalidDataFormat(_))));
+
+        let mut reader = BinaryReader::from_be_bytes(&[0, 0, 0, 9, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(matches!(reader.read_frame_limited::<u8>(8), Err(ReadError::InvalidDataFormat(_))));
+    }
+
+    #[test]
+    fn test_skip_large_unseekable() {
+        use std::io::Read;
+        // Skipping used to allocate a buffer as large as the skipped region.
+        const SKIPPED: u64 = 64 * 1024 * 1024;
+        let source = std::io::repeat(0).take(SKIPPED).chain(&[0x01u8, 0x02][..]);
+        let mut reader = BinaryReader::new_le_with_capacity(source, 8192);
+        reader.skip(SKIPPED as usize).unwrap();
+        assert_eq!(reader.position(), SKIPPED);
+        assert_eq!(reader.u16().unwrap(), 0x0201);
+        assert!(matches!(reader.skip(1), Err(ReadError::IoError(_))));
+        assert_eq!(reader.position(), SKIPPED + 2);
+    }
+
+    #[test]
+    fn test_by_ref_take() {
+        struct Greedy;
+        impl Read for Greedy {
+            fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+                // widens the logical limit, so only the `Take` underneath can stop it
+                reader.read_partial::<u64>(8).map(|_| Greedy)
+            }
+        }
+
+        let data = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        let mut reader = BinaryReader::new_le(&data[..]);
+        reader.u8().unwrap();
+        {
+            let mut sub = reader.by_ref_take(4);
+            assert_eq!(sub.u16().unwrap(), 0x0302);
+            assert!(matches!(sub.read::<Greedy>(), Err(ReadError::IoError(_))));
+        }
+        assert_eq!(reader.position(), 3);
+
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        {
+            let mut sub = reader.by_ref_take(4);
+            assert_eq!(sub.u32().unwrap(), 0x04030201);
+            assert!(matches!(sub.u8(), Err(ReadError::NotEnoughBytes { .. })));
+        }
+        assert_eq!(reader.position(), 4);
+        assert_eq!(reader.u8().unwrap(), 5);
+
+        // an unbounded take saturates instead of overflowing the end position
+        let mut reader = BinaryReader::new_le(&data[..]);
+        reader.u8().unwrap();
+        assert_eq!(reader.by_ref_take(u64::MAX).u8().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_read_num() {
+        fn load_matrix<T: Primitive, R: BufRead>(reader: &mut BinaryReader<BigEndian, R>) -> Result<Vec<Vec<T>>> {
+            let rows = reader.u8()? as usize;
+            let cols = reader.u8()? as usize;
+            (0..rows).map(|_| (0..cols).map(|_| reader.read_num::<T>()).collect()).collect()
+        }
+
+        let data = [2, 2, 0, 1, 0, 2, 0, 3, 0xff, 0xff];
+        let mut reader = BinaryReader::from_be_bytes(&data);
+        assert_eq!(load_matrix::<u16, _>(&mut reader).unwrap(), vec![vec![1, 2], vec![3, 0xffff]]);
+
+        let data = [1, 2, 0xff, 0xff, 0xff, 0xfe, 0, 0, 0, 7];
+        let mut reader = BinaryReader::from_be_bytes(&data);
+        assert_eq!(load_matrix::<i32, _>(&mut reader).unwrap(), vec![vec![-2, 7]]);
+
+        let mut data = vec![1, 1];
+        data.extend_from_slice(&1.5f64.to_be_bytes());
+        let mut reader = BinaryReader::from_be_bytes(&data);
+        assert_eq!(load_matrix::<f64, _>(&mut reader).unwrap(), vec![vec![1.5]]);
+        assert_eq!(reader.position(), 10);
+        assert!(matches!(reader.read_num::<u16>(), Err(ReadError::NotEnoughBytes { .. })));
+    }
+
+    #[test]
+    fn test_detect_endian() {
+        let mut reader = EitherEndianReader::detect_endian(&b"II\x2a\x00\x08\x00\x00\x00"[..], EndianMarker::Tiff).unwrap();
+        assert!(reader.is_little_endian());
+        assert_eq!(reader.position(), 2);
+        assert_eq!(reader.u16().unwrap(), 42);
+        assert_eq!(reader.u32().unwrap(), 8);
+
+        let mut reader = EitherEndianReader::detect_endian(&b"MM\x00\x2a\x00\x00\x00\x08"[..], EndianMarker::Tiff).unwrap();
+        assert!(!reader.is_little_endian());
+        assert_eq!(reader.read::<u16>().unwrap(), 42);
+        assert_eq!(reader.read_num::<u32>().unwrap(), 8);
+        assert_eq!(reader.position(), 8);
+
+        let mut reader = EitherEndianReader::detect_endian(&[0xfe, 0xff, 0x00, 0x41][..], EndianMarker::Utf16Bom).unwrap();
+        assert_eq!(reader.utf16_str(crate::string::StringMode::FixedChars(1)).unwrap(), "A");
+
+        let mut reader = EitherEndianReader::detect_endian(&b"BE01"[..], EndianMarker::Custom(b"LE", b"BE")).unwrap();
+        assert_eq!(reader.u16().unwrap(), 0x3031);
+
+        assert!(matches!(
+            EitherEndianReader::detect_endian(&b"XX\x00\x2a"[..], EndianMarker::Tiff),
+            Err(ReadError::InvalidDataFormat(_))
+        ));
+        assert!(matches!(
+            EitherEndianReader::detect_endian(&b"II"[..], EndianMarker::Custom(b"L", b"BE")),
+            Err(ReadError::InvalidArgument)
+        ));
+    }
+
+    #[test]
+    fn test_detect_endian_with() {
+        fn detect(data: &[u8]) -> Result<EitherEndianReader<&[u8]>> {
+            EitherEndianReader::detect_endian_with(data, 4, |magic| match magic {
+                b"\x7fEL\x01" => Some(Endianness::Little),
+                b"\x7fEL\x02" => Some(Endianness::Big),
+                _ => None,
+            })
+        }
+        let mut reader = detect(b"\x7fEL\x02\x00\x03").unwrap();
+        assert_eq!(reader.endianness(), Endianness::Big);
+        assert_eq!(reader.position(), 4);
+        assert_eq!(reader.u16().unwrap(), 3);
+        assert_eq!(detect(b"\x7fEL\x01\x03\x00").unwrap().read::<u16>().unwrap(), 3);
+        assert!(matches!(detect(b"\x7fEL\x03\x00\x03"), Err(ReadError::InvalidDataFormat(_))));
+        assert!(detect(b"\x7fE").is_err());
+    }
+
+    #[test]
+    fn test_dyn_endian() {
+        use crate::writer::BinaryWriter;
+
+        for endianness in [Endianness::Little, Endianness::Big] {
+            let mut writer = BinaryWriter::new_dyn(Vec::new(), endianness);
+            writer.u8((endianness == Endianness::Big) as u8).unwrap();
+            writer.u16(0x0102).unwrap();
+            writer.write(&(-3i32, 1.5f64)).unwrap();
+            writer.write_num(7u64).unwrap();
+            writer.utf16_str("A", crate::string::StringMode::NullTerminated).unwrap();
+            assert_eq!(writer.position(), 27);
+            let data = writer.finish().unwrap();
+            assert_eq!(&data[1..3], if endianness == Endianness::Little { [0x02, 0x01] } else { [0x01, 0x02] });
+
+            let flag = BinaryReader::from_le_bytes(&data).u8().unwrap();
+            let mut reader = BinaryReader::new_dyn(&data[1..], if flag == 1 { Endianness::Big } else { Endianness::Little });
+            assert_eq!(reader.endianness(), endianness);
+            assert_eq!(reader.u16().unwrap(), 0x0102);
+            assert_eq!(reader.read::<(i32, f64)>().unwrap(), (-3, 1.5));
+            assert_eq!(reader.read_num::<u64>().unwrap(), 7);
+            assert_eq!(reader.utf16_str(crate::string::StringMode::NullTerminated).unwrap(), "A");
+        }
+        assert_eq!(Endianness::native() == Endianness::Little, cfg!(target_endian = "little"));
+    }
+
+    #[test]
+    fn test_either_endian_surface() {
+        let data = b"\x00\x02\x01\x02\x00\x03\x00\x04\xff\x00\x00\x00\x02hi\x05";
+        let mut reader = EitherEndianReader::Big(BinaryReader::from_be_bytes(data));
+        assert_eq!(reader.read_len_prefixed::<u16, u16>(LenPolicy::Exclusive).unwrap(), 0x0102);
+        assert_eq!(reader.read_with::<Vec<u16>, _>(2).unwrap(), [3, 4]);
+        reader.skip(1).unwrap();
+        assert_eq!(reader.read_os_string(crate::string::OsStrEncoding::Utf8Lossy).unwrap(), "hi");
+        assert_eq!((reader.position(), reader.remaining()), (15, Some(1)));
+        assert!(reader.ensure(2).is_err());
+        assert_eq!(reader.read_all::<u8>().unwrap(), [5]);
+        assert_eq!(reader.into_inner(), &[] as &[u8]);
+    }
+
+    #[test]
+    fn test_peek() {
+        let data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+        let mut reader = BinaryReader::from_be_bytes(&data);
+        assert_eq!(reader.peek_u8().unwrap(), 0x01);
+        assert_eq!(reader.peek_u16().unwrap(), 0x0102);
+        assert_eq!(reader.peek_u32().unwrap(), 0x01020304);
+        assert_eq!(reader.peek::<(u8, u16)>().unwrap(), (0x01, 0x0203));
+        assert_eq!(reader.position(), 0);
+        assert_eq!(reader.u16().unwrap(), 0x0102);
+        assert_eq!(reader.peek_u32().unwrap(), 0x03040506);
+        assert_eq!(reader.u32().unwrap(), 0x03040506);
+
+        // The limit and the end of the stream fail like the read, and the reader stays usable.
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        reader.limit_bytes = Some(3);
+        assert!(matches!(
+            reader.peek_u32(),
+            Err(ReadError::NotEnoughBytes {
+                requested: 4,
+                remaining: 3,
+                limit: 3,
+                offset: 0
+            })
+        ));
+        assert_eq!(reader.u16().unwrap(), 0x0201);
+        let mut reader = BinaryReader::new_le(BufReader::new(&data[..3]));
+        reader.skip(3).unwrap();
+        assert!(matches!(reader.peek_u8(), Err(ReadError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof));
+        assert!(reader.ensure(0).is_ok());
+
+        // A value straddling the end of the buffer is staged, and the reads that follow take it up.
+        let mut reader = BinaryReader::new_le_with_capacity(&data[..], 4);
+        reader.u16().unwrap();
+        assert_eq!(reader.peek_u16().unwrap(), 0x0403);
+        assert_eq!(reader.peek_u32().unwrap(), 0x06050403);
+        assert_eq!(reader.position(), 2);
+        assert_eq!(reader.u32().unwrap(), 0x06050403);
+        assert!(reader.read_if_remaining::<u8>().unwrap().is_none());
+
+        // Only values that fit the staging space can straddle the buffer.
+        let long: Vec<u8> = (1..=40).collect();
+        let mut reader = BinaryReader::new_le_with_capacity(&long[..], 4);
+        reader.u16().unwrap();
+        assert!(matches!(reader.peek::<[u8; 20]>(), Err(ReadError::PeekBeyondBuffer { offset: 2, buffered: 16 })));
+        assert_eq!(reader.u32().unwrap(), 0x06050403);
+
+        let mut reader = EitherEndianReader::new(&data[..], Endianness::Big);
+        assert_eq!(reader.peek_u16().unwrap(), 0x0102);
+        assert_eq!(reader.u8().unwrap(), 0x01);
+    }
+
+    #[test]
+    fn test_peek_across_refills() {
+        // The peeked u64 needs the 2 bytes left in the buffer and two more refills of 4.
+        let data: Vec<u8> = (1..=12).collect();
+        let mut reader = BinaryReader::new_le(BufReader::with_capacity(4, &data[..]));
+        reader.u16().unwrap();
+        assert_eq!(reader.peek::<u64>().unwrap(), 0x0a09080706050403);
+        assert_eq!(reader.peek_u32().unwrap(), 0x06050403);
+        assert_eq!(reader.position(), 2);
+        assert_eq!(reader.u16().unwrap(), 0x0403);
+        assert_eq!(reader.u64().unwrap(), 0x0c0b0a0908070605);
+        assert!(matches!(reader.peek_u8(), Err(ReadError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof));
+
+        // Skipping, copying and seeking account for the staged bytes.
+        let mut reader = BinaryReader::new_le(BufReader::with_capacity(4, Cursor::new(&data)));
+        reader.u16().unwrap();
+        assert_eq!(reader.peek::<u64>().unwrap(), 0x0a09080706050403);
+        assert_eq!(reader.read_at::<u8>(0).unwrap(), 0x01);
+        assert_eq!(reader.peek::<u64>().unwrap(), 0x0a09080706050403);
+        reader.skip(1).unwrap();
+        let mut copied = Vec::new();
+        reader.copy_to(&mut copied, 3).unwrap();
+        assert_eq!(copied, [0x04, 0x05, 0x06]);
+        assert_eq!(reader.peek_u32().unwrap(), 0x0a090807);
+        reader.seek_relative(2).unwrap();
+        assert_eq!(reader.u32().unwrap(), 0x0c0b0a09);
+
+        // A peek cut short by the end of the stream fails like the read, keeping what it staged.
+        let mut reader = BinaryReader::new_le(BufReader::with_capacity(4, &data[..6]));
+        reader.u16().unwrap();
+        assert!(matches!(reader.peek::<u64>(), Err(ReadError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof));
+        assert_eq!(reader.u32().unwrap(), 0x06050403);
+    }
+
+    #[test]
+    fn test_read_vec_validated() {
+        let data: Vec<u8> = (0..10u16).flat_map(|i| [i as u8 * 3, 0]).collect();
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        reader.u16().unwrap();
+        let in_range = |_: usize, value: &u16| if *value < 9 { Ok(()) } else { Err(ReadError::InvalidArgument) };
+        match reader.read_vec_validated::<u16>(9, in_range) {
+            Err(ReadError::InElement { index, offset, source }) => {
+                assert_eq!((index, offset), (2, 6));
+                assert!(matches!(*source, ReadError::InvalidArgument));
+            }
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+
+        let mut reader = BinaryReader::from_le_bytes(&data);
+        assert_eq!(reader.read_vec_validated(3, |_, _: &u16| Ok(())).unwrap(), vec![0, 3, 6]);
+        assert!(matches!(reader.read_vec_validated(10, |_, _: &u16| Ok(())), Err(ReadError::InElement { index: 7, offset: 20, .. })));
+    }
+
+    #[test]
+    fn test_as_bufread_limited() {
+        let data = b"\x05hello\x2a";
+        let mut reader = BinaryReader::new_le(&data[..]);
+        let len = reader.u8().unwrap() as u64;
+        {
+            let mut section = reader.by_ref_take(len);
+            assert_eq!(std::io::read_to_string(section.as_bufread_limited()).unwrap(), "hello");
+            assert_eq!(section.remaining(), Some(0));
+        }
+        assert_eq!(reader.position(), 6);
+        assert_eq!(reader.u8().unwrap(), 0x2a);
+
+        let mut reader = BinaryReader::from_le_bytes(&data[..]);
+        let mut head = [0u8; 2];
+        std::io::Read::read_exact(&mut reader.as_bufread_limited(), &mut head).unwrap();
+        assert_eq!(head, [0x05, b'h']);
+        assert_eq!(reader.position(), 2);
+        assert_eq!(reader.u16().unwrap(), u16::from_le_bytes(*b"el"));
     }
 
     #[test]
-    fn test_read_with() {
-        struct Test {
-            a: u8,
-            b: u16,
-            opt: Option<u8>,
+    fn test_poisoned_after_short_read() {
+        // Serves each chunk from one `read` call; an empty chunk reports EOF once.
+        struct Chunks(Vec<Vec<u8>>);
+        impl std::io::Read for Chunks {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if self.0.is_empty() {
+                    return Ok(0);
+                }
+                let chunk = self.0.remove(0);
+                buf[..chunk.len()].copy_from_slice(&chunk);
+                Ok(chunk.len())
+            }
         }
-        impl ReadWith<u8> for Test {
-            fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, arg: u8) -> Result<Self> {
-                let (a, b) = reader.read::<(u8, u16)>()?;
-                Ok(Test { a, b, opt: Some(arg) })
+
+        let mut reader = BinaryReader::new_le_with_capacity(Chunks(vec![vec![1, 2], vec![], vec![0xaa]]), 16);
+        assert!(matches!(reader.u32(), Err(ReadError::IoError(_))));
+        assert!(reader.is_poisoned());
+        assert!(matches!(reader.u8(), Err(ReadError::Desynchronized { at_offset: 0 })));
+        assert!(matches!(reader.skip(1), Err(ReadError::Desynchronized { .. })));
+        reader.clear_poison();
+        assert_eq!(reader.u8().unwrap(), 0xaa);
+
+        let mut reader = BinaryReader::from_le_bytes(&[1, 2]);
+        assert!(matches!(reader.u32(), Err(ReadError::NotEnoughBytes { .. })));
+        assert!(!reader.is_poisoned());
+        assert_eq!(reader.u16().unwrap(), 0x0201);
+    }
+
+    #[test]
+    fn test_read_array_without_default() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Debug)]
+        struct Entry(u8);
+        impl Read for Entry {
+            fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+                match reader.u8()? {
+                    0xff => Err(ReadError::InvalidDataFormat("reserved entry".into())),
+                    value => Ok(Entry(value)),
+                }
             }
         }
-        let data = [0x01, 0x02, 0x03, 0x04];
-        let mut reader = BinaryReader::from_le_bytes(&data);
-        let test: Test = reader.read_with(0x10).unwrap();
-        assert_eq!(test.a, 0x01);
-        assert_eq!(test.b, 0x302);
-        assert_eq!(test.opt, Some(0x10));
+        impl Drop for Entry {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let mut reader = BinaryReader::from_le_bytes(&[1, 2, 3, 4]);
+        let entries: [Entry; 3] = reader.read().unwrap();
+        assert_eq!(entries.iter().map(|entry| entry.0).collect::<Vec<_>>(), vec![1, 2, 3]);
+        drop(entries);
+        assert_eq!(DROPS.swap(0, Ordering::SeqCst), 3);
+
+        let mut reader = BinaryReader::from_le_bytes(&[1, 2, 0xff, 4]);
+        assert!(matches!(reader.read_array::<Entry, 4>(), Err(ReadError::InvalidDataFormat(_))));
+        assert_eq!(DROPS.load(Ordering::SeqCst), 2);
     }
 
     #[test]
-    fn test_read_vec() {
-        let data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
-        let mut reader = BinaryReader::from_le_bytes(&data);
-        let vec: Vec<u16> = reader.read_with(3).unwrap();
-        assert_eq!(vec, vec![0x0201, 0x0403, 0x0605]);
+    fn test_read_slice_containers() {
+        struct Scaled(u16);
+        impl ReadWith<&u16> for Scaled {
+            fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, factor: &u16) -> Result<Self> {
+                Ok(Scaled(reader.u8()? as u16 * factor))
+            }
+        }
+
+        let data = [0u8, 1, 0, 2, 0, 3, 4, 5];
+        let mut reader = BinaryReader::from_be_bytes(&data);
+        let boxed: Box<[u16]> = reader.read_with(2).unwrap();
+        assert_eq!(&*boxed, &[1, 2]);
+        let shared: Arc<[u16]> = reader.read_with(1).unwrap();
+        assert_eq!(&*shared, &[3]);
+        let empty: Box<[u16]> = reader.read_with(0).unwrap();
+        assert!(empty.is_empty());
+        let empty: Arc<[u32]> = reader.read_with(0).unwrap();
+        assert!(empty.is_empty());
+        let scaled: Box<[Scaled]> = reader.read_with((2, &10)).unwrap();
+        assert_eq!(scaled.iter().map(|s| s.0).collect::<Vec<_>>(), vec![40, 50]);
+        assert_eq!(reader.remaining(), Some(0));
     }
 
     #[test]
-    fn test_read_if() {
-        let data = [0x01, 0x02, 0x03, 0x04];
+    fn test_seek_record() {
+        type Record = (u32, u16);
+        let mut table = Vec::new();
+        let mut writer = crate::writer::BinaryWriter::new_le(&mut table);
+        writer.utf8_str("HEAD", crate::string::StringMode::FixedChars(4)).unwrap();
+        for index in 0..100u32 {
+            writer.write(&(index * 3, index as u16)).unwrap();
+        }
+
+        let mut reader = BinaryReader::new_le(Cursor::new(&table));
+        reader.magic(b"HEAD").unwrap();
+        reader.seek_record::<Record>(4, 42).unwrap();
+        assert_eq!(reader.position(), 4 + 42 * 6);
+        assert_eq!(reader.read::<Record>().unwrap(), (126, 42));
+        reader.seek_record::<Record>(4, 7).unwrap();
+        assert_eq!(reader.read::<Record>().unwrap(), (21, 7));
+        reader.skip_records::<Record>(90).unwrap();
+        assert_eq!(reader.read::<Record>().unwrap(), (294, 98));
+
+        let mut reader = BinaryReader::new_le(Cursor::new(&table)).with_base_offset(1000);
+        reader.seek_record::<Record>(1004, 99).unwrap();
+        assert_eq!(reader.read::<Record>().unwrap(), (297, 99));
+        assert!(reader.u8().is_err());
+        reader.seek_record::<Record>(1004, 0).unwrap();
+        assert_eq!(reader.read::<Record>().unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn test_seek_file() {
+        let dir = std::env::temp_dir().join(format!("rwbin-seek-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("archive.bin");
+        let mut data = Vec::new();
+        let mut writer = crate::writer::BinaryWriter::new_le(&mut data);
+        writer.u32(1 << 20).unwrap();
+        writer.reserved(0xee, (1 << 20) - 4).unwrap();
+        writer.u32(0xC0FFEE).unwrap();
+        std::fs::write(&path, &data).unwrap();
+
+        let file = std::fs::File::open(&path).unwrap();
+        let mut reader = BinaryReader::new_le(BufReader::with_capacity(64, file));
+        reader.limit_bytes = Some(data.len() as u64);
+        let section = reader.u32().unwrap();
+        reader.seek_relative(i64::from(section) - 4).unwrap();
+        assert_eq!(reader.position(), 1 << 20);
+        assert_eq!(reader.remaining(), Some(4));
+        assert_eq!(reader.u32().unwrap(), 0xC0FFEE);
+        reader.seek_relative(-8).unwrap();
+        assert_eq!(reader.u32().unwrap(), 0xeeeeeeee);
+        assert!(matches!(reader.seek_relative(5), Err(ReadError::PositionBeyondLimit { position, limit }) if position == limit + 1));
+        assert!(matches!(reader.seek_relative(-(1 << 21)), Err(ReadError::InvalidArgument)));
+        reader.seek_to(2).unwrap();
+        assert_eq!(reader.u16().unwrap(), 0x0010);
+        assert_eq!(reader.position(), 4);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_into_reuses_allocations() {
+        use crate::string::StringMode;
+
+        #[derive(Default)]
+        struct Frame {
+            id: u16,
+            samples: Vec<u16>,
+            label: String,
+        }
+        impl ReadInto for Frame {
+            fn read_into<E: Endian, R: BufRead>(&mut self, reader: &mut BinaryReader<E, R>) -> Result<()> {
+                reader.read_into(&mut self.id)?;
+                let count = reader.u8()? as usize;
+                reader.read_into_with(&mut self.samples, count)?;
+                reader.read_into_with(&mut self.label, StringMode::FixedChars(4))
+            }
+        }
+
+        let data = [1, 0, 3, 10, 0, 11, 0, 12, 0, b'a', b'b', 0, 0, 2, 0, 2, 20, 0, 21, 0, b'w', b'x', b'y', b'z'];
         let mut reader = BinaryReader::from_le_bytes(&data);
-        let a: Option<u8> = reader.read_with(true).unwrap();
-        assert_eq!(a, Some(0x01));
-        let b: Option<u16> = reader.read_with(false).unwrap();
-        assert_eq!(b, None);
-        let c: Option<u16> = reader.read_with(true).unwrap();
-        assert_eq!(c, Some(0x0302));
-        let _ = reader.u8().unwrap();
-        assert!(matches!(reader.check_size(1), Err(ReadError::NotEnoughBytes { .. })));
+        let mut frame = Frame::default();
+        reader.read_into(&mut frame).unwrap();
+        assert_eq!((frame.id, frame.samples.as_slice(), frame.label.as_str()), (1, &[10, 11, 12][..], "ab"));
+        let samples = (frame.samples.as_ptr(), frame.samples.capacity());
+
+        reader.read_into(&mut frame).unwrap();
+        assert_eq!((frame.id, frame.samples.as_slice(), frame.label.as_str()), (2, &[20, 21][..], "wxyz"));
+        assert_eq!((frame.samples.as_ptr(), frame.samples.capacity()), samples);
     }
 
     #[test]
-    fn test_values() {
-        let data = [0x01, 0x02, 0x03, 0x04];
+    fn test_read_vec_prefixed_chunked() {
+        use crate::writer::{BinaryWriter, PrefixOverflow};
+        let chunked = PrefixOverflow::Chunked { continuation_flag: 0xcc };
+        for len in [0usize, 255, 256, 600] {
+            let values: Vec<u32> = (0..len as u32).map(|v| v * 7).collect();
+            let mut buf = Vec::new();
+            BinaryWriter::new_be(&mut buf).write_vec_prefixed::<u8, u32>(&values, chunked).unwrap();
+            let mut reader = BinaryReader::from_be_bytes(&buf);
+            assert_eq!(reader.read_vec_prefixed_chunked::<u8, u32>(0xcc).unwrap(), values);
+            assert_eq!(reader.remaining(), Some(0));
+        }
+
+        let mut reader = BinaryReader::from_be_bytes(&[1, 9, 0x55]);
+        assert!(matches!(reader.read_vec_prefixed_chunked::<u8, u8>(0xcc), Err(ReadError::InvalidDataFormat(_))));
+    }
+
+    #[test]
+    fn test_value_mismatch() {
+        let data = [0x01, 0x07, 0x00];
         let mut reader = BinaryReader::from_le_bytes(&data);
-        reader.values::<u8>(&[0x01, 0x02]).unwrap();
-        reader.values::<u8>(&[0x03, 0x04]).unwrap();
-        assert!(matches!(reader.check_size(1), Err(ReadError::NotEnoughBytes { .. })));
+        reader.value(&1u8).unwrap();
+        let err = reader.value(&5u16).unwrap_err();
+        assert!(matches!(&err, ReadError::UnexpectedValue { expected, found, offset: 1 } if expected == "5" && found == "7"));
+        assert_eq!(err.to_string(), "unexpected value at offset 0x1 (1): expected 5, found 7");
     }
 
     #[test]
-    fn test_read_as_be() {
-        let data = [0x01, 0x02, 0x03, 0x04];
+    fn test_reserved_report() {
+        let mut data = [0u8; 24];
+        data[3] = 0x5e;
+        data[17] = 0x01;
         let mut reader = BinaryReader::from_le_bytes(&data);
-        let a: u16 = reader.read_as_be().unwrap();
-        assert_eq!(a, 0x0102);
-        let b: u16 = reader.read().unwrap();
-        assert_eq!(b, 0x0403);
-        assert!(matches!(reader.check_size(1), Err(ReadError::NotEnoughBytes { .. })));
+        reader.skip(1).unwrap();
+        assert_eq!(reader.reserved_report::<20>(0x00).unwrap(), vec![(2, 0x5e), (16, 0x01)]);
+        assert_eq!(reader.position(), 21);
+        assert_eq!(reader.reserved_report_len(3, 0x00).unwrap(), vec![]);
+        assert!(matches!(reader.reserved_report_len(1, 0x00), Err(ReadError::NotEnoughBytes { .. })));
     }
 
     #[test]
-    fn test_read_as_be_with() {
-        struct Test {
-            a: u8,
-            b: u16,
-            opt: Option<u8>,
+    fn test_scan_for() {
+        let data = b"MAGC\x01\x02garbageMAGC\x03";
+        let mut reader = BinaryReader::new_be(std::io::Cursor::new(&data[..]));
+        assert_eq!(reader.scan_for(b"MAGC", None).unwrap(), 0);
+        reader.magic(b"MAGC").unwrap();
+        assert_eq!(reader.scan_for(b"MAGC", None).unwrap(), 9);
+        assert_eq!(reader.position(), 13);
+        reader.magic(b"MAGC").unwrap();
+
+        // with a 4-byte buffer the second match spans two refills
+        let mut reader = BinaryReader::new_be(std::io::BufReader::with_capacity(4, std::io::Cursor::new(&data[4..])));
+        assert_eq!(reader.scan_for(b"MAGC", None).unwrap(), 9);
+        assert_eq!(reader.position(), 9);
+        reader.magic(b"MAGC").unwrap();
+        assert_eq!(reader.u8().unwrap(), 3);
+
+        let mut reader = BinaryReader::new_be(std::io::BufReader::with_capacity(4, std::io::Cursor::new(&data[..])));
+        reader.u8().unwrap();
+        assert!(matches!(reader.scan_for(b"MAGC", Some(8)), Err(ReadError::PatternNotFound { offset: 1, scanned: 12 })));
+        assert!(matches!(reader.scan_for(b"ZZ", None), Err(ReadError::PatternNotFound { offset: 13, scanned: 5 })));
+        assert!(matches!(reader.scan_for(b"", None), Err(ReadError::InvalidArgument)));
+    }
+
+    #[test]
+    fn test_error_offsets() {
+        let err = ReadError::unexpected_value(&1u8, &2u8, 38193245);
+        assert_eq!(err.to_string(), "unexpected value at offset 0x246C85D (38193245): expected 1, found 2");
+        assert_eq!(err.offset(), Some(0x246c85d));
+
+        let nested = ReadError::InElement {
+            index: 2,
+            offset: 16,
+            source: Box::new(err),
+        };
+        assert_eq!(nested.offset(), Some(38193245));
+        assert_eq!(nested.to_string(), "element 2 at offset 0x10 (16)");
+        let source = std::error::Error::source(&nested).unwrap();
+        assert_eq!(source.to_string(), "unexpected value at offset 0x246C85D (38193245): expected 1, found 2");
+        let nested = ReadError::InElement {
+            index: 2,
+            offset: 16,
+            source: Box::new(ReadError::InvalidArgument),
+        };
+        assert_eq!(nested.offset(), Some(16));
+        assert_eq!(ReadError::TimedOut.offset(), None);
+
+        let mut reader = BinaryReader::from_le_bytes(&[1, 2, 3]);
+        reader.u16().unwrap();
+        let err = reader.u32().unwrap_err();
+        assert_eq!(err.offset(), Some(2));
+    }
+
+    #[test]
+    fn test_no_progress() {
+        /// Takes up no bytes at all, like a unit struct or a buggy parser.
+        #[derive(Debug, PartialEq)]
+        struct Nothing;
+
+        impl Read for Nothing {
+            fn read<E: Endian, R: BufRead>(_: &mut BinaryReader<E, R>) -> Result<Self> {
+                Ok(Nothing)
+            }
         }
-        impl ReadWith<u8> for Test {
-            fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, arg: u8) -> Result<Self> {
-                let (a, b) = reader.read::<(u8, u16)>()?;
-                Ok(Test { a, b, opt: Some(arg) })
+
+        let mut reader = BinaryReader::from_le_bytes(&[1, 2, 3, 4]);
+        assert_eq!(reader.read_all::<u16>().unwrap(), vec![0x0201, 0x0403]);
+        assert_eq!(reader.read_all::<u16>().unwrap(), vec![]);
+
+        let mut reader = BinaryReader::new_le(std::io::BufReader::new(&[1, 2, 3][..]));
+        assert_eq!(reader.read_all::<u8>().unwrap(), vec![1, 2, 3]);
+
+        let mut reader = BinaryReader::from_le_bytes(&[1, 2]);
+        assert!(matches!(reader.read_all::<Nothing>(), Err(ReadError::InvalidDataFormat(msg)) if msg == "parser made no progress"));
+        assert!(matches!(reader.read_while(|_: &[u8; 0]| Some(())), Err(ReadError::InvalidDataFormat(_))));
+        assert_eq!(reader.read_while(|_: &[u8; 0]| None::<()>).unwrap(), vec![]);
+        assert_eq!(reader.position(), 0);
+    }
+
+    #[test]
+    fn test_read_with_cloned_args() {
+        struct Names(Vec<&'static str>);
+
+        /// Borrows from its argument, so it only implements `ReadWith<&'h Names>` for its own `'h`;
+        /// a `for<'a> ReadWith<&'a Names>` bound could never be met.
+        #[derive(Debug, PartialEq)]
+        struct Entry<'h> {
+            name: &'h str,
+            value: u8,
+        }
+
+        impl<'h> ReadWith<&'h Names> for Entry<'h> {
+            fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, names: &'h Names) -> Result<Self> {
+                let name = names.0.get(reader.u8()? as usize).ok_or(ReadError::InvalidArgument)?;
+                Ok(Entry { name, value: reader.u8()? })
             }
         }
-        let data = [0x01, 0x02, 0x03, 0x04];
+
+        #[derive(Clone, Copy)]
+        struct Version(u8);
+
+        impl ReadWith<Version> for u32 {
+            fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, version: Version) -> Result<Self> {
+                if version.0 >= 2 { reader.u32() } else { reader.u16().map(u32::from) }
+            }
+        }
+
+        // a generic helper needs no higher-ranked bound either
+        fn read_table<T: ReadWith<A>, A: Clone>(reader: &mut BinaryReader<LittleEndian, &[u8]>, arg: A) -> Result<Vec<T>> {
+            let len = reader.u8()? as usize;
+            reader.read_with((len, arg))
+        }
+
+        let names = Names(vec!["width", "height"]);
+        let data = [2, 1, 20, 0, 10, 2, 1, 0, 2, 0];
         let mut reader = BinaryReader::from_le_bytes(&data);
-        let test: Test = reader.read_as_be_with(0x10).unwrap();
-        assert_eq!(test.a, 0x01);
-        assert_eq!(test.b, 0x0203);
-        assert_eq!(test.opt, Some(0x10));
+        let entries: Vec<Entry> = read_table(&mut reader, &names).unwrap();
+        assert_eq!(entries, [Entry { name: "height", value: 20 }, Entry { name: "width", value: 10 }]);
+        reader.u8().unwrap();
+        let sizes: Box<[u32]> = reader.read_with((2, Version(1))).unwrap();
+        assert_eq!(&*sizes, [1, 2]);
     }
 
     #[test]
-    fn test_read_tuple() {
-        let data = [0x01, 0x02, 0x03, 0x04];
+    fn test_copy_to() {
+        let mut data = vec![0, 0x10, 0, 0];
+        data.extend((0..0x1000u32).map(|i| i as u8));
+        data.extend_from_slice(&[0xcd, 0xab]);
+
+        let mut reader = BinaryReader::new_le(std::io::BufReader::with_capacity(100, data.as_slice()));
+        let len = reader.u32().unwrap();
+        let mut sink = Vec::new();
+        reader.copy_to(&mut sink, len as u64).unwrap();
+        assert_eq!(sink, &data[4..4 + 0x1000]);
+        assert_eq!(reader.position(), 4 + 0x1000);
+        assert_eq!(reader.u16().unwrap(), 0xabcd);
+
+        let mut reader = BinaryReader::from_le_bytes(&data[..8]);
+        assert!(matches!(reader.copy_to(&mut Vec::new(), 9), Err(ReadError::NotEnoughBytes { .. })));
+        let mut reader = BinaryReader::new_le(&data[..8]);
+        let mut sink = Vec::new();
+        assert!(matches!(reader.copy_to(&mut sink, 9), Err(ReadError::IoError(_))));
+        assert_eq!(sink, &data[..8]);
+        assert!(reader.is_poisoned());
+        assert!(matches!(
+            BinaryReader::from_le_bytes(&data).copy_to(&mut sink, u64::MAX),
+            Err(ReadError::NotEnoughBytes { requested: usize::MAX, .. })
+        ));
+
+        // `dst` fails on the first chunk: nothing was consumed
+        let mut reader = BinaryReader::new_le(std::io::BufReader::with_capacity(100, data.as_slice()));
+        let mut out = [0u8; 10];
+        assert!(matches!(reader.copy_to(&mut &mut out[..], 200), Err(ReadError::IoError(e)) if e.kind() == std::io::ErrorKind::WriteZero));
+        assert!(!reader.is_poisoned());
+        assert_eq!(reader.position(), 0);
+        // ... and on the second: the first is consumed
+        let mut out = [0u8; 150];
+        assert!(matches!(reader.copy_to(&mut &mut out[..], 200), Err(ReadError::IoError(_))));
+        assert_eq!(reader.position(), 100);
+        assert!(matches!(reader.u8(), Err(ReadError::Desynchronized { at_offset: 100 })));
+    }
+
+    #[test]
+    fn test_read_partial_endian_views() {
+        struct Window(u32);
+
+        impl Read for Window {
+            fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+                let len = reader.u8()? as usize;
+                Ok(Window(reader.read_partial(len)?))
+            }
+        }
+
+        let data = [0x02, 0x00, 0x00, 0x00, 0x01, 0x04, 0x00, 0x00, 0x00, 0x01];
+        let mut reader = BinaryReader::new_le(&data[..]);
+        assert!(matches!(
+            reader.read_as_be::<Window>(),
+            Err(ReadError::NotEnoughBytes {
+                requested: 4,
+                remaining: 2,
+                offset: 1,
+                ..
+            })
+        ));
+        assert_eq!(reader.limit_bytes, None);
+        assert_eq!(reader.position(), 1);
+
+        let mut reader = BinaryReader::new_le(&data[5..]);
+        assert_eq!(reader.read_as_be::<Window>().unwrap().0, 1);
+        let mut reader = BinaryReader::new_be(&data[5..]);
+        assert_eq!(reader.read_as_le::<Window>().unwrap().0, 0x0100_0000);
+
+        let mut reader = BinaryReader::new_be(&data[..]);
+        reader.limit_bytes = Some(3);
+        assert!(matches!(reader.read_as_le::<u32>(), Err(ReadError::NotEnoughBytes { requested: 4, remaining: 3, .. })));
+        assert!(matches!(reader.read_partial::<Window>(8), Err(ReadError::NotEnoughBytes { requested: 4, remaining: 2, .. })));
+        assert_eq!(reader.limit_bytes, Some(3));
+        assert_eq!(reader.position(), 1);
+        assert!(matches!(reader.read_as_le_with::<Vec<u16>, _>(2), Err(ReadError::NotEnoughBytes { requested: 2, remaining: 0, .. })));
+        assert_eq!(reader.position(), 3);
+    }
+
+    #[test]
+    fn test_small_buf_size() {
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+        for size in [0, 100, DEFAULT_SMALL_BUF_SIZE, 4096, MAX_SMALL_BUF_SIZE] {
+            for len in [size.saturating_sub(1), size, size + 1, 3000] {
+                let mut reader = BinaryReader::from_le_bytes(&data).with_small_buf_size(size);
+                reader.u8().unwrap();
+                let read = reader.read_from_slice(len, |bytes| Ok(bytes.to_vec())).unwrap();
+                assert_eq!(read, &data[1..1 + len], "threshold {size}, length {len}");
+                assert_eq!(reader.position(), 1 + len as u64);
+            }
+        }
+
+        let mut reader = BinaryReader::from_le_bytes(&data[..600]).with_small_buf_size(1 << 20);
+        assert_eq!(reader.small_buf_size, MAX_SMALL_BUF_SIZE);
+        assert!(matches!(reader.read_from_slice(601, |_| Ok(())), Err(ReadError::NotEnoughBytes { requested: 601, .. })));
+        let mut reader = BinaryReader::new_le(&data[..600]).with_small_buf_size(4096);
+        assert!(matches!(reader.read_from_slice(601, |_| Ok(())), Err(ReadError::IoError(_))));
+        assert!(reader.is_poisoned());
+    }
+
+    #[test]
+    fn test_from_cursor() {
+        use std::io::Read as _;
+
+        let mut cursor = Cursor::new(vec![1, 2, 3, 0, 0, 0, 0, 4, 5, 6]);
+        let mut head = [0u8; 2];
+        cursor.read_exact(&mut head).unwrap();
+
+        let mut reader = BinaryReader::from_cursor_le(&mut cursor);
+        assert_eq!(reader.position(), 2);
+        assert_eq!(reader.remaining(), Some(8));
+        assert_eq!(reader.u16().unwrap(), 3);
+        assert_eq!(reader.position(), 4);
+        reader.into_inner();
+        assert_eq!(cursor.position(), 4);
+
+        cursor.read_exact(&mut head).unwrap();
+        assert_eq!(head, [0, 0]);
+        {
+            let mut reader = BinaryReader::from_cursor_be(&mut cursor);
+            assert_eq!(reader.u16().unwrap(), 4);
+            assert_eq!(reader.position(), 8);
+            assert!(matches!(
+                reader.u32(),
+                Err(ReadError::NotEnoughBytes {
+                    requested: 4,
+                    remaining: 2,
+                    offset: 8,
+                    ..
+                })
+            ));
+        }
+        assert_eq!(cursor.position(), 8);
+        cursor.read_exact(&mut head).unwrap();
+        assert_eq!(head, [5, 6]);
+
+        let mut reader = BinaryReader::from_cursor_le(&mut cursor);
+        assert!(matches!(reader.u8(), Err(ReadError::NotEnoughBytes { remaining: 0, .. })));
+    }
+
+    #[test]
+    fn test_max_depth() {
+        #[derive(Debug)]
+        struct Node {
+            value: u8,
+            child: Option<Box<Node>>,
+        }
+
+        impl Read for Node {
+            fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+                let value = reader.u8()?;
+                let child = match reader.u8()? {
+                    0 => None,
+                    _ => Some(Box::new(reader.read()?)),
+                };
+                Ok(Node { value, child })
+            }
+        }
+
+        fn depth(node: &Node) -> usize {
+            1 + node.child.as_deref().map_or(0, depth)
+        }
+
+        let mut shallow = [7, 1].repeat(9);
+        shallow.extend([7, 0]);
+        let mut reader = BinaryReader::from_le_bytes(&shallow);
+        reader.set_max_depth(10);
+        let node: Node = reader.read().unwrap();
+        assert_eq!((node.value, depth(&node)), (7, 10));
+        let mut reader = BinaryReader::from_le_bytes(&shallow);
+        reader.set_max_depth(9);
+        assert!(matches!(reader.read_partial::<Node>(20), Err(ReadError::DepthLimitExceeded { max_depth: 9, offset: 18 })));
+        let mut reader = BinaryReader::from_le_bytes(&shallow[2..]);
+        reader.set_max_depth(9);
+        assert_eq!(depth(&reader.read_partial::<Node>(18).unwrap()), 9);
+        assert_eq!(depth(&BinaryReader::from_le_bytes(&shallow[2..]).read::<Node>().unwrap()), 9);
+
+        // deep enough to overflow the stack without a limit
+        let hostile = [0, 1].repeat(1_000_000);
+        let mut reader = BinaryReader::from_le_bytes(&hostile);
+        reader.set_max_depth(64);
+        let err = reader.read::<Node>().unwrap_err();
+        assert!(matches!(err, ReadError::DepthLimitExceeded { max_depth: 64, offset: 128 }), "{err}");
+        assert_eq!(err.offset(), Some(128));
+    }
+
+    #[test]
+    fn test_len_policy() {
+        use crate::writer::BinaryWriter;
+
+        let payload = (0x0102u16, 0x03u8);
+        let cases = [
+            (LenPolicy::Exclusive, vec![0, 3, 1, 2, 3]),
+            (LenPolicy::InclusiveOfPrefix, vec![0, 5, 1, 2, 3]),
+            (LenPolicy::InclusiveOfHeader(6), vec![0, 9, 1, 2, 3]),
+        ];
+        for (policy, expected) in cases {
+            let mut buf = Vec::new();
+            BinaryWriter::new_be(&mut buf).write_len_prefixed::<u16, _>(&payload, policy).unwrap();
+            assert_eq!(buf, expected);
+            let mut reader = BinaryReader::from_be_bytes(&buf);
+            assert_eq!(reader.read_len_prefixed::<u16, (u16, u8)>(policy).unwrap(), payload);
+            assert_eq!(reader.remaining(), Some(0));
+        }
+
+        // a chunk whose u32 length counts its 4-byte tag and itself
+        let mut reader = BinaryReader::from_be_bytes(b"DATA\x00\x00\x00\x0a\xaa\xbb");
+        reader.u32().unwrap();
+        assert_eq!(reader.read_len_prefixed::<u32, u16>(LenPolicy::InclusiveOfHeader(8)).unwrap(), 0xaabb);
+
+        for (policy, stored) in [(LenPolicy::InclusiveOfPrefix, 0u8), (LenPolicy::InclusiveOfHeader(4), 3)] {
+            let data = [stored, 0, 0];
+            let mut reader = BinaryReader::from_be_bytes(&data);
+            let err = reader.read_len_prefixed::<u8, u8>(policy).unwrap_err();
+            assert!(matches!(err, ReadError::InvalidDataFormat(msg) if msg.contains("smaller than")));
+        }
+        let mut reader = BinaryReader::from_be_bytes(&[3, 1, 2, 3]);
+        assert!(matches!(reader.read_len_prefixed::<u8, u16>(LenPolicy::Exclusive), Err(ReadError::InvalidDataFormat(_))));
+        let overflow = BinaryWriter::new_be(Vec::new()).write_len_prefixed::<u8, _>(&[0u8; 254], LenPolicy::InclusiveOfHeader(2));
+        assert!(matches!(overflow, Err(crate::result::WriteError::InvalidArgument)));
+    }
+
+    #[test]
+    fn test_read_indexed_iter() {
+        #[derive(Debug, PartialEq)]
+        struct Prefixed(Vec<u8>);
+
+        impl Read for Prefixed {
+            fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+                let len = reader.u8()? as usize;
+                reader.read_with(len).map(Prefixed)
+            }
+        }
+
+        let mut data = Vec::new();
+        let mut writer = crate::writer::BinaryWriter::new_be(&mut data);
+        for i in 0..10u8 {
+            writer.u8(i).unwrap();
+            writer.write(&vec![i; i as usize]).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let mut reader = BinaryReader::new_be(Cursor::new(&data[..]));
+        let index: Vec<_> = reader.read_indexed_iter::<Prefixed>().map(|entry| entry.unwrap()).collect();
+        assert_eq!(index.len(), 10);
+        assert_eq!(index[0].0, 0);
+        assert_eq!(index[9].0, (0..9).map(|i| 1 + i as u64).sum::<u64>());
+        for (offset, record) in [&index[3], &index[7], &index[0]] {
+            assert_eq!(reader.read_at::<Prefixed>(*offset).unwrap(), *record);
+        }
+
+        let mut reader = BinaryReader::from_be_bytes(&data[..data.len() - 1]);
+        let results: Vec<_> = reader.read_indexed_iter::<Prefixed>().collect();
+        assert_eq!(results.len(), 10);
+        assert!(results[..9].iter().all(Result::is_ok));
+        assert!(matches!(results[9], Err(ReadError::NotEnoughBytes { .. })));
+    }
+
+    #[test]
+    fn test_optional_tail() {
+        #[derive(Debug, PartialEq)]
+        struct Record {
+            id: u16,
+            flags: u8,
+            scale: Option<f32>,
+            extra: u32,
+        }
+
+        impl Read for Record {
+            fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> Result<Self> {
+                Ok(Record {
+                    id: reader.u16()?,
+                    flags: reader.u8()?,
+                    scale: reader.read_if_remaining()?,
+                    extra: reader.read_remaining_or_default()?,
+                })
+            }
+        }
+
+        // v1 record (3 bytes), v2 record (11 bytes), then a v2 record cut off in its tail
+        let data = [3, 1, 0, 0xff, 11, 2, 0, 0, 0, 0, 0, 0x3f, 7, 0, 0, 0, 9, 3, 0, 0, 0, 0, 0x80, 0x3f, 1, 0];
         let mut reader = BinaryReader::from_le_bytes(&data);
-        let (a, b): (u8, u16) = reader.read().unwrap();
-        assert_eq!(a, 0x01);
-        assert_eq!(b, 0x0302);
+        let mut records = Vec::new();
+        for _ in 0..2 {
+            let len = reader.u8().unwrap() as usize;
+            records.push(reader.read_partial::<Record>(len).unwrap());
+        }
+        assert_eq!(
+            records[0],
+            Record {
+                id: 1,
+                flags: 0xff,
+                scale: None,
+                extra: 0
+            }
+        );
+        assert_eq!(
+            records[1],
+            Record {
+                id: 2,
+                flags: 0,
+                scale: Some(0.5),
+                extra: 7
+            }
+        );
+        let len = reader.u8().unwrap() as usize;
+        assert!(matches!(reader.read_partial::<Record>(len), Err(ReadError::NotEnoughBytes { requested: 4, remaining: 2, .. })));
+
+        let mut reader = BinaryReader::new_be(&[0, 9][..]);
+        assert_eq!(reader.read_if_remaining::<u16>().unwrap(), Some(9));
+        assert_eq!(reader.read_if_remaining::<u16>().unwrap(), None);
+        assert_eq!(reader.read_remaining_or_default::<u64>().unwrap(), 0);
     }
 
     #[test]
-    fn test_read_array() {
-        let data = [0x01, 0x02, 0x03, 0x04];
+    fn test_read_from_slice_panic_poisons() {
+        let data = [0u8; 2000];
+        for len in [3, 1000] {
+            let mut reader = BinaryReader::from_le_bytes(&data);
+            reader.u16().unwrap();
+            let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| reader.read_from_slice::<()>(len, |_| panic!("bad field"))));
+            assert!(panicked.is_err());
+            assert_eq!(reader.position(), 2 + len as u64);
+            assert!(matches!(reader.u8(), Err(ReadError::Desynchronized { at_offset: 2 })));
+            reader.clear_poison();
+            assert_eq!(reader.u8().unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn test_trailing_checksum() {
+        use crate::{
+            checksum::{ChecksumKind, ChecksumWriter},
+            writer::BinaryWriter,
+        };
+
+        for kind in [ChecksumKind::Crc32, ChecksumKind::Adler32] {
+            let mut writer = BinaryWriter::new_le(ChecksumWriter::new(Vec::new(), kind)).with_buffer_capacity(8);
+            writer.u32(0x4653_4952).unwrap();
+            writer.utf8_str("payload", crate::string::StringMode::NullTerminated).unwrap();
+            writer.f64(-0.5).unwrap();
+            let mut data = writer.finish_with_checksum().unwrap();
+            assert_eq!(data[20..], kind.of(&data[..20]).to_le_bytes());
+
+            let mut reader = BinaryReader::from_le_bytes(&data);
+            reader.verify_trailing_checksum(kind).unwrap();
+            assert_eq!(reader.remaining(), Some(0));
+
+            data[9] ^= 0x10;
+            let mut reader = BinaryReader::from_le_bytes(&data);
+            let err = reader.verify_trailing_checksum(kind).unwrap_err();
+            assert!(matches!(err, ReadError::ChecksumMismatch { kind: k, computed, offset: 20, .. } if k == kind && computed == kind.of(&data[..20])));
+        }
+
+        let err = BinaryReader::new_le(&[1, 2, 3, 4][..]).verify_trailing_checksum(ChecksumKind::Crc32).unwrap_err();
+        assert!(matches!(err, ReadError::InvalidArgument));
+        let err = BinaryReader::from_le_bytes(&[1, 2]).verify_trailing_checksum(ChecksumKind::Crc32).unwrap_err();
+        assert!(matches!(err, ReadError::NotEnoughBytes { .. }));
+        let err = ReadError::ChecksumMismatch {
+            kind: ChecksumKind::Crc32,
+            stored: 0xDEAD_BEEF,
+            computed: 0x0012_3456,
+            offset: 0x40,
+        };
+        assert_eq!(err.to_string(), "CRC-32 mismatch at offset 0x40 (64): stored 0xDEADBEEF, computed 0x00123456");
+    }
+
+    #[test]
+    fn test_int_width() {
+        use crate::{result::WriteError, writer::BinaryWriter};
+
+        let widths = [IntWidth::U8, IntWidth::U16, IntWidth::U32, IntWidth::U64];
+        let mut writer = BinaryWriter::new_be(Vec::new());
+        for width in widths {
+            writer.write_uint(width.max(), width).unwrap();
+            writer.write_uint(0x12, width).unwrap();
+        }
+        let data = writer.finish().unwrap();
+        assert_eq!(data.len(), widths.iter().map(|width| 2 * width.size()).sum::<usize>());
+        assert_eq!(data[..6], [0xff, 0x12, 0xff, 0xff, 0x00, 0x12]);
+
         let mut reader = BinaryReader::from_be_bytes(&data);
-        let array: [u16; 2] = reader.read().unwrap();
-        assert_eq!(array, [0x0102, 0x0304]);
+        for width in widths {
+            assert_eq!(reader.read_uint(width).unwrap(), width.max());
+            assert_eq!(reader.read_with::<u64, _>(width).unwrap(), 0x12);
+        }
+
+        // offsets are 4 bytes wide when the header's small flag is set
+        let mut reader = BinaryReader::from_le_bytes(&[1, 0x10, 0, 0, 0, 0x20, 0, 0, 0]);
+        let width = if reader.u8().unwrap() & 1 != 0 { IntWidth::U32 } else { IntWidth::U64 };
+        assert_eq!(reader.read_with::<Vec<u64>, _>((2, width)).unwrap(), [0x10, 0x20]);
+
+        let mut writer = BinaryWriter::new_le(Vec::new());
+        for (value, width) in [(0x100, IntWidth::U8), (0x1_0000, IntWidth::U16), (0x1_0000_0000, IntWidth::U32)] {
+            let err = writer.write_uint(value, width).unwrap_err();
+            assert!(matches!(err, WriteError::ValueTooLarge { value: v, max } if v == value && max == value - 1));
+        }
+        assert_eq!(writer.position(), 0);
+    }
+
+    #[test]
+    fn test_would_block_resume() {
+        use std::{collections::VecDeque, io};
+
+        /// A non-blocking source that hands out `chunks` in turn, with `None` meaning no data yet.
+        struct NonBlocking {
+            chunks: VecDeque<Option<Vec<u8>>>,
+            buf: Vec<u8>,
+        }
+
+        impl io::Read for NonBlocking {
+            fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+                let available = self.fill_buf()?;
+                let len = available.len().min(out.len());
+                out[..len].copy_from_slice(&available[..len]);
+                self.consume(len);
+                Ok(len)
+            }
+        }
+
+        impl BufRead for NonBlocking {
+            fn fill_buf(&mut self) -> io::Result<&[u8]> {
+                if self.buf.is_empty() {
+                    match self.chunks.pop_front() {
+                        Some(Some(chunk)) => self.buf = chunk,
+                        Some(None) => return Err(io::ErrorKind::WouldBlock.into()),
+                        None => {}
+                    }
+                }
+                Ok(&self.buf)
+            }
+
+            fn consume(&mut self, amt: usize) {
+                self.buf.drain(..amt);
+            }
+        }
+
+        let chunks = [Some(vec![4, 3]), None, Some(vec![2, 1, 5]), None, None, Some(vec![6]), None, Some(vec![7, 8, 9])];
+        let mut reader = BinaryReader::new_le(NonBlocking {
+            chunks: chunks.into(),
+            buf: Vec::new(),
+        });
+        assert!(matches!(reader.u32(), Err(ReadError::WouldBlock { offset: 0, buffered: 2 })));
+        assert!(reader.is_poisoned());
+        // anything but a retry of the interrupted read is refused, and leaves it resumable
+        assert!(matches!(reader.u8(), Err(ReadError::Desynchronized { at_offset: 0 })));
+        assert_eq!(reader.u32().unwrap(), 0x0102_0304);
+        assert!(!reader.is_poisoned());
+
+        assert!(matches!(reader.u16(), Err(ReadError::WouldBlock { offset: 4, buffered: 1 })));
+        assert!(matches!(reader.read_num::<u16>(), Err(ReadError::WouldBlock { offset: 4, buffered: 1 })));
+        assert_eq!(reader.read_num::<u16>().unwrap(), 0x0605);
+
+        // blocking between values consumes nothing, so any read may follow
+        let err = reader.u8().unwrap_err();
+        assert!(matches!(err, ReadError::WouldBlock { offset: 6, buffered: 0 }));
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::WouldBlock);
+        assert!(!reader.is_poisoned());
+        assert_eq!(reader.u8().unwrap(), 7);
+
+        let mut reader = BinaryReader::new_le(NonBlocking {
+            chunks: [Some(vec![1]), None].into(),
+            buf: Vec::new(),
+        });
+        assert!(matches!(reader.skip(2), Err(ReadError::IoError(e)) if e.kind() == io::ErrorKind::WouldBlock));
+        assert!(matches!(reader.u8(), Err(ReadError::Desynchronized { .. })));
+
+        // reads through a view hand the bytes they got back to this reader
+        let chunks = [Some(vec![1, 2]), None, Some(vec![3, 4, 5]), None, Some(vec![6, 7])];
+        let mut reader = BinaryReader::new_le(NonBlocking {
+            chunks: chunks.into(),
+            buf: Vec::new(),
+        });
+        assert!(matches!(reader.read_as_be::<u32>(), Err(ReadError::WouldBlock { offset: 0, buffered: 2 })));
+        assert_eq!(reader.read_as_be::<u32>().unwrap(), 0x0102_0304);
+        {
+            let mut take = reader.by_ref_take(3);
+            assert!(matches!(take.u16(), Err(ReadError::WouldBlock { offset: 4, buffered: 1 })));
+        }
+        assert_eq!(reader.by_ref_take(3).u16().unwrap(), 0x0605);
+        assert_eq!(reader.u8().unwrap(), 7);
+    }
+
+    /// Times 4 KiB field reads with the default threshold, where every field allocates, against
+    /// `with_small_buf_size(4096)`, where none does. Run with
+    /// `cargo test --release small_buf_size_bench -- --ignored --nocapture`; the gap depends on
+    /// the allocator and grows when other threads allocate at the same time.
+    #[test]
+    #[ignore]
+    fn small_buf_size_bench() {
+        let data = vec![0x61u8; 4096 * 1000];
+        for size in [DEFAULT_SMALL_BUF_SIZE, 4096] {
+            let start = std::time::Instant::now();
+            for _ in 0..100 {
+                let mut reader = BinaryReader::from_le_bytes(&data).with_small_buf_size(size);
+                while reader.remaining() != Some(0) {
+                    std::hint::black_box(reader.read_from_slice(4096, |bytes| Ok(bytes[4095])).unwrap());
+                }
+            }
+            println!("threshold {size:>5}: {:?}", start.elapsed());
+        }
     }
 }
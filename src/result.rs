@@ -1,3 +1,7 @@
+use crate::{
+    checksum::ChecksumKind,
+    version::{Version, VersionRange},
+};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -5,19 +9,76 @@ pub enum ReadError {
     #[error("io error: {0}")]
     IoError(std::io::Error),
 
-    #[error("not enough bytes: expected {expected}, actual: {actual}")]
-    NotEnoughBytes { expected: usize, actual: usize },
+    /// A read of `requested` bytes at `offset` would cross the active `limit`, leaving only
+    /// `remaining` bytes available.
+    ///
+    /// Before 0.2 this variant carried `expected` (now `requested`) and `actual` (now `remaining`).
+    #[error("not enough bytes: requested {requested} at offset {}, but only {remaining} remain before the limit at {}", HexOffset(*offset), HexOffset(*limit))]
+    NotEnoughBytes { requested: usize, remaining: u64, limit: u64, offset: u64 },
 
     #[error("invalid argument")]
     InvalidArgument,
 
+    #[error("position {} is beyond the limit {}", HexOffset(*position), HexOffset(*limit))]
+    PositionBeyondLimit { position: u64, limit: u64 },
+
+    /// A run of bytes (a magic number, a reserved region, ...) did not match what was expected.
+    #[error("unexpected bytes, first difference at byte {first_diff}:\n{}", render_hex_diff(expected, actual, *first_diff))]
+    ExpectedMismatch { expected: Vec<u8>, actual: Vec<u8>, first_diff: usize },
+
     #[error("invalid data format: {0}")]
     InvalidDataFormat(String),
+
+    /// A value read at `offset` was not the one required, e.g. by `value`. Both sides are `Debug` renderings.
+    #[error("unexpected value at offset {}: expected {expected}, found {found}", HexOffset(*offset))]
+    UnexpectedValue { expected: String, found: String, offset: u64 },
+
+    /// Reading or validating element `index` of a collection, which starts at `offset`, failed.
+    /// The failure is the error's `source`, so error chains print it once.
+    #[error("element {index} at offset {}", HexOffset(*offset))]
+    InElement { index: usize, offset: u64, source: Box<ReadError> },
+    /// An earlier read failed at `at_offset` after possibly consuming part of a value, so the
+    /// stream is no longer aligned with the position. Cleared by `clear_poison`.
+    #[error("reader is desynchronized by a failed read at offset {}", HexOffset(*at_offset))]
+    Desynchronized { at_offset: u64 },
+    /// A deadline passed before a timed read completed.
+    #[error("read timed out")]
+    TimedOut,
+    /// `scan_for` read `scanned` bytes from `offset` without finding its pattern.
+    #[error("pattern not found in {scanned} bytes scanned from offset {}", HexOffset(*offset))]
+    PatternNotFound { offset: u64, scanned: u64 },
+    /// A read at `offset` would nest deeper than the `max_depth` set with `set_max_depth`.
+    #[error("nesting exceeds the maximum depth of {max_depth} at offset {}", HexOffset(*offset))]
+    DepthLimitExceeded { max_depth: u32, offset: u64 },
+    /// A read at `offset` would allocate `requested` bytes up front, more than the `max_alloc` set
+    /// with `BinaryReaderBuilder::max_alloc`.
+    #[error("allocation of {requested} bytes at offset {} exceeds the limit of {max_alloc} bytes", HexOffset(*offset))]
+    AllocationLimitExceeded { requested: usize, max_alloc: usize, offset: u64 },
+    /// `version` read a format version at `offset` outside the `supported` range.
+    #[error("unsupported version {found} at offset {}, expected {supported}", HexOffset(*offset))]
+    UnsupportedVersion { found: Version, supported: VersionRange, offset: u64 },
+    /// The `kind` digest `stored` at `offset` does not match the one `computed` over the bytes before it.
+    #[error("{kind} mismatch at offset {}: stored {stored:#010X}, computed {computed:#010X}", HexOffset(*offset))]
+    ChecksumMismatch { kind: ChecksumKind, stored: u32, computed: u32, offset: u64 },
+    /// A non-blocking source had no data for the fixed-size read at `offset` after `buffered` of
+    /// its bytes. The reader keeps them, and retrying the same read resumes it.
+    #[error("read at offset {} would block after {buffered} bytes, retry it to resume", HexOffset(*offset))]
+    WouldBlock { offset: u64, buffered: usize },
+    /// A peek at `offset` needed more than the `buffered` bytes the source held or the reader
+    /// could stage. The stream may continue past them, but only values of up to 16 bytes are
+    /// staged across a refill.
+    #[error("peek at offset {} needs more than the {buffered} bytes buffered", HexOffset(*offset))]
+    PeekBeyondBuffer { offset: u64, buffered: usize },
 }
 
 impl ReadError {
-    pub fn not_enough_bytes(expected: usize, actual: usize) -> Self {
-        Self::NotEnoughBytes { expected, actual }
+    pub fn not_enough_bytes(requested: usize, limit: u64, offset: u64) -> Self {
+        Self::NotEnoughBytes {
+            requested,
+            remaining: limit.saturating_sub(offset),
+            limit,
+            offset,
+        }
     }
     pub fn invalid_data_format<M: AsRef<str>>(msg: M) -> Self {
         Self::InvalidDataFormat(msg.as_ref().to_string())
@@ -25,12 +86,95 @@ impl ReadError {
     pub fn io_error(err: std::io::Error) -> Self {
         Self::IoError(err)
     }
+    pub fn unexpected_value<T: std::fmt::Debug + ?Sized>(expected: &T, found: &T, offset: u64) -> Self {
+        Self::UnexpectedValue {
+            expected: format!("{expected:?}"),
+            found: format!("{found:?}"),
+            offset,
+        }
+    }
+    /// Returns the stream position the error refers to, if it has one.
+    ///
+    /// For `InElement` this is the innermost known offset, so it points at the failing field
+    /// rather than the start of the collection.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Self::NotEnoughBytes { offset, .. }
+            | Self::UnexpectedValue { offset, .. }
+            | Self::PatternNotFound { offset, .. }
+            | Self::DepthLimitExceeded { offset, .. }
+            | Self::AllocationLimitExceeded { offset, .. }
+            | Self::UnsupportedVersion { offset, .. }
+            | Self::ChecksumMismatch { offset, .. }
+            | Self::WouldBlock { offset, .. }
+            | Self::PeekBeyondBuffer { offset, .. } => Some(*offset),
+            Self::PositionBeyondLimit { position, .. } => Some(*position),
+            Self::Desynchronized { at_offset } => Some(*at_offset),
+            Self::InElement { offset, source, .. } => source.offset().or(Some(*offset)),
+            Self::IoError(_) | Self::InvalidArgument | Self::ExpectedMismatch { .. } | Self::InvalidDataFormat(_) | Self::TimedOut => None,
+        }
+    }
+    /// The error for a looping read whose item parser consumed no bytes, which would otherwise spin forever.
+    pub(crate) fn no_progress() -> Self {
+        Self::invalid_data_format("parser made no progress")
+    }
+    pub fn expected_mismatch(expected: &[u8], actual: &[u8]) -> Self {
+        let first_diff = expected.iter().zip(actual).position(|(e, a)| e != a).unwrap_or(expected.len().min(actual.len()));
+        Self::ExpectedMismatch {
+            expected: expected.to_vec(),
+            actual: actual.to_vec(),
+            first_diff,
+        }
+    }
+}
+
+/// Displays a stream offset in hex, followed by decimal in parentheses: `0x246C85D (38193245)`.
+struct HexOffset(u64);
+
+impl std::fmt::Display for HexOffset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#X} ({})", self.0, self.0)
+    }
+}
+
+/// Renders `expected` and `actual` as aligned hex rows with a caret under `first_diff`.
+///
+/// Only the 16-byte row containing the difference is shown; elided bytes are marked with `...`.
+pub fn render_hex_diff(expected: &[u8], actual: &[u8], first_diff: usize) -> String {
+    const ROW: usize = 16;
+    let len = expected.len().max(actual.len());
+    let start = first_diff / ROW * ROW;
+    let end = (start + ROW).min(len);
+    let lead = if start > 0 { "... " } else { "" };
+    let tail = if end < len { " ..." } else { "" };
+    let row = |bytes: &[u8]| {
+        let cells = (start..end).map(|i| bytes.get(i).map_or_else(|| "--".to_string(), |b| format!("{:02X}", b)));
+        format!("{}{}{}", lead, cells.collect::<Vec<_>>().join(" "), tail)
+    };
+    let caret = " ".repeat(lead.len() + 3 * (first_diff - start));
+    format!("expected: {}\n  actual: {}\n          {}^^", row(expected), row(actual), caret)
 }
 
 #[derive(Error, Debug)]
 pub enum WriteError {
     #[error("io error: {0}")]
     IoError(std::io::Error),
+
+    #[error("invalid argument")]
+    InvalidArgument,
+
+    /// `value` does not fit in the narrower integer the format stores it as, whose largest value is `max`.
+    #[error("value {value} does not fit in a field whose maximum is {max}")]
+    ValueTooLarge { value: u64, max: u64 },
+
+    /// A value written through `ValidatingWriter` did not read back as itself; `detail` says how.
+    #[error("{type_name} does not read back as written: {detail}")]
+    RoundTripMismatch { type_name: &'static str, detail: String },
+
+    /// Writing `requested` bytes at `position` would pass the `limit` set with `BinaryWriterBuilder::limit`.
+    /// Nothing was written.
+    #[error("writing {requested} bytes at position {} would pass the limit at {}", HexOffset(*position), HexOffset(*limit))]
+    LimitExceeded { requested: usize, position: u64, limit: u64 },
 }
 impl WriteError {
     pub fn io_error(err: std::io::Error) -> Self {
@@ -38,5 +182,38 @@ impl WriteError {
     }
 }
 
+/// Converts to the `std::io::Error` an equivalent `std::io` operation would have returned: the
+/// wrapped error for `IoError`, `UnexpectedEof` for running out of bytes, `InvalidInput` for bad
+/// arguments, `TimedOut` for timeouts, `WouldBlock` for resumable reads that would block and
+/// `InvalidData` with the error as its payload otherwise.
+impl From<ReadError> for std::io::Error {
+    fn from(err: ReadError) -> Self {
+        use std::io::ErrorKind;
+        match err {
+            ReadError::IoError(err) => err,
+            ReadError::NotEnoughBytes { .. } | ReadError::PositionBeyondLimit { .. } => std::io::Error::new(ErrorKind::UnexpectedEof, err),
+            ReadError::InvalidArgument => std::io::Error::new(ErrorKind::InvalidInput, err),
+            ReadError::TimedOut => std::io::Error::new(ErrorKind::TimedOut, err),
+            ReadError::WouldBlock { .. } => std::io::Error::new(ErrorKind::WouldBlock, err),
+            err => std::io::Error::new(ErrorKind::InvalidData, err),
+        }
+    }
+}
+
+/// Converts to a `std::io::Error`: the wrapped error for `IoError`, `InvalidData` for a failed
+/// round-trip check, `WriteZero` for a full sink, as from a `&mut [u8]`, and `InvalidInput` for
+/// values that cannot be written.
+impl From<WriteError> for std::io::Error {
+    fn from(err: WriteError) -> Self {
+        use std::io::ErrorKind;
+        match err {
+            WriteError::IoError(err) => err,
+            WriteError::RoundTripMismatch { .. } => std::io::Error::new(ErrorKind::InvalidData, err),
+            WriteError::LimitExceeded { .. } => std::io::Error::new(ErrorKind::WriteZero, err),
+            err => std::io::Error::new(ErrorKind::InvalidInput, err),
+        }
+    }
+}
+
 pub type ReadResult<T> = Result<T, ReadError>;
 pub type WriteResult<T> = Result<T, WriteError>;
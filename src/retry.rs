@@ -0,0 +1,206 @@
+use std::io::{self, ErrorKind};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Runs `op` until it finishes with anything other than `ErrorKind::Interrupted`.
+pub(crate) fn retry<T>(mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    loop {
+        match op() {
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            result => return result,
+        }
+    }
+}
+
+/// Like `AsyncReadExt::read_exact`, but an interrupted read is retried instead of losing the
+/// bytes read so far.
+pub(crate) async fn read_exact<R: AsyncRead + Unpin + ?Sized>(reader: &mut R, mut buf: &mut [u8]) -> io::Result<()> {
+    while !buf.is_empty() {
+        match reader.read(buf).await {
+            Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+            Ok(n) => buf = &mut buf[n..],
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Like `AsyncReadExt::read`, but an interrupted read is retried.
+pub(crate) async fn read<R: AsyncRead + Unpin + ?Sized>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    loop {
+        match reader.read(buf).await {
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            result => return result,
+        }
+    }
+}
+
+/// Like `AsyncBufReadExt::fill_buf`, but an interrupted fill is retried. Returns how many bytes
+/// are buffered; the next `fill_buf` hands them out without reading again.
+pub(crate) async fn fill_buf_len<R: AsyncBufRead + Unpin + ?Sized>(reader: &mut R) -> io::Result<usize> {
+    loop {
+        match reader.fill_buf().await {
+            Ok(buf) => return Ok(buf.len()),
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Like `AsyncWriteExt::write_all`, but an interrupted write is retried.
+pub(crate) async fn write_all<W: AsyncWrite + Unpin + ?Sized>(writer: &mut W, mut buf: &[u8]) -> io::Result<()> {
+    while !buf.is_empty() {
+        match writer.write(buf).await {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(n) => buf = &buf[n..],
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Like `AsyncWriteExt::flush`, but an interrupted flush is retried.
+pub(crate) async fn flush<W: AsyncWrite + Unpin + ?Sized>(writer: &mut W) -> io::Result<()> {
+    loop {
+        match writer.flush().await {
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{async_reader::AsyncBinaryReader, async_writer::AsyncBinaryWriter, reader::BinaryReader, string::StringMode, writer::BinaryWriter};
+    use std::{
+        io::{self, BufReader, ErrorKind},
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    /// Fails every other call with `Interrupted` and moves at most three bytes per successful call.
+    struct Flaky<T> {
+        inner: T,
+        interrupt: bool,
+    }
+
+    impl<T> Flaky<T> {
+        fn new(inner: T) -> Self {
+            Self { inner, interrupt: true }
+        }
+
+        fn tick(&mut self) -> io::Result<()> {
+            self.interrupt = !self.interrupt;
+            if self.interrupt { Ok(()) } else { Err(ErrorKind::Interrupted.into()) }
+        }
+    }
+
+    impl io::Read for Flaky<&[u8]> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.tick()?;
+            let len = buf.len().min(3);
+            self.inner.read(&mut buf[..len])
+        }
+    }
+
+    impl io::Write for Flaky<Vec<u8>> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.tick()?;
+            self.inner.write(&buf[..buf.len().min(3)])
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.tick()
+        }
+    }
+
+    impl tokio::io::AsyncRead for Flaky<&[u8]> {
+        fn poll_read(mut self: Pin<&mut Self>, _: &mut Context<'_>, buf: &mut tokio::io::ReadBuf<'_>) -> Poll<io::Result<()>> {
+            self.tick()?;
+            let len = buf.remaining().min(3).min(self.inner.len());
+            let (head, tail) = self.inner.split_at(len);
+            buf.put_slice(head);
+            self.inner = tail;
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl tokio::io::AsyncWrite for Flaky<Vec<u8>> {
+        fn poll_write(mut self: Pin<&mut Self>, _: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            self.tick()?;
+            let len = buf.len().min(3);
+            self.inner.extend_from_slice(&buf[..len]);
+            Poll::Ready(Ok(len))
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(self.tick())
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn expected_bytes() -> Vec<u8> {
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_le(&mut buf);
+        writer.u32(0xdead_beef).unwrap();
+        writer.utf8_str("interrupted", StringMode::NullTerminated).unwrap();
+        writer.reserved(0xaa, 40).unwrap();
+        writer.u64(7).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_interrupted_writes() {
+        let mut writer = BinaryWriter::new_le(Flaky::new(Vec::new())).with_buffer_capacity(8);
+        writer.u32(0xdead_beef).unwrap();
+        writer.utf8_str("interrupted", StringMode::NullTerminated).unwrap();
+        writer.reserved(0xaa, 40).unwrap();
+        writer.u64(7).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.finish().unwrap().inner, expected_bytes());
+    }
+
+    #[test]
+    fn test_interrupted_reads() {
+        let data = expected_bytes();
+        let mut reader = BinaryReader::new_le(BufReader::with_capacity(4, Flaky::new(data.as_slice())));
+        assert_eq!(reader.peek::<u16>().unwrap(), 0xbeef);
+        assert_eq!(reader.u32().unwrap(), 0xdead_beef);
+        assert_eq!(reader.utf8_str(StringMode::NullTerminated).unwrap(), "interrupted");
+        reader.skip(20).unwrap();
+        let mut copied = Vec::new();
+        reader.copy_to(&mut copied, 20).unwrap();
+        assert_eq!(copied, [0xaa; 20]);
+        assert_eq!(reader.u64().unwrap(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_async_interrupted_writes() {
+        let mut writer = AsyncBinaryWriter::new_le(Flaky::new(Vec::new()));
+        writer.u32(0xdead_beef).await.unwrap();
+        writer.utf8_str("interrupted", StringMode::NullTerminated).await.unwrap();
+        writer.reserved(0xaa, 20).await.unwrap();
+        assert_eq!(writer.copy_from(&mut Flaky::new([0xaa; 20].as_slice()), Some(20)).await.unwrap(), 20);
+        writer.u64(7).await.unwrap();
+        writer.flush().await.unwrap();
+        assert_eq!(writer.finish().await.unwrap().inner, expected_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_async_interrupted_reads() {
+        let data = expected_bytes();
+        let mut reader = AsyncBinaryReader::new_le(tokio::io::BufReader::with_capacity(4, Flaky::new(data.as_slice())));
+        assert_eq!(reader.peek::<u16>().await.unwrap(), 0xbeef);
+        assert_eq!(reader.u32().await.unwrap(), 0xdead_beef);
+        assert_eq!(reader.utf8_str(StringMode::NullTerminated).await.unwrap(), "interrupted");
+        reader.skip(20).await.unwrap();
+        let mut copied = Vec::new();
+        reader.copy_to(&mut copied, 20).await.unwrap();
+        assert_eq!(copied, [0xaa; 20]);
+        assert_eq!(reader.u64().await.unwrap(), 7);
+    }
+}
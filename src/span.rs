@@ -0,0 +1,226 @@
+use crate::{
+    async_reader::{self, AsyncBinaryReader, AsyncReadWith, ReaderBase},
+    async_writer::{self, AsyncBinaryWriter, AsyncWriteWith, WriterBase},
+    endian::Endian,
+    reader::{self, BinaryReader, ReadWith},
+    result::{ReadError, WriteError},
+    writer::{self, BinaryWriter, WriteWith},
+};
+use std::{io::BufRead, ops::Range};
+
+/// A region given by its offset and length, as stored in container indexes and chunk tables.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub offset: u64,
+    pub len: u64,
+}
+
+impl Span {
+    pub const fn new(offset: u64, len: u64) -> Self {
+        Self { offset, len }
+    }
+
+    /// Returns the offset one past the region, or `None` if it overflows `u64`.
+    pub const fn end(&self) -> Option<u64> {
+        self.offset.checked_add(self.len)
+    }
+}
+
+/// How a `Span` or `Range<u64>` is laid out on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanEncoding {
+    /// `u32` offset, then `u32` length.
+    OffsetLen32,
+    /// `u64` offset, then `u64` length.
+    OffsetLen64,
+    /// `u32` start, then `u32` end (exclusive).
+    StartEnd32,
+    /// `u64` start, then `u64` end (exclusive).
+    StartEnd64,
+}
+
+impl SpanEncoding {
+    fn max(self) -> u64 {
+        match self {
+            SpanEncoding::OffsetLen32 | SpanEncoding::StartEnd32 => u32::MAX as u64,
+            SpanEncoding::OffsetLen64 | SpanEncoding::StartEnd64 => u64::MAX,
+        }
+    }
+
+    /// Turns the two stored integers into `(start, end)`, rejecting regions that end before they
+    /// start or past what the encoding can address.
+    fn decode(self, first: u64, second: u64) -> reader::Result<(u64, u64)> {
+        match self {
+            SpanEncoding::OffsetLen32 | SpanEncoding::OffsetLen64 => match first.checked_add(second) {
+                Some(end) if end <= self.max() => Ok((first, end)),
+                _ => Err(ReadError::invalid_data_format(format!("span at {first} with length {second} overflows {self:?}"))),
+            },
+            SpanEncoding::StartEnd32 | SpanEncoding::StartEnd64 if first <= second => Ok((first, second)),
+            SpanEncoding::StartEnd32 | SpanEncoding::StartEnd64 => Err(ReadError::invalid_data_format(format!("span ends at {second} before its start {first}"))),
+        }
+    }
+
+    /// Turns `(start, end)` into the two integers to store.
+    fn encode(self, start: u64, end: u64) -> writer::Result<(u64, u64)> {
+        if start > end || end > self.max() {
+            return Err(WriteError::InvalidArgument);
+        }
+        match self {
+            SpanEncoding::OffsetLen32 | SpanEncoding::OffsetLen64 => Ok((start, end - start)),
+            SpanEncoding::StartEnd32 | SpanEncoding::StartEnd64 => Ok((start, end)),
+        }
+    }
+
+    fn is_wide(self) -> bool {
+        matches!(self, SpanEncoding::OffsetLen64 | SpanEncoding::StartEnd64)
+    }
+}
+
+fn read_span<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, encoding: SpanEncoding) -> reader::Result<(u64, u64)> {
+    let (first, second) = if encoding.is_wide() {
+        (reader.u64()?, reader.u64()?)
+    } else {
+        (reader.u32()? as u64, reader.u32()? as u64)
+    };
+    encoding.decode(first, second)
+}
+
+fn write_span<E: Endian, W: std::io::Write>(writer: &mut BinaryWriter<E, W>, encoding: SpanEncoding, start: u64, end: u64) -> writer::Result<()> {
+    let (first, second) = encoding.encode(start, end)?;
+    if encoding.is_wide() {
+        writer.u64(first)?;
+        writer.u64(second)
+    } else {
+        writer.u32(first as u32)?;
+        writer.u32(second as u32)
+    }
+}
+
+async fn read_span_async<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>, encoding: SpanEncoding) -> async_reader::Result<(u64, u64)> {
+    let (first, second) = if encoding.is_wide() {
+        (reader.u64().await?, reader.u64().await?)
+    } else {
+        (reader.u32().await? as u64, reader.u32().await? as u64)
+    };
+    encoding.decode(first, second)
+}
+
+async fn write_span_async<E: Endian, W: WriterBase>(writer: &mut AsyncBinaryWriter<E, W>, encoding: SpanEncoding, start: u64, end: u64) -> async_writer::Result<()> {
+    let (first, second) = encoding.encode(start, end)?;
+    if encoding.is_wide() {
+        writer.u64(first).await?;
+        writer.u64(second).await
+    } else {
+        writer.u32(first as u32).await?;
+        writer.u32(second as u32).await
+    }
+}
+
+impl ReadWith<SpanEncoding> for Range<u64> {
+    fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, encoding: SpanEncoding) -> reader::Result<Self> {
+        read_span(reader, encoding).map(|(start, end)| start..end)
+    }
+}
+
+impl ReadWith<SpanEncoding> for Span {
+    fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, encoding: SpanEncoding) -> reader::Result<Self> {
+        read_span(reader, encoding).map(|(start, end)| Span::new(start, end - start))
+    }
+}
+
+impl WriteWith<SpanEncoding> for Range<u64> {
+    fn write_with<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>, encoding: SpanEncoding) -> writer::Result<()> {
+        write_span(writer, encoding, self.start, self.end)
+    }
+}
+
+impl WriteWith<SpanEncoding> for Span {
+    fn write_with<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>, encoding: SpanEncoding) -> writer::Result<()> {
+        write_span(writer, encoding, self.offset, self.end().ok_or(WriteError::InvalidArgument)?)
+    }
+}
+
+impl AsyncReadWith<SpanEncoding> for Range<u64> {
+    async fn read_with<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>, encoding: SpanEncoding) -> async_reader::Result<Self> {
+        read_span_async(reader, encoding).await.map(|(start, end)| start..end)
+    }
+}
+
+impl AsyncReadWith<SpanEncoding> for Span {
+    async fn read_with<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>, encoding: SpanEncoding) -> async_reader::Result<Self> {
+        read_span_async(reader, encoding).await.map(|(start, end)| Span::new(start, end - start))
+    }
+}
+
+impl AsyncWriteWith<SpanEncoding> for Range<u64> {
+    async fn write_with<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>, encoding: &SpanEncoding) -> async_writer::Result<()> {
+        write_span_async(writer, *encoding, self.start, self.end).await
+    }
+}
+
+impl AsyncWriteWith<SpanEncoding> for Span {
+    async fn write_with<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>, encoding: &SpanEncoding) -> async_writer::Result<()> {
+        write_span_async(writer, *encoding, self.offset, self.end().ok_or(WriteError::InvalidArgument)?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span_round_trip() {
+        for encoding in [SpanEncoding::OffsetLen32, SpanEncoding::OffsetLen64, SpanEncoding::StartEnd32, SpanEncoding::StartEnd64] {
+            let mut buf = Vec::new();
+            let mut writer = BinaryWriter::new_le(&mut buf);
+            writer.write_with(&(16u64..48), encoding).unwrap();
+            writer.write_with(&Span::new(100, 0), encoding).unwrap();
+
+            let mut reader = BinaryReader::from_le_bytes(&buf);
+            assert_eq!(reader.read_with::<Range<u64>, _>(encoding).unwrap(), 16..48);
+            assert_eq!(reader.read_with::<Span, _>(encoding).unwrap(), Span::new(100, 0));
+            assert_eq!(reader.remaining(), Some(0));
+        }
+
+        let mut buf = Vec::new();
+        BinaryWriter::new_be(&mut buf).write_with(&Span::new(8, 4), SpanEncoding::OffsetLen32).unwrap();
+        assert_eq!(buf, [0, 0, 0, 8, 0, 0, 0, 4]);
+        buf.clear();
+        BinaryWriter::new_be(&mut buf).write_with(&Span::new(8, 4), SpanEncoding::StartEnd32).unwrap();
+        assert_eq!(buf, [0, 0, 0, 8, 0, 0, 0, 12]);
+    }
+
+    #[test]
+    fn test_corrupt_spans() {
+        let end_before_start = [0, 0, 0, 9, 0, 0, 0, 3];
+        let mut reader = BinaryReader::from_be_bytes(&end_before_start);
+        assert!(matches!(reader.read_with::<Range<u64>, _>(SpanEncoding::StartEnd32), Err(ReadError::InvalidDataFormat(_))));
+
+        let overflowing = [0xff, 0xff, 0xff, 0xf0, 0, 0, 0, 0x20];
+        let mut reader = BinaryReader::from_be_bytes(&overflowing);
+        assert!(matches!(reader.read_with::<Span, _>(SpanEncoding::OffsetLen32), Err(ReadError::InvalidDataFormat(_))));
+
+        let mut writer = BinaryWriter::new_le(Vec::new());
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = 9u64..3;
+        assert!(matches!(writer.write_with(&reversed, SpanEncoding::StartEnd64), Err(WriteError::InvalidArgument)));
+        assert!(matches!(writer.write_with(&Span::new(u32::MAX as u64, 1), SpanEncoding::OffsetLen32), Err(WriteError::InvalidArgument)));
+        assert!(matches!(writer.write_with(&Span::new(u64::MAX, 1), SpanEncoding::OffsetLen64), Err(WriteError::InvalidArgument)));
+        assert_eq!(writer.position(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_async_span() {
+        let mut buf = Vec::new();
+        let mut writer = AsyncBinaryWriter::new_le(&mut buf);
+        writer.write_with(&(3u64..7), &SpanEncoding::OffsetLen64).await.unwrap();
+        writer.write_with(&Span::new(5, 1), &SpanEncoding::StartEnd32).await.unwrap();
+        writer.u32(10).await.unwrap();
+        writer.u32(2).await.unwrap();
+
+        let mut reader = AsyncBinaryReader::from_le_bytes(&buf);
+        assert_eq!(reader.read_with::<_, Span>(SpanEncoding::OffsetLen64).await.unwrap(), Span::new(3, 4));
+        assert_eq!(reader.read_with::<_, Range<u64>>(SpanEncoding::StartEnd32).await.unwrap(), 5..6);
+        assert!(matches!(reader.read_with::<_, Range<u64>>(SpanEncoding::StartEnd32).await, Err(ReadError::InvalidDataFormat(_))));
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The groups of reader/writer methods that `Stats` tallies separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    /// Fixed-width values: integers, floats, `reserved` checks.
+    Primitive,
+    /// UTF-8/UTF-16 strings, OS strings and string lists.
+    String,
+    /// `skip` and friends on readers, padding on writers.
+    Skip,
+    /// Variable-length byte runs: slices, magic numbers, record batches, frames.
+    Bulk,
+}
+
+#[derive(Debug, Default)]
+struct Counter {
+    calls: AtomicU64,
+    bytes: AtomicU64,
+}
+
+/// Call and byte counters per method category, shared between any number of readers and writers.
+///
+/// Attach it with `attach_stats`; a reader or writer without one pays only an `Option` check per call.
+/// A string or skip counts as a single call of its own category, not as the primitive reads it is made of.
+#[derive(Debug, Default)]
+pub struct Stats {
+    counters: [Counter; 4],
+}
+
+/// Calls and bytes recorded for one category.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CategoryReport {
+    pub calls: u64,
+    pub bytes: u64,
+}
+
+/// A snapshot of a `Stats` collector.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatsReport {
+    pub primitives: CategoryReport,
+    pub strings: CategoryReport,
+    pub skips: CategoryReport,
+    pub bulk: CategoryReport,
+}
+
+impl Stats {
+    /// Creates an empty collector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records one call of `category` that moved `bytes` bytes.
+    #[inline]
+    pub fn record(&self, category: Category, bytes: u64) {
+        let counter = &self.counters[category as usize];
+        counter.calls.fetch_add(1, Ordering::Relaxed);
+        counter.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Returns the counts recorded so far.
+    pub fn report(&self) -> StatsReport {
+        let snapshot = |category: Category| {
+            let counter = &self.counters[category as usize];
+            CategoryReport {
+                calls: counter.calls.load(Ordering::Relaxed),
+                bytes: counter.bytes.load(Ordering::Relaxed),
+            }
+        };
+        StatsReport {
+            primitives: snapshot(Category::Primitive),
+            strings: snapshot(Category::String),
+            skips: snapshot(Category::Skip),
+            bulk: snapshot(Category::Bulk),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{async_reader::AsyncBinaryReader, reader::BinaryReader, string::StringMode, writer::BinaryWriter};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_reader_and_writer_stats() {
+        let stats = Arc::new(Stats::new());
+
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_le(&mut buf);
+        writer.attach_stats(&stats);
+        writer.u32(7).unwrap();
+        writer.u16(8).unwrap();
+        writer.utf8_str("hello", StringMode::NullTerminated).unwrap();
+        writer.reserved(0, 10).unwrap();
+        let bytes: &[u8] = &[1, 2, 3];
+        writer.write(&bytes).unwrap();
+        let report = stats.report();
+        assert_eq!(report.primitives, CategoryReport { calls: 2, bytes: 6 });
+        assert_eq!(report.strings, CategoryReport { calls: 1, bytes: 6 });
+        assert_eq!(report.skips, CategoryReport { calls: 1, bytes: 10 });
+        assert_eq!(report.bulk, CategoryReport { calls: 1, bytes: 3 });
+
+        let stats = Arc::new(Stats::new());
+        let mut reader = BinaryReader::from_le_bytes(&buf);
+        reader.attach_stats(&stats);
+        assert_eq!(reader.u32().unwrap(), 7);
+        assert_eq!(reader.u16().unwrap(), 8);
+        assert_eq!(reader.utf8_str(StringMode::NullTerminated).unwrap(), "hello");
+        reader.skip(10).unwrap();
+        reader.magic([1, 2, 3]).unwrap();
+        let report = stats.report();
+        assert_eq!(report.primitives, CategoryReport { calls: 2, bytes: 6 });
+        assert_eq!(report.strings, CategoryReport { calls: 1, bytes: 6 });
+        assert_eq!(report.skips, CategoryReport { calls: 1, bytes: 10 });
+        assert_eq!(report.bulk, CategoryReport { calls: 1, bytes: 3 });
+    }
+
+    #[tokio::test]
+    async fn test_async_reader_stats() {
+        let stats = Arc::new(Stats::new());
+        let data = [0u8, 1, 0, 0, 0, 0, 0, 2, 0x61, 0x62];
+        let mut reader = AsyncBinaryReader::from_be_bytes(&data);
+        reader.attach_stats(&stats);
+        assert_eq!(reader.u16().await.unwrap(), 1);
+        reader.skip_to(6).await.unwrap();
+        assert_eq!(reader.read_as_le::<u16>().await.unwrap(), 0x0200);
+        assert_eq!(reader.utf8_str(StringMode::FixedChars(2)).await.unwrap(), "ab");
+        let report = stats.report();
+        assert_eq!(report.primitives, CategoryReport { calls: 2, bytes: 4 });
+        assert_eq!(report.skips, CategoryReport { calls: 1, bytes: 4 });
+        assert_eq!(report.strings, CategoryReport { calls: 1, bytes: 2 });
+        assert_eq!(report.bulk, CategoryReport::default());
+    }
+}
@@ -1,27 +1,233 @@
 use crate::{async_reader::AsyncBinaryReader, async_writer::AsyncBinaryWriter, endian::Endian, reader::BinaryReader, writer::BinaryWriter};
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+};
 
 pub enum StringMode {
     FixedChars(usize),
     NullTerminated,
 }
 
-#[test]
-fn hoge() {
-    let data = b"Hello, world!\0\0\0";
-    let len = data.iter().position(|&c| c == 0).unwrap_or(data.len());
-    let parsed = String::from_utf8_lossy(&data[..len]).to_string();
-    println!("{:?}", parsed);
+/// Wire encoding for `OsStr`/`Path` values. Every encoding is preceded by a `u32` length.
+///
+/// | encoding    | length unit | Unix                  | Windows               |
+/// |-------------|-------------|-----------------------|-----------------------|
+/// | `Utf8Lossy` | bytes       | lossy for non-UTF-8   | lossy for non-UTF-16  |
+/// | `Bytes`     | bytes       | exact                 | lossy for non-UTF-16  |
+/// | `Wtf16`     | u16 units   | lossy for non-UTF-8   | exact                 |
+///
+/// "Exact" means any value produced by the platform round-trips unchanged. Lossy
+/// conversions replace invalid sequences with U+FFFD.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OsStrEncoding {
+    Utf8Lossy,
+    Bytes,
+    Wtf16,
 }
 
-fn parse_fixed_utf8(data: &[u8]) -> String {
-    let len = data.iter().position(|&c| c == 0).unwrap_or(data.len());
-    String::from_utf8_lossy(&data[..len]).to_string()
+impl OsStrEncoding {
+    fn unit_size(self) -> usize {
+        match self {
+            OsStrEncoding::Utf8Lossy | OsStrEncoding::Bytes => 1,
+            OsStrEncoding::Wtf16 => 2,
+        }
+    }
+
+    /// Returns the byte length of a payload of `count` units, read at `offset`.
+    fn payload_len(self, count: u32, offset: u64) -> crate::reader::Result<usize> {
+        crate::reader::widen(count, offset)?
+            .checked_mul(self.unit_size())
+            .ok_or_else(|| crate::result::ReadError::invalid_data_format(format!("length {count} at offset {offset:#X} does not fit in usize")))
+    }
+}
+
+fn encode_os_str<E: Endian>(value: &OsStr, encoding: OsStrEncoding) -> (usize, Vec<u8>) {
+    match encoding {
+        OsStrEncoding::Utf8Lossy => {
+            let bytes = value.to_string_lossy().into_owned().into_bytes();
+            (bytes.len(), bytes)
+        }
+        OsStrEncoding::Bytes => {
+            #[cfg(unix)]
+            let bytes = std::os::unix::ffi::OsStrExt::as_bytes(value).to_vec();
+            #[cfg(not(unix))]
+            let bytes = value.to_string_lossy().into_owned().into_bytes();
+            (bytes.len(), bytes)
+        }
+        OsStrEncoding::Wtf16 => {
+            #[cfg(windows)]
+            let units: Vec<u16> = std::os::windows::ffi::OsStrExt::encode_wide(value).collect();
+            #[cfg(not(windows))]
+            let units: Vec<u16> = value.to_string_lossy().encode_utf16().collect();
+            (units.len(), E::u16iter_to_bytes(units.iter().copied(), units.len() * 2))
+        }
+    }
+}
+
+fn decode_os_string<E: Endian>(data: &[u8], encoding: OsStrEncoding) -> OsString {
+    match encoding {
+        OsStrEncoding::Utf8Lossy => OsString::from(String::from_utf8_lossy(data).into_owned()),
+        OsStrEncoding::Bytes => {
+            #[cfg(unix)]
+            return std::os::unix::ffi::OsStringExt::from_vec(data.to_vec());
+            #[cfg(not(unix))]
+            return OsString::from(String::from_utf8_lossy(data).into_owned());
+        }
+        OsStrEncoding::Wtf16 => {
+            let units = E::u16vec_from_bytes(data);
+            #[cfg(windows)]
+            return std::os::windows::ffi::OsStringExt::from_wide(&units);
+            #[cfg(not(windows))]
+            return OsString::from(String::from_utf16_lossy(&units));
+        }
+    }
+}
+
+fn list_too_long(max_bytes: usize) -> crate::result::ReadError {
+    crate::result::ReadError::invalid_data_format(format!("String list exceeds {} bytes", max_bytes))
+}
+
+/// Rejects list entries the reader could not tell from the end of the list.
+fn check_list_entries<T: AsRef<str>>(values: &[T]) -> crate::writer::Result<()> {
+    if values.iter().any(|v| v.as_ref().is_empty() || v.as_ref().contains('\0')) {
+        return Err(crate::result::WriteError::io_error(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "String list entries must be non-empty and contain no NUL",
+        )));
+    }
+    Ok(())
+}
+
+/// Assembles a NUL-separated string list from the code units the sync or async reader fetches,
+/// so both share one parser. Memory grows only with the units actually read.
+struct StrListBuilder<U> {
+    list: Vec<String>,
+    current: Vec<U>,
+    decode: fn(&[U]) -> String,
+}
+
+impl<U: Copy + Default + PartialEq> StrListBuilder<U> {
+    fn new(decode: fn(&[U]) -> String) -> Self {
+        Self {
+            list: Vec::new(),
+            current: Vec::new(),
+            decode,
+        }
+    }
+
+    /// Adds the next code unit, returning the list once the empty entry that ends it is complete.
+    fn push(&mut self, unit: U) -> Option<Vec<String>> {
+        if unit != U::default() {
+            self.current.push(unit);
+        } else if self.current.is_empty() {
+            return Some(std::mem::take(&mut self.list));
+        } else {
+            let entry = (self.decode)(&std::mem::take(&mut self.current));
+            self.list.push(entry);
+        }
+        None
+    }
+}
+
+fn decode_utf8(units: &[u8]) -> String {
+    String::from_utf8_lossy(units).into_owned()
+}
+
+fn decode_utf16(units: &[u16]) -> String {
+    String::from_utf16_lossy(units)
+}
+
+fn os_str_too_long() -> crate::result::WriteError {
+    crate::result::WriteError::io_error(std::io::Error::new(std::io::ErrorKind::InvalidInput, "OsStr is too long for a u32 length prefix"))
+}
+
+/// Returns the offset of the first NUL byte in `bytes`, i.e. the length of the UTF-8 string a
+/// NUL-padded or NUL-terminated field holds, or `None` if the field has no terminator.
+pub fn utf8_nul_position(bytes: &[u8]) -> Option<usize> {
+    bytes.iter().position(|&c| c == 0)
+}
+
+/// Returns the byte offset of the first NUL code unit in UTF-16 `bytes`, in either byte order,
+/// or `None` if there is none. A trailing odd byte is ignored.
+pub fn utf16_nul_position(bytes: &[u8]) -> Option<usize> {
+    bytes.chunks_exact(2).position(|unit| unit == [0, 0]).map(|units| units * 2)
+}
+
+fn utf16_units<E: Endian>(bytes: &[u8]) -> impl Iterator<Item = u16> + '_ {
+    let len = utf16_nul_position(bytes).unwrap_or(bytes.len() & !1);
+    bytes[..len].chunks_exact(2).map(|unit| E::u16_from_bytes(&[unit[0], unit[1]]))
+}
+
+/// Decodes a fixed-size UTF-8 field such as the one `StringMode::FixedChars` reads: the bytes up
+/// to the first NUL, or all of them, with invalid sequences replaced by U+FFFD.
+///
+/// Useful when the raw field bytes come from somewhere other than a reader, e.g. a memory map.
+pub fn decode_fixed_utf8(bytes: &[u8]) -> String {
+    let len = utf8_nul_position(bytes).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+/// Decodes a fixed-size UTF-16 field in the byte order `E`: the code units up to the first NUL,
+/// or all of them, with unpaired surrogates replaced by U+FFFD. A trailing odd byte is ignored.
+pub fn decode_fixed_utf16<E: Endian>(bytes: &[u8]) -> String {
+    char::decode_utf16(utf16_units::<E>(bytes)).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)).collect()
+}
+
+/// Like `decode_fixed_utf8`, but fails with `ReadError::InvalidDataFormat` on invalid UTF-8
+/// instead of replacing it.
+pub fn decode_fixed_utf8_strict(bytes: &[u8]) -> crate::reader::Result<String> {
+    let len = utf8_nul_position(bytes).unwrap_or(bytes.len());
+    match std::str::from_utf8(&bytes[..len]) {
+        Ok(s) => Ok(s.to_owned()),
+        Err(e) => Err(crate::result::ReadError::invalid_data_format(format!("invalid UTF-8 at byte {}", e.valid_up_to()))),
+    }
+}
+
+/// Like `decode_fixed_utf16`, but fails with `ReadError::InvalidDataFormat` on an unpaired
+/// surrogate or an odd number of bytes instead of replacing or ignoring them.
+pub fn decode_fixed_utf16_strict<E: Endian>(bytes: &[u8]) -> crate::reader::Result<String> {
+    if !bytes.len().is_multiple_of(2) {
+        return Err(crate::result::ReadError::invalid_data_format(format!("UTF-16 field has an odd length of {} bytes", bytes.len())));
+    }
+    utf16_strict(utf16_units::<E>(bytes))
 }
-fn parse_fixed_utf16<E: Endian>(data: &[u8]) -> String {
-    let data = E::u16vec_from_bytes(data);
-    let len = data.iter().position(|&c| c == 0).unwrap_or(data.len());
-    String::from_utf16_lossy(&data[..len])
+
+/// Decodes UTF-16 code units, failing on the first unpaired surrogate.
+fn utf16_strict(units: impl Iterator<Item = u16>) -> crate::reader::Result<String> {
+    let (mut decoded, mut len) = (String::new(), 0);
+    for c in char::decode_utf16(units) {
+        match c {
+            Ok(c) => {
+                decoded.push(c);
+                len += c.len_utf16();
+            }
+            Err(e) => {
+                let offset = 2 * len;
+                return Err(crate::result::ReadError::invalid_data_format(format!(
+                    "unpaired UTF-16 surrogate {:#06X} at byte {offset}",
+                    e.unpaired_surrogate()
+                )));
+            }
+        }
+    }
+    Ok(decoded)
 }
+
+/// Decodes the bytes of a NUL-terminated UTF-8 string, lossily unless `strict`.
+fn utf8_from_units(units: Vec<u8>, strict: bool) -> crate::reader::Result<String> {
+    match String::from_utf8(units) {
+        Ok(s) => Ok(s),
+        Err(e) if strict => Err(crate::result::ReadError::invalid_data_format(format!("invalid UTF-8 at byte {}", e.utf8_error().valid_up_to()))),
+        Err(e) => Ok(String::from_utf8_lossy(e.as_bytes()).into_owned()),
+    }
+}
+
+/// Decodes the code units of a NUL-terminated UTF-16 string, lossily unless `strict`.
+fn utf16_from_units(units: &[u16], strict: bool) -> crate::reader::Result<String> {
+    if strict { utf16_strict(units.iter().copied()) } else { Ok(String::from_utf16_lossy(units)) }
+}
+
 fn take_utf8_char(data: &[u8; 1]) -> Option<u8> {
     match data[0] {
         0 => None,
@@ -36,229 +242,618 @@ fn take_u16_char<E: Endian>(data: &[u8; 2]) -> Option<u16> {
 }
 
 impl<E: Endian, R: std::io::BufRead> BinaryReader<E, R> {
+    /// Reads a UTF-8 string. Invalid sequences are replaced with U+FFFD, or fail with
+    /// `ReadError::InvalidDataFormat` if the reader was built with `strict_strings`.
     pub fn utf8_str(&mut self, mode: StringMode) -> crate::reader::Result<String> {
-        match mode {
-            StringMode::FixedChars(num_chars) => self.read_from_slice(num_chars, |data| Ok(parse_fixed_utf8(data))),
-            StringMode::NullTerminated => {
-                let buf = self.read_while(take_utf8_char)?;
-                Ok(String::from_utf8_lossy(&buf).to_string())
+        let strict = self.strict_strings();
+        count_stats!(self, String, {
+            match mode {
+                StringMode::FixedChars(num_chars) if strict => self.read_from_slice(num_chars, decode_fixed_utf8_strict),
+                StringMode::FixedChars(num_chars) => self.read_from_slice(num_chars, |data| Ok(decode_fixed_utf8(data))),
+                StringMode::NullTerminated => utf8_from_units(self.read_while(take_utf8_char)?, strict),
             }
-        }
+        })
     }
 
+    /// Reads a UTF-16 string. Unpaired surrogates are replaced with U+FFFD, or fail with
+    /// `ReadError::InvalidDataFormat` if the reader was built with `strict_strings`.
     pub fn utf16_str(&mut self, mode: StringMode) -> crate::reader::Result<String> {
+        let strict = self.strict_strings();
+        count_stats!(self, String, {
+            match mode {
+                StringMode::FixedChars(num_chars) if strict => self.read_from_slice(2 * num_chars, decode_fixed_utf16_strict::<E>),
+                StringMode::FixedChars(num_chars) => self.read_from_slice(2 * num_chars, |data| Ok(decode_fixed_utf16::<E>(data))),
+                StringMode::NullTerminated => utf16_from_units(&self.read_while(take_u16_char::<E>)?, strict),
+            }
+        })
+    }
+}
+
+impl crate::reader::ReadIntoWith<StringMode> for String {
+    /// Reads a UTF-8 string over this one, keeping its capacity.
+    fn read_into_with<E: Endian, R: std::io::BufRead>(&mut self, reader: &mut BinaryReader<E, R>, mode: StringMode) -> crate::reader::Result<()> {
+        self.clear();
         match mode {
-            StringMode::FixedChars(num_chars) => self.read_from_slice(2 * num_chars, |data| Ok(parse_fixed_utf16::<E>(data))),
+            StringMode::FixedChars(num_chars) => count_stats!(reader, String, {
+                reader.read_from_slice(num_chars, |data| {
+                    let len = utf8_nul_position(data).unwrap_or(data.len());
+                    self.push_str(&String::from_utf8_lossy(&data[..len]));
+                    Ok(())
+                })
+            }),
             StringMode::NullTerminated => {
-                let buf = self.read_while(take_u16_char::<E>)?;
-                Ok(String::from_utf16_lossy(&buf))
+                self.push_str(&reader.utf8_str(mode)?);
+                Ok(())
             }
         }
     }
 }
 
+impl<E: Endian, R: std::io::BufRead> BinaryReader<E, R> {
+    /// Reads an `OsString` stored as a `u32` length and the payload in `encoding`.
+    ///
+    /// Whether this is lossy depends on the encoding and platform, see the table on `OsStrEncoding`.
+    pub fn read_os_string(&mut self, encoding: OsStrEncoding) -> crate::reader::Result<OsString> {
+        count_stats!(self, String, {
+            let offset = self.position();
+            let len = encoding.payload_len(self.u32()?, offset)?;
+            self.read_from_slice(len, |data| Ok(decode_os_string::<E>(data, encoding)))
+        })
+    }
+
+    /// Reads a `PathBuf` like `read_os_string`; see `OsStrEncoding` for which encodings are lossy.
+    pub fn read_path_buf(&mut self, encoding: OsStrEncoding) -> crate::reader::Result<PathBuf> {
+        self.read_os_string(encoding).map(PathBuf::from)
+    }
+
+    /// Reads NUL-terminated UTF-8 strings until an empty one (`"a\0b\0\0"`), as used by
+    /// `REG_MULTI_SZ`. The final empty string is not returned, so an empty list is a single NUL.
+    ///
+    /// Fails with `InvalidDataFormat` once more than `max_bytes` bytes (terminators included)
+    /// would be consumed.
+    pub fn utf8_str_list(&mut self, max_bytes: usize) -> crate::reader::Result<Vec<String>> {
+        count_stats!(self, String, {
+            let mut builder = StrListBuilder::new(decode_utf8);
+            for _ in 0..max_bytes {
+                if let Some(list) = builder.push(self.u8()?) {
+                    return Ok(list);
+                }
+            }
+            Err(list_too_long(max_bytes))
+        })
+    }
+
+    /// UTF-16 variant of `utf8_str_list`; `max_bytes` counts bytes, not code units.
+    pub fn utf16_str_list(&mut self, max_bytes: usize) -> crate::reader::Result<Vec<String>> {
+        count_stats!(self, String, {
+            let mut builder = StrListBuilder::new(decode_utf16);
+            for _ in 0..max_bytes / 2 {
+                if let Some(list) = builder.push(self.u16()?) {
+                    return Ok(list);
+                }
+            }
+            Err(list_too_long(max_bytes))
+        })
+    }
+}
+
 impl<E: Endian, R: crate::async_reader::ReaderBase> AsyncBinaryReader<E, R> {
-    pub async fn utf8_str(&mut self, mode: StringMode) -> crate::async_reader::Result<String> {
+    pub async fn read_os_string(&mut self, encoding: OsStrEncoding) -> crate::async_reader::Result<OsString> {
+        count_stats!(self, String, async {
+            let offset = self.position();
+            let len = encoding.payload_len(self.u32().await?, offset)?;
+            self.read_from_slice(len, |data| Ok(decode_os_string::<E>(data, encoding))).await
+        })
+    }
+
+    pub async fn read_path_buf(&mut self, encoding: OsStrEncoding) -> crate::async_reader::Result<PathBuf> {
+        self.read_os_string(encoding).await.map(PathBuf::from)
+    }
+
+    pub async fn utf8_str_list(&mut self, max_bytes: usize) -> crate::async_reader::Result<Vec<String>> {
+        count_stats!(self, String, async {
+            let mut builder = StrListBuilder::new(decode_utf8);
+            for _ in 0..max_bytes {
+                if let Some(list) = builder.push(self.u8().await?) {
+                    return Ok(list);
+                }
+            }
+            Err(list_too_long(max_bytes))
+        })
+    }
+
+    pub async fn utf16_str_list(&mut self, max_bytes: usize) -> crate::async_reader::Result<Vec<String>> {
+        count_stats!(self, String, async {
+            let mut builder = StrListBuilder::new(decode_utf16);
+            for _ in 0..max_bytes / 2 {
+                if let Some(list) = builder.push(self.u16().await?) {
+                    return Ok(list);
+                }
+            }
+            Err(list_too_long(max_bytes))
+        })
+    }
+}
+
+impl crate::async_reader::AsyncReadIntoWith<StringMode> for String {
+    async fn read_into_with<E: Endian, R: crate::async_reader::ReaderBase>(&mut self, reader: &mut AsyncBinaryReader<E, R>, mode: StringMode) -> crate::async_reader::Result<()> {
+        self.clear();
         match mode {
-            StringMode::FixedChars(num_chars) => self.read_from_slice(num_chars, |data| Ok(parse_fixed_utf8(data))).await,
+            StringMode::FixedChars(num_chars) => count_stats!(reader, String, async {
+                reader
+                    .read_from_slice(num_chars, |data| {
+                        let len = utf8_nul_position(data).unwrap_or(data.len());
+                        self.push_str(&String::from_utf8_lossy(&data[..len]));
+                        Ok(())
+                    })
+                    .await
+            }),
             StringMode::NullTerminated => {
-                let buf: Vec<u8> = self.read_while(take_utf8_char).await?;
-                Ok(String::from_utf8_lossy(&buf).to_string())
+                self.push_str(&reader.utf8_str(mode).await?);
+                Ok(())
             }
         }
     }
+}
+
+impl<E: Endian, R: crate::async_reader::ReaderBase> AsyncBinaryReader<E, R> {
+    pub async fn utf8_str(&mut self, mode: StringMode) -> crate::async_reader::Result<String> {
+        let strict = self.strict_strings();
+        count_stats!(self, String, async {
+            match mode {
+                StringMode::FixedChars(num_chars) if strict => self.read_from_slice(num_chars, decode_fixed_utf8_strict).await,
+                StringMode::FixedChars(num_chars) => self.read_from_slice(num_chars, |data| Ok(decode_fixed_utf8(data))).await,
+                StringMode::NullTerminated => utf8_from_units(self.read_while(take_utf8_char).await?, strict),
+            }
+        })
+    }
 
     pub async fn utf16_str(&mut self, mode: StringMode) -> crate::async_reader::Result<String> {
-        match mode {
-            StringMode::FixedChars(num_chars) => self.read_from_slice(2 * num_chars, |data| Ok(parse_fixed_utf16::<E>(data))).await,
-            StringMode::NullTerminated => {
-                let buf: Vec<u16> = self.read_while(take_u16_char::<E>).await?;
-                Ok(String::from_utf16_lossy(&buf))
+        let strict = self.strict_strings();
+        count_stats!(self, String, async {
+            match mode {
+                StringMode::FixedChars(num_chars) if strict => self.read_from_slice(2 * num_chars, decode_fixed_utf16_strict::<E>).await,
+                StringMode::FixedChars(num_chars) => self.read_from_slice(2 * num_chars, |data| Ok(decode_fixed_utf16::<E>(data))).await,
+                StringMode::NullTerminated => utf16_from_units(&self.read_while(take_u16_char::<E>).await?, strict),
             }
-        }
+        })
     }
 }
 
 impl<W: std::io::Write, E: Endian> BinaryWriter<E, W> {
     pub fn utf8_str<T: AsRef<str>>(&mut self, value: T, mode: StringMode) -> crate::writer::Result<()> {
-        let bytes = value.as_ref().as_bytes();
-        match mode {
-            StringMode::FixedChars(size) => {
-                if bytes.len() > size {
-                    return Err(crate::result::WriteError::io_error(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        "String is too long for fixed size",
-                    )));
+        count_stats!(self, String, {
+            let bytes = value.as_ref().as_bytes();
+            match mode {
+                StringMode::FixedChars(size) => {
+                    if bytes.len() > size {
+                        return Err(crate::result::WriteError::io_error(std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            "String is too long for fixed size",
+                        )));
+                    }
+                    let padding = size - bytes.len();
+                    self.write(&bytes)?;
+                    self.reserved(0, padding)?;
+                    Ok(())
+                }
+                StringMode::NullTerminated => {
+                    self.write(&bytes)?;
+                    self.reserved(0, 1)?;
+                    Ok(())
                 }
-                let padding = size - bytes.len();
-                self.write(&bytes)?;
-                self.reserved(0, padding)?;
-                Ok(())
-            }
-            StringMode::NullTerminated => {
-                self.write(&bytes)?;
-                self.reserved(0, 1)?;
-                Ok(())
             }
-        }
+        })
     }
 
     pub fn utf16_str<T: AsRef<str>>(&mut self, value: T, mode: StringMode) -> crate::writer::Result<()> {
-        const CHAR_SIZE: usize = std::mem::size_of::<u16>();
-        let bytes = E::u16iter_to_bytes(value.as_ref().encode_utf16(), value.as_ref().len() * CHAR_SIZE);
-        match mode {
-            StringMode::FixedChars(size) => {
-                if bytes.len() > size * CHAR_SIZE {
-                    return Err(crate::result::WriteError::io_error(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        "String is too long for fixed size",
-                    )));
+        count_stats!(self, String, {
+            const CHAR_SIZE: usize = std::mem::size_of::<u16>();
+            let bytes = E::u16iter_to_bytes(value.as_ref().encode_utf16(), value.as_ref().len() * CHAR_SIZE);
+            match mode {
+                StringMode::FixedChars(size) => {
+                    if bytes.len() > size * CHAR_SIZE {
+                        return Err(crate::result::WriteError::io_error(std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            "String is too long for fixed size",
+                        )));
+                    }
+                    let padding = size * CHAR_SIZE - bytes.len();
+                    self.write(&bytes)?;
+                    self.reserved(0, padding)?;
+                    Ok(())
+                }
+                StringMode::NullTerminated => {
+                    self.write(&bytes)?;
+                    self.reserved(0, CHAR_SIZE)?;
+                    Ok(())
                 }
-                let padding = size * CHAR_SIZE - bytes.len();
-                self.write(&bytes)?;
-                self.reserved(0, padding)?;
-                Ok(())
             }
-            StringMode::NullTerminated => {
-                self.write(&bytes)?;
-                self.reserved(0, CHAR_SIZE)?;
-                Ok(())
+        })
+    }
+}
+impl<W: std::io::Write, E: Endian> BinaryWriter<E, W> {
+    /// Writes `value` as a `u32` length followed by the payload in `encoding`.
+    ///
+    /// Whether it reads back unchanged depends on the encoding and platform, see the table on
+    /// `OsStrEncoding`.
+    pub fn write_os_string<T: AsRef<OsStr>>(&mut self, value: T, encoding: OsStrEncoding) -> crate::writer::Result<()> {
+        count_stats!(self, String, {
+            let (count, bytes) = encode_os_str::<E>(value.as_ref(), encoding);
+            self.u32(u32::try_from(count).map_err(|_| os_str_too_long())?)?;
+            self.write(&bytes)
+        })
+    }
+
+    /// Writes a path like `write_os_string`; see `OsStrEncoding` for which encodings are lossy.
+    pub fn write_path<T: AsRef<Path>>(&mut self, value: T, encoding: OsStrEncoding) -> crate::writer::Result<()> {
+        self.write_os_string(value.as_ref().as_os_str(), encoding)
+    }
+
+    /// Writes each string NUL-terminated followed by one more NUL. Empty entries and entries
+    /// containing NUL are rejected because the reader could not tell them from the end.
+    pub fn utf8_str_list<T: AsRef<str>>(&mut self, values: &[T]) -> crate::writer::Result<()> {
+        count_stats!(self, String, {
+            check_list_entries(values)?;
+            for value in values {
+                self.utf8_str(value, StringMode::NullTerminated)?;
             }
-        }
+            self.u8(0)
+        })
+    }
+
+    /// UTF-16 variant of `utf8_str_list`.
+    pub fn utf16_str_list<T: AsRef<str>>(&mut self, values: &[T]) -> crate::writer::Result<()> {
+        count_stats!(self, String, {
+            check_list_entries(values)?;
+            for value in values {
+                self.utf16_str(value, StringMode::NullTerminated)?;
+            }
+            self.u16(0)
+        })
     }
 }
+
+impl<E: Endian, W: crate::async_writer::WriterBase> AsyncBinaryWriter<E, W> {
+    pub async fn write_os_string<T: AsRef<OsStr>>(&mut self, value: T, encoding: OsStrEncoding) -> crate::async_writer::Result<()> {
+        count_stats!(self, String, async {
+            let (count, bytes) = encode_os_str::<E>(value.as_ref(), encoding);
+            self.u32(u32::try_from(count).map_err(|_| os_str_too_long())?).await?;
+            self.write(&bytes).await
+        })
+    }
+
+    pub async fn write_path<T: AsRef<Path>>(&mut self, value: T, encoding: OsStrEncoding) -> crate::async_writer::Result<()> {
+        self.write_os_string(value.as_ref().as_os_str(), encoding).await
+    }
+
+    pub async fn utf8_str_list<T: AsRef<str> + Sync>(&mut self, values: &[T]) -> crate::async_writer::Result<()> {
+        count_stats!(self, String, async {
+            check_list_entries(values)?;
+            for value in values {
+                self.utf8_str(value, StringMode::NullTerminated).await?;
+            }
+            self.u8(0).await
+        })
+    }
+
+    pub async fn utf16_str_list<T: AsRef<str> + Sync>(&mut self, values: &[T]) -> crate::async_writer::Result<()> {
+        count_stats!(self, String, async {
+            check_list_entries(values)?;
+            for value in values {
+                self.utf16_str(value, StringMode::NullTerminated).await?;
+            }
+            self.u16(0).await
+        })
+    }
+}
+
 impl<E: Endian, W: crate::async_writer::WriterBase> AsyncBinaryWriter<E, W> {
     pub async fn utf8_str<T: AsRef<str>>(&mut self, value: T, mode: StringMode) -> crate::async_writer::Result<()> {
-        let bytes = value.as_ref().as_bytes();
-        match mode {
-            StringMode::FixedChars(size) => {
-                if bytes.len() > size {
-                    return Err(crate::result::WriteError::io_error(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        "String is too long for fixed size",
-                    )));
+        count_stats!(self, String, async {
+            let bytes = value.as_ref().as_bytes();
+            match mode {
+                StringMode::FixedChars(size) => {
+                    if bytes.len() > size {
+                        return Err(crate::result::WriteError::io_error(std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            "String is too long for fixed size",
+                        )));
+                    }
+                    let padding = size - bytes.len();
+                    self.write(&bytes).await?;
+                    self.reserved(0, padding).await?;
+                }
+                StringMode::NullTerminated => {
+                    self.write(&bytes).await?;
+                    self.reserved(0, 1).await?;
                 }
-                let padding = size - bytes.len();
-                self.write(&bytes).await?;
-                self.reserved(0, padding).await?;
-            }
-            StringMode::NullTerminated => {
-                self.write(&bytes).await?;
-                self.reserved(0, 1).await?;
             }
-        }
-        Ok(())
+            Ok(())
+        })
     }
 
     pub async fn utf16_str<T: AsRef<str>>(&mut self, value: T, mode: StringMode) -> crate::async_writer::Result<()> {
-        const CHAR_SIZE: usize = std::mem::size_of::<u16>();
-        let bytes = E::u16iter_to_bytes(value.as_ref().encode_utf16(), value.as_ref().len() * CHAR_SIZE);
-        match mode {
-            StringMode::FixedChars(size) => {
-                if bytes.len() > size * CHAR_SIZE {
-                    return Err(crate::result::WriteError::io_error(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        "String is too long for fixed size",
-                    )));
+        count_stats!(self, String, async {
+            const CHAR_SIZE: usize = std::mem::size_of::<u16>();
+            let bytes = E::u16iter_to_bytes(value.as_ref().encode_utf16(), value.as_ref().len() * CHAR_SIZE);
+            match mode {
+                StringMode::FixedChars(size) => {
+                    if bytes.len() > size * CHAR_SIZE {
+                        return Err(crate::result::WriteError::io_error(std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            "String is too long for fixed size",
+                        )));
+                    }
+                    let padding = size * CHAR_SIZE - bytes.len();
+                    self.write(&bytes).await?;
+                    self.reserved(0, padding).await?;
+                }
+                StringMode::NullTerminated => {
+                    self.write(&bytes).await?;
+                    self.reserved(0, CHAR_SIZE).await?;
                 }
-                let padding = size * CHAR_SIZE - bytes.len();
-                self.write(&bytes).await?;
-                self.reserved(0, padding).await?;
-            }
-            StringMode::NullTerminated => {
-                self.write(&bytes).await?;
-                self.reserved(0, CHAR_SIZE).await?;
             }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_fixed_utf8() {
+        assert_eq!(utf8_nul_position(b"ab\0c\0"), Some(2));
+        assert_eq!(utf8_nul_position(b"abc"), None);
+        assert_eq!(decode_fixed_utf8(b"name\0\0\0\0"), "name");
+        // an embedded NUL ends the string, whatever follows it
+        assert_eq!(decode_fixed_utf8(b"ab\0cd"), "ab");
+        assert_eq!(decode_fixed_utf8(b"full"), "full");
+        assert_eq!(decode_fixed_utf8(b""), "");
+        assert_eq!(decode_fixed_utf8(b"a\xFFb\0"), "a\u{FFFD}b");
+
+        assert_eq!(decode_fixed_utf8_strict("käse\0\0".as_bytes()).unwrap(), "käse");
+        let err = decode_fixed_utf8_strict(b"ok\xC3(\0").unwrap_err();
+        assert_eq!(err.to_string(), "invalid data format: invalid UTF-8 at byte 2");
+        // bytes after the terminator are padding and not validated
+        assert_eq!(decode_fixed_utf8_strict(b"ok\0\xFF").unwrap(), "ok");
+    }
+
+    #[test]
+    fn test_decode_fixed_utf16() {
+        use crate::endian::{BigEndian, LittleEndian};
+
+        assert_eq!(utf16_nul_position(b"a\0\0\0b\0"), Some(2));
+        assert_eq!(utf16_nul_position(b"\0a\0"), None);
+        assert_eq!(decode_fixed_utf16::<LittleEndian>(b"h\0i\0\0\0x\0"), "hi");
+        assert_eq!(decode_fixed_utf16::<BigEndian>(b"\0h\0i"), "hi");
+        assert_eq!(decode_fixed_utf16::<BigEndian>(b"\0h\0i\0"), "hi");
+        // a lone high surrogate
+        assert_eq!(decode_fixed_utf16::<LittleEndian>(b"a\0\x00\xD8b\0"), "a\u{FFFD}b");
+        assert_eq!(decode_fixed_utf16::<LittleEndian>(b"\x3D\xD8\x00\xDE\0\0"), "😀");
+
+        assert_eq!(decode_fixed_utf16_strict::<BigEndian>(b"\xD8\x3D\xDE\x00\0!\0\0").unwrap(), "😀!");
+        let err = decode_fixed_utf16_strict::<LittleEndian>(b"a\0\x00\xD8b\0").unwrap_err();
+        assert_eq!(err.to_string(), "invalid data format: unpaired UTF-16 surrogate 0xD800 at byte 2");
+        assert!(decode_fixed_utf16_strict::<LittleEndian>(b"a\0b").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_async_write_strings() {
+        use std::io::Cursor;
+        let mut data = vec![0; 16];
+
+        // UTF-8 Null-terminated string
+        let stream = tokio::io::BufStream::new(Cursor::new(&mut data));
+        let mut writer = AsyncBinaryWriter::new_be(stream);
+        assert!(writer.utf8_str("Hello", StringMode::NullTerminated).await.is_ok());
+        assert!(writer.utf8_str("World", StringMode::NullTerminated).await.is_ok());
+        writer.flush().await.ok();
+        // check the written data
+        let expected = [
+            72, 101, 108, 108, 111, 0, // "Hello\0"
+            87, 111, 114, 108, 100, 0, // "World\0"
+            0, 0, 0, 0,
+        ];
+        assert_eq!(&data[..], &expected[..]);
+
+        // UTF-8 Fixed size string
+        let stream = tokio::io::BufStream::new(Cursor::new(&mut data));
+        let mut writer = AsyncBinaryWriter::new_be(stream);
+        assert!(writer.utf8_str("Hello", StringMode::FixedChars(10)).await.is_ok());
+        assert!(writer.utf8_str("World", StringMode::FixedChars(10)).await.is_ok());
+        writer.flush().await.ok();
+        // check the written data
+        let expected = [
+            72, 101, 108, 108, 111, 0, 0, 0, 0, 0, // "Hello\0\0\0\0"
+            87, 111, 114, 108, 100, 0, 0, 0, 0, 0, // "World\0\0\0\0"
+        ];
+        assert_eq!(&data[..], &expected[..]);
+
+        // UTF-16 Null-terminated string
+        let stream = tokio::io::BufStream::new(Cursor::new(&mut data));
+        let mut writer = AsyncBinaryWriter::new_be(stream);
+        assert!(writer.utf16_str("Hello", StringMode::NullTerminated).await.is_ok());
+        assert!(writer.utf16_str("World", StringMode::NullTerminated).await.is_ok());
+        writer.flush().await.ok();
+        // check the written data
+        let expected = [
+            0, b'H', 0, b'e', 0, b'l', 0, b'l', 0, b'o', 0, 0, // "Hello\0"
+            0, b'W', 0, b'o', 0, b'r', 0, b'l', 0, b'd', 0, 0, // "World\0"
+        ];
+        assert_eq!(&data[..], &expected[..]);
+
+        // UTF-16 Fixed size string
+        let stream = tokio::io::BufStream::new(Cursor::new(&mut data));
+        let mut writer = AsyncBinaryWriter::new_be(stream);
+        assert!(writer.utf16_str("Hello", StringMode::FixedChars(6)).await.is_ok());
+        assert!(writer.utf16_str("World", StringMode::FixedChars(6)).await.is_ok());
+        writer.flush().await.ok();
+        // check the written data
+        let expected = [
+            0, b'H', 0, b'e', 0, b'l', 0, b'l', 0, b'o', 0, 0, // "Hello\0\0"
+            0, b'W', 0, b'o', 0, b'r', 0, b'l', 0, b'd', 0, 0, // "World\0\0"
+        ];
+        assert_eq!(&data[..], &expected[..]);
+    }
+
+    #[test]
+    fn test_read_strings() {
+        let data = b"Hello, world!\0";
+        assert_eq!(BinaryReader::from_le_bytes(data).utf8_str(StringMode::NullTerminated).unwrap(), "Hello, world!");
+        assert_eq!(BinaryReader::from_le_bytes(data).utf8_str(StringMode::FixedChars(13)).unwrap(), "Hello, world!");
+
+        let data = b"Hello, world!\0";
+        assert_eq!(BinaryReader::from_be_bytes(data).utf8_str(StringMode::NullTerminated).unwrap(), "Hello, world!");
+        assert_eq!(BinaryReader::from_be_bytes(data).utf8_str(StringMode::FixedChars(13)).unwrap(), "Hello, world!");
+
+        let data = b"H\0e\0l\0l\0o\0,\0 \0w\0o\0r\0l\0d\0!\0\0\0";
+        assert_eq!(BinaryReader::from_le_bytes(data).utf16_str(StringMode::NullTerminated).unwrap(), "Hello, world!");
+        assert_eq!(BinaryReader::from_le_bytes(data).utf16_str(StringMode::FixedChars(13)).unwrap(), "Hello, world!");
+
+        let data = b"\0H\0e\0l\0l\0o\0,\0 \0w\0o\0r\0l\0d\0!\0\0\0";
+        assert_eq!(BinaryReader::from_be_bytes(data).utf16_str(StringMode::NullTerminated).unwrap(), "Hello, world!");
+        assert_eq!(BinaryReader::from_be_bytes(data).utf16_str(StringMode::FixedChars(13)).unwrap(), "Hello, world!");
+    }
+
+    #[test]
+    fn test_write_strings() {
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_le(&mut buf);
+
+        writer.utf8_str("Hello", StringMode::NullTerminated).unwrap();
+        writer.utf16_str("World", StringMode::NullTerminated).unwrap();
+
+        assert_eq!(buf, b"Hello\0W\0o\0r\0l\0d\0\0\0");
+    }
+
+    #[test]
+    fn test_os_string_round_trip() {
+        for encoding in [OsStrEncoding::Utf8Lossy, OsStrEncoding::Bytes, OsStrEncoding::Wtf16] {
+            let mut buf = Vec::new();
+            let mut writer = BinaryWriter::new_be(&mut buf);
+            writer.write_path(Path::new("assets/ミク.png"), encoding).unwrap();
+            let mut reader = BinaryReader::from_be_bytes(&buf);
+            assert_eq!(reader.read_path_buf(encoding).unwrap(), PathBuf::from("assets/ミク.png"));
         }
-        Ok(())
-    }
-}
-
-#[tokio::test]
-async fn test_async_write_strings() {
-    use std::io::Cursor;
-    let mut data = vec![0; 16];
-
-    // UTF-8 Null-terminated string
-    let stream = tokio::io::BufStream::new(Cursor::new(&mut data));
-    let mut writer = AsyncBinaryWriter::new_be(stream);
-    assert!(writer.utf8_str("Hello", StringMode::NullTerminated).await.is_ok());
-    assert!(writer.utf8_str("World", StringMode::NullTerminated).await.is_ok());
-    writer.flush().await.ok();
-    // check the written data
-    let expected = [
-        72, 101, 108, 108, 111, 0, // "Hello\0"
-        87, 111, 114, 108, 100, 0, // "World\0"
-        0, 0, 0, 0,
-    ];
-    assert_eq!(&data[..], &expected[..]);
-
-    // UTF-8 Fixed size string
-    let stream = tokio::io::BufStream::new(Cursor::new(&mut data));
-    let mut writer = AsyncBinaryWriter::new_be(stream);
-    assert!(writer.utf8_str("Hello", StringMode::FixedChars(10)).await.is_ok());
-    assert!(writer.utf8_str("World", StringMode::FixedChars(10)).await.is_ok());
-    writer.flush().await.ok();
-    // check the written data
-    let expected = [
-        72, 101, 108, 108, 111, 0, 0, 0, 0, 0, // "Hello\0\0\0\0"
-        87, 111, 114, 108, 100, 0, 0, 0, 0, 0, // "World\0\0\0\0"
-    ];
-    assert_eq!(&data[..], &expected[..]);
-
-    // UTF-16 Null-terminated string
-    let stream = tokio::io::BufStream::new(Cursor::new(&mut data));
-    let mut writer = AsyncBinaryWriter::new_be(stream);
-    assert!(writer.utf16_str("Hello", StringMode::NullTerminated).await.is_ok());
-    assert!(writer.utf16_str("World", StringMode::NullTerminated).await.is_ok());
-    writer.flush().await.ok();
-    // check the written data
-    let expected = [
-        0, b'H', 0, b'e', 0, b'l', 0, b'l', 0, b'o', 0, 0, // "Hello\0"
-        0, b'W', 0, b'o', 0, b'r', 0, b'l', 0, b'd', 0, 0, // "World\0"
-    ];
-    assert_eq!(&data[..], &expected[..]);
-
-    // UTF-16 Fixed size string
-    let stream = tokio::io::BufStream::new(Cursor::new(&mut data));
-    let mut writer = AsyncBinaryWriter::new_be(stream);
-    assert!(writer.utf16_str("Hello", StringMode::FixedChars(6)).await.is_ok());
-    assert!(writer.utf16_str("World", StringMode::FixedChars(6)).await.is_ok());
-    writer.flush().await.ok();
-    // check the written data
-    let expected = [
-        0, b'H', 0, b'e', 0, b'l', 0, b'l', 0, b'o', 0, 0, // "Hello\0\0"
-        0, b'W', 0, b'o', 0, b'r', 0, b'l', 0, b'd', 0, 0, // "World\0\0"
-    ];
-    assert_eq!(&data[..], &expected[..]);
-}
-
-#[test]
-fn test_read_strings() {
-    let data = b"Hello, world!\0";
-    assert_eq!(BinaryReader::from_le_bytes(data).utf8_str(StringMode::NullTerminated).unwrap(), "Hello, world!");
-    assert_eq!(BinaryReader::from_le_bytes(data).utf8_str(StringMode::FixedChars(13)).unwrap(), "Hello, world!");
-
-    let data = b"Hello, world!\0";
-    assert_eq!(BinaryReader::from_be_bytes(data).utf8_str(StringMode::NullTerminated).unwrap(), "Hello, world!");
-    assert_eq!(BinaryReader::from_be_bytes(data).utf8_str(StringMode::FixedChars(13)).unwrap(), "Hello, world!");
-
-    let data = b"H\0e\0l\0l\0o\0,\0 \0w\0o\0r\0l\0d\0!\0\0\0";
-    assert_eq!(BinaryReader::from_le_bytes(data).utf16_str(StringMode::NullTerminated).unwrap(), "Hello, world!");
-    assert_eq!(BinaryReader::from_le_bytes(data).utf16_str(StringMode::FixedChars(13)).unwrap(), "Hello, world!");
-
-    let data = b"\0H\0e\0l\0l\0o\0,\0 \0w\0o\0r\0l\0d\0!\0\0\0";
-    assert_eq!(BinaryReader::from_be_bytes(data).utf16_str(StringMode::NullTerminated).unwrap(), "Hello, world!");
-    assert_eq!(BinaryReader::from_be_bytes(data).utf16_str(StringMode::FixedChars(13)).unwrap(), "Hello, world!");
-}
-
-#[test]
-fn test_write_strings() {
-    let mut buf = Vec::new();
-    let mut writer = BinaryWriter::new_le(&mut buf);
-
-    writer.utf8_str("Hello", StringMode::NullTerminated).unwrap();
-    writer.utf16_str("World", StringMode::NullTerminated).unwrap();
-
-    assert_eq!(buf, b"Hello\0W\0o\0r\0l\0d\0\0\0");
+
+        let mut buf = Vec::new();
+        BinaryWriter::new_le(&mut buf).write_os_string("ab", OsStrEncoding::Wtf16).unwrap();
+        assert_eq!(buf, b"\x02\0\0\0a\0b\0");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_os_string_non_utf8_unix() {
+        use std::os::unix::ffi::OsStrExt;
+        let path = OsStr::from_bytes(b"data/\xFF\xFEraw.bin");
+
+        let mut buf = Vec::new();
+        BinaryWriter::new_le(&mut buf).write_os_string(path, OsStrEncoding::Bytes).unwrap();
+        assert_eq!(BinaryReader::from_le_bytes(&buf).read_os_string(OsStrEncoding::Bytes).unwrap(), path);
+
+        let mut buf = Vec::new();
+        BinaryWriter::new_le(&mut buf).write_os_string(path, OsStrEncoding::Utf8Lossy).unwrap();
+        assert_eq!(BinaryReader::from_le_bytes(&buf).read_os_string(OsStrEncoding::Utf8Lossy).unwrap(), "data/\u{FFFD}\u{FFFD}raw.bin");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_os_string_lone_surrogate_windows() {
+        use std::os::windows::ffi::OsStringExt;
+        let path = OsString::from_wide(&[b'a' as u16, 0xD800, b'b' as u16]);
+
+        let mut buf = Vec::new();
+        BinaryWriter::new_le(&mut buf).write_os_string(&path, OsStrEncoding::Wtf16).unwrap();
+        assert_eq!(BinaryReader::from_le_bytes(&buf).read_os_string(OsStrEncoding::Wtf16).unwrap(), path);
+    }
+
+    #[tokio::test]
+    async fn test_async_os_string_round_trip() {
+        let mut buf = Vec::new();
+        let mut writer = AsyncBinaryWriter::new_le(&mut buf);
+        writer.write_path(Path::new("a/b.txt"), OsStrEncoding::Bytes).await.unwrap();
+        let mut reader = AsyncBinaryReader::from_le_bytes(&buf);
+        assert_eq!(reader.read_path_buf(OsStrEncoding::Bytes).await.unwrap(), PathBuf::from("a/b.txt"));
+    }
+
+    #[test]
+    fn test_str_list_round_trip() {
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_le(&mut buf);
+        writer.utf8_str_list(&["one", "two"]).unwrap();
+        writer.utf8_str_list::<&str>(&[]).unwrap();
+        writer.utf16_str_list(&["x", "yz"]).unwrap();
+        assert_eq!(&buf[..9], b"one\0two\0\0");
+        assert_eq!(buf[9], 0);
+
+        let mut reader = BinaryReader::from_le_bytes(&buf);
+        assert_eq!(reader.utf8_str_list(64).unwrap(), vec!["one", "two"]);
+        assert!(reader.utf8_str_list(64).unwrap().is_empty());
+        assert_eq!(reader.utf16_str_list(64).unwrap(), vec!["x", "yz"]);
+    }
+
+    #[test]
+    fn test_str_list_edge_cases() {
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_le(&mut buf);
+        assert!(writer.utf8_str_list(&["a", ""]).is_err());
+        assert!(writer.utf8_str_list(&["a\0b"]).is_err());
+        assert!(buf.is_empty());
+
+        let data = b"abcdef\0\0";
+        assert!(matches!(BinaryReader::from_le_bytes(data).utf8_str_list(4), Err(crate::result::ReadError::InvalidDataFormat(_))));
+    }
+
+    #[tokio::test]
+    async fn test_async_str_list_round_trip() {
+        let mut buf = Vec::new();
+        let mut writer = AsyncBinaryWriter::new_be(&mut buf);
+        writer.utf8_str_list(&["one", "two"]).await.unwrap();
+        writer.utf16_str_list(&["x"]).await.unwrap();
+        let mut reader = AsyncBinaryReader::from_be_bytes(&buf);
+        assert_eq!(reader.utf8_str_list(64).await.unwrap(), vec!["one", "two"]);
+        assert_eq!(reader.utf16_str_list(64).await.unwrap(), vec!["x"]);
+    }
+
+    #[tokio::test]
+    async fn test_async_read_string_into() {
+        let data = b"abc\0wxyzhi\0";
+        let mut reader = AsyncBinaryReader::from_le_bytes(data);
+        let mut label = String::with_capacity(16);
+        let buffer = label.as_ptr();
+        reader.read_into_with(&mut label, StringMode::FixedChars(4)).await.unwrap();
+        assert_eq!(label, "abc");
+        reader.read_into_with(&mut label, StringMode::FixedChars(4)).await.unwrap();
+        assert_eq!(label, "wxyz");
+        reader.read_into_with(&mut label, StringMode::NullTerminated).await.unwrap();
+        assert_eq!(label, "hi");
+        assert_eq!(label.as_ptr(), buffer);
+    }
+
+    #[tokio::test]
+    async fn test_async_str_list_limits() {
+        let mut buf = Vec::new();
+        let mut writer = AsyncBinaryWriter::new_le(&mut buf);
+        writer.utf8_str_list::<&str>(&[]).await.unwrap();
+        writer.utf16_str_list(&["käse", "ß"]).await.unwrap();
+        assert!(writer.utf8_str_list(&["ok", ""]).await.is_err());
+        assert!(writer.utf16_str_list(&["a\0b"]).await.is_err());
+        assert_eq!(buf.len(), 1 + 2 * (5 + 2 + 1));
+
+        let mut reader = AsyncBinaryReader::from_le_bytes(&buf);
+        assert!(reader.utf8_str_list(1).await.unwrap().is_empty());
+        assert_eq!(reader.utf16_str_list(16).await.unwrap(), vec!["käse", "ß"]);
+
+        // an unterminated list is cut off at max_bytes instead of growing with the input
+        let hostile = vec![b'x'; 1 << 16];
+        let mut reader = AsyncBinaryReader::from_le_bytes(&hostile);
+        assert!(matches!(reader.utf8_str_list(32).await, Err(crate::result::ReadError::InvalidDataFormat(_))));
+        assert_eq!(reader.position(), 32);
+        let mut reader = AsyncBinaryReader::from_le_bytes(&hostile);
+        assert!(matches!(reader.utf16_str_list(32).await, Err(crate::result::ReadError::InvalidDataFormat(_))));
+        assert_eq!(reader.position(), 32);
+    }
 }
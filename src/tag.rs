@@ -0,0 +1,173 @@
+use crate::{
+    async_reader::{self, AsyncBinaryReader, AsyncRead, ReaderBase},
+    async_writer::{self, AsyncBinaryWriter, AsyncWrite, WriterBase},
+    endian::Endian,
+    fixed_size::FixedSize,
+    reader::{self, BinaryReader, Read},
+    writer::{self, BinaryWriter, Write},
+};
+use std::{fmt, io::BufRead};
+
+/// A fixed-width byte tag such as a FourCC (`RIFF`, `fmt `), copied verbatim regardless of endianness.
+///
+/// Tags compare against byte-string and string literals, and print as text with anything outside
+/// printable ASCII escaped as `\xNN`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Tag<const N: usize>(pub [u8; N]);
+
+impl<const N: usize> Tag<N> {
+    /// Creates a tag from its bytes, e.g. `Tag::new(*b"RIFF")`.
+    pub const fn new(bytes: [u8; N]) -> Self {
+        Self(bytes)
+    }
+
+    /// Returns the raw bytes of the tag.
+    pub const fn as_bytes(&self) -> &[u8; N] {
+        &self.0
+    }
+
+    fn fmt_escaped(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &byte in &self.0 {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                write!(f, "{}", byte as char)?;
+            } else {
+                write!(f, "\\x{byte:02x}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for Tag<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for Tag<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const N: usize> PartialEq<[u8; N]> for Tag<N> {
+    fn eq(&self, other: &[u8; N]) -> bool {
+        self.0 == *other
+    }
+}
+
+impl<const N: usize> PartialEq<&[u8; N]> for Tag<N> {
+    fn eq(&self, other: &&[u8; N]) -> bool {
+        self.0 == **other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for Tag<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == other.as_bytes()
+    }
+}
+
+impl<const N: usize> fmt::Display for Tag<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_escaped(f)
+    }
+}
+
+impl<const N: usize> fmt::Debug for Tag<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Tag(\"")?;
+        self.fmt_escaped(f)?;
+        f.write_str("\")")
+    }
+}
+
+impl<const N: usize> FixedSize for Tag<N> {
+    const SIZE: usize = N;
+}
+
+impl<const N: usize> Read for Tag<N> {
+    fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> reader::Result<Self> {
+        reader.read_from_array(|bytes: &[u8; N]| Tag(*bytes))
+    }
+}
+
+impl<const N: usize> Write for Tag<N> {
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> writer::Result<()> {
+        writer.write(&self.0.as_slice())
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(N)
+    }
+}
+
+impl<const N: usize> AsyncRead for Tag<N> {
+    async fn read<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>) -> async_reader::Result<Self> {
+        reader.read_from_array(|bytes: &[u8; N]| Tag(*bytes)).await
+    }
+}
+
+impl<const N: usize> AsyncWrite for Tag<N> {
+    async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> async_writer::Result<()> {
+        writer.write(&self.0.as_slice()).await
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(N)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RIFF: Tag<4> = Tag::new(*b"RIFF");
+
+    #[test]
+    fn test_tag_comparisons() {
+        let tag = Tag::new(*b"fmt ");
+        assert_eq!(tag, b"fmt ");
+        assert_eq!(tag, *b"fmt ");
+        assert_eq!(tag, "fmt ");
+        assert_ne!(tag, "fmt");
+        assert_ne!(tag, RIFF);
+        assert_eq!(tag.as_bytes(), b"fmt ");
+    }
+
+    #[test]
+    fn test_tag_formatting() {
+        let tag = Tag::new([b'a', b'b', 0x00, 0xff]);
+        assert_eq!(format!("{tag}"), "ab\\x00\\xff");
+        assert_eq!(format!("{tag:?}"), "Tag(\"ab\\x00\\xff\")");
+        assert_eq!(format!("{RIFF:?}"), "Tag(\"RIFF\")");
+    }
+
+    #[test]
+    fn test_tag_round_trip() {
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_be(&mut buf);
+        writer.write(&RIFF).unwrap();
+        writer.u32(36).unwrap();
+        writer.write(&Tag::new(*b"WAVE")).unwrap();
+        assert_eq!(&buf[..4], b"RIFF");
+
+        let mut reader = BinaryReader::from_be_bytes(&buf);
+        reader.magic(RIFF).unwrap();
+        assert_eq!(reader.u32().unwrap(), 36);
+        let form: Tag<4> = reader.read().unwrap();
+        assert_eq!(form, "WAVE");
+    }
+
+    #[tokio::test]
+    async fn test_async_tag_round_trip() {
+        let mut buf = Vec::new();
+        let mut writer = AsyncBinaryWriter::new_le(&mut buf);
+        writer.write(&RIFF).await.unwrap();
+        writer.flush().await.unwrap();
+        assert_eq!(buf, b"RIFF");
+
+        let mut reader = AsyncBinaryReader::from_le_bytes(&buf);
+        assert_eq!(reader.read::<Tag<4>>().await.unwrap(), b"RIFF");
+    }
+}
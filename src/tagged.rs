@@ -0,0 +1,154 @@
+use crate::{
+    reader::{BinaryReader, Read, Result},
+    result::ReadError,
+};
+use std::{collections::HashMap, fmt::Debug, hash::Hash, io::BufRead};
+
+/// A reader whose stream type is erased, as handed to the parse functions of a `TagTable`.
+pub type DynReader<'a, E> = BinaryReader<E, &'a mut dyn BufRead>;
+
+type ParseFn<E, T> = Box<dyn Fn(&mut DynReader<'_, E>) -> Result<T> + Send + Sync>;
+type FallbackFn<E, Tag, T> = Box<dyn Fn(Tag, &mut DynReader<'_, E>) -> Result<T> + Send + Sync>;
+
+/// Parse functions for the bodies of a tagged union, keyed by tag, for `BinaryReader::read_tagged`.
+///
+/// Each function reads everything after the tag. Tags without an entry go to the fallback, or
+/// fail with `ReadError::InvalidDataFormat` if there is none.
+pub struct TagTable<E, Tag, T> {
+    entries: HashMap<Tag, ParseFn<E, T>>,
+    fallback: Option<FallbackFn<E, Tag, T>>,
+}
+
+impl<E, Tag: Eq + Hash, T> TagTable<E, Tag, T> {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            fallback: None,
+        }
+    }
+
+    /// Parses the body of `tag` with `parse`, replacing any function registered for it before.
+    pub fn with(mut self, tag: Tag, parse: impl Fn(&mut DynReader<'_, E>) -> Result<T> + Send + Sync + 'static) -> Self {
+        self.entries.insert(tag, Box::new(parse));
+        self
+    }
+
+    /// Handles tags without an entry with `fallback`, which gets the tag that was read.
+    pub fn with_fallback(mut self, fallback: impl Fn(Tag, &mut DynReader<'_, E>) -> Result<T> + Send + Sync + 'static) -> Self {
+        self.fallback = Some(Box::new(fallback));
+        self
+    }
+
+    /// Skips the body of tags without an entry, given by an `L` length prefix, and returns `unknown(tag)`.
+    pub fn skip_unknown_prefixed<L: Read + Into<u64>>(self, unknown: impl Fn(Tag) -> T + Send + Sync + 'static) -> Self
+    where
+        E: crate::endian::Endian,
+    {
+        self.with_fallback(move |tag, reader| {
+            let len = reader.read::<L>()?.into();
+            reader.skip(usize::try_from(len).map_err(|_| ReadError::InvalidArgument)?)?;
+            Ok(unknown(tag))
+        })
+    }
+
+    /// Runs the entry for `tag`, or the fallback.
+    pub(crate) fn dispatch(&self, tag: Tag, reader: &mut DynReader<'_, E>) -> Result<T>
+    where
+        Tag: Debug,
+    {
+        match (self.entries.get(&tag), &self.fallback) {
+            (Some(parse), _) => parse(reader),
+            (None, Some(fallback)) => fallback(tag, reader),
+            (None, None) => Err(ReadError::invalid_data_format(format!("unknown tag {tag:?}"))),
+        }
+    }
+}
+
+impl<E, Tag: Eq + Hash, T> Default for TagTable<E, Tag, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        endian::{BigEndian, Endian},
+        result::WriteError,
+        writer::BinaryWriter,
+    };
+
+    #[derive(Debug, PartialEq)]
+    enum Msg {
+        Ping(u32),
+        Text(String),
+        Move { x: i16, y: i16 },
+        Unknown(u8),
+    }
+
+    fn table<E: Endian>() -> TagTable<E, u8, Msg> {
+        TagTable::new()
+            .with(1, |r| {
+                r.u16()?;
+                r.u32().map(Msg::Ping)
+            })
+            .with(2, |r| {
+                let len = r.u16()? as usize;
+                r.utf8_str(crate::string::StringMode::FixedChars(len)).map(Msg::Text)
+            })
+            .with(3, |r| {
+                r.u16()?;
+                Ok(Msg::Move { x: r.i16()?, y: r.i16()? })
+            })
+            .skip_unknown_prefixed::<u16>(Msg::Unknown)
+    }
+
+    fn write_msg(writer: &mut BinaryWriter<BigEndian, Vec<u8>>, msg: &Msg) {
+        writer
+            .write_tagged_prefixed::<_, u8, u16>(msg, |msg, body| match msg {
+                Msg::Ping(n) => body.u32(*n).map(|()| 1),
+                Msg::Text(text) => body.utf8_str(text, crate::string::StringMode::FixedChars(text.len())).map(|()| 2),
+                Msg::Move { x, y } => body.i16(*x).and_then(|()| body.i16(*y)).map(|()| 3),
+                Msg::Unknown(tag) => body.u8(0xee).map(|()| *tag),
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_tagged_round_trip() {
+        let msgs = [Msg::Ping(7), Msg::Unknown(9), Msg::Text("hi".into()), Msg::Move { x: -1, y: 2 }];
+        let mut writer = BinaryWriter::new_be(Vec::new());
+        for msg in &msgs {
+            write_msg(&mut writer, msg);
+        }
+        let data = writer.finish().unwrap();
+        assert_eq!(&data[..7], [1, 0, 4, 0, 0, 0, 7]);
+        assert_eq!(&data[7..11], [9, 0, 1, 0xee]);
+
+        let table = table::<BigEndian>();
+        let mut reader = BinaryReader::from_be_bytes(&data);
+        let read: Vec<_> = (0..msgs.len()).map(|_| reader.read_tagged(&table).unwrap()).collect();
+        assert_eq!(read, msgs);
+        assert_eq!(reader.remaining(), Some(0));
+
+        let strict = TagTable::<BigEndian, u8, Msg>::new().with(1, |r| r.u32().map(Msg::Ping));
+        let mut reader = BinaryReader::from_be_bytes(&data[7..]);
+        assert!(matches!(reader.read_tagged(&strict), Err(ReadError::InvalidDataFormat(msg)) if msg == "unknown tag 9"));
+        assert_eq!(reader.position(), 1);
+    }
+
+    #[test]
+    fn test_write_tagged_in_place() {
+        let mut writer = crate::builder::BinaryWriterBuilder::new().limit(6).endian_be().build_vec();
+        let msg = Msg::Ping(7);
+        assert!(matches!(
+            writer.write_tagged_prefixed::<_, u8, u16>(&msg, |_, body| body.u32(7).map(|()| 1)),
+            Err(WriteError::LimitExceeded { .. })
+        ));
+        assert_eq!(writer.position(), 0);
+        // the body is padded by where it lands, after the tag
+        writer.write_tagged(&msg, |_, body| body.u8(7).and_then(|()| body.align_to(4)).map(|()| 1u8)).unwrap();
+        assert_eq!(writer.finish().unwrap(), [1, 7, 0, 0]);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{
+    endian::Endian,
+    result::render_hex_diff,
+    writer::{BinaryWriter, Write},
+};
+use std::path::Path;
+
+/// Environment variable that makes `assert_golden` rewrite golden files instead of comparing.
+pub const BLESS_VAR: &str = "RWBIN_BLESS";
+
+/// Asserts that `value` serializes to exactly the bytes stored in the golden file at `path`.
+///
+/// Guards `Write` impls against accidental format changes. On a mismatch it panics with the size
+/// of both outputs and a hex diff of the first differing row. Run the tests with `RWBIN_BLESS=1`
+/// to create or update the golden files from the current output, then review and check them in.
+#[track_caller]
+pub fn assert_golden<E: Endian, T: Write>(value: &T, path: &Path) {
+    let actual = BinaryWriter::<E, _>::to_vec(value).unwrap_or_else(|err| panic!("writing {} failed: {err}", std::any::type_name::<T>()));
+    if std::env::var_os(BLESS_VAR).is_some_and(|bless| !bless.is_empty()) {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).unwrap_or_else(|err| panic!("creating {} failed: {err}", dir.display()));
+        }
+        std::fs::write(path, &actual).unwrap_or_else(|err| panic!("writing golden file {} failed: {err}", path.display()));
+        return;
+    }
+    let expected = match std::fs::read(path) {
+        Ok(expected) => expected,
+        Err(err) => panic!("reading golden file {} failed: {err}\nrerun with {BLESS_VAR}=1 to create it", path.display()),
+    };
+    if actual != expected {
+        let first_diff = expected.iter().zip(&actual).position(|(e, a)| e != a).unwrap_or(expected.len().min(actual.len()));
+        panic!(
+            "{} does not match golden file {} ({} bytes written, {} expected)\n{}\nrerun with {BLESS_VAR}=1 to accept the new output",
+            std::any::type_name::<T>(),
+            path.display(),
+            actual.len(),
+            expected.len(),
+            render_hex_diff(&expected, &actual, first_diff),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        endian::{BigEndian, LittleEndian},
+        reader::LenPolicy,
+        string::StringMode,
+        writer::Result,
+    };
+    use std::path::PathBuf;
+
+    fn golden(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/golden").join(name)
+    }
+
+    struct Strings;
+
+    impl Write for Strings {
+        fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+            writer.utf8_str("name", StringMode::NullTerminated)?;
+            writer.utf8_str("id", StringMode::FixedChars(4))?;
+            writer.utf16_str("ü!", StringMode::NullTerminated)?;
+            writer.utf16_str("ab", StringMode::FixedChars(6))
+        }
+    }
+
+    /// A RIFF-style chunk: a FourCC tag and a `u32` length that counts only the payload.
+    struct Chunk(&'static [u8; 4], Vec<u16>);
+
+    impl Write for Chunk {
+        fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+            writer.write(self.0)?;
+            writer.write_len_prefixed::<u32, _>(&self.1, LenPolicy::Exclusive)
+        }
+    }
+
+    #[test]
+    fn test_golden_strings() {
+        assert_golden::<LittleEndian, _>(&Strings, &golden("strings_le.bin"));
+        assert_golden::<BigEndian, _>(&Strings, &golden("strings_be.bin"));
+    }
+
+    #[test]
+    fn test_golden_chunk() {
+        assert_golden::<LittleEndian, _>(&Chunk(b"data", vec![1, 0x0203, 0xfffe]), &golden("chunk_le.bin"));
+    }
+
+    #[test]
+    fn test_golden_mismatch() {
+        if std::env::var_os(BLESS_VAR).is_some() {
+            return;
+        }
+        let path = std::env::temp_dir().join(format!("rwbin-golden-{}.bin", std::process::id()));
+        std::fs::write(&path, BinaryWriter::<LittleEndian, _>::to_vec(&Strings).unwrap()).unwrap();
+        let panic = std::panic::catch_unwind(|| assert_golden::<BigEndian, _>(&Strings, &path)).unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.contains("(27 bytes written, 27 expected)"), "{message}");
+        assert!(message.contains("expected: 6E 61 6D 65 00 69 64 00 00 FC 00 21 00 00 00 61"), "{message}");
+        assert!(message.contains("  actual: 6E 61 6D 65 00 69 64 00 00 00 FC 00 21 00 00 00"), "{message}");
+        std::fs::remove_file(&path).unwrap();
+
+        let missing = std::panic::catch_unwind(|| assert_golden::<BigEndian, _>(&0u8, &path)).unwrap_err();
+        assert!(missing.downcast_ref::<String>().unwrap().contains("RWBIN_BLESS=1 to create it"));
+    }
+}
@@ -61,6 +61,11 @@ fn test_sync_read_write() -> Result<(), Box<dyn std::error::Error>> {
     let mut writer = BinaryWriter::new_le(buf_writer);
     writer.u32(0xDEADBEEF)?;
     writer.i16(-42)?;
+    let record = TestStruct {
+        points: [(0.0, 1.0), (2.5, -3.0), (4.0, 5.0), (-6.5, 7.0)],
+        items: vec![vec!["ab".to_string(), "cde".to_string()]],
+    };
+    writer.write(&record)?;
     writer.flush()?;
 
     let file = File::open(&path)?;
@@ -68,6 +73,9 @@ fn test_sync_read_write() -> Result<(), Box<dyn std::error::Error>> {
     let mut reader = BinaryReader::new_le(buf_reader);
     assert_eq!(reader.u32()?, 0xDEADBEEF);
     assert_eq!(reader.i16()?, -42);
+    let read_back: TestStruct = reader.read()?;
+    assert_eq!(read_back.points, record.points);
+    assert_eq!(read_back.items, record.items);
 
     std::fs::remove_file(&path)?;
 
@@ -101,3 +109,397 @@ async fn test_async_read_write() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+// Every endian-override path, sync and async, must produce and accept exactly the bytes of a
+// native writer or reader of the other byte order, with the position carried across.
+#[cfg(test)]
+mod endian_override {
+    use crate::async_reader::{AsyncBinaryReader, AsyncRead, AsyncReadWith, ReaderBase};
+    use crate::async_writer::{AsyncBinaryWriter, AsyncWrite, AsyncWriteWith, WriterBase};
+    use crate::endian::{BigEndian, Endian, LittleEndian};
+    use crate::reader::{self, BinaryReader, Read, ReadWith};
+    use crate::result::ReadError;
+    use crate::writer::{self, BinaryWriter, Write, WriteWith};
+    use std::io::BufRead;
+
+    #[derive(Debug, PartialEq)]
+    struct Record {
+        id: u16,
+        value: u32,
+    }
+
+    const RECORD: Record = Record { id: 0x0102, value: 0x0304_0506 };
+    const TAG: u8 = 0x7e;
+
+    fn check_tag(tag: u8, expected: u8) -> reader::Result<()> {
+        if tag == expected {
+            Ok(())
+        } else {
+            Err(ReadError::invalid_data_format(format!("tag {tag:#04x}, expected {expected:#04x}")))
+        }
+    }
+
+    impl Read for Record {
+        fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> reader::Result<Self> {
+            Ok(Record {
+                id: reader.u16()?,
+                value: reader.u32()?,
+            })
+        }
+    }
+
+    impl ReadWith<u8> for Record {
+        fn read_with<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>, tag: u8) -> reader::Result<Self> {
+            let record = reader.read()?;
+            check_tag(reader.u8()?, tag)?;
+            Ok(record)
+        }
+    }
+
+    impl Write for Record {
+        fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> writer::Result<()> {
+            writer.u16(self.id)?;
+            writer.u32(self.value)
+        }
+    }
+
+    impl WriteWith<u8> for Record {
+        fn write_with<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>, tag: u8) -> writer::Result<()> {
+            writer.write(self)?;
+            writer.u8(tag)
+        }
+    }
+
+    impl AsyncRead for Record {
+        async fn read<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>) -> reader::Result<Self> {
+            Ok(Record {
+                id: reader.u16().await?,
+                value: reader.u32().await?,
+            })
+        }
+    }
+
+    impl AsyncReadWith<u8> for Record {
+        async fn read_with<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>, tag: u8) -> reader::Result<Self> {
+            let record = reader.read().await?;
+            check_tag(reader.u8().await?, tag)?;
+            Ok(record)
+        }
+    }
+
+    impl AsyncWrite for Record {
+        async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> writer::Result<()> {
+            writer.u16(self.id).await?;
+            writer.u32(self.value).await
+        }
+    }
+
+    impl AsyncWriteWith<u8> for Record {
+        async fn write_with<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>, tag: &u8) -> writer::Result<()> {
+            writer.write(self).await?;
+            writer.u8(*tag).await
+        }
+    }
+
+    /// The bytes a writer in byte order `E` produces for a `u16` marker, the record in byte
+    /// order `F`, with the tag if `tagged`, and a closing `u16` marker.
+    fn expected<E: Endian, F: Endian>(tagged: bool) -> Vec<u8> {
+        let mut bytes = BinaryWriter::<E, _>::to_vec(&0xaa55u16).unwrap();
+        bytes.extend(BinaryWriter::<F, _>::to_vec(&RECORD).unwrap());
+        if tagged {
+            bytes.push(TAG);
+        }
+        bytes.extend(BinaryWriter::<E, _>::to_vec(&0x1234u16).unwrap());
+        bytes
+    }
+
+    #[test]
+    fn test_sync_matrix() {
+        let mut writer = BinaryWriter::new_le(Vec::new());
+        writer.u16(0xaa55).unwrap();
+        writer.write_as_be(&RECORD).unwrap();
+        writer.u16(0x1234).unwrap();
+        assert_eq!(writer.finish().unwrap(), expected::<LittleEndian, BigEndian>(false));
+
+        let mut writer = BinaryWriter::new_le(Vec::new());
+        writer.u16(0xaa55).unwrap();
+        writer.write_as_be_with(&RECORD, TAG).unwrap();
+        writer.u16(0x1234).unwrap();
+        assert_eq!(writer.finish().unwrap(), expected::<LittleEndian, BigEndian>(true));
+
+        let mut writer = BinaryWriter::new_be(Vec::new());
+        writer.u16(0xaa55).unwrap();
+        writer.write_as_le(&RECORD).unwrap();
+        writer.u16(0x1234).unwrap();
+        assert_eq!(writer.finish().unwrap(), expected::<BigEndian, LittleEndian>(false));
+
+        let mut writer = BinaryWriter::new_be(Vec::new());
+        writer.u16(0xaa55).unwrap();
+        writer.write_as_le_with(&RECORD, TAG).unwrap();
+        writer.u16(0x1234).unwrap();
+        assert_eq!(writer.finish().unwrap(), expected::<BigEndian, LittleEndian>(true));
+
+        let bytes = expected::<LittleEndian, BigEndian>(false);
+        let mut reader = BinaryReader::from_le_bytes(&bytes);
+        assert_eq!(reader.u16().unwrap(), 0xaa55);
+        assert_eq!(reader.read_as_be::<Record>().unwrap(), RECORD);
+        assert_eq!((reader.u16().unwrap(), reader.remaining()), (0x1234, Some(0)));
+
+        let bytes = expected::<LittleEndian, BigEndian>(true);
+        let mut reader = BinaryReader::from_le_bytes(&bytes);
+        assert_eq!(reader.u16().unwrap(), 0xaa55);
+        assert_eq!(reader.read_as_be_with::<Record, _>(TAG).unwrap(), RECORD);
+        assert_eq!((reader.u16().unwrap(), reader.remaining()), (0x1234, Some(0)));
+
+        let bytes = expected::<BigEndian, LittleEndian>(false);
+        let mut reader = BinaryReader::from_be_bytes(&bytes);
+        assert_eq!(reader.u16().unwrap(), 0xaa55);
+        assert_eq!(reader.read_as_le::<Record>().unwrap(), RECORD);
+        assert_eq!((reader.u16().unwrap(), reader.remaining()), (0x1234, Some(0)));
+
+        let bytes = expected::<BigEndian, LittleEndian>(true);
+        let mut reader = BinaryReader::from_be_bytes(&bytes);
+        assert_eq!(reader.u16().unwrap(), 0xaa55);
+        assert_eq!(reader.read_as_le_with::<Record, _>(TAG).unwrap(), RECORD);
+        assert_eq!((reader.u16().unwrap(), reader.remaining()), (0x1234, Some(0)));
+        assert!(matches!(
+            BinaryReader::from_be_bytes(&bytes[2..]).read_as_le_with::<Record, _>(TAG + 1),
+            Err(ReadError::InvalidDataFormat(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_async_matrix() {
+        let mut writer = AsyncBinaryWriter::new_le(Vec::new());
+        writer.u16(0xaa55).await.unwrap();
+        writer.write_as_be(&RECORD).await.unwrap();
+        writer.u16(0x1234).await.unwrap();
+        assert_eq!(writer.finish().await.unwrap(), expected::<LittleEndian, BigEndian>(false));
+
+        let mut writer = AsyncBinaryWriter::new_le(Vec::new());
+        writer.u16(0xaa55).await.unwrap();
+        writer.write_as_be_with(&RECORD, TAG).await.unwrap();
+        writer.u16(0x1234).await.unwrap();
+        assert_eq!(writer.finish().await.unwrap(), expected::<LittleEndian, BigEndian>(true));
+
+        let mut writer = AsyncBinaryWriter::new_be(Vec::new());
+        writer.u16(0xaa55).await.unwrap();
+        writer.write_as_le(&RECORD).await.unwrap();
+        writer.u16(0x1234).await.unwrap();
+        assert_eq!(writer.finish().await.unwrap(), expected::<BigEndian, LittleEndian>(false));
+
+        let mut writer = AsyncBinaryWriter::new_be(Vec::new());
+        writer.u16(0xaa55).await.unwrap();
+        writer.write_as_le_with(&RECORD, TAG).await.unwrap();
+        writer.u16(0x1234).await.unwrap();
+        assert_eq!(writer.finish().await.unwrap(), expected::<BigEndian, LittleEndian>(true));
+
+        let bytes = expected::<LittleEndian, BigEndian>(false);
+        let mut reader = AsyncBinaryReader::from_le_bytes(&bytes);
+        assert_eq!(reader.u16().await.unwrap(), 0xaa55);
+        assert_eq!(reader.read_as_be::<Record>().await.unwrap(), RECORD);
+        assert_eq!((reader.u16().await.unwrap(), reader.remaining()), (0x1234, Some(0)));
+
+        let bytes = expected::<LittleEndian, BigEndian>(true);
+        let mut reader = AsyncBinaryReader::from_le_bytes(&bytes);
+        assert_eq!(reader.u16().await.unwrap(), 0xaa55);
+        assert_eq!(reader.read_as_be_with::<Record, _>(TAG).await.unwrap(), RECORD);
+        assert_eq!((reader.u16().await.unwrap(), reader.remaining()), (0x1234, Some(0)));
+
+        let bytes = expected::<BigEndian, LittleEndian>(false);
+        let mut reader = AsyncBinaryReader::from_be_bytes(&bytes);
+        assert_eq!(reader.u16().await.unwrap(), 0xaa55);
+        assert_eq!(reader.read_as_le::<Record>().await.unwrap(), RECORD);
+        assert_eq!((reader.u16().await.unwrap(), reader.remaining()), (0x1234, Some(0)));
+
+        let bytes = expected::<BigEndian, LittleEndian>(true);
+        let mut reader = AsyncBinaryReader::from_be_bytes(&bytes);
+        assert_eq!(reader.u16().await.unwrap(), 0xaa55);
+        assert_eq!(reader.read_as_le_with::<Record, _>(TAG).await.unwrap(), RECORD);
+        assert_eq!((reader.u16().await.unwrap(), reader.remaining()), (0x1234, Some(0)));
+    }
+}
+
+// Zero-length and otherwise degenerate arguments succeed and consume or emit nothing, on empty
+// sources and sinks as much as on full ones.
+#[cfg(test)]
+mod degenerate {
+    use crate::async_reader::AsyncBinaryReader;
+    use crate::async_writer::AsyncBinaryWriter;
+    use crate::reader::BinaryReader;
+    use crate::string::StringMode;
+    use crate::writer::BinaryWriter;
+
+    #[test]
+    fn test_sync_reader() {
+        for data in [&[][..], &[1, 2, 3]] {
+            let mut reader = BinaryReader::from_le_bytes(data).with_small_buf_size(0);
+            reader.skip(0).unwrap();
+            reader.skip_aligned(1).unwrap();
+            reader.reserved::<0>(0xff).unwrap();
+            assert_eq!(reader.reserved_report::<0>(0xff).unwrap(), []);
+            assert_eq!(reader.utf8_str(StringMode::FixedChars(0)).unwrap(), "");
+            assert_eq!(reader.utf16_str(StringMode::FixedChars(0)).unwrap(), "");
+            assert_eq!(reader.read_with::<Vec<u32>, _>(0).unwrap(), []);
+            assert_eq!(reader.read::<[u16; 0]>().unwrap(), []);
+            assert_eq!(reader.read_partial::<[u64; 0]>(0).unwrap(), []);
+            assert_eq!(reader.read_from_slice(0, |bytes| Ok(bytes.len())).unwrap(), 0);
+            assert_eq!((reader.position(), reader.remaining()), (0, Some(data.len() as u64)));
+            assert!(!reader.is_poisoned());
+        }
+
+        // past the end, a zero-length read still succeeds
+        let mut reader = BinaryReader::from_be_bytes(&[7]);
+        reader.u8().unwrap();
+        reader.skip(0).unwrap();
+        assert_eq!(reader.utf8_str(StringMode::FixedChars(0)).unwrap(), "");
+        assert!(matches!(reader.u8(), Err(crate::result::ReadError::NotEnoughBytes { requested: 1, remaining: 0, .. })));
+        assert!(!reader.is_poisoned());
+
+        // a stream reader over an empty source
+        let mut reader = BinaryReader::new_le(std::io::BufReader::new(std::io::empty()));
+        reader.skip(0).unwrap();
+        assert_eq!(reader.read_with::<Vec<u8>, _>(0).unwrap(), []);
+        assert_eq!(reader.position(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_async_reader() {
+        for data in [&[][..], &[1, 2, 3]] {
+            let mut reader = AsyncBinaryReader::from_le_bytes(data);
+            reader.skip(0).await.unwrap();
+            reader.skip_aligned(1).await.unwrap();
+            reader.reserved::<0>(0xff).await.unwrap();
+            assert_eq!(reader.reserved_report::<0>(0xff).await.unwrap(), []);
+            assert_eq!(reader.utf8_str(StringMode::FixedChars(0)).await.unwrap(), "");
+            assert_eq!(reader.utf16_str(StringMode::FixedChars(0)).await.unwrap(), "");
+            assert_eq!(reader.read_with::<_, Vec<u32>>(0).await.unwrap(), []);
+            assert_eq!(reader.read::<[u16; 0]>().await.unwrap(), []);
+            assert_eq!(reader.read_partial::<[u64; 0]>(0).await.unwrap(), []);
+            assert_eq!(reader.read_from_slice(0, |bytes| Ok(bytes.len())).await.unwrap(), 0);
+            assert_eq!((reader.position(), reader.remaining()), (0, Some(data.len() as u64)));
+            assert!(!reader.is_poisoned());
+        }
+    }
+
+    /// A sink that fails every call, to show that nothing reaches it.
+    struct Closed;
+
+    impl std::io::Write for Closed {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl tokio::io::AsyncWrite for Closed {
+        fn poll_write(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>, _: &[u8]) -> std::task::Poll<std::io::Result<usize>> {
+            std::task::Poll::Ready(Err(std::io::ErrorKind::BrokenPipe.into()))
+        }
+
+        fn poll_flush(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn test_sync_writer() {
+        for capacity in [0, 4] {
+            let mut writer = BinaryWriter::new_le(Closed).with_buffer_capacity(capacity);
+            writer.reserved(0xff, 0).unwrap();
+            writer.reserved_for::<[u8; 0]>(0xff).unwrap();
+            writer.fill_aligned(1, 0).unwrap();
+            writer.fill_aligned(1, 3).unwrap();
+            writer.fill_aligned(4, 8).unwrap();
+            writer.write(&Vec::<u8>::new()).unwrap();
+            writer.write(&[0i8; 0]).unwrap();
+            writer.write(&<&[u32]>::default()).unwrap();
+            writer.utf8_str("", StringMode::FixedChars(0)).unwrap();
+            writer.utf16_str("", StringMode::FixedChars(0)).unwrap();
+            assert_eq!(writer.position(), 0);
+            writer.finish().unwrap();
+        }
+
+        let mut writer = BinaryWriter::new_be(Vec::new());
+        writer.u8(1).unwrap();
+        writer.fill_aligned(1, 1).unwrap();
+        writer.write(&[0u8; 0]).unwrap();
+        assert_eq!(writer.finish().unwrap(), [1]);
+    }
+
+    #[tokio::test]
+    async fn test_async_writer() {
+        for capacity in [0, 4] {
+            let mut writer = AsyncBinaryWriter::new_le(Closed).with_buffer_capacity(capacity);
+            writer.reserved(0xff, 0).await.unwrap();
+            writer.reserved_for::<[u8; 0]>(0xff).await.unwrap();
+            writer.fill_aligned(1, 0).await.unwrap();
+            writer.fill_aligned(1, 3).await.unwrap();
+            writer.fill_aligned(4, 8).await.unwrap();
+            writer.write(&Vec::<u8>::new()).await.unwrap();
+            writer.write(&[0i8; 0]).await.unwrap();
+            writer.write(&<&[u32]>::default()).await.unwrap();
+            writer.utf8_str("", StringMode::FixedChars(0)).await.unwrap();
+            writer.utf16_str("", StringMode::FixedChars(0)).await.unwrap();
+            assert_eq!(writer.position(), 0);
+            writer.finish().await.unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod position {
+    use crate::async_reader::AsyncBinaryReader;
+    use crate::reader::BinaryReader;
+    use crate::string::StringMode;
+
+    // u16 LE, u32 BE, a 6-byte region holding a u16, 3 skipped bytes, "abc\0", UTF-16LE "hi", u16 BE.
+    const DATA: &[u8] = b"\x01\x02\x00\x00\x00\x07\x05\x00\xee\xee\xee\xee\xff\xff\xffabc\0h\0i\0\x00\x09";
+
+    #[test]
+    fn test_position_interleaved() {
+        let mut reader = BinaryReader::from_le_bytes(DATA);
+        assert_eq!(reader.position(), 0);
+        assert_eq!(reader.u16().unwrap(), 0x0201);
+        assert_eq!(reader.position(), 2);
+        assert_eq!(reader.read_as_be::<u32>().unwrap(), 7);
+        assert_eq!(reader.position(), 6);
+        assert_eq!(reader.read_partial::<u16>(6).unwrap(), 5);
+        assert_eq!(reader.position(), 8);
+        reader.skip(4).unwrap();
+        assert_eq!(reader.position(), 12);
+        reader.skip(3).unwrap();
+        assert_eq!(reader.position(), 15);
+        assert_eq!(reader.utf8_str(StringMode::NullTerminated).unwrap(), "abc");
+        assert_eq!(reader.position(), 19);
+        assert_eq!(reader.utf16_str(StringMode::FixedChars(2)).unwrap(), "hi");
+        assert_eq!(reader.position(), 23);
+        assert_eq!(reader.read_as_be::<u16>().unwrap(), 9);
+        assert_eq!(reader.position(), DATA.len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_async_position_interleaved() {
+        let mut reader = AsyncBinaryReader::from_le_bytes(DATA);
+        assert_eq!(reader.u16().await.unwrap(), 0x0201);
+        assert_eq!(reader.read_as_be::<u32>().await.unwrap(), 7);
+        assert_eq!(reader.position(), 6);
+        assert_eq!(reader.read_partial::<u16>(6).await.unwrap(), 5);
+        assert_eq!(reader.position(), 8);
+        reader.skip(7).await.unwrap();
+        assert_eq!(reader.position(), 15);
+        assert_eq!(reader.utf8_str(StringMode::NullTerminated).await.unwrap(), "abc");
+        assert_eq!(reader.position(), 19);
+        assert_eq!(reader.utf16_str(StringMode::FixedChars(2)).await.unwrap(), "hi");
+        assert_eq!(reader.position(), 23);
+        assert_eq!(reader.read_as_be::<u16>().await.unwrap(), 9);
+        assert_eq!(reader.position(), DATA.len() as u64);
+    }
+}
@@ -0,0 +1,281 @@
+use std::io::{BufRead, Read, Seek, SeekFrom, Write};
+
+/// Returns a transform that XORs every byte with a repeating `key`, phased by absolute offset.
+///
+/// XOR is its own inverse, so the same transform decodes in a `TransformReader` and encodes in a
+/// `TransformWriter`. An empty key leaves the data unchanged.
+pub fn xor(key: impl Into<Vec<u8>>) -> impl FnMut(u64, &mut [u8]) {
+    let key = key.into();
+    move |offset, bytes| {
+        if key.is_empty() {
+            return;
+        }
+        let phase = (offset % key.len() as u64) as usize;
+        for (byte, k) in bytes.iter_mut().zip(key.iter().cycle().skip(phase)) {
+            *byte ^= k;
+        }
+    }
+}
+
+/// A `BufRead` adapter that decodes bytes with `transform` as they are read from `inner`.
+///
+/// `transform(offset, bytes)` is called on every chunk taken from `inner`, where `offset` is the
+/// absolute stream offset of `bytes[0]`, so keyed ciphers decode correctly however the data is
+/// split into chunks. Every byte passes through the transform exactly once, including bytes
+/// dropped by `BinaryReader::skip`, so stateful transforms stay in phase too. Wrap it in a
+/// `BinaryReader` to parse obfuscated files without decoding them into memory first.
+pub struct TransformReader<R, F> {
+    inner: R,
+    transform: F,
+    buf: Vec<u8>,
+    pos: usize,
+    /// Stream offset of `buf[0]`.
+    start: u64,
+}
+
+impl<R: BufRead, F: FnMut(u64, &mut [u8])> TransformReader<R, F> {
+    /// Decodes `inner` from its current position, which is taken as offset 0.
+    pub fn new(inner: R, transform: F) -> Self {
+        Self::starting_at(inner, 0, transform)
+    }
+
+    /// Decodes `inner` from its current position, which is taken as offset `start`, e.g. when
+    /// the obfuscated part of a file begins after a plain header.
+    pub fn starting_at(inner: R, start: u64, transform: F) -> Self {
+        TransformReader {
+            inner,
+            transform,
+            buf: Vec::new(),
+            pos: 0,
+            start,
+        }
+    }
+}
+
+impl<R, F> TransformReader<R, F> {
+    /// Returns the stream offset of the next byte to be read.
+    pub fn offset(&self) -> u64 {
+        self.start + self.pos as u64
+    }
+
+    /// Returns the inner reader. Bytes already taken from it but not read yet are lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: BufRead, F: FnMut(u64, &mut [u8])> BufRead for TransformReader<R, F> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if self.pos >= self.buf.len() {
+            let chunk = self.inner.fill_buf()?;
+            let len = chunk.len();
+            self.start += self.buf.len() as u64;
+            self.buf.clear();
+            self.buf.extend_from_slice(chunk);
+            self.pos = 0;
+            self.inner.consume(len);
+            (self.transform)(self.start, &mut self.buf);
+        }
+        Ok(&self.buf[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.buf.len());
+    }
+}
+
+impl<R: BufRead, F: FnMut(u64, &mut [u8])> Read for TransformReader<R, F> {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(out.len());
+        out[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+/// Seeking drops the decoded buffer; the inner reader's position becomes the new offset.
+impl<R: BufRead + Seek, F: FnMut(u64, &mut [u8])> Seek for TransformReader<R, F> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let pos = match pos {
+            // the inner reader is already past the buffered bytes
+            SeekFrom::Current(delta) => SeekFrom::Current(delta - (self.buf.len() - self.pos) as i64),
+            pos => pos,
+        };
+        let offset = self.inner.seek(pos)?;
+        self.buf.clear();
+        self.pos = 0;
+        self.start = offset;
+        Ok(offset)
+    }
+}
+
+/// A `Write` adapter that encodes bytes with `transform` before passing them to `inner`.
+///
+/// The counterpart of `TransformReader`: `transform(offset, bytes)` sees a copy of every chunk
+/// written, with `offset` the absolute stream offset of `bytes[0]`. Each chunk is written to
+/// `inner` in full before the offset advances.
+pub struct TransformWriter<W, F> {
+    inner: W,
+    transform: F,
+    scratch: Vec<u8>,
+    offset: u64,
+}
+
+impl<W: Write, F: FnMut(u64, &mut [u8])> TransformWriter<W, F> {
+    /// Encodes into `inner` from its current position, which is taken as offset 0.
+    pub fn new(inner: W, transform: F) -> Self {
+        Self::starting_at(inner, 0, transform)
+    }
+
+    /// Encodes into `inner` from its current position, which is taken as offset `start`.
+    pub fn starting_at(inner: W, start: u64, transform: F) -> Self {
+        TransformWriter {
+            inner,
+            transform,
+            scratch: Vec::new(),
+            offset: start,
+        }
+    }
+}
+
+impl<W, F> TransformWriter<W, F> {
+    /// Returns the stream offset of the next byte to be written.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the inner writer without flushing it.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write, F: FnMut(u64, &mut [u8])> Write for TransformWriter<W, F> {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.scratch.clear();
+        self.scratch.extend_from_slice(bytes);
+        (self.transform)(self.offset, &mut self.scratch);
+        self.inner.write_all(&self.scratch)?;
+        self.offset += bytes.len() as u64;
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Seeking moves the offset with the inner writer, so backpatched bytes are encoded in place.
+impl<W: Write + Seek, F: FnMut(u64, &mut [u8])> Seek for TransformWriter<W, F> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.offset = self.inner.seek(pos)?;
+        Ok(self.offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reader::BinaryReader, writer::BinaryWriter};
+    use std::io::BufReader;
+
+    const KEY: &[u8] = b"\x5a\xa5\x3c";
+
+    fn plaintext() -> Vec<u8> {
+        let mut writer = BinaryWriter::new_le(Vec::new());
+        writer.u32(0xdead_beef).unwrap();
+        writer.u16(7).unwrap();
+        writer.reserved(0, 5).unwrap();
+        writer.i64(-2).unwrap();
+        writer.f32(1.5).unwrap();
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn test_xor_reader() {
+        let plain = plaintext();
+        let mut obfuscated = plain.clone();
+        xor(KEY)(0, &mut obfuscated);
+        assert_ne!(obfuscated, plain);
+
+        // a tiny buffer splits the data into chunks that start at every key phase
+        let inner = BufReader::with_capacity(2, obfuscated.as_slice());
+        let mut reader = BinaryReader::new_le(TransformReader::new(inner, xor(KEY)));
+        assert_eq!(reader.u32().unwrap(), 0xdead_beef);
+        assert_eq!(reader.u16().unwrap(), 7);
+        reader.skip(5).unwrap();
+        assert_eq!(reader.i64().unwrap(), -2);
+        assert_eq!(reader.f32().unwrap(), 1.5);
+        assert!(reader.u8().is_err());
+
+        let mut reader = TransformReader::new(obfuscated.as_slice(), xor(KEY));
+        let mut decoded = Vec::new();
+        reader.read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, plain);
+        assert_eq!(reader.offset(), plain.len() as u64);
+    }
+
+    #[test]
+    fn test_rolling_cipher_round_trip() {
+        // each byte is shifted by a running sum that depends on every byte before it
+        let encode = {
+            let mut sum = 0u8;
+            move |_, bytes: &mut [u8]| {
+                for byte in bytes {
+                    *byte = byte.wrapping_add(sum);
+                    sum = sum.wrapping_add(*byte);
+                }
+            }
+        };
+        let decode = {
+            let mut sum = 0u8;
+            move |_, bytes: &mut [u8]| {
+                for byte in bytes {
+                    let encoded = *byte;
+                    *byte = encoded.wrapping_sub(sum);
+                    sum = sum.wrapping_add(encoded);
+                }
+            }
+        };
+
+        let mut writer = BinaryWriter::new_be(TransformWriter::new(Vec::new(), encode));
+        writer.u16(0x0102).unwrap();
+        writer.reserved(0xff, 3).unwrap();
+        writer.u32(0x0a0b_0c0d).unwrap();
+        let encoded = writer.finish().unwrap().into_inner();
+        assert_ne!(encoded, [1, 2, 0xff, 0xff, 0xff, 0x0a, 0x0b, 0x0c, 0x0d]);
+
+        let inner = BufReader::with_capacity(3, encoded.as_slice());
+        let mut reader = BinaryReader::new_be(TransformReader::new(inner, decode));
+        assert_eq!(reader.u16().unwrap(), 0x0102);
+        reader.skip(3).unwrap();
+        assert_eq!(reader.u32().unwrap(), 0x0a0b_0c0d);
+    }
+
+    #[test]
+    fn test_xor_seek() {
+        let plain = plaintext();
+        let mut obfuscated = plain.clone();
+        xor(KEY)(0, &mut obfuscated);
+
+        let mut reader = TransformReader::new(std::io::Cursor::new(&obfuscated), xor(KEY));
+        let mut byte = [0];
+        reader.read_exact(&mut byte).unwrap();
+        assert_eq!(reader.seek(SeekFrom::Current(10)).unwrap(), 11);
+        reader.read_exact(&mut byte).unwrap();
+        assert_eq!(byte[0], plain[11]);
+        reader.seek(SeekFrom::Start(4)).unwrap();
+        reader.read_exact(&mut byte).unwrap();
+        assert_eq!((byte[0], reader.offset()), (plain[4], 5));
+
+        let mut writer = TransformWriter::new(std::io::Cursor::new(Vec::new()), xor(KEY));
+        writer.write_all(&[0; 8]).unwrap();
+        writer.seek(SeekFrom::Start(5)).unwrap();
+        writer.write_all(&[0x11]).unwrap();
+        let written = writer.into_inner().into_inner();
+        let mut expected = [0, 0, 0, 0, 0, 0x11, 0, 0];
+        xor(KEY)(0, &mut expected);
+        assert_eq!(written, expected);
+    }
+}
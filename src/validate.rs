@@ -0,0 +1,226 @@
+use crate::{
+    endian::Endian,
+    reader::{BinaryReader, Read},
+    result::WriteError,
+    writer::{BinaryWriter, Result, Write},
+};
+use std::{
+    fmt::{Debug, Write as _},
+    ops::{Deref, DerefMut},
+};
+
+/// A `BinaryWriter` whose `write` checks, in debug builds, that every value reads back as itself.
+///
+/// Each value is first serialized into a scratch buffer and parsed again with `Read`. If the read
+/// fails, leaves bytes over, or yields a different value, `write` returns
+/// `WriteError::RoundTripMismatch` and nothing reaches the sink; otherwise the buffered bytes are
+/// forwarded. This catches `Read`/`Write` impls that disagree on a field right where the value is
+/// written. In release builds `write` is a plain `BinaryWriter::write`.
+///
+/// All other methods are reached through `Deref` and are not checked.
+pub struct ValidatingWriter<E, W> {
+    inner: BinaryWriter<E, W>,
+    enabled: bool,
+}
+
+impl<E, W> ValidatingWriter<E, W> {
+    /// Wraps `inner`, checking values written from now on.
+    pub fn new(inner: BinaryWriter<E, W>) -> Self {
+        Self { inner, enabled: true }
+    }
+
+    /// Wraps `inner` with the checks switched on or off, so that one writer type serves both
+    /// checked and unchecked runs, see `BinaryWriterBuilder::validate_roundtrip`.
+    pub(crate) fn with_checks(inner: BinaryWriter<E, W>, enabled: bool) -> Self {
+        Self { inner, enabled }
+    }
+
+    /// Returns the wrapped writer.
+    pub fn into_inner(self) -> BinaryWriter<E, W> {
+        self.inner
+    }
+}
+
+impl<E: Endian, W: std::io::Write> ValidatingWriter<E, W> {
+    /// Writes `value` after checking that it reads back unchanged.
+    pub fn write<T: Read + Write + PartialEq + Debug>(&mut self, value: &T) -> Result<()> {
+        if !cfg!(debug_assertions) || !self.enabled {
+            return self.inner.write(value);
+        }
+        let bytes = BinaryWriter::<E, _>::to_vec(value)?;
+        check_round_trip::<E, T>(value, &bytes)?;
+        self.inner.write_bytes(&bytes)
+    }
+}
+
+impl<E, W> Deref for ValidatingWriter<E, W> {
+    type Target = BinaryWriter<E, W>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<E, W> DerefMut for ValidatingWriter<E, W> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+/// Parses `bytes` as a `T` and compares it with the `value` they were written from.
+fn check_round_trip<E: Endian, T: Read + PartialEq + Debug>(value: &T, bytes: &[u8]) -> Result<()> {
+    let mismatch = |detail: String| WriteError::RoundTripMismatch {
+        type_name: std::any::type_name::<T>(),
+        detail,
+    };
+    let mut reader = BinaryReader::<E, _>::from_bytes(bytes);
+    let read_back = reader.read::<T>().map_err(|err| mismatch(format!("reading back failed: {err}")))?;
+    if read_back != *value {
+        return Err(mismatch(format!("read back a different value:\n{}", debug_diff(value, &read_back))));
+    }
+    if reader.position() < bytes.len() as u64 {
+        return Err(mismatch(format!("read consumed only {} of {} written bytes", reader.position(), bytes.len())));
+    }
+    Ok(())
+}
+
+/// Lines of the pretty `Debug` renderings that differ, `-` for `written` and `+` for `read`.
+///
+/// Every field of a derived `Debug` is on a line of its own, so this points at the fields that disagree.
+fn debug_diff<T: Debug>(written: &T, read: &T) -> String {
+    let (written, read) = (format!("{written:#?}"), format!("{read:#?}"));
+    let (mut written, mut read) = (written.lines(), read.lines());
+    let mut diff = String::new();
+    loop {
+        match (written.next(), read.next()) {
+            (None, None) => return diff,
+            (w, r) if w == r => {}
+            (w, r) => {
+                if let Some(line) = w {
+                    let _ = writeln!(diff, "- {line}");
+                }
+                if let Some(line) = r {
+                    let _ = writeln!(diff, "+ {line}");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{endian::LittleEndian, result::ReadResult};
+    use std::io::BufRead;
+
+    #[derive(Debug, PartialEq)]
+    struct Entry {
+        id: u16,
+        flags: u8,
+        len: u32,
+    }
+
+    impl Read for Entry {
+        fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> ReadResult<Self> {
+            Ok(Entry {
+                id: reader.u16()?,
+                flags: reader.u8()?,
+                len: reader.u32()?,
+            })
+        }
+    }
+
+    impl Write for Entry {
+        fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+            writer.u16(self.id)?;
+            writer.u8(self.flags)?;
+            writer.u32(self.len)
+        }
+    }
+
+    /// Writes `len` as a `u16` although `read` expects a `u32`.
+    #[derive(Debug, PartialEq)]
+    struct Narrowed(Entry);
+
+    impl Read for Narrowed {
+        fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> ReadResult<Self> {
+            reader.read().map(Narrowed)
+        }
+    }
+
+    impl Write for Narrowed {
+        fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+            writer.u16(self.0.id)?;
+            writer.u8(self.0.flags)?;
+            writer.u16(self.0.len as u16)
+        }
+    }
+
+    /// Writes a trailing version byte that `read` never consumes.
+    #[derive(Debug, PartialEq)]
+    struct Versioned(Entry);
+
+    impl Read for Versioned {
+        fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> ReadResult<Self> {
+            reader.read().map(Versioned)
+        }
+    }
+
+    impl Write for Versioned {
+        fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+            writer.write(&self.0)?;
+            writer.u8(1)
+        }
+    }
+
+    fn entry() -> Entry {
+        Entry { id: 7, flags: 0x80, len: 0x0102_0304 }
+    }
+
+    #[test]
+    fn test_round_trip_ok() {
+        let mut writer = ValidatingWriter::new(BinaryWriter::new_le(Vec::new()));
+        writer.write(&entry()).unwrap();
+        writer.u8(0xff).unwrap();
+        assert_eq!(writer.position(), 8);
+        let bytes = writer.into_inner().finish().unwrap();
+        assert_eq!(bytes, BinaryWriter::<LittleEndian, _>::to_vec(&(entry().id, entry().flags, entry().len, 0xffu8)).unwrap());
+    }
+
+    #[test]
+    fn test_round_trip_mismatch() {
+        let mut writer = ValidatingWriter::new(BinaryWriter::new_le(Vec::new()));
+        let err = writer.write(&Narrowed(entry())).unwrap_err();
+        assert!(matches!(&err, WriteError::RoundTripMismatch { detail, .. } if detail.contains("reading back failed")));
+        assert_eq!(writer.position(), 0);
+
+        let err = writer.write(&Versioned(entry())).unwrap_err();
+        assert!(matches!(&err, WriteError::RoundTripMismatch { detail, .. } if detail == "read consumed only 7 of 8 written bytes"));
+
+        #[derive(Debug, PartialEq)]
+        struct Swapped(Entry);
+        impl Read for Swapped {
+            fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> ReadResult<Self> {
+                let Entry { id, flags, len } = reader.read()?;
+                Ok(Swapped(Entry {
+                    id,
+                    flags: len as u8,
+                    len: (len & !0xff) | flags as u32,
+                }))
+            }
+        }
+        impl Write for Swapped {
+            fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+                writer.write(&self.0)
+            }
+        }
+        let err = writer.write(&Swapped(entry())).unwrap_err();
+        let WriteError::RoundTripMismatch { type_name, detail } = &err else {
+            panic!("unexpected error {err:?}")
+        };
+        assert_eq!(*type_name, std::any::type_name::<Swapped>());
+        let diff: Vec<_> = detail.lines().skip(1).map(|line| line.split_whitespace().collect::<Vec<_>>().join(" ")).collect();
+        assert_eq!(diff, ["- flags: 128,", "+ flags: 4,", "- len: 16909060,", "+ len: 16909184,"]);
+        assert!(writer.into_inner().finish().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,370 @@
+use crate::{
+    async_reader::{self, AsyncBinaryReader, ReaderBase},
+    async_writer::{self, AsyncBinaryWriter, WriterBase},
+    endian::Endian,
+    reader::{self, BinaryReader},
+    result::ReadError,
+    writer::{self, BinaryWriter},
+};
+use std::io::BufRead;
+
+/// The longest LEB128 encoding of a `u64`.
+pub const MAX_VARINT_LEN: usize = 10;
+
+/// The longest big-endian VLQ encoding of a `u32`.
+pub const MAX_VLQ_LEN: usize = 5;
+
+/// A variable-length integer encoding, named in the errors for malformed input.
+#[derive(Clone, Copy)]
+enum Encoding {
+    /// Unsigned LEB128 holding a `u64`, read by `varint_u64` and `varint_i64`.
+    Leb128,
+    /// Big-endian variable-length quantity holding a `u32`, read by `vlq_u32`.
+    Vlq,
+}
+
+impl Encoding {
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Leb128 => "LEB128 varint",
+            Encoding::Vlq => "VLQ",
+        }
+    }
+
+    /// Fails an encoding starting at `start` that is too long or overflows its type.
+    fn malformed(self, start: u64) -> ReadError {
+        let (max_len, value_type) = match self {
+            Encoding::Leb128 => (MAX_VARINT_LEN, "u64"),
+            Encoding::Vlq => (MAX_VLQ_LEN, "u32"),
+        };
+        ReadError::invalid_data_format(format!("{} at offset {start:#x} is longer than {max_len} bytes or overflows {value_type}", self.name()))
+    }
+
+    /// Fails an encoding starting at `start` that still continues when the limit is reached.
+    fn truncated(self, start: u64) -> ReadError {
+        ReadError::invalid_data_format(format!("{} at offset {start:#x} continues past the limit", self.name()))
+    }
+}
+
+/// Accumulates LEB128 bytes, least significant group first, into a `u64`.
+#[derive(Default)]
+struct VarintDecoder {
+    value: u64,
+    shift: u32,
+}
+
+impl VarintDecoder {
+    /// Adds the next byte of the varint starting at `start`, returning the value after its last byte.
+    fn push(&mut self, byte: u8, start: u64) -> reader::Result<Option<u64>> {
+        // The tenth byte holds the top bit of a u64 and must end the encoding.
+        if self.shift == 63 && byte > 1 {
+            return Err(Encoding::Leb128.malformed(start));
+        }
+        self.value |= u64::from(byte & 0x7f) << self.shift;
+        if byte & 0x80 == 0 {
+            return Ok(Some(self.value));
+        }
+        self.shift += 7;
+        Ok(None)
+    }
+}
+
+/// Accumulates big-endian VLQ bytes, most significant group first, into a `u32`.
+#[derive(Default)]
+struct VlqDecoder {
+    value: u32,
+    len: usize,
+}
+
+impl VlqDecoder {
+    /// Adds the next byte of the VLQ starting at `start`, returning the value after its last byte.
+    fn push(&mut self, byte: u8, start: u64) -> reader::Result<Option<u32>> {
+        self.len += 1;
+        if self.len > MAX_VLQ_LEN || self.value > u32::MAX >> 7 {
+            return Err(Encoding::Vlq.malformed(start));
+        }
+        self.value = self.value << 7 | u32::from(byte & 0x7f);
+        Ok((byte & 0x80 == 0).then_some(self.value))
+    }
+}
+
+/// Encodes `value` as LEB128, returning the buffer and the number of bytes used.
+fn encode(mut value: u64) -> ([u8; MAX_VARINT_LEN], usize) {
+    let mut buf = [0u8; MAX_VARINT_LEN];
+    let mut len = 0;
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf[len] = byte;
+            return (buf, len + 1);
+        }
+        buf[len] = byte | 0x80;
+        len += 1;
+    }
+}
+
+/// Encodes `value` as a big-endian VLQ, returning the buffer and the offset its bytes start at.
+fn encode_vlq(mut value: u32) -> ([u8; MAX_VLQ_LEN], usize) {
+    let mut buf = [0u8; MAX_VLQ_LEN];
+    let mut start = MAX_VLQ_LEN - 1;
+    buf[start] = (value & 0x7f) as u8;
+    value >>= 7;
+    while value != 0 {
+        start -= 1;
+        buf[start] = (value & 0x7f) as u8 | 0x80;
+        value >>= 7;
+    }
+    (buf, start)
+}
+
+/// Maps signed values to unsigned ones so that small magnitudes stay short: 0, -1, 1, -2 become 0, 1, 2, 3.
+#[inline]
+fn zigzag_encode(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+#[inline]
+fn zigzag_decode(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+impl<E: Endian, R: BufRead> BinaryReader<E, R> {
+    /// Reads an unsigned LEB128 varint of at most 10 bytes.
+    ///
+    /// Fails with `ReadError::InvalidDataFormat` if the encoding is longer than 10 bytes, overflows
+    /// a `u64`, or still has its continuation bit set when the limit is reached.
+    pub fn varint_u64(&mut self) -> reader::Result<u64> {
+        let start = self.position();
+        let mut decoder = VarintDecoder::default();
+        loop {
+            if let Some(value) = decoder.push(self.u8()?, start)? {
+                return Ok(value);
+            }
+            if self.remaining() == Some(0) {
+                return Err(Encoding::Leb128.truncated(start));
+            }
+        }
+    }
+
+    /// Reads a zigzag-encoded signed LEB128 varint, as protobuf's `sint64`, see `varint_u64`.
+    pub fn varint_i64(&mut self) -> reader::Result<i64> {
+        self.varint_u64().map(zigzag_decode)
+    }
+
+    /// Reads a MIDI-style variable-length quantity: 7-bit groups, most significant first, each
+    /// byte but the last with its high bit set.
+    ///
+    /// Fails with `ReadError::InvalidDataFormat` if the encoding is longer than 5 bytes, overflows
+    /// a `u32`, or still has its continuation bit set when the limit is reached.
+    pub fn vlq_u32(&mut self) -> reader::Result<u32> {
+        let start = self.position();
+        let mut decoder = VlqDecoder::default();
+        loop {
+            if let Some(value) = decoder.push(self.u8()?, start)? {
+                return Ok(value);
+            }
+            if self.remaining() == Some(0) {
+                return Err(Encoding::Vlq.truncated(start));
+            }
+        }
+    }
+}
+
+impl<E: Endian, R: ReaderBase> AsyncBinaryReader<E, R> {
+    pub async fn varint_u64(&mut self) -> async_reader::Result<u64> {
+        let start = self.position();
+        let mut decoder = VarintDecoder::default();
+        loop {
+            if let Some(value) = decoder.push(self.u8().await?, start)? {
+                return Ok(value);
+            }
+            if self.remaining() == Some(0) {
+                return Err(Encoding::Leb128.truncated(start));
+            }
+        }
+    }
+
+    pub async fn varint_i64(&mut self) -> async_reader::Result<i64> {
+        self.varint_u64().await.map(zigzag_decode)
+    }
+
+    pub async fn vlq_u32(&mut self) -> async_reader::Result<u32> {
+        let start = self.position();
+        let mut decoder = VlqDecoder::default();
+        loop {
+            if let Some(value) = decoder.push(self.u8().await?, start)? {
+                return Ok(value);
+            }
+            if self.remaining() == Some(0) {
+                return Err(Encoding::Vlq.truncated(start));
+            }
+        }
+    }
+}
+
+impl<E: Endian, W: std::io::Write> BinaryWriter<E, W> {
+    /// Writes `value` as an unsigned LEB128 varint of 1 to 10 bytes.
+    pub fn varint_u64(&mut self, value: u64) -> writer::Result<()> {
+        let (buf, len) = encode(value);
+        self.write_bytes(&buf[..len])
+    }
+
+    /// Writes `value` zigzag-encoded as a LEB128 varint, as protobuf's `sint64`.
+    pub fn varint_i64(&mut self, value: i64) -> writer::Result<()> {
+        self.varint_u64(zigzag_encode(value))
+    }
+
+    /// Writes `value` as a MIDI-style big-endian variable-length quantity of 1 to 5 bytes.
+    pub fn vlq_u32(&mut self, value: u32) -> writer::Result<()> {
+        let (buf, start) = encode_vlq(value);
+        self.write_bytes(&buf[start..])
+    }
+}
+
+impl<E: Endian, W: WriterBase> AsyncBinaryWriter<E, W> {
+    pub async fn varint_u64(&mut self, value: u64) -> async_writer::Result<()> {
+        let (buf, len) = encode(value);
+        self.write_bytes(&buf[..len]).await
+    }
+
+    pub async fn varint_i64(&mut self, value: i64) -> async_writer::Result<()> {
+        self.varint_u64(zigzag_encode(value)).await
+    }
+
+    pub async fn vlq_u32(&mut self, value: u32) -> async_writer::Result<()> {
+        let (buf, start) = encode_vlq(value);
+        self.write_bytes(&buf[start..]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNSIGNED: [(u64, &[u8]); 6] = [
+        (0, &[0x00]),
+        (127, &[0x7f]),
+        (128, &[0x80, 0x01]),
+        (300, &[0xac, 0x02]),
+        (1 << 63, &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01]),
+        (u64::MAX, &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]),
+    ];
+    const SIGNED: [(i64, &[u8]); 6] = [
+        (0, &[0x00]),
+        (-1, &[0x01]),
+        (1, &[0x02]),
+        (-64, &[0x7f]),
+        (i64::MAX, &[0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]),
+        (i64::MIN, &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]),
+    ];
+
+    // Examples from the Standard MIDI File specification, plus the u32 extremes.
+    const VLQ: [(u32, &[u8]); 8] = [
+        (0, &[0x00]),
+        (0x40, &[0x40]),
+        (0x7f, &[0x7f]),
+        (0x80, &[0x81, 0x00]),
+        (0x2000, &[0xc0, 0x00]),
+        (0x0fff_ffff, &[0xff, 0xff, 0xff, 0x7f]),
+        (0x1000_0000, &[0x81, 0x80, 0x80, 0x80, 0x00]),
+        (u32::MAX, &[0x8f, 0xff, 0xff, 0xff, 0x7f]),
+    ];
+
+    #[test]
+    fn test_varint_round_trip() {
+        for (value, encoded) in UNSIGNED {
+            let mut buf = Vec::new();
+            BinaryWriter::new_le(&mut buf).varint_u64(value).unwrap();
+            assert_eq!(buf, encoded, "{value}");
+            let mut reader = BinaryReader::from_be_bytes(&buf);
+            assert_eq!(reader.varint_u64().unwrap(), value);
+            assert_eq!(reader.position(), encoded.len() as u64);
+        }
+        for (value, encoded) in SIGNED {
+            let mut buf = Vec::new();
+            BinaryWriter::new_be(&mut buf).varint_i64(value).unwrap();
+            assert_eq!(buf, encoded, "{value}");
+            assert_eq!(BinaryReader::from_le_bytes(&buf).varint_i64().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_varint_invalid() {
+        let too_long = [0x80; 11];
+        assert!(matches!(BinaryReader::from_le_bytes(&too_long).varint_u64(), Err(ReadError::InvalidDataFormat(_))));
+        let mut overflow = vec![0x00];
+        overflow.extend([0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02]);
+        let mut reader = BinaryReader::from_le_bytes(&overflow);
+        reader.u8().unwrap();
+        assert!(matches!(
+            reader.varint_u64(),
+            Err(ReadError::InvalidDataFormat(msg)) if msg == "LEB128 varint at offset 0x1 is longer than 10 bytes or overflows u64"
+        ));
+        assert!(matches!(
+            BinaryReader::from_le_bytes(&[0x80, 0x80]).varint_u64(),
+            Err(ReadError::InvalidDataFormat(msg)) if msg == "LEB128 varint at offset 0x0 continues past the limit"
+        ));
+        // Non-canonical padding is accepted as long as it fits in ten bytes.
+        assert_eq!(BinaryReader::from_le_bytes(&[0x81, 0x80, 0x00]).varint_u64().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_vlq() {
+        for (value, encoded) in VLQ {
+            let mut buf = Vec::new();
+            BinaryWriter::new_le(&mut buf).vlq_u32(value).unwrap();
+            assert_eq!(buf, encoded, "{value:#x}");
+            let mut reader = BinaryReader::from_le_bytes(&buf);
+            assert_eq!(reader.vlq_u32().unwrap(), value);
+            assert_eq!(reader.position(), encoded.len() as u64);
+        }
+        let invalid: [(&[u8], &str); 3] = [
+            (&[0x80, 0x80, 0x80, 0x80, 0x80, 0x00], "VLQ at offset 0x0 is longer than 5 bytes or overflows u32"),
+            (&[0x90, 0x80, 0x80, 0x80, 0x00], "VLQ at offset 0x0 is longer than 5 bytes or overflows u32"),
+            (&[0x81, 0x81], "VLQ at offset 0x0 continues past the limit"),
+        ];
+        for (data, expected) in invalid {
+            let err = BinaryReader::from_le_bytes(data).vlq_u32().unwrap_err();
+            assert!(matches!(&err, ReadError::InvalidDataFormat(msg) if msg == expected), "{data:02x?}: {err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_async_vlq() {
+        let mut writer = AsyncBinaryWriter::new_be(Vec::new());
+        for (value, _) in VLQ {
+            writer.vlq_u32(value).await.unwrap();
+        }
+        let data = writer.finish().await.unwrap();
+        assert_eq!(data, VLQ.iter().flat_map(|(_, e)| e.iter().copied()).collect::<Vec<_>>());
+        let mut reader = AsyncBinaryReader::from_be_bytes(&data);
+        for (value, _) in VLQ {
+            assert_eq!(reader.vlq_u32().await.unwrap(), value);
+        }
+        assert!(matches!(AsyncBinaryReader::from_be_bytes(&[0x80; 6]).vlq_u32().await, Err(ReadError::InvalidDataFormat(_))));
+    }
+
+    #[tokio::test]
+    async fn test_async_varint_round_trip() {
+        let mut writer = AsyncBinaryWriter::new_le(Vec::new());
+        for (value, _) in UNSIGNED {
+            writer.varint_u64(value).await.unwrap();
+        }
+        for (value, _) in SIGNED {
+            writer.varint_i64(value).await.unwrap();
+        }
+        let data = writer.finish().await.unwrap();
+        let expected: Vec<u8> = UNSIGNED.iter().map(|(_, e)| *e).chain(SIGNED.iter().map(|(_, e)| *e)).flatten().copied().collect();
+        assert_eq!(data, expected);
+
+        let mut reader = AsyncBinaryReader::from_le_bytes(&data);
+        for (value, _) in UNSIGNED {
+            assert_eq!(reader.varint_u64().await.unwrap(), value);
+        }
+        for (value, _) in SIGNED {
+            assert_eq!(reader.varint_i64().await.unwrap(), value);
+        }
+        assert!(matches!(AsyncBinaryReader::from_le_bytes(&[0xff; 4]).varint_u64().await, Err(ReadError::InvalidDataFormat(_))));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{
+    async_reader::{self, AsyncBinaryReader, AsyncRead, ReaderBase},
+    async_writer::{self, AsyncBinaryWriter, AsyncWrite, WriterBase},
+    endian::Endian,
+    fixed_size::FixedSize,
+    reader::{self, BinaryReader, Read},
+    writer::{self, BinaryWriter, Write},
+};
+use std::{fmt, io::BufRead, ops::RangeInclusive};
+
+/// A format version stored as a `u16` major followed by a `u16` minor number.
+///
+/// Versions order by major, then minor, and print as `major.minor`. Check them against the
+/// versions a parser supports with `BinaryReader::version`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl Version {
+    pub const fn new(major: u16, minor: u16) -> Self {
+        Self { major, minor }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// The versions from `min` to `max`, both included, that a parser supports.
+///
+/// Converts from `RangeInclusive<Version>`, so `Version::new(1, 0)..=Version::new(1, 4)` can be
+/// passed wherever a `VersionRange` is expected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VersionRange {
+    pub min: Version,
+    pub max: Version,
+}
+
+impl VersionRange {
+    pub const fn new(min: Version, max: Version) -> Self {
+        Self { min, max }
+    }
+
+    /// Returns `true` if `version` is within the range.
+    pub fn contains(&self, version: Version) -> bool {
+        self.min <= version && version <= self.max
+    }
+}
+
+impl From<RangeInclusive<Version>> for VersionRange {
+    fn from(range: RangeInclusive<Version>) -> Self {
+        Self::new(*range.start(), *range.end())
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.min == self.max {
+            write!(f, "{}", self.min)
+        } else {
+            write!(f, "{} to {}", self.min, self.max)
+        }
+    }
+}
+
+impl FixedSize for Version {
+    const SIZE: usize = 4;
+}
+
+impl Read for Version {
+    fn read<E: Endian, R: BufRead>(reader: &mut BinaryReader<E, R>) -> reader::Result<Self> {
+        Ok(Version {
+            major: reader.u16()?,
+            minor: reader.u16()?,
+        })
+    }
+}
+
+impl Write for Version {
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> writer::Result<()> {
+        writer.u16(self.major)?;
+        writer.u16(self.minor)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(Self::SIZE)
+    }
+}
+
+impl AsyncRead for Version {
+    async fn read<E: Endian, R: ReaderBase>(reader: &mut AsyncBinaryReader<E, R>) -> async_reader::Result<Self> {
+        Ok(Version {
+            major: reader.u16().await?,
+            minor: reader.u16().await?,
+        })
+    }
+}
+
+impl AsyncWrite for Version {
+    async fn write<E: Endian, W: WriterBase>(&self, writer: &mut AsyncBinaryWriter<E, W>) -> async_writer::Result<()> {
+        writer.u16(self.major).await?;
+        writer.u16(self.minor).await
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(Self::SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::result::ReadError;
+
+    const V1_4: Version = Version::new(1, 4);
+
+    #[test]
+    fn test_version_ordering() {
+        let mut versions = [Version::new(2, 0), Version::new(1, 10), V1_4, Version::new(1, 9)];
+        versions.sort();
+        assert_eq!(versions.map(|v| v.to_string()), ["1.4", "1.9", "1.10", "2.0"]);
+        assert!(Version::new(1, 10) > Version::new(1, 9));
+
+        let supported = VersionRange::from(Version::new(1, 0)..=V1_4);
+        assert!(supported.contains(V1_4) && supported.contains(Version::new(1, 0)));
+        assert!(!supported.contains(Version::new(1, 5)) && !supported.contains(Version::new(0, 9)));
+        assert_eq!(supported.to_string(), "1.0 to 1.4");
+        assert_eq!(VersionRange::new(V1_4, V1_4).to_string(), "1.4");
+    }
+
+    #[test]
+    fn test_header_version_check() {
+        let mut data = Vec::new();
+        let mut writer = BinaryWriter::new_be(&mut data);
+        writer.write(&V1_4).unwrap();
+        writer.write(&Version::new(2, 1)).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(data, [0, 1, 0, 4, 0, 2, 0, 1]);
+
+        let mut reader = BinaryReader::from_be_bytes(&data);
+        assert_eq!(reader.version(Version::new(1, 0)..=V1_4).unwrap(), V1_4);
+        let err = reader.version(Version::new(1, 0)..=V1_4).unwrap_err();
+        assert!(matches!(err, ReadError::UnsupportedVersion { found, offset: 4, .. } if found == Version::new(2, 1)));
+        assert_eq!(err.to_string(), "unsupported version 2.1 at offset 0x4 (4), expected 1.0 to 1.4");
+    }
+}
@@ -1,35 +1,347 @@
 use crate::{
-    endian::{BigEndian, Endian, LittleEndian},
+    atomic::AtomicFile,
+    checksum::ChecksumWriter,
+    endian::{BigEndian, Endian, Endianness, LittleEndian, NativeEndian, Primitive},
+    fixed_size::FixedSize,
+    reader::{DEFAULT_MAX_FRAME_SIZE, IntWidth, LenPolicy},
     result::{WriteError, WriteResult},
+    retry,
+};
+use std::{
+    borrow::{Borrow, Cow},
+    collections::{HashMap, HashSet},
+    marker::PhantomData,
+    rc::Rc,
+    sync::Arc,
 };
-use std::marker::PhantomData;
 
 pub struct BinaryWriter<E, W> {
     writer: W,
+    total_bytes_written: u64,
+    staging: Option<Vec<u8>>,
+    region_start: Option<u64>,
+    limit_bytes: Option<u64>,
+    /// Set once an alignment helper has run, so `write_repeated` knows an encoding may depend on its position.
+    padded: bool,
+    /// Stream offset of position 0, taken from the sink by `new_seekable` or else by the first
+    /// seek-aware call.
+    stream_base: Option<u64>,
+    #[cfg(feature = "stats")]
+    pub(crate) stats: Option<std::sync::Arc<crate::stats::Stats>>,
     _endian: PhantomData<fn() -> E>,
 }
 
 pub type Result<T> = WriteResult<T>;
 
+/// Size of the buffer `copy_from` moves data through.
+pub(crate) const COPY_CHUNK_SIZE: usize = 64 * 1024;
+
+impl<E: Endian, W: std::io::Write> BinaryWriter<E, W> {
+    /// Creates a new BinaryWriter using the byte order `E`, e.g. `BinaryWriter::<MiddleEndian, _>::new(writer)`.
+    pub fn new(writer: W) -> Self {
+        BinaryWriter {
+            writer,
+            total_bytes_written: 0,
+            staging: None,
+            region_start: None,
+            limit_bytes: None,
+            padded: false,
+            stream_base: None,
+            #[cfg(feature = "stats")]
+            stats: None,
+            _endian: PhantomData,
+        }
+    }
+}
+
 impl<W: std::io::Write> BinaryWriter<LittleEndian, W> {
     /// Creates a new little-endian BinaryWriter wrapping the given writer.
     pub fn new_le(writer: W) -> Self {
-        BinaryWriter { writer, _endian: PhantomData }
+        Self::new(writer)
     }
 }
 
 impl<W: std::io::Write> BinaryWriter<BigEndian, W> {
     /// Creates a new big-endian BinaryWriter wrapping the given writer.
     pub fn new_be(writer: W) -> Self {
-        BinaryWriter { writer, _endian: PhantomData }
+        Self::new(writer)
+    }
+}
+
+impl<W: std::io::Write> BinaryWriter<NativeEndian, W> {
+    /// Creates a new native-endian BinaryWriter wrapping the given writer.
+    pub fn new_ne(writer: W) -> Self {
+        Self::new(writer)
+    }
+}
+
+impl BinaryWriter<LittleEndian, AtomicFile> {
+    /// Creates a little-endian writer whose output replaces `path` only when `commit` is called.
+    pub fn create_atomic_le<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        AtomicFile::create(path).map(Self::new_le).map_err(WriteError::io_error)
+    }
+}
+
+impl BinaryWriter<BigEndian, AtomicFile> {
+    /// Creates a big-endian writer whose output replaces `path` only when `commit` is called.
+    pub fn create_atomic_be<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        AtomicFile::create(path).map(Self::new_be).map_err(WriteError::io_error)
+    }
+}
+
+impl<E: Endian> BinaryWriter<E, AtomicFile> {
+    /// Flushes, fsyncs and renames the temporary file over the target.
+    ///
+    /// Dropping the writer without calling `commit` discards everything written.
+    pub fn commit(mut self) -> Result<()> {
+        self.drain_staging()?;
+        self.writer.commit().map_err(WriteError::io_error)
+    }
+}
+
+impl<E: Endian, W: std::io::Write> BinaryWriter<E, ChecksumWriter<W>> {
+    /// Appends the digest of everything written so far as a `u32` in the writer's byte order,
+    /// flushes, and returns the sink inside the `ChecksumWriter`.
+    ///
+    /// `BinaryReader::verify_trailing_checksum` checks the result.
+    pub fn finish_with_checksum(mut self) -> Result<W> {
+        self.drain_staging()?;
+        let digest = self.writer.checksum().value();
+        self.u32(digest)?;
+        Ok(self.finish()?.into_inner())
+    }
+}
+
+impl<E: Endian, W: std::io::Write + std::io::Seek> BinaryWriter<E, W> {
+    /// Creates a new `BinaryWriter` using the byte order `E` whose position 0 is the sink's
+    /// current offset, taken right away.
+    ///
+    /// A writer made with `new` asks the sink only at its first seek-aware call, so it miscounts
+    /// if the sink is moved before that, e.g. through another handle to a shared `&File`.
+    pub fn new_seekable(mut writer: W) -> Result<Self> {
+        let base = writer.stream_position().map_err(WriteError::io_error)?;
+        let mut this = Self::new(writer);
+        this.stream_base = Some(base);
+        Ok(this)
+    }
+
+    /// Re-synchronizes the position with the underlying stream and returns it.
+    ///
+    /// `position`, and every layout decision made from it such as `fill_aligned(n, position)`,
+    /// assumes that bytes are only ever appended through this writer. Call this after seeking the
+    /// sink behind the writer's back. Positions keep counting from where the sink was when the
+    /// writer was made with `new_seekable`, or else when it first needed its stream offset, so a
+    /// sink that did not start at offset 0 still starts at position 0. Flushes the staging
+    /// buffer first.
+    pub fn sync_position(&mut self) -> Result<u64> {
+        let base = self.stream_base()?;
+        let stream_pos = self.writer.stream_position().map_err(WriteError::io_error)?;
+        self.total_bytes_written = stream_pos.checked_sub(base).ok_or(WriteError::InvalidArgument)?;
+        Ok(self.total_bytes_written)
+    }
+
+    /// Overwrites the bytes at position `pos` with `value`, e.g. to fill in a length placeholder,
+    /// then returns to the end of the output.
+    ///
+    /// Fails with `WriteError::InvalidArgument`, leaving the output untouched, unless the encoded
+    /// `value` fits between `pos` and the end. The position is re-synchronized with the stream
+    /// afterwards, see `sync_position`.
+    pub fn patch<T: Write>(&mut self, pos: u64, value: &T) -> Result<()> {
+        let end = self.sync_position()?;
+        let mut scratch = self.scratch_at(pos);
+        scratch.write(value)?;
+        if pos.checked_add(scratch.writer.len() as u64).is_none_or(|patch_end| patch_end > end) {
+            return Err(WriteError::InvalidArgument);
+        }
+        self.overwrite_at(pos, |writer| writer.write_bytes(&scratch.writer))
+    }
+
+    /// Moves to position `pos`; following writes overwrite or extend the output from there.
+    /// Flushes the staging buffer first.
+    pub fn seek_to(&mut self, pos: u64) -> Result<()> {
+        let target = self.stream_base()?.checked_add(pos).ok_or(WriteError::InvalidArgument)?;
+        self.writer.seek(std::io::SeekFrom::Start(target)).map_err(WriteError::io_error)?;
+        self.total_bytes_written = pos;
+        Ok(())
+    }
+
+    /// Flushes the staging buffer and returns the stream offset of position 0, asking the sink the first time.
+    fn stream_base(&mut self) -> Result<u64> {
+        self.drain_staging()?;
+        if let Some(base) = self.stream_base {
+            return Ok(base);
+        }
+        let stream_pos = self.writer.stream_position().map_err(WriteError::io_error)?;
+        let base = stream_pos.checked_sub(self.total_bytes_written).ok_or(WriteError::InvalidArgument)?;
+        self.stream_base = Some(base);
+        Ok(base)
+    }
+
+    /// Runs `write` at position `pos`, then returns to the position the writer had before, even
+    /// if `write` fails. Like `seek_to`, `pos` is a `position`, not a stream offset, so this also
+    /// works on a sink that did not start at offset 0.
+    ///
+    /// Reserve a placeholder, write the payload, then fill the placeholder in once its value is known:
+    ///
+    /// ```
+    /// use rwbin::writer::BinaryWriter;
+    /// use std::io::Cursor;
+    ///
+    /// let mut writer = BinaryWriter::new_be(Cursor::new(Vec::new()));
+    /// let len_pos = writer.position();
+    /// writer.reserved(0, 4)?;
+    /// writer.write(&b"payload".as_slice())?;
+    /// let len = writer.position() - len_pos - 4;
+    /// writer.overwrite_at(len_pos, |writer| writer.u32(len as u32))?;
+    /// assert_eq!(&writer.finish()?.into_inner()[..4], &[0, 0, 0, 7]);
+    /// # Ok::<(), rwbin::result::WriteError>(())
+    /// ```
+    pub fn overwrite_at<T>(&mut self, pos: u64, write: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let here = self.sync_position()?;
+        // the limit applies to the bytes being replaced, not to the end of the output
+        let result = self.seek_to(pos).and_then(|()| write(self)).and_then(|value| self.drain_staging().map(|()| value));
+        self.seek_to(here)?;
+        result
+    }
+}
+
+impl<E, W> BinaryWriter<E, W> {
+    /// Starts counting calls and bytes into `stats`, replacing any collector attached before.
+    #[cfg(feature = "stats")]
+    pub fn attach_stats(&mut self, stats: &std::sync::Arc<crate::stats::Stats>) {
+        self.stats = Some(stats.clone());
+    }
+
+    /// Coalesces writes in an internal buffer of `capacity` bytes before handing them to the underlying writer.
+    ///
+    /// Off by default. Useful when the sink is unbuffered and the format is made of many tiny fields;
+    /// buffered bytes reach the sink on overflow, `flush` or `finish`, and are lost if the writer is simply dropped.
+    pub fn with_buffer_capacity(mut self, capacity: usize) -> Self {
+        self.staging = (capacity > 0).then(|| Vec::with_capacity(capacity));
+        self
+    }
+
+    /// Returns the number of bytes written so far.
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.total_bytes_written
+    }
+
+    /// Returns the number of bytes left before the limit, or `None` if unlimited.
+    #[inline]
+    pub fn remaining(&self) -> Option<u64> {
+        self.limit_bytes.map(|limit| limit.saturating_sub(self.total_bytes_written))
+    }
+
+    /// Applies the options collected in `builder`.
+    pub(crate) fn with_options<S>(mut self, builder: &crate::builder::BinaryWriterBuilder<E, S>) -> Self {
+        self.limit_bytes = builder.limit;
+        #[cfg(feature = "stats")]
+        if let Some(stats) = &builder.stats {
+            self.attach_stats(stats);
+        }
+        self.with_buffer_capacity(builder.buffer_capacity)
+    }
+
+    /// Lowers the limit to `limit` bytes, e.g. the length of a slice sink.
+    pub(crate) fn limit_to(&mut self, limit: u64) {
+        self.limit_bytes = Some(self.limit_bytes.map_or(limit, |current| current.min(limit)));
+    }
+
+    /// Fails if writing `len` more bytes would pass the limit.
+    #[inline]
+    fn check_limit(&self, len: usize) -> Result<()> {
+        match self.limit_bytes {
+            Some(limit) if self.total_bytes_written + len as u64 > limit => Err(WriteError::LimitExceeded {
+                requested: len,
+                position: self.total_bytes_written,
+                limit,
+            }),
+            _ => Ok(()),
+        }
     }
 }
 
 impl<W: std::io::Write, E: Endian> BinaryWriter<E, W> {
+    /// Returns an in-memory writer that encodes as if at `position` of this one, under the same limit.
+    fn scratch_at(&self, position: u64) -> BinaryWriter<E, Vec<u8>> {
+        let mut scratch = BinaryWriter::new(Vec::new());
+        (scratch.total_bytes_written, scratch.limit_bytes) = (position, self.limit_bytes);
+        scratch
+    }
+
+    /// Writes raw bytes and advances the position.
+    ///
+    /// With a staging buffer, small writes are coalesced and only reach the sink once the buffer would overflow.
+    #[inline]
+    pub(crate) fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        self.check_limit(bytes.len())?;
+        match &mut self.staging {
+            Some(staging) if staging.len() + bytes.len() <= staging.capacity() => staging.extend_from_slice(bytes),
+            Some(_) => {
+                self.drain_staging()?;
+                match &mut self.staging {
+                    Some(staging) if bytes.len() < staging.capacity() => staging.extend_from_slice(bytes),
+                    _ => self.writer.write_all(bytes).map_err(WriteError::io_error)?,
+                }
+            }
+            None => self.writer.write_all(bytes).map_err(WriteError::io_error)?,
+        }
+        self.total_bytes_written += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Hands everything held in the staging buffer to the underlying writer.
+    fn drain_staging(&mut self) -> Result<()> {
+        if let Some(staging) = &mut self.staging
+            && !staging.is_empty()
+        {
+            self.writer.write_all(staging).map_err(WriteError::io_error)?;
+            staging.clear();
+        }
+        Ok(())
+    }
+
+    /// Runs `f` on a view of this writer that uses the byte order `F`, sharing the position and staging buffer.
+    fn with_endian<F: Endian>(&mut self, f: impl FnOnce(&mut BinaryWriter<F, &mut W>) -> Result<()>) -> Result<()> {
+        let mut writer = BinaryWriter {
+            writer: &mut self.writer,
+            total_bytes_written: self.total_bytes_written,
+            staging: self.staging.take(),
+            region_start: self.region_start,
+            limit_bytes: self.limit_bytes,
+            padded: self.padded,
+            stream_base: self.stream_base,
+            #[cfg(feature = "stats")]
+            stats: self.stats.clone(),
+            _endian: PhantomData::<fn() -> F>,
+        };
+        let result = f(&mut writer);
+        (self.total_bytes_written, self.padded, self.stream_base) = (writer.total_bytes_written, writer.padded, writer.stream_base);
+        self.staging = writer.staging.take();
+        result
+    }
+
     /// Writes raw bytes.
     #[inline]
     fn write_value<const N: usize>(&mut self, value: &[u8; N]) -> Result<()> {
-        self.writer.write_all(value).map_err(WriteError::io_error)?;
+        self.write_bytes(value)?;
+        record_stats!(self, Primitive, N);
+        Ok(())
+    }
+
+    /// Writes a numeric value of type `T` according to the configured endian.
+    #[inline]
+    pub fn write_num<T: Primitive>(&mut self, value: T) -> Result<()> {
+        self.write_num_as::<E, T>(value)
+    }
+
+    /// Writes a numeric value of type `T` in the byte order `B`, whatever the writer's own.
+    #[inline]
+    pub(crate) fn write_num_as<B: Endian, T: Primitive>(&mut self, value: T) -> Result<()> {
+        let bytes = value.to_bytes::<B>();
+        self.write_bytes(bytes.as_ref())?;
+        record_stats!(self, Primitive, bytes.as_ref().len());
         Ok(())
     }
 
@@ -93,32 +405,270 @@ impl<W: std::io::Write, E: Endian> BinaryWriter<E, W> {
         self.write_value(&E::f64_to_bytes(value))
     }
 
+    /// Writes an unsigned 128‑bit integer according to the configured endian.
+    #[inline]
+    pub fn u128(&mut self, value: u128) -> Result<()> {
+        self.write_value(&E::u128_to_bytes(value))
+    }
+
+    /// Writes a signed 128‑bit integer according to the configured endian.
+    #[inline]
+    pub fn i128(&mut self, value: i128) -> Result<()> {
+        self.write_value(&E::i128_to_bytes(value))
+    }
+
+    /// Writes `value` as a `u8`, failing with `WriteError::ValueTooLarge` if it does not fit.
+    #[inline]
+    pub fn u8_from_usize(&mut self, value: usize) -> Result<()> {
+        self.u8(narrow(value)?)
+    }
+
+    /// Writes `value` as a `u16`, failing with `WriteError::ValueTooLarge` if it does not fit.
+    #[inline]
+    pub fn u16_from_usize(&mut self, value: usize) -> Result<()> {
+        self.u16(narrow(value)?)
+    }
+
+    /// Writes `value` as a `u32`, e.g. a length from `len()`, failing with
+    /// `WriteError::ValueTooLarge` if it does not fit.
+    #[inline]
+    pub fn u32_from_usize(&mut self, value: usize) -> Result<()> {
+        self.u32(narrow(value)?)
+    }
+
+    /// Writes `value` as a `u64`, which always fits on supported targets.
+    #[inline]
+    pub fn u64_from_usize(&mut self, value: usize) -> Result<()> {
+        self.u64(narrow(value)?)
+    }
+
+    /// Writes `value` as an unsigned integer of the given `width`, failing with
+    /// `WriteError::ValueTooLarge` if it does not fit.
+    #[inline]
+    pub fn write_uint(&mut self, value: u64, width: IntWidth) -> Result<()> {
+        if value > width.max() {
+            return Err(WriteError::ValueTooLarge { value, max: width.max() });
+        }
+        match width {
+            IntWidth::U8 => self.u8(value as u8),
+            IntWidth::U16 => self.u16(value as u16),
+            IntWidth::U32 => self.u32(value as u32),
+            IntWidth::U64 => self.u64(value),
+        }
+    }
+
     /// Writes `len` bytes of the given value (reserved space).
     #[inline]
     pub fn reserved(&mut self, value: u8, len: usize) -> Result<()> {
-        self.writer.write_all(&vec![value; len]).map_err(WriteError::io_error)?;
+        self.write_bytes(&vec![value; len])?;
+        record_stats!(self, Skip, len);
         Ok(())
     }
 
+    /// Writes `T::SIZE` bytes of `value`, for reserved regions sized as a whole type.
+    #[inline]
+    pub fn reserved_for<T: FixedSize>(&mut self, value: u8) -> Result<()> {
+        self.reserved(value, T::SIZE)
+    }
+
     /// Pads output with zeros up to the next multiple of `alignment`.
+    ///
+    /// Returns `WriteError::InvalidArgument` if `alignment` is zero. Alignments that are not
+    /// a power of two are accepted and treated as plain multiples.
     #[inline]
     pub fn fill_aligned(&mut self, alignment: usize, offset: usize) -> Result<()> {
-        let remainder = alignment - (offset % alignment);
-        if remainder == 0 {
-            return Ok(());
+        if alignment == 0 {
+            return Err(WriteError::InvalidArgument);
+        }
+        self.padded = true;
+        // an offset that is already aligned needs no padding
+        let padding = (alignment - offset % alignment) % alignment;
+        self.reserved(0x00, padding)
+    }
+
+    /// Pads output with zeros until `position()` is a multiple of `alignment`, without the
+    /// caller tracking the offset as `fill_aligned` requires.
+    ///
+    /// Returns `WriteError::InvalidArgument` if `alignment` is zero.
+    #[inline]
+    pub fn align_to(&mut self, alignment: usize) -> Result<()> {
+        if alignment == 0 {
+            return Err(WriteError::InvalidArgument);
         }
-        self.reserved(0x00, remainder)
+        self.padded = true;
+        let alignment = alignment as u64;
+        let padding = (alignment - self.total_bytes_written % alignment) % alignment;
+        self.reserved(0x00, padding as usize)
     }
 
-    /// Flushes the underlying writer.
+    /// Writes `msg` as a frame: its encoded length as a `u32`, then the payload.
+    #[inline]
+    pub fn write_frame<T: Write>(&mut self, msg: &T) -> Result<()> {
+        self.write_frame_limited(msg, DEFAULT_MAX_FRAME_SIZE)
+    }
+
+    /// Writes `msg` as a frame, failing with `InvalidArgument` if its payload exceeds `max_size` bytes.
+    pub fn write_frame_limited<T: Write>(&mut self, msg: &T, max_size: u32) -> Result<()> {
+        count_stats!(self, Bulk, {
+            // encoded where it will land, so the payload is held to the limit before the prefix goes out
+            let mut payload = self.scratch_at(self.total_bytes_written + u32::SIZE as u64);
+            payload.write(msg)?;
+            let len = u32::try_from(payload.writer.len()).ok().filter(|len| *len <= max_size).ok_or(WriteError::InvalidArgument)?;
+            self.u32(len)?;
+            self.write_bytes(&payload.writer)
+        })
+    }
+
+    /// Writes `value` preceded by its encoded length as an `L`, counted according to `policy`.
+    ///
+    /// Fails with `WriteError::InvalidArgument` if the length does not fit in `L`.
+    pub fn write_len_prefixed<L: Write + FixedSize + TryFrom<usize>, T: Write>(&mut self, value: &T, policy: LenPolicy) -> Result<()> {
+        count_stats!(self, Bulk, {
+            let mut scratch = self.scratch_at(self.total_bytes_written + L::SIZE as u64);
+            scratch.write_partial(value)?;
+            let len = policy
+                .stored_len(scratch.writer.len(), L::SIZE)
+                .and_then(|len| L::try_from(len).ok())
+                .ok_or(WriteError::InvalidArgument)?;
+            self.write(&len)?;
+            self.write_bytes(&scratch.writer)
+        })
+    }
+
+    /// Writes the bytes produced by `body`, preceded by their count as a `u32` in the writer's byte order.
+    ///
+    /// `body` writes into an in-memory buffer whose position continues from the current one, after
+    /// the prefix, so alignment inside the body and the writer's limit work as if it wrote directly.
+    /// Calls can be nested. Fails with `WriteError::ValueTooLarge` if the body exceeds `u32::MAX` bytes.
+    pub fn write_len_prefixed_u32(&mut self, body: impl FnOnce(&mut BinaryWriter<E, Vec<u8>>) -> Result<()>) -> Result<()> {
+        count_stats!(self, Bulk, {
+            let mut scratch = self.scratch_at(self.total_bytes_written + u32::SIZE as u64);
+            body(&mut scratch)?;
+            self.u32_from_usize(scratch.writer.len())?;
+            self.write_bytes(&scratch.writer)
+        })
+    }
+
+    /// Copies bytes from `src` into the output in chunks and returns how many were copied.
+    ///
+    /// Copies `len` bytes, or everything up to the end of `src` when `len` is `None`. If `src` ends
+    /// before `len` bytes, fails with an `UnexpectedEof` I/O error after writing what it got.
+    pub fn copy_from(&mut self, src: &mut impl std::io::Read, len: Option<u64>) -> Result<u64> {
+        count_stats!(self, Bulk, {
+            let mut buf = vec![0u8; len.map_or(COPY_CHUNK_SIZE, |len| len.min(COPY_CHUNK_SIZE as u64) as usize)];
+            let mut copied = 0u64;
+            loop {
+                let want = len.map_or(buf.len(), |len| (len - copied).min(buf.len() as u64) as usize);
+                if want == 0 {
+                    return Ok(copied);
+                }
+                let read = retry::retry(|| src.read(&mut buf[..want])).map_err(WriteError::io_error)?;
+                if read == 0 {
+                    return match len {
+                        Some(_) => Err(WriteError::io_error(std::io::ErrorKind::UnexpectedEof.into())),
+                        None => Ok(copied),
+                    };
+                }
+                self.write_bytes(&buf[..read])?;
+                copied += read as u64;
+            }
+        })
+    }
+
+    /// Flushes the staging buffer, then the underlying writer.
     #[inline]
     pub fn flush(&mut self) -> Result<()> {
-        self.writer.flush().map_err(WriteError::io_error)
+        self.drain_staging()?;
+        retry::retry(|| self.writer.flush()).map_err(WriteError::io_error)
+    }
+
+    /// Flushes everything and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.flush()?;
+        Ok(self.writer)
     }
 }
 
+/// Converts `value` to the narrower `T`, or fails with `WriteError::ValueTooLarge`.
+pub(crate) fn narrow<T: TryFrom<usize> + Into<u64> + Bounded>(value: usize) -> Result<T> {
+    T::try_from(value).map_err(|_| WriteError::ValueTooLarge {
+        value: value as u64,
+        max: T::MAX.into(),
+    })
+}
+
+/// Unsigned integers that `narrow` can convert to.
+pub(crate) trait Bounded: Sized {
+    const MAX: Self;
+}
+
+impl Bounded for u8 {
+    const MAX: Self = u8::MAX;
+}
+
+impl Bounded for u16 {
+    const MAX: Self = u16::MAX;
+}
+
+impl Bounded for u32 {
+    const MAX: Self = u32::MAX;
+}
+
+impl Bounded for u64 {
+    const MAX: Self = u64::MAX;
+}
+
+/// How `write_vec_prefixed` handles more values than its count prefix can represent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefixOverflow {
+    /// Fail with `WriteError::InvalidArgument`.
+    Error,
+    /// Write only as many values as the prefix can count.
+    Truncate,
+    /// Split the values into chunks of at most the prefix's maximum, each followed by a `u8`
+    /// that is `continuation_flag` when another chunk follows and `0` after the last one.
+    /// `continuation_flag` must not be `0`.
+    Chunked { continuation_flag: u8 },
+}
+
+/// Returns the largest count, up to `len`, that `P` can represent.
+pub(crate) fn max_prefix_count<P: TryFrom<usize>>(len: usize) -> usize {
+    if P::try_from(len).is_ok() {
+        return len;
+    }
+    let (mut fits, mut overflows) = (0, len);
+    while overflows - fits > 1 {
+        let mid = fits + (overflows - fits) / 2;
+        if P::try_from(mid).is_ok() {
+            fits = mid;
+        } else {
+            overflows = mid;
+        }
+    }
+    fits
+}
+
 pub trait Write {
     fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()>;
+
+    /// Returns the number of bytes `write` will produce, if known up front.
+    fn size_hint(&self) -> Option<usize> {
+        None
+    }
+
+    /// Writes `values` in order; this is what writing a slice, array or `Vec` of `Self` calls.
+    ///
+    /// The default writes the values one by one. `u8` and `i8` override it to copy the slice in a
+    /// single call, which a type whose encoding is exactly its in-memory bytes may do as well.
+    fn write_slice<E: Endian, W: std::io::Write>(values: &[Self], writer: &mut BinaryWriter<E, W>) -> Result<()>
+    where
+        Self: Sized,
+    {
+        for value in values {
+            value.write(writer)?;
+        }
+        Ok(())
+    }
 }
 
 pub trait WriteWith<T> {
@@ -128,142 +678,598 @@ pub trait WriteWith<T> {
 impl<W: std::io::Write> BinaryWriter<LittleEndian, W> {
     /// Writes the given value as big-endian without changing this writer’s endian.
     pub fn write_as_be<T: Write>(&mut self, value: &T) -> Result<()> {
-        let mut writer = BinaryWriter {
-            writer: &mut self.writer,
-            _endian: PhantomData::<fn() -> BigEndian>,
-        };
-        writer.write(value)?;
-        Ok(())
+        self.with_endian::<BigEndian>(|writer| writer.write(value))
     }
 
     /// Writes the given value with parameter as big-endian.
     pub fn write_as_be_with<T: WriteWith<U>, U>(&mut self, value: &T, with: U) -> Result<()> {
-        let mut writer = BinaryWriter {
-            writer: &mut self.writer,
-            _endian: PhantomData::<fn() -> BigEndian>,
-        };
-        value.write_with(&mut writer, with)?;
-        Ok(())
+        self.with_endian::<BigEndian>(|writer| value.write_with(writer, with))
     }
 }
 
 impl<W: std::io::Write> BinaryWriter<BigEndian, W> {
     /// Writes the given value as little-endian without changing this writer’s endian.
     pub fn write_as_le<T: Write>(&mut self, value: &T) -> Result<()> {
-        let mut writer = BinaryWriter {
-            writer: &mut self.writer,
-            _endian: PhantomData::<fn() -> LittleEndian>,
-        };
-        writer.write(value)?;
-        Ok(())
+        self.with_endian::<LittleEndian>(|writer| writer.write(value))
     }
 
     /// Writes the given value with parameter as little-endian.
     pub fn write_as_le_with<T: WriteWith<U>, U>(&mut self, value: &T, with: U) -> Result<()> {
-        let mut writer = BinaryWriter {
-            writer: &mut self.writer,
-            _endian: PhantomData::<fn() -> LittleEndian>,
-        };
-        value.write_with(&mut writer, with)?;
-        Ok(())
+        self.with_endian::<LittleEndian>(|writer| value.write_with(writer, with))
     }
 }
 
-impl<W: std::io::Write, E: Endian> BinaryWriter<E, W> {
-    /// Writes any value implementing the `Write` trait.
-    #[inline]
-    pub fn write<T: Write>(&mut self, value: &T) -> Result<()> {
-        value.write(self)
-    }
-
-    /// Writes any value implementing the `WriteWith` trait with a parameter.
-    #[inline]
-    pub fn write_with<T, U>(&mut self, value: &T, with: U) -> Result<()>
-    where
-        T: WriteWith<U>,
-    {
-        value.write_with(self, with)
+impl<E: Endian> BinaryWriter<E, Vec<u8>> {
+    /// Serializes `value` into a fresh `Vec`, preallocated from its `size_hint`.
+    pub fn to_vec<T: Write>(value: &T) -> Result<Vec<u8>> {
+        let mut writer = Self::new(Vec::with_capacity(value.size_hint().unwrap_or(0)));
+        writer.write(value)?;
+        Ok(writer.writer)
     }
 }
 
-macro_rules! impl_binary_writable {
-    ($($ty:ty => $func:ident),* $(,)?) => {
+/// Defines `write_interleavedN`, which fails with `WriteError::InvalidArgument` unless all
+/// columns are as long as the first one.
+macro_rules! write_interleaved_fns {
+    ( $( $(#[$meta:meta])* fn $name:ident($first:ident: $F:ident $(, $col:ident: $C:ident)+); )* ) => {
         $(
-            impl Write for $ty {
-                fn write<E: Endian, W: std::io::Write>(
-                    &self,
-                    writer: &mut BinaryWriter<E, W>,
-                ) -> Result<()> {
-                    writer.$func(*self)?;
-                    Ok(())
+            $(#[$meta])*
+            pub fn $name<$F: Write $(, $C: Write)+>(&mut self, $first: &[$F] $(, $col: &[$C])+) -> Result<()> {
+                if $($col.len() != $first.len())||+ {
+                    return Err(WriteError::InvalidArgument);
                 }
+                for row in 0..$first.len() {
+                    self.write(&$first[row])?;
+                    $(self.write(&$col[row])?;)+
+                }
+                Ok(())
             }
         )*
     };
 }
 
-impl_binary_writable! {
-    u8 => u8,
-    i8 => i8,
-    u16 => u16,
-    i16 => i16,
-    u32 => u32,
-    i32 => i32,
-    f32 => f32,
-    u64 => u64,
-    i64 => i64,
-    f64 => f64,
-}
-
-impl Write for bool {
-    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
-        let value = if *self { 1u8 } else { 0u8 };
-        writer.u8(value)
+impl<W: std::io::Write, E: Endian> BinaryWriter<E, W> {
+    /// Writes any value implementing the `Write` trait.
+    #[inline]
+    pub fn write<T: Write>(&mut self, value: &T) -> Result<()> {
+        value.write(self)
     }
-}
 
-impl Write for char {
-    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
-        writer.u32(*self as u32)
+    /// Writes `value` as a region of its own, the counterpart of `read_partial`: inside it,
+    /// `align_in_region` pads relative to where `value` starts rather than to the stream start.
+    pub fn write_partial<T: Write>(&mut self, value: &T) -> Result<()> {
+        let outer = self.region_start.replace(self.total_bytes_written);
+        let result = self.write(value);
+        self.region_start = outer;
+        result
     }
-}
 
-impl<T: Write> Write for Option<T> {
-    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
-        if let Some(value) = self {
-            writer.write(value)?;
+    /// Pads with `fill` up to the next multiple of `align` bytes from the start of the innermost
+    /// region, i.e. the value being written by `write_partial` or the payload of `write_len_prefixed`.
+    ///
+    /// Returns `WriteError::InvalidArgument` if `align` is zero or no region is being written.
+    pub fn align_in_region(&mut self, align: usize, fill: u8) -> Result<()> {
+        let start = self.region_start.ok_or(WriteError::InvalidArgument)?;
+        if align == 0 {
+            return Err(WriteError::InvalidArgument);
+        }
+        self.padded = true;
+        let offset = ((self.total_bytes_written - start) % align as u64) as usize;
+        if offset != 0 {
+            self.reserved(fill, align - offset)?;
         }
         Ok(())
     }
-}
 
-impl<T: Write> Write for &[T] {
-    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
-        if std::mem::size_of::<T>() == 1 {
-            // SAFETY: We are assuming that T is a byte, so this is safe
-            let bytes = unsafe { std::slice::from_raw_parts(self.as_ptr() as *const u8, self.len()) };
-            writer.writer.write_all(bytes).map_err(WriteError::io_error)?;
-            Ok(())
-        } else {
-            for item in self.iter() {
-                writer.write(item)?;
-            }
-            Ok(())
+    /// Writes a tagged union variant: `body` writes the body of `value` into a scratch writer and
+    /// returns its tag, then the tag and the body are written, as read by `BinaryReader::read_tagged`.
+    ///
+    /// The body is encoded at the position it lands at, after the tag, and nothing is written
+    /// unless the tag and body both fit under the limit.
+    pub fn write_tagged<T, Tag: Write + FixedSize>(&mut self, value: &T, body: impl FnOnce(&T, &mut BinaryWriter<E, Vec<u8>>) -> Result<Tag>) -> Result<()> {
+        let mut scratch = self.scratch_at(self.total_bytes_written + Tag::SIZE as u64);
+        let tag = body(value, &mut scratch)?;
+        self.check_limit(Tag::SIZE + scratch.writer.len())?;
+        self.write(&tag)?;
+        self.write_bytes(&scratch.writer)
+    }
+
+    /// Like `write_tagged`, with the body's length as an `L` between the tag and the body, so that
+    /// readers that do not know the tag can skip it, see `TagTable::skip_unknown_prefixed`.
+    pub fn write_tagged_prefixed<T, Tag: Write + FixedSize, L: Write + FixedSize + TryFrom<usize>>(
+        &mut self,
+        value: &T,
+        body: impl FnOnce(&T, &mut BinaryWriter<E, Vec<u8>>) -> Result<Tag>,
+    ) -> Result<()> {
+        let mut scratch = self.scratch_at(self.total_bytes_written + (Tag::SIZE + L::SIZE) as u64);
+        let tag = body(value, &mut scratch)?;
+        let len = L::try_from(scratch.writer.len()).map_err(|_| WriteError::InvalidArgument)?;
+        self.check_limit(Tag::SIZE + L::SIZE + scratch.writer.len())?;
+        self.write(&tag)?;
+        self.write(&len)?;
+        self.write_bytes(&scratch.writer)
+    }
+
+    write_interleaved_fns! {
+        /// Writes two equally long columns as rows of `(a[i], b[i])`, without building the rows.
+        fn write_interleaved2(a: A, b: B);
+        /// Writes three equally long columns as rows of `(a[i], b[i], c[i])`, e.g. the x, y and z
+        /// of a vertex table kept as separate `Vec`s.
+        fn write_interleaved3(a: A, b: B, c: C);
+        /// Writes four equally long columns as rows of `(a[i], b[i], c[i], d[i])`.
+        fn write_interleaved4(a: A, b: B, c: C, d: D);
+    }
+
+    /// Writes `f(&item)` for every item of `iter`, without collecting the mapped values.
+    pub fn write_map_iter<I: IntoIterator, T: Write>(&mut self, iter: I, f: impl Fn(&I::Item) -> T) -> Result<()> {
+        for item in iter {
+            self.write(&f(&item))?;
         }
+        Ok(())
     }
-}
 
-impl<T: Write, const N: usize> Write for [T; N] {
-    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
-        writer.write(&self.as_slice())
+    /// Writes the number of items as a `P`, then `f(&item)` for every item of `iter`.
+    ///
+    /// Returns `WriteError::InvalidArgument` if the count does not fit in `P`.
+    pub fn write_map_iter_counted<P, I, T>(&mut self, iter: I, f: impl Fn(&I::Item) -> T) -> Result<()>
+    where
+        P: Write + TryFrom<usize>,
+        I: IntoIterator,
+        I::IntoIter: ExactSizeIterator,
+        T: Write,
+    {
+        let iter = iter.into_iter();
+        let count = P::try_from(iter.len()).map_err(|_| WriteError::InvalidArgument)?;
+        self.write(&count)?;
+        self.write_map_iter(iter, f)
     }
-}
 
-impl<T: Write> Write for Vec<T> {
-    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
-        writer.write(&self.as_slice())
+    /// Writes every key/value pair of `map` in ascending key order, so the bytes do not depend on the hasher.
+    pub fn write_map_sorted<K: Ord + Write, V: Write, S>(&mut self, map: &HashMap<K, V, S>) -> Result<()> {
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+        for (key, value) in entries {
+            self.write(key)?;
+            self.write(value)?;
+        }
+        Ok(())
     }
-}
+
+    /// Writes every element of `set` in ascending order, so the bytes do not depend on the hasher.
+    pub fn write_set_sorted<T: Ord + Write, S>(&mut self, set: &HashSet<T, S>) -> Result<()> {
+        let mut items: Vec<_> = set.iter().collect();
+        items.sort_unstable();
+        for item in items {
+            self.write(item)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the number of values as a `P`, then the values, handling counts `P` cannot hold per `policy`.
+    ///
+    /// `PrefixOverflow::Chunked` output is read back with `BinaryReader::read_vec_prefixed_chunked`.
+    pub fn write_vec_prefixed<P, T>(&mut self, values: &[T], policy: PrefixOverflow) -> Result<()>
+    where
+        P: Write + TryFrom<usize>,
+        T: Write,
+    {
+        match policy {
+            PrefixOverflow::Error => self.write_prefixed_chunk::<P, T>(values),
+            PrefixOverflow::Truncate => self.write_prefixed_chunk::<P, T>(&values[..max_prefix_count::<P>(values.len())]),
+            PrefixOverflow::Chunked { continuation_flag } => {
+                let chunk_len = max_prefix_count::<P>(values.len());
+                if continuation_flag == 0 || (chunk_len == 0 && !values.is_empty()) {
+                    return Err(WriteError::InvalidArgument);
+                }
+                let mut chunks = values.chunks(chunk_len.max(1)).peekable();
+                if chunks.peek().is_none() {
+                    self.write_prefixed_chunk::<P, T>(&[])?;
+                    return self.u8(0);
+                }
+                while let Some(chunk) = chunks.next() {
+                    self.write_prefixed_chunk::<P, T>(chunk)?;
+                    self.u8(if chunks.peek().is_some() { continuation_flag } else { 0 })?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn write_prefixed_chunk<P: Write + TryFrom<usize>, T: Write>(&mut self, values: &[T]) -> Result<()> {
+        let count = P::try_from(values.len()).map_err(|_| WriteError::InvalidArgument)?;
+        self.write(&count)?;
+        self.write(&values)
+    }
+
+    /// Writes every item, then `terminator`, for sequences read back with `BinaryReader::read_until`.
+    ///
+    /// Returns `WriteError::InvalidArgument`, before writing anything, if an item equals the
+    /// terminator, since a reader would stop there.
+    pub fn write_terminated<T: Write + PartialEq>(&mut self, items: &[T], terminator: &T) -> Result<()> {
+        if items.contains(terminator) {
+            return Err(WriteError::InvalidArgument);
+        }
+        self.write(&items)?;
+        self.write(terminator)
+    }
+
+    /// Like `write_terminated`, for items from an iterator. An item equal to the terminator is
+    /// only detected when reached, so the items before it have already been written.
+    pub fn write_terminated_iter<T, I>(&mut self, items: I, terminator: &T) -> Result<()>
+    where
+        T: Write + PartialEq,
+        I: IntoIterator,
+        I::Item: Borrow<T>,
+    {
+        for item in items {
+            let item = item.borrow();
+            if item == terminator {
+                return Err(WriteError::InvalidArgument);
+            }
+            self.write(item)?;
+        }
+        self.write(terminator)
+    }
+
+    /// Writes `value` `count` times.
+    ///
+    /// The value is encoded once at the current position and the bytes are replicated in chunks
+    /// if the encoding is as long as its `size_hint` and uses no alignment helper, so every copy
+    /// comes out the same. Other values are written one by one.
+    pub fn write_repeated<T: Write>(&mut self, value: &T, count: usize) -> Result<()> {
+        count_stats!(self, Bulk, {
+            const CHUNK_SIZE: usize = 4096;
+            if count == 0 {
+                return Ok(());
+            }
+            let Some(encoded) = self.encode_repeatable(value)? else {
+                for _ in 0..count {
+                    self.write(value)?;
+                }
+                return Ok(());
+            };
+            if encoded.is_empty() {
+                return Ok(());
+            }
+            let per_chunk = (CHUNK_SIZE / encoded.len()).clamp(1, count.max(1));
+            let chunk = encoded.repeat(per_chunk);
+            for _ in 0..count / per_chunk {
+                self.write_bytes(&chunk)?;
+            }
+            self.write_bytes(&chunk[..(count % per_chunk) * encoded.len()])
+        })
+    }
+
+    /// Encodes `value` as `write_repeated` would write its first copy, or returns `None` if later
+    /// copies might differ from it.
+    fn encode_repeatable<T: Write>(&self, value: &T) -> Result<Option<Vec<u8>>> {
+        let Some(hint) = value.size_hint() else {
+            return Ok(None);
+        };
+        let mut scratch = self.scratch_at(self.total_bytes_written);
+        scratch.region_start = self.region_start;
+        scratch.write(value)?;
+        Ok((scratch.writer.len() == hint && !scratch.padded).then_some(scratch.writer))
+    }
+
+    /// Writes any value implementing the `WriteWith` trait with a parameter.
+    #[inline]
+    pub fn write_with<T, U>(&mut self, value: &T, with: U) -> Result<()>
+    where
+        T: WriteWith<U>,
+    {
+        value.write_with(self, with)
+    }
+}
+
+impl<W: std::io::Write> BinaryWriter<LittleEndian, W> {
+    /// Creates a writer whose byte order `endianness` is chosen at runtime.
+    ///
+    /// Equivalent to `EitherEndianWriter::new`.
+    pub fn new_dyn(writer: W, endianness: Endianness) -> EitherEndianWriter<W> {
+        EitherEndianWriter::new(writer, endianness)
+    }
+}
+
+/// A writer whose byte order is decided at runtime, created by `BinaryWriter::new_dyn`.
+pub enum EitherEndianWriter<W> {
+    Little(BinaryWriter<LittleEndian, W>),
+    Big(BinaryWriter<BigEndian, W>),
+}
+
+macro_rules! forward_either_writer {
+    ( $( $(#[$meta:meta])* fn $name:ident(&mut self $(, $arg:ident: $ty:ty)*) -> $ret:ty; )* ) => {
+        $(
+            $(#[$meta])*
+            #[inline]
+            pub fn $name(&mut self $(, $arg: $ty)*) -> $ret {
+                match self {
+                    EitherEndianWriter::Little(writer) => writer.$name($($arg),*),
+                    EitherEndianWriter::Big(writer) => writer.$name($($arg),*),
+                }
+            }
+        )*
+    };
+}
+
+impl<W: std::io::Write> EitherEndianWriter<W> {
+    /// Wraps `writer` in a writer using the byte order `endianness`.
+    pub fn new(writer: W, endianness: Endianness) -> Self {
+        match endianness {
+            Endianness::Little => EitherEndianWriter::Little(BinaryWriter::new_le(writer)),
+            Endianness::Big => EitherEndianWriter::Big(BinaryWriter::new_be(writer)),
+        }
+    }
+
+    /// Returns the byte order in use.
+    pub fn endianness(&self) -> Endianness {
+        match self {
+            EitherEndianWriter::Little(_) => Endianness::Little,
+            EitherEndianWriter::Big(_) => Endianness::Big,
+        }
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn position(&self) -> u64 {
+        match self {
+            EitherEndianWriter::Little(writer) => writer.position(),
+            EitherEndianWriter::Big(writer) => writer.position(),
+        }
+    }
+
+    /// Returns the number of bytes left before the limit, or `None` if unlimited.
+    pub fn remaining(&self) -> Option<u64> {
+        match self {
+            EitherEndianWriter::Little(writer) => writer.remaining(),
+            EitherEndianWriter::Big(writer) => writer.remaining(),
+        }
+    }
+
+    forward_either_writer! {
+        /// Writes an unsigned 8‑bit integer.
+        fn u8(&mut self, value: u8) -> Result<()>;
+        /// Writes a signed 8‑bit integer.
+        fn i8(&mut self, value: i8) -> Result<()>;
+        /// Writes an unsigned 16‑bit integer in the chosen byte order.
+        fn u16(&mut self, value: u16) -> Result<()>;
+        /// Writes a signed 16‑bit integer in the chosen byte order.
+        fn i16(&mut self, value: i16) -> Result<()>;
+        /// Writes an unsigned 32‑bit integer in the chosen byte order.
+        fn u32(&mut self, value: u32) -> Result<()>;
+        /// Writes a signed 32‑bit integer in the chosen byte order.
+        fn i32(&mut self, value: i32) -> Result<()>;
+        /// Writes a 32‑bit floating point value in the chosen byte order.
+        fn f32(&mut self, value: f32) -> Result<()>;
+        /// Writes an unsigned 64‑bit integer in the chosen byte order.
+        fn u64(&mut self, value: u64) -> Result<()>;
+        /// Writes a signed 64‑bit integer in the chosen byte order.
+        fn i64(&mut self, value: i64) -> Result<()>;
+        /// Writes a 64‑bit floating point value in the chosen byte order.
+        fn f64(&mut self, value: f64) -> Result<()>;
+        /// Writes an unsigned 128‑bit integer in the chosen byte order.
+        fn u128(&mut self, value: u128) -> Result<()>;
+        /// Writes a signed 128‑bit integer in the chosen byte order.
+        fn i128(&mut self, value: i128) -> Result<()>;
+        /// Writes an unsigned integer of the given `width` in the chosen byte order.
+        fn write_uint(&mut self, value: u64, width: IntWidth) -> Result<()>;
+        /// Writes `len` bytes of the given value (reserved space).
+        fn reserved(&mut self, value: u8, len: usize) -> Result<()>;
+        /// Pads with zeros up to the next multiple of `alignment`.
+        fn fill_aligned(&mut self, alignment: usize, offset: usize) -> Result<()>;
+        /// Pads with zeros until the position is a multiple of `alignment`.
+        fn align_to(&mut self, alignment: usize) -> Result<()>;
+        /// Flushes buffered data to the underlying writer.
+        fn flush(&mut self) -> Result<()>;
+    }
+
+    /// Writes a UTF-8 string.
+    pub fn utf8_str<T: AsRef<str>>(&mut self, value: T, mode: crate::string::StringMode) -> Result<()> {
+        match self {
+            EitherEndianWriter::Little(writer) => writer.utf8_str(value, mode),
+            EitherEndianWriter::Big(writer) => writer.utf8_str(value, mode),
+        }
+    }
+
+    /// Writes a UTF-16 string in the chosen byte order.
+    pub fn utf16_str<T: AsRef<str>>(&mut self, value: T, mode: crate::string::StringMode) -> Result<()> {
+        match self {
+            EitherEndianWriter::Little(writer) => writer.utf16_str(value, mode),
+            EitherEndianWriter::Big(writer) => writer.utf16_str(value, mode),
+        }
+    }
+
+    /// Writes a numeric value of type `T` in the chosen byte order.
+    #[inline]
+    pub fn write_num<T: Primitive>(&mut self, value: T) -> Result<()> {
+        match self {
+            EitherEndianWriter::Little(writer) => writer.write_num(value),
+            EitherEndianWriter::Big(writer) => writer.write_num(value),
+        }
+    }
+
+    /// Writes any value implementing the `Write` trait in the chosen byte order.
+    #[inline]
+    pub fn write<T: Write>(&mut self, value: &T) -> Result<()> {
+        match self {
+            EitherEndianWriter::Little(writer) => writer.write(value),
+            EitherEndianWriter::Big(writer) => writer.write(value),
+        }
+    }
+
+    /// Writes any value implementing the `WriteWith` trait in the chosen byte order.
+    #[inline]
+    pub fn write_with<T: WriteWith<U>, U>(&mut self, value: &T, with: U) -> Result<()> {
+        match self {
+            EitherEndianWriter::Little(writer) => writer.write_with(value, with),
+            EitherEndianWriter::Big(writer) => writer.write_with(value, with),
+        }
+    }
+
+    /// Flushes everything and returns the underlying writer.
+    pub fn finish(self) -> Result<W> {
+        match self {
+            EitherEndianWriter::Little(writer) => writer.finish(),
+            EitherEndianWriter::Big(writer) => writer.finish(),
+        }
+    }
+}
+
+macro_rules! impl_binary_writable {
+    ($($ty:ty => $func:ident),* $(,)?) => {
+        $(
+            impl Write for $ty {
+                fn write<E: Endian, W: std::io::Write>(
+                    &self,
+                    writer: &mut BinaryWriter<E, W>,
+                ) -> Result<()> {
+                    writer.$func(*self)?;
+                    Ok(())
+                }
+
+                fn size_hint(&self) -> Option<usize> {
+                    Some(std::mem::size_of::<$ty>())
+                }
+            }
+        )*
+    };
+}
+
+impl_binary_writable! {
+    u16 => u16,
+    i16 => i16,
+    u32 => u32,
+    i32 => i32,
+    f32 => f32,
+    u64 => u64,
+    i64 => i64,
+    f64 => f64,
+    u128 => u128,
+    i128 => i128,
+}
+
+impl Write for u8 {
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+        writer.u8(*self)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(1)
+    }
+
+    fn write_slice<E: Endian, W: std::io::Write>(values: &[Self], writer: &mut BinaryWriter<E, W>) -> Result<()> {
+        writer.write_bytes(values)?;
+        record_stats!(writer, Bulk, values.len());
+        Ok(())
+    }
+}
+
+impl Write for i8 {
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+        writer.i8(*self)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(1)
+    }
+
+    fn write_slice<E: Endian, W: std::io::Write>(values: &[Self], writer: &mut BinaryWriter<E, W>) -> Result<()> {
+        // SAFETY: `i8` and `u8` have the same size and alignment, and every bit pattern is a valid `u8`.
+        let bytes = unsafe { std::slice::from_raw_parts(values.as_ptr().cast::<u8>(), values.len()) };
+        u8::write_slice(bytes, writer)
+    }
+}
+
+impl Write for bool {
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+        let value = if *self { 1u8 } else { 0u8 };
+        writer.u8(value)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(1)
+    }
+}
+
+impl Write for char {
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+        writer.u32(*self as u32)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(4)
+    }
+}
+
+impl<T: Write> Write for Option<T> {
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+        if let Some(value) = self {
+            writer.write(value)?;
+        }
+        Ok(())
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.as_ref().map_or(Some(0), T::size_hint)
+    }
+}
+
+impl<T: Write> Write for &[T] {
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+        T::write_slice(self, writer)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.iter().try_fold(0usize, |sum, item| sum.checked_add(item.size_hint()?))
+    }
+}
+
+impl<T: Write, const N: usize> Write for [T; N] {
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+        writer.write(&self.as_slice())
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.as_slice().size_hint()
+    }
+}
+
+impl<T: Write> Write for Vec<T> {
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+        writer.write(&self.as_slice())
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.as_slice().size_hint()
+    }
+}
+
+macro_rules! impl_writable_for_slice_containers {
+    ( $( $container:ident ),* ) => {
+        $(
+            impl<T: Write> Write for $container<[T]> {
+                fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+                    writer.write(&&self[..])
+                }
+
+                fn size_hint(&self) -> Option<usize> {
+                    (&self[..]).size_hint()
+                }
+            }
+        )*
+    };
+}
+
+impl_writable_for_slice_containers!(Box, Rc, Arc);
+
+impl<T: Write + Clone> Write for Cow<'_, [T]> {
+    fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+        writer.write(&&self[..])
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        (&self[..]).size_hint()
+    }
+}
 
 macro_rules! impl_writable_for_tuples {
     ( $( ( $( $T:ident ),+ ), )+ ) => {
@@ -280,6 +1286,11 @@ macro_rules! impl_writable_for_tuples {
                     )+
                     Ok(())
                 }
+
+                fn size_hint(&self) -> Option<usize> {
+                    let ($(ref $T,)+) = *self;
+                    [$( $T.size_hint()? ),+].into_iter().try_fold(0usize, usize::checked_add)
+                }
             }
         )+
     }
@@ -305,6 +1316,7 @@ mod tests {
         writer.write(&0x0203i16).unwrap();
         writer.write(&0x04050607u32).unwrap();
         writer.write(&0x08090a0b0c0d0e0fu64).unwrap();
+        assert_eq!(writer.position(), 15);
 
         assert_eq!(buf, vec![0x01, 0x03, 0x02, 0x07, 0x06, 0x05, 0x04, 0x0f, 0x0e, 0x0d, 0x0c, 0x0b, 0x0a, 0x09, 0x08]);
     }
@@ -315,9 +1327,300 @@ mod tests {
         let mut writer = BinaryWriter::new_le(&mut buf);
 
         writer.write_as_be(&0x0102_u16).unwrap();
+        assert_eq!(writer.position(), 2);
         writer.write(&0x0304_u16).unwrap();
+        assert_eq!(writer.position(), 4);
+        writer.write_as_be(&(0x05u8, 0x0607u16)).unwrap();
+        assert_eq!(writer.position(), 7);
+
+        assert_eq!(buf, vec![0x01, 0x02, 0x04, 0x03, 0x05, 0x06, 0x07]);
+    }
 
-        assert_eq!(buf, vec![0x01, 0x02, 0x04, 0x03]);
+    #[test]
+    fn test_write_as_nested_position() {
+        use crate::string::StringMode;
+        struct Record<'a> {
+            name: &'a str,
+        }
+        impl Write for Record<'_> {
+            fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+                writer.u16(self.name.len() as u16)?;
+                writer.utf8_str(self.name, StringMode::NullTerminated)?;
+                writer.fill_aligned(4, writer.position() as usize)?;
+                writer.u32(0x0102_0304)
+            }
+        }
+
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_le(&mut buf);
+        writer.u8(0xff).unwrap();
+        writer.write_as_be(&Record { name: "abc" }).unwrap();
+        assert_eq!(writer.position(), 12);
+        writer.fill_aligned(8, writer.position() as usize).unwrap();
+        writer.u16(0x0506).unwrap();
+        assert_eq!(writer.position(), 18);
+        assert_eq!(buf, vec![0xff, 0, 3, b'a', b'b', b'c', 0, 0, 1, 2, 3, 4, 0, 0, 0, 0, 6, 5]);
+    }
+
+    #[test]
+    fn test_size_hint() {
+        assert_eq!(0x01u8.size_hint(), Some(1));
+        assert_eq!((0x01u8, 0x0203i16, 'a').size_hint(), Some(7));
+        assert_eq!([1u32, 2, 3].size_hint(), Some(12));
+        assert_eq!(vec![(1u16, true); 5].size_hint(), Some(15));
+        assert_eq!(Some(1.0f64).size_hint(), Some(8));
+        assert_eq!(None::<u32>.size_hint(), Some(0));
+
+        struct Opaque;
+        impl Write for Opaque {
+            fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+                writer.u8(0)
+            }
+        }
+        assert_eq!((1u8, Opaque).size_hint(), None);
+
+        struct Huge;
+        impl Write for Huge {
+            fn write<E: Endian, W: std::io::Write>(&self, _: &mut BinaryWriter<E, W>) -> Result<()> {
+                Ok(())
+            }
+            fn size_hint(&self) -> Option<usize> {
+                Some(usize::MAX)
+            }
+        }
+        assert_eq!((1u8, Huge).size_hint(), None);
+    }
+
+    #[test]
+    fn test_to_vec() {
+        let value = (0x0102u16, [0x03040506u32; 3]);
+        let buf = BinaryWriter::<LittleEndian, _>::to_vec(&value).unwrap();
+        assert_eq!(buf.len(), 14);
+        assert_eq!(buf.capacity(), 14);
+        assert_eq!(&buf[..4], &[0x02, 0x01, 0x06, 0x05]);
+    }
+
+    #[test]
+    fn test_write_repeated() {
+        struct Entry {
+            id: u16,
+            flags: [u8; 3],
+        }
+        impl Write for Entry {
+            fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+                writer.write(&(self.id, self.flags))
+            }
+            fn size_hint(&self) -> Option<usize> {
+                Some(5)
+            }
+        }
+        let entry = Entry { id: 0xBEEF, flags: [1, 2, 3] };
+
+        for count in [0, 1, 819, 820, 2000] {
+            let mut expected = Vec::new();
+            let mut writer = BinaryWriter::new_be(&mut expected);
+            for _ in 0..count {
+                writer.write(&entry).unwrap();
+            }
+
+            let mut buf = Vec::new();
+            let mut writer = BinaryWriter::new_be(&mut buf);
+            writer.write_repeated(&entry, count).unwrap();
+            assert_eq!(writer.position(), 5 * count as u64);
+            assert_eq!(buf, expected);
+        }
+
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_le(&mut buf);
+        writer.write_repeated(&0xFFFFu16, 1024).unwrap();
+        assert_eq!(buf, vec![0xFF; 2048]);
+
+        // an aligned value at an unaligned offset pads differently for every copy
+        struct Aligned(u8);
+        impl Write for Aligned {
+            fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+                writer.align_to(4)?;
+                writer.u8(self.0)
+            }
+            fn size_hint(&self) -> Option<usize> {
+                Some(1)
+            }
+        }
+        let mut expected = Vec::new();
+        let mut writer = BinaryWriter::new_le(&mut expected);
+        writer.u8(0xEE).unwrap();
+        for _ in 0..3 {
+            writer.write(&Aligned(7)).unwrap();
+        }
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_le(&mut buf);
+        writer.u8(0xEE).unwrap();
+        writer.write_repeated(&Aligned(7), 3).unwrap();
+        assert_eq!(writer.position(), 13);
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn test_atomic_commit_and_discard() {
+        let dir = std::env::temp_dir().join(format!("rwbin-atomic-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("save.bin");
+
+        let mut writer = BinaryWriter::create_atomic_le(&path).unwrap();
+        writer.u32(0xDEADBEEF).unwrap();
+        writer.commit().unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), vec![0xEF, 0xBE, 0xAD, 0xDE]);
+
+        // An interrupted rewrite (dropped before commit) must leave the original intact.
+        let mut writer = BinaryWriter::create_atomic_be(&path).unwrap();
+        writer.u32(0x01020304).unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+        assert_eq!(std::fs::read(&path).unwrap(), vec![0xEF, 0xBE, 0xAD, 0xDE]);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_fill_aligned_zero() {
+        let header = [0x00u8];
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_le(&mut buf);
+
+        writer.u8(0x01).unwrap();
+        assert!(matches!(writer.fill_aligned(header[0] as usize, 1), Err(WriteError::InvalidArgument)));
+        assert_eq!(buf, vec![0x01]);
+    }
+
+    #[test]
+    fn test_fill_aligned_boundaries() {
+        for (offset, padding) in [(0, 0), (7, 1), (8, 0), (9, 7), (16, 0)] {
+            let mut buf = Vec::new();
+            BinaryWriter::new_le(&mut buf).fill_aligned(8, offset).unwrap();
+            assert_eq!(buf, vec![0; padding], "offset {offset}");
+        }
+    }
+
+    #[test]
+    fn test_align_to() {
+        use crate::string::StringMode;
+
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_le(&mut buf);
+        writer.align_to(4).unwrap();
+        assert_eq!(writer.position(), 0);
+        writer.write_as_be(&0x0102u16).unwrap();
+        writer.align_to(4).unwrap();
+        assert_eq!(writer.position(), 4);
+        writer.reserved(0xff, 3).unwrap();
+        writer.align_to(4).unwrap();
+        assert_eq!(writer.position(), 8);
+        writer.utf8_str("abc", StringMode::NullTerminated).unwrap();
+        writer.align_to(8).unwrap();
+        assert_eq!(writer.position(), 16);
+        writer.utf16_str("a", StringMode::FixedChars(1)).unwrap();
+        writer.align_to(3).unwrap();
+        assert_eq!(writer.position(), 18);
+        assert!(matches!(writer.align_to(0), Err(WriteError::InvalidArgument)));
+        assert_eq!(buf, b"\x01\x02\0\0\xff\xff\xff\0abc\0\0\0\0\0a\0");
+    }
+
+    #[derive(Default)]
+    struct CountingSink {
+        data: Vec<u8>,
+        writes: usize,
+    }
+
+    impl std::io::Write for CountingSink {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.writes += 1;
+            self.data.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_buffer_capacity() {
+        let mut unbuffered = BinaryWriter::new_le(CountingSink::default());
+        for i in 0..32u16 {
+            unbuffered.u8(i as u8).unwrap();
+            unbuffered.u16(i).unwrap();
+        }
+        let unbuffered = unbuffered.finish().unwrap();
+        assert_eq!(unbuffered.writes, 64);
+
+        let mut buffered = BinaryWriter::new_le(CountingSink::default()).with_buffer_capacity(64);
+        for i in 0..32u16 {
+            buffered.u8(i as u8).unwrap();
+            buffered.u16(i).unwrap();
+        }
+        buffered.write_as_be(&0x0102u16).unwrap();
+        buffered.reserved(0xff, 100).unwrap();
+        assert_eq!(buffered.position(), 32 * 3 + 2 + 100);
+        let buffered = buffered.finish().unwrap();
+        assert!(buffered.writes < 8);
+
+        let mut expected = unbuffered.data;
+        expected.extend_from_slice(&[0x01, 0x02]);
+        expected.extend_from_slice(&[0xff; 100]);
+        assert_eq!(buffered.data, expected);
+    }
+
+    #[test]
+    fn test_write_num() {
+        fn store<T: Primitive, W: std::io::Write>(writer: &mut BinaryWriter<BigEndian, W>, values: &[T]) -> Result<()> {
+            values.iter().try_for_each(|&value| writer.write_num(value))
+        }
+
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_be(&mut buf);
+        store(&mut writer, &[1u16, 0xfffe]).unwrap();
+        store(&mut writer, &[-2i32]).unwrap();
+        store(&mut writer, &[1.5f64]).unwrap();
+        assert_eq!(writer.position(), 16);
+        assert_eq!(&buf[..8], &[0, 1, 0xff, 0xfe, 0xff, 0xff, 0xff, 0xfe]);
+        assert_eq!(&buf[8..], &1.5f64.to_be_bytes());
+    }
+
+    #[test]
+    fn test_write_map_iter() {
+        use crate::string::StringMode;
+        let items = vec![vec!["ab".to_string(), "cde".to_string()], vec![], vec!["f".to_string()]];
+
+        let mut collected = Vec::new();
+        let mut writer = BinaryWriter::new_le(&mut collected);
+        writer.u32(items.len() as u32).unwrap();
+        writer.write(&items.iter().map(|item_vec| item_vec.len() as u32).collect::<Vec<u32>>()).unwrap();
+        for item_vec in &items {
+            writer.u32(item_vec.len() as u32).unwrap();
+            writer.write(&item_vec.iter().map(|s| s.len() as u32).collect::<Vec<u32>>()).unwrap();
+            for s in item_vec {
+                writer.utf8_str(s, StringMode::FixedChars(s.len())).unwrap();
+            }
+        }
+
+        let mut streamed = Vec::new();
+        let mut writer = BinaryWriter::new_le(&mut streamed);
+        writer.write_map_iter_counted::<u32, _, _>(&items, |item_vec| item_vec.len() as u32).unwrap();
+        for item_vec in &items {
+            writer.write_map_iter_counted::<u32, _, _>(item_vec, |s| s.len() as u32).unwrap();
+            for s in item_vec {
+                writer.utf8_str(s, StringMode::FixedChars(s.len())).unwrap();
+            }
+        }
+        assert_eq!(streamed, collected);
+
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_be(&mut buf);
+        writer.write_map_iter(1..=3u8, |i| *i as u16 * 0x0101).unwrap();
+        assert_eq!(buf, vec![1, 1, 2, 2, 3, 3]);
+        let mut writer = BinaryWriter::new_be(&mut buf);
+        assert!(matches!(writer.write_map_iter_counted::<u8, _, _>(0..256, |_| 0u8), Err(WriteError::InvalidArgument)));
     }
 
     #[test]
@@ -335,4 +1638,451 @@ mod tests {
             vec![0x01, 0x03, 0x02, 0x07, 0x06, 0x05, 0x04, 0x0f, 0x0e, 0x0d, 0x0c, 0x0b, 0x0a, 0x09, 0x08, 0x01, 0x00, 0x03, 0x02]
         );
     }
+
+    #[test]
+    fn test_write_sorted_is_hasher_independent() {
+        use std::collections::hash_map::RandomState;
+        let encode_map = |map: &HashMap<u16, u8, RandomState>| {
+            let mut buf = Vec::new();
+            BinaryWriter::new_le(&mut buf).write_map_sorted(map).unwrap();
+            buf
+        };
+        let encode_set = |set: &HashSet<u16, RandomState>| {
+            let mut buf = Vec::new();
+            BinaryWriter::new_le(&mut buf).write_set_sorted(set).unwrap();
+            buf
+        };
+
+        let mut first_map = HashMap::with_hasher(RandomState::new());
+        let mut second_map = HashMap::with_hasher(RandomState::new());
+        let mut first_set = HashSet::with_hasher(RandomState::new());
+        let mut second_set = HashSet::with_hasher(RandomState::new());
+        for key in 0..64u16 {
+            first_map.insert(key, key as u8);
+            second_map.insert(63 - key, (63 - key) as u8);
+            first_set.insert(key * 3);
+            second_set.insert((63 - key) * 3);
+        }
+
+        let map_bytes = encode_map(&first_map);
+        assert_eq!(map_bytes, encode_map(&second_map));
+        assert_eq!(&map_bytes[..6], &[0, 0, 0, 1, 0, 1]);
+        assert_eq!(map_bytes.len(), 64 * 3);
+
+        let set_bytes = encode_set(&first_set);
+        assert_eq!(set_bytes, encode_set(&second_set));
+        assert_eq!(&set_bytes[..4], &[0, 0, 3, 0]);
+    }
+
+    #[test]
+    fn test_write_slice_containers() {
+        fn encode<T: Write>(value: &T) -> Vec<u8> {
+            BinaryWriter::<BigEndian, Vec<u8>>::to_vec(value).unwrap()
+        }
+        let bytes = [1u8, 2, 3];
+        let words = [0x0102_0304u32, 5];
+        let expected_words = encode(&words);
+        assert_eq!(expected_words, vec![1, 2, 3, 4, 0, 0, 0, 5]);
+
+        let boxed: Box<[u8]> = Box::new(bytes);
+        assert_eq!(encode(&boxed), bytes);
+        assert_eq!(encode(&Rc::<[u8]>::from(bytes)), bytes);
+        assert_eq!(encode(&Arc::<[u8]>::from(bytes)), bytes);
+        assert_eq!(encode(&Cow::Borrowed(&bytes[..])), bytes);
+        assert_eq!(boxed.size_hint(), Some(3));
+
+        let boxed: Box<[u32]> = Box::new(words);
+        assert_eq!(encode(&boxed), expected_words);
+        assert_eq!(encode(&Rc::<[u32]>::from(words)), expected_words);
+        assert_eq!(encode(&Arc::<[u32]>::from(words)), expected_words);
+        assert_eq!(encode(&Cow::<[u32]>::Owned(words.to_vec())), expected_words);
+        assert_eq!(boxed.size_hint(), Some(8));
+
+        let mut reader = crate::reader::BinaryReader::from_be_bytes(&expected_words);
+        assert_eq!(reader.read::<[u32; 2]>().unwrap(), words);
+    }
+
+    #[test]
+    fn test_write_vec_prefixed_policies() {
+        let encode = |values: &[u16], policy| {
+            let mut buf = Vec::new();
+            BinaryWriter::new_le(&mut buf).write_vec_prefixed::<u8, u16>(values, policy).map(|()| buf)
+        };
+        let at_limit: Vec<u16> = (0..255).collect();
+        let over_limit: Vec<u16> = (0..256).collect();
+
+        let bytes = encode(&at_limit, PrefixOverflow::Error).unwrap();
+        assert_eq!((bytes[0], bytes.len()), (255, 1 + 255 * 2));
+        assert!(matches!(encode(&over_limit, PrefixOverflow::Error), Err(WriteError::InvalidArgument)));
+
+        let bytes = encode(&over_limit, PrefixOverflow::Truncate).unwrap();
+        assert_eq!((bytes[0], bytes.len()), (255, 1 + 255 * 2));
+        assert_eq!(&bytes[bytes.len() - 2..], &254u16.to_le_bytes());
+
+        let chunked = PrefixOverflow::Chunked { continuation_flag: 0x80 };
+        let bytes = encode(&at_limit, chunked).unwrap();
+        assert_eq!((bytes[0], bytes.len(), bytes[bytes.len() - 1]), (255, 1 + 255 * 2 + 1, 0));
+        let bytes = encode(&over_limit, chunked).unwrap();
+        assert_eq!((bytes[0], bytes[1 + 255 * 2], bytes[2 + 255 * 2]), (255, 0x80, 1));
+        assert_eq!(encode(&[], chunked).unwrap(), vec![0, 0]);
+        assert!(matches!(encode(&at_limit, PrefixOverflow::Chunked { continuation_flag: 0 }), Err(WriteError::InvalidArgument)));
+    }
+
+    #[test]
+    fn test_reserved_for_round_trip() {
+        use crate::reader::BinaryReader;
+        type Small = (u8, u16);
+        type Large = [u32; 3];
+
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_le(&mut buf);
+        writer.reserved_for::<Small>(0x00).unwrap();
+        writer.u8(7).unwrap();
+        writer.reserved_for::<Large>(0xff).unwrap();
+        assert_eq!(buf.len(), 3 + 1 + 12);
+
+        let mut reader = BinaryReader::from_le_bytes(&buf);
+        reader.reserved_for::<Small>(0x00).unwrap();
+        assert_eq!(reader.u8().unwrap(), 7);
+        reader.reserved_for::<Large>(0xff).unwrap();
+        assert_eq!(reader.remaining(), Some(0));
+
+        let mut reader = BinaryReader::from_le_bytes(&buf);
+        assert!(matches!(reader.reserved_for::<Large>(0x00), Err(crate::result::ReadError::ExpectedMismatch { first_diff: 3, .. })));
+    }
+
+    #[test]
+    fn test_byte_sized_custom_write() {
+        struct Doubled(u8);
+        impl Write for Doubled {
+            fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+                writer.u8(self.0)?;
+                writer.u8(self.0)
+            }
+        }
+        #[derive(Clone, Copy)]
+        #[repr(u8)]
+        enum Flag {
+            A = 3,
+        }
+        impl Write for Flag {
+            fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+                match self {
+                    Flag::A => writer.u8(b'A'),
+                }
+            }
+        }
+
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_le(&mut buf);
+        writer.write(&[Doubled(1), Doubled(2)]).unwrap();
+        writer.write(&vec![Flag::A; 2]).unwrap();
+        writer.write(&[-1i8, 2]).unwrap();
+        writer.write(&[true, false]).unwrap();
+        assert_eq!(buf, vec![1, 1, 2, 2, b'A', b'A', 0xff, 2, 1, 0]);
+    }
+
+    #[test]
+    fn test_write_terminated() {
+        use crate::reader::BinaryReader;
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_be(&mut buf);
+        writer.write_terminated(&[3u16, 1, 2], &0).unwrap();
+        writer.write_terminated_iter([7u32, 8].iter(), &u32::MAX).unwrap();
+        writer.write_terminated::<u8>(&[], &0).unwrap();
+        assert!(matches!(writer.write_terminated(&[1u8, 0, 2], &0), Err(WriteError::InvalidArgument)));
+        assert_eq!(writer.position(), 8 + 12 + 1);
+
+        let mut reader = BinaryReader::from_be_bytes(&buf);
+        assert_eq!(reader.read_until(&0u16).unwrap(), vec![3, 1, 2]);
+        assert_eq!(reader.read_until(&u32::MAX).unwrap(), vec![7, 8]);
+        assert_eq!(reader.read_until(&0u8).unwrap(), vec![]);
+        assert_eq!(reader.remaining(), Some(0));
+
+        let mut partial = Vec::new();
+        let mut writer = BinaryWriter::new_be(&mut partial);
+        assert!(matches!(writer.write_terminated_iter(vec![5u8, 9, 6], &9), Err(WriteError::InvalidArgument)));
+        assert_eq!(partial, vec![5]);
+    }
+
+    #[test]
+    fn test_patch_and_sync_position() {
+        let mut cursor = std::io::Cursor::new(vec![0xee; 3]);
+        cursor.set_position(3);
+        let mut writer = BinaryWriter::new_le(cursor).with_buffer_capacity(16);
+        writer.limit_to(16);
+        assert_eq!(writer.sync_position().unwrap(), 0);
+
+        let placeholder = writer.position();
+        writer.u32(0).unwrap();
+        writer.write(&b"hello!".as_slice()).unwrap();
+        writer.align_to(4).unwrap();
+        assert_eq!(writer.position(), 12);
+        writer.patch(placeholder, &6u32).unwrap();
+        assert_eq!((writer.position(), writer.remaining()), (12, Some(4)));
+        writer.u16(0xbeef).unwrap();
+        assert!(matches!(writer.patch(15, &0u8), Err(WriteError::InvalidArgument)));
+        assert!(matches!(writer.patch(13, &0u16), Err(WriteError::InvalidArgument)));
+        assert!(matches!(writer.patch(14, &0u8), Err(WriteError::InvalidArgument)));
+        writer.patch(12, &0xcafeu16).unwrap();
+        assert_eq!(writer.sync_position().unwrap(), 14);
+        writer.u16(0x0102).unwrap();
+        assert!(matches!(writer.u8(0), Err(WriteError::LimitExceeded { position: 16, .. })));
+
+        let buf = writer.finish().unwrap().into_inner();
+        assert_eq!(buf, [0xee, 0xee, 0xee, 6, 0, 0, 0, b'h', b'e', b'l', b'l', b'o', b'!', 0, 0, 0xfe, 0xca, 2, 1]);
+    }
+
+    #[test]
+    fn test_sync_position_after_external_seek() {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let path = std::env::temp_dir().join(format!("rwbin-sync-position-{}.bin", std::process::id()));
+        let file = std::fs::File::options().read(true).write(true).create(true).truncate(true).open(&path).unwrap();
+        (&file).seek(SeekFrom::Start(4)).unwrap();
+        let mut writer = BinaryWriter::<LittleEndian, _>::new_seekable(&file).unwrap();
+        writer.u16(0x0102).unwrap();
+        writer.flush().unwrap();
+
+        // Moved through the shared handle before the writer ever looked at the stream.
+        (&file).seek(SeekFrom::Start(10)).unwrap();
+        assert_eq!(writer.sync_position().unwrap(), 6);
+        writer.u8(3).unwrap();
+        writer.patch(0, &0x0405u16).unwrap();
+        assert_eq!(writer.position(), 7);
+        writer.finish().unwrap();
+
+        let mut data = Vec::new();
+        (&file).seek(SeekFrom::Start(0)).unwrap();
+        (&file).read_to_end(&mut data).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(data, [0, 0, 0, 0, 5, 4, 0, 0, 0, 0, 3]);
+    }
+
+    #[test]
+    fn test_write_len_prefixed_u32() {
+        let mut buf = Vec::new();
+        let mut writer = BinaryWriter::new_be(&mut buf);
+        writer.u8(0xaa).unwrap();
+        writer
+            .write_len_prefixed_u32(|chunk| {
+                assert_eq!(chunk.position(), 5);
+                chunk.u16(0x0102)?;
+                chunk.write_len_prefixed_u32(|inner| {
+                    inner.u8(3)?;
+                    inner.align_to(8)
+                })?;
+                chunk.write_len_prefixed_u32(|_| Ok(()))
+            })
+            .unwrap();
+        assert_eq!(writer.position(), 20);
+        assert_eq!(buf, [0xaa, 0, 0, 0, 15, 1, 2, 0, 0, 0, 5, 3, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        let mut writer = BinaryWriter::new_le(Vec::new());
+        writer.limit_to(8);
+        assert!(matches!(writer.write_len_prefixed_u32(|chunk| chunk.u64(0)), Err(WriteError::LimitExceeded { .. })));
+        assert!(matches!(writer.write_len_prefixed_u32(|chunk| chunk.u8_from_usize(256)), Err(WriteError::ValueTooLarge { .. })));
+        assert_eq!(writer.position(), 0);
+    }
+
+    #[test]
+    fn test_overwrite_at_length_prefixed_chunk() {
+        let mut writer = BinaryWriter::new_be(std::io::Cursor::new(Vec::new())).with_buffer_capacity(8);
+        writer.write(&b"RIFF".as_slice()).unwrap();
+        let size_pos = writer.position();
+        writer.reserved(0, 4).unwrap();
+        writer.write(&(1u16, 2u32, 3u64)).unwrap();
+        let size = writer.position() - size_pos - 4;
+        let returned = writer.overwrite_at(size_pos, |writer| writer.u32(size as u32).map(|()| writer.position())).unwrap();
+        assert_eq!(returned, size_pos + 4);
+        assert_eq!(writer.position(), 22);
+        writer.u8(0xff).unwrap();
+
+        // a failing patch still returns to where the writer was
+        assert!(writer.overwrite_at(0, |writer| writer.u8_from_usize(300)).is_err());
+        assert_eq!(writer.position(), 23);
+
+        writer.seek_to(4).unwrap();
+        assert_eq!(writer.position(), 4);
+        writer.u8(0).unwrap();
+        writer.seek_to(23).unwrap();
+        writer.u8(0xaa).unwrap();
+
+        let buf = writer.finish().unwrap().into_inner();
+        assert_eq!(&buf[..8], b"RIFF\0\0\0\x0e");
+        assert_eq!(&buf[8..], [0, 1, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0xff, 0xaa]);
+    }
+
+    #[test]
+    fn test_write_len_prefixed_at_position() {
+        use crate::reader::LenPolicy;
+
+        struct Padded(u8);
+
+        impl Write for Padded {
+            fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+                writer.u8(self.0)?;
+                writer.align_to(4)
+            }
+        }
+
+        let mut writer = crate::builder::BinaryWriterBuilder::new().limit(6).endian_le().build_vec();
+        assert!(matches!(writer.write_len_prefixed::<u16, _>(&[0u8; 5], LenPolicy::Exclusive), Err(WriteError::LimitExceeded { .. })));
+        assert_eq!(writer.position(), 0);
+        writer.u8(0xaa).unwrap();
+        writer.write_len_prefixed::<u8, _>(&Padded(1), LenPolicy::Exclusive).unwrap();
+        writer.write_len_prefixed_u32(|body| body.write(&Padded(2))).unwrap_err();
+        assert_eq!(writer.finish().unwrap(), [0xaa, 2, 1, 0]);
+
+        // both helpers pad the payload by where it lands in the stream
+        let mut a = BinaryWriter::new_le(Vec::new());
+        a.write_len_prefixed::<u32, _>(&Padded(3), LenPolicy::Exclusive).unwrap();
+        let mut b = BinaryWriter::new_le(Vec::new());
+        b.write_len_prefixed_u32(|body| body.write(&Padded(3))).unwrap();
+        assert_eq!(a.finish().unwrap(), b.finish().unwrap());
+    }
+
+    #[test]
+    fn test_write_frame_under_limit() {
+        let mut writer = crate::builder::BinaryWriterBuilder::new().limit(6).endian_be().build_vec();
+        assert!(matches!(writer.write_frame(&0x1122_3344u32), Err(WriteError::LimitExceeded { position: 4, limit: 6, .. })));
+        assert_eq!(writer.position(), 0);
+        writer.write_frame(&0x1122u16).unwrap();
+        assert_eq!(writer.finish().unwrap(), [0, 0, 0, 2, 0x11, 0x22]);
+    }
+
+    #[test]
+    fn test_overwrite_at_mid_stream() {
+        let mut cursor = std::io::Cursor::new(b"HDR".to_vec());
+        cursor.set_position(3);
+        let mut writer = BinaryWriter::new_le(cursor);
+        let len_pos = writer.position();
+        writer.reserved(0, 2).unwrap();
+        writer.write(&b"abc".as_slice()).unwrap();
+        writer.overwrite_at(len_pos, |writer| writer.u16(3)).unwrap();
+        assert_eq!(writer.position(), 5);
+        writer.seek_to(2).unwrap();
+        writer.u8(b'A').unwrap();
+        writer.seek_to(5).unwrap();
+        writer.align_to(4).unwrap();
+
+        let buf = writer.finish().unwrap().into_inner();
+        assert_eq!(buf, b"HDR\x03\0Abc\0\0\0");
+    }
+
+    #[test]
+    fn test_copy_from() {
+        let blob: Vec<u8> = (0..3 * 1024 * 1024 + 5).map(|i| (i % 251) as u8).collect();
+        let mut out = Vec::new();
+        let mut writer = BinaryWriter::new_le(&mut out).with_buffer_capacity(100);
+        writer.u64(blob.len() as u64).unwrap();
+        assert_eq!(writer.copy_from(&mut blob.as_slice(), None).unwrap(), blob.len() as u64);
+        writer.fill_aligned(4096, writer.position() as usize).unwrap();
+        assert_eq!(writer.position(), 3 * 1024 * 1024 + 4096);
+        assert_eq!(writer.copy_from(&mut &blob[..10], Some(4)).unwrap(), 4);
+        writer.flush().unwrap();
+        assert_eq!(&out[8..8 + blob.len()], blob);
+        assert!(out[8 + blob.len()..out.len() - 4].iter().all(|&b| b == 0));
+        assert_eq!(&out[out.len() - 4..], &blob[..4]);
+
+        let mut writer = BinaryWriter::new_le(Vec::new());
+        let err = writer.copy_from(&mut &blob[..10], Some(11)).unwrap_err();
+        assert!(matches!(err, WriteError::IoError(e) if e.kind() == std::io::ErrorKind::UnexpectedEof));
+        assert_eq!(writer.position(), 10);
+    }
+
+    #[test]
+    fn test_write_interleaved() {
+        let xs = [1.0f32, 2.0, 3.0];
+        let ys = [-1.0f32, -2.0, -3.0];
+        let ids = [10u16, 20, 30];
+        let mut writer = BinaryWriter::new_le(Vec::new());
+        writer.write_interleaved3(&xs, &ys, &ids).unwrap();
+        let data = writer.finish().unwrap();
+
+        let mut reference = BinaryWriter::new_le(Vec::new());
+        for row in 0..3 {
+            reference.write(&(xs[row], ys[row], ids[row])).unwrap();
+        }
+        assert_eq!(data, reference.finish().unwrap());
+
+        let (mut rx, mut ry, mut rids) = (vec![0.5f32], Vec::new(), Vec::new());
+        let mut reader = crate::reader::BinaryReader::from_le_bytes(&data);
+        reader.read_deinterleaved3(3, &mut rx, &mut ry, &mut rids).unwrap();
+        assert_eq!((&rx[1..], &ry[..], &rids[..]), (&xs[..], &ys[..], &ids[..]));
+
+        let mut reader = crate::reader::BinaryReader::from_le_bytes(&data[..19]);
+        let err = reader.read_deinterleaved2::<u64, u16>(2, &mut Vec::new(), &mut Vec::new()).unwrap_err();
+        assert!(matches!(err, crate::result::ReadError::InElement { index: 1, offset: 10, .. }));
+
+        let mut writer = BinaryWriter::new_le(Vec::new());
+        assert!(matches!(writer.write_interleaved4(&xs, &ys, &ids, &xs[..2]), Err(WriteError::InvalidArgument)));
+        assert_eq!(writer.position(), 0);
+    }
+
+    #[test]
+    fn test_checked_narrowing() {
+        let name = b"chunk";
+        let mut writer = BinaryWriter::new_be(Vec::new());
+        writer.u16_from_usize(name.len()).unwrap();
+        writer.write(&&name[..]).unwrap();
+        writer.u64_from_usize(usize::MAX).unwrap();
+        let data = writer.finish().unwrap();
+        assert_eq!(&data[..7], b"\x00\x05chunk");
+
+        let mut reader = crate::reader::BinaryReader::from_be_bytes(&data);
+        let len = reader.usize_from_u16().unwrap();
+        assert_eq!(reader.read_with::<Vec<u8>, _>(len).unwrap(), name);
+        assert_eq!(reader.usize_from_u64().unwrap(), usize::MAX);
+
+        let mut writer = BinaryWriter::new_le(Vec::new());
+        assert!(matches!(writer.u8_from_usize(256), Err(WriteError::ValueTooLarge { value: 256, max: 255 })));
+        assert!(matches!(writer.u16_from_usize(70_000), Err(WriteError::ValueTooLarge { value: 70_000, max: 65_535 })));
+        #[cfg(target_pointer_width = "64")]
+        assert!(matches!(
+            writer.u32_from_usize(1 << 32),
+            Err(WriteError::ValueTooLarge {
+                value: 4_294_967_296,
+                max: 4_294_967_295
+            })
+        ));
+        writer.u32_from_usize(u32::MAX as usize).unwrap();
+        assert_eq!(writer.position(), 4);
+    }
+
+    #[test]
+    fn test_align_in_region() {
+        use crate::reader::LenPolicy;
+
+        /// An id followed by a table that must start 16-byte aligned within the enclosing chunk.
+        struct Aligned(u8, [u32; 2]);
+
+        impl Write for Aligned {
+            fn write<E: Endian, W: std::io::Write>(&self, writer: &mut BinaryWriter<E, W>) -> Result<()> {
+                writer.u8(self.0)?;
+                writer.align_in_region(16, 0xee)?;
+                writer.write(&self.1)
+            }
+        }
+
+        let mut writer = BinaryWriter::new_le(Vec::new());
+        writer.write(&[0xaau8; 3]).unwrap();
+        writer.write(b"CHK1").unwrap();
+        writer.write_len_prefixed::<u32, _>(&Aligned(1, [2, 3]), LenPolicy::Exclusive).unwrap();
+        assert_eq!(writer.position(), 35);
+        writer.write(b"CHK2").unwrap();
+        writer.write_partial(&Aligned(4, [5, 6])).unwrap();
+        assert!(matches!(writer.align_in_region(16, 0), Err(WriteError::InvalidArgument)));
+        let data = writer.finish().unwrap();
+
+        assert_eq!(&data[7..12], [24, 0, 0, 0, 1]);
+        assert!(data[12..27].iter().all(|&byte| byte == 0xee));
+        assert_eq!(&data[27..35], [2, 0, 0, 0, 3, 0, 0, 0]);
+        assert_eq!(data[39], 4);
+        assert!(data[40..55].iter().all(|&byte| byte == 0xee));
+        assert_eq!(&data[55..], [5, 0, 0, 0, 6, 0, 0, 0]);
+
+        let mut writer = BinaryWriter::new_le(Vec::new());
+        assert!(matches!(writer.align_in_region(4, 0), Err(WriteError::InvalidArgument)));
+    }
 }